@@ -12,7 +12,7 @@ use crate::ui::QueryBuilderWindow;
 use crate::ui::GraphWindow;
 use std::boxed;
 use glib::MainContext;
-// use std::collections::HashMap;
+use std::collections::HashMap;
 use super::listener::SqlListener;
 use crate::server::*;
 use std::thread;
@@ -23,7 +23,8 @@ use serde::{Serialize, Deserialize};
 use std::rc::Rc;
 use std::cell::RefCell;
 use crate::sql::object::DBObject;
-use crate::ui::{SchemaTree};
+use crate::ui::{SchemaTree, QueriesWorkspace};
+use crate::ui::QueriesSettings;
 use crate::sql::object::DBType;
 use crate::sql::copy::*;
 use std::time::Duration;
@@ -31,10 +32,17 @@ use std::hash::Hash;
 use crate::client::SharedUserState;
 use super::listener::ExecMode;
 use crate::tables::table::Table;
+use crate::tables::dialect::CsvOptions;
+use crate::sql::edit::TableEdit;
 use std::str::FromStr;
 use std::net::{Ipv4Addr, Ipv6Addr};
 use crate::client::UserState;
 use url::Url;
+use super::connstr::{ConnString, ServiceFile, PassFile};
+use super::vault::{CredentialStore, FileVault};
+use super::tunnel::{SshJump, SshTunnel};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::fmt;
 use std::error::Error;
 
@@ -42,7 +50,8 @@ use std::error::Error;
 pub enum Engine {
     Postgres,
     MySQL,
-    SQLite
+    SQLite,
+    DataFusion
 }
 
 impl fmt::Display for Engine {
@@ -51,7 +60,8 @@ impl fmt::Display for Engine {
         match self {
             Engine::Postgres => write!(f, "Postgres"),
             Engine::MySQL => write!(f, "MySQL"),
-            Engine::SQLite => write!(f, "SQLite")
+            Engine::SQLite => write!(f, "SQLite"),
+            Engine::DataFusion => write!(f, "DataFusion")
         }
     }
 
@@ -71,6 +81,104 @@ impl std::string::ToString for TlsVersion {
 
 }
 
+/// Levels of protection of a connection against eavesdropping and server
+/// impersonation, with the semantics of the libpq sslmode parameter.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SslMode {
+
+    // Never encrypt.
+    Disable,
+
+    // Try a non-encrypted connection first, then an encrypted one.
+    Allow,
+
+    // Try an encrypted connection first, then a non-encrypted one.
+    Prefer,
+
+    // Always encrypt, without verifying the server certificate (unless a root
+    // certificate is informed, in which case this behaves as VerifyCa).
+    Require,
+
+    // Always encrypt, verifying the server certificate is signed by a trusted authority.
+    VerifyCa,
+
+    // Always encrypt, verifying the server certificate and that the server hostname matches it.
+    VerifyFull
+
+}
+
+impl SslMode {
+
+    pub const ALL : [SslMode; 6] = [
+        SslMode::Disable,
+        SslMode::Allow,
+        SslMode::Prefer,
+        SslMode::Require,
+        SslMode::VerifyCa,
+        SslMode::VerifyFull
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SslMode::Disable => "disable",
+            SslMode::Allow => "allow",
+            SslMode::Prefer => "prefer",
+            SslMode::Require => "require",
+            SslMode::VerifyCa => "verify-ca",
+            SslMode::VerifyFull => "verify-full"
+        }
+    }
+
+    // Whether the connection might be established without encryption.
+    pub fn accepts_plaintext(&self) -> bool {
+        match self {
+            SslMode::Disable | SslMode::Allow | SslMode::Prefer => true,
+            _ => false
+        }
+    }
+
+    pub fn verifies_certificate(&self) -> bool {
+        *self == SslMode::VerifyCa || *self == SslMode::VerifyFull
+    }
+
+    pub fn verifies_hostname(&self) -> bool {
+        *self == SslMode::VerifyFull
+    }
+
+    /* The postgres driver only understands the disable, prefer and require modes at the
+    connection string. The remaining modes are implemented by the TLS connector and by the
+    order in which connections are attempted. */
+    pub fn url_param(&self) -> &'static str {
+        match self {
+            SslMode::Disable | SslMode::Allow => "disable",
+            SslMode::Prefer => "prefer",
+            SslMode::Require | SslMode::VerifyCa | SslMode::VerifyFull => "require"
+        }
+    }
+
+}
+
+impl FromStr for SslMode {
+
+    type Err = String;
+
+    fn from_str(s : &str) -> Result<Self, String> {
+        SslMode::ALL.iter()
+            .find(|mode| mode.name() == s )
+            .cloned()
+            .ok_or(format!("Invalid sslmode: {}", s))
+    }
+
+}
+
+impl fmt::Display for SslMode {
+
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Security {
 
@@ -85,7 +193,55 @@ pub struct Security {
 
     // Path to certificate. If tls_version is Some(.), then
     // this field should be Some(.) as well.
-    pub cert_path : Option<String>
+    pub cert_path : Option<String>,
+
+    // Certificate and private key presented to servers that authenticate
+    // clients by their certificates. Only used when tls_version is Some(.).
+    #[serde(default)]
+    pub client_cert : Option<ClientCert>,
+
+    // The sslmode chosen by the user. If this is None, the mode is derived from
+    // the fields above (see effective_ssl_mode).
+    #[serde(default)]
+    pub ssl_mode : Option<SslMode>
+
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ClientCert {
+
+    // PEM certificate (or certificate chain) and PKCS#8 PEM private key.
+    Pem { cert_path : String, key_path : String },
+
+    // PKCS#12 archive (.p12 or .pfx) holding both the certificate and the private key.
+    Pkcs12 { path : String, passphrase : Option<String> }
+
+}
+
+impl ClientCert {
+
+    pub fn is_pkcs12_path(path : &str) -> bool {
+        path.ends_with(".p12") || path.ends_with(".pfx")
+    }
+
+    /// Path and passphrase of a PKCS#12 archive, if a passphrase was informed.
+    pub fn pkcs12_passphrase(&self) -> Option<(&str, &str)> {
+        match self {
+            ClientCert::Pkcs12 { path, passphrase : Some(passphrase) } => Some((&path[..], &passphrase[..])),
+            _ => None
+        }
+    }
+
+    pub fn paths(&self) -> Vec<&str> {
+        match self {
+            ClientCert::Pem { cert_path, key_path } => vec![&cert_path[..], &key_path[..]],
+            ClientCert::Pkcs12 { path, .. } => vec![&path[..]]
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.paths().iter().all(|p| std::path::Path::new(p).is_file() )
+    }
 
 }
 
@@ -100,7 +256,9 @@ impl Security {
         Self {
             tls_version : Some(TlsVersion { major : 1, minor : 2 }),
             verify_hostname : Some(true),
-            cert_path : None
+            cert_path : None,
+            client_cert : None,
+            ssl_mode : None
         }
     }
 
@@ -111,7 +269,38 @@ impl Security {
         Self {
             tls_version : None,
             verify_hostname : None,
-            cert_path : None
+            cert_path : None,
+            client_cert : None,
+            ssl_mode : None
+        }
+    }
+
+    /* Settings saved before the sslmode could be chosen always verified the server
+    certificate when encrypted, and the hostname when verify_hostname was set. */
+    pub fn effective_ssl_mode(&self) -> SslMode {
+        match (self.ssl_mode, self.tls_version, self.verify_hostname) {
+            (Some(mode), _, _) => mode,
+            (None, None, _) => SslMode::Disable,
+            (None, Some(_), Some(true)) => SslMode::VerifyFull,
+            (None, Some(_), _) => SslMode::VerifyCa
+        }
+    }
+
+    /* The passphrase of a PKCS#12 client certificate is only held for the session, and is
+    removed before the settings are saved (it is kept at the credential store if the user
+    enabled the vault). */
+    pub fn forget_passphrase(&mut self) {
+        if let Some(ClientCert::Pkcs12 { passphrase, .. }) = &mut self.client_cert {
+            *passphrase = None;
+        }
+    }
+
+    // Sets the passphrase of a PKCS#12 client certificate that does not have one yet.
+    pub fn recover_passphrase(&mut self, store : &dyn CredentialStore) {
+        if let Some(ClientCert::Pkcs12 { path, passphrase }) = &mut self.client_cert {
+            if passphrase.is_none() {
+                *passphrase = store.cert_passphrase(path);
+            }
         }
     }
 
@@ -149,6 +338,15 @@ pub struct ConnectionInfo {
     // associated certificate, it is non-encrypted.
     pub security : Security,
 
+    // Extra connection parameters (application_name, options, target_session_attrs, etc.)
+    // informed at a connection string or service file.
+    #[serde(default)]
+    pub params : Vec<(String, String)>,
+
+    // Bastion host through which the database host is reached.
+    #[serde(default)]
+    pub ssh : Option<SshJump>
+
 }
 
 impl fmt::Display for ConnectionInfo {
@@ -219,13 +417,21 @@ impl ConnectionInfo {
         let mut s = String::from(self.host_description());
         s += "\t\t";
         if self.is_encrypted() {
-            s += "✓ Encrypted";
+            s += &format!("✓ Encrypted (sslmode={})", self.security.effective_ssl_mode());
             s += "\t\t";
             if self.is_certificate_valid(){
                 s += "✓ Certificate path valid";
             } else {
                 s += "⨯ Certificate path invalid";
             }
+            if let Some(client_cert) = &self.security.client_cert {
+                s += "\t\t";
+                if client_cert.is_valid() {
+                    s += "✓ Client certificate";
+                } else {
+                    s += "⨯ Client certificate path invalid";
+                }
+            }
             s += "\t\t";
             if self.is_verified() {
                 s += "✓ Hostname verified";
@@ -239,7 +445,9 @@ impl ConnectionInfo {
     }
 
     pub fn host_description(&self) -> &'static str {
-        if self.is_localhost() || self.is_loopback() {
+        if self.is_socket() {
+            "Unix socket"
+        } else if self.is_localhost() || self.is_loopback() {
             "Local"
         } else if self.is_private_network() {
             "Private network"
@@ -264,6 +472,22 @@ impl ConnectionInfo {
         self.kind() == Some(HostKind::Uri) && self.host.starts_with("file://")
     }
 
+    // Postgres servers accept local connections through the Unix domain socket
+    // at a directory such as /var/run/postgresql, which is informed as the host.
+    pub fn is_socket(&self) -> bool {
+        self.host.starts_with('/')
+    }
+
+    // Host as written at the authority of the connection URL. Socket directories are
+    // percent-encoded, as libpq expects them.
+    pub fn uri_host(&self) -> String {
+        if self.is_socket() {
+            self.host.replace('/', "%2F")
+        } else {
+            self.host.clone()
+        }
+    }
+
     pub fn is_loopback(&self) -> bool {
         if let Ok(ip) = Ipv4Addr::from_str(&self.host[..]) {
             ip.is_loopback()
@@ -304,16 +528,43 @@ impl ConnectionInfo {
         }
     }
 
-    /* Defines if an user can disble TLS altogether for this connection. This
-    is done by selecting TLS version 'None' at the TLS version combo. While the
-    user can configure anything here, the application will refuse to proceed with
-    the connection unless the connection is to a file, private, localhost or loopback. */
-    pub fn requires_tls(&self) -> bool {
-        let accept_insecure = self.is_file() ||
-            self.is_private_network() ||
-            self.is_localhost() ||
-            self.is_loopback();
-        !accept_insecure
+    /* Security settings of a host that was not configured yet. Files, sockets and local
+    hosts are not encrypted by default, and connections to any other host verify the server
+    certificate and hostname. The user can then choose any sslmode at the security settings. */
+    pub fn default_security(&self) -> Security {
+        if self.is_file() || self.is_socket() || self.is_localhost() || self.is_loopback() {
+            Security::new_insecure()
+        } else {
+            Security::new_secure()
+        }
+    }
+
+    // Connection parameters as written at the query string of the connection URL.
+    pub fn query_params(&self) -> Vec<String> {
+        self.params.iter()
+            .map(|(k, v)| format!("{}={}", k, super::percent_encode(v)) )
+            .collect()
+    }
+
+    /* The engine of file connections is decided from the host (CSV and Parquet files or
+    directories are queried by the DataFusion engine, other files are SQLite databases). Servers
+    keep the engine chosen at the connection form, which is Postgres unless MySQL was chosen. */
+    pub fn infer_engine(&self) -> Engine {
+        if self.is_file() {
+            let path = std::path::Path::new(self.host.trim_start_matches("file://"));
+            let is_data = path.extension()
+                .map(|ext| ext == "csv" || ext == "parquet" )
+                .unwrap_or(false);
+            if is_data || path.is_dir() {
+                Engine::DataFusion
+            } else {
+                Engine::SQLite
+            }
+        } else if self.engine == Engine::MySQL {
+            Engine::MySQL
+        } else {
+            Engine::Postgres
+        }
     }
 
 }
@@ -321,8 +572,12 @@ impl ConnectionInfo {
 #[derive(Debug, Clone)]
 pub struct ConnConfig {
 
-    // Statement timeout, in milliseconds.
-    pub timeout : usize
+    // Statement timeout, in milliseconds (zero means no timeout).
+    pub timeout : usize,
+
+    // Number of rows fetched at a time for query results (zero means
+    // results are fetched at once).
+    pub page_size : usize
 
 }
 
@@ -334,6 +589,10 @@ pub const DEFAULT_USER : &str = "User";
 
 pub const DEFAULT_DB : &str = "Database";
 
+pub const MYSQL_DEFAULT_PORT : &str = "3306";
+
+pub const PG_DEFAULT_PORT : &str = "5432";
+
 impl Default for ConnectionInfo {
 
     fn default() -> Self {
@@ -344,6 +603,8 @@ impl Default for ConnectionInfo {
             user : String::from(DEFAULT_USER),
             database : String::from(DEFAULT_DB),
             security : Security::new_secure(),
+            params : Vec::new(),
+            ssh : None
         }
     }
 
@@ -363,7 +624,20 @@ pub enum ConnectionAction {
     UpdateUser(String),
 
     UpdateDB(String),
+
+    // Sets the server engine chosen at the connection form.
+    UpdateEngine(Engine),
+
     Remove(i32),
+
+    // Replaces the selected connection by the one informed at a connection string.
+    Paste(ConnString),
+
+    // Adds the connections read from the service and password files.
+    Import(Vec<ConnectionInfo>),
+
+    // Sets (or removes) the bastion host of the selected connection.
+    UpdateSsh(Option<SshJump>)
 }
 
 // The ConnectionSet keeps all history of established connections. It is loaded
@@ -387,6 +661,21 @@ pub struct ConnectionSet {
 
     selected : Callbacks<Option<(i32, ConnectionInfo)>>,
 
+    pasted : Callbacks<(i32, ConnString)>,
+
+    // Credential store, set when the user unlocks the password vault.
+    vault : Rc<RefCell<Option<boxed::Box<dyn CredentialStore>>>>,
+
+    // Connection and password informed by the user at the last activation, which are
+    // kept at the credential store if the connection is established.
+    pending : Rc<RefCell<Option<(ConnectionInfo, String)>>>,
+
+    // Path and passphrase of the PKCS#12 client certificate used at the last activation,
+    // kept at the credential store if the connection is established.
+    pending_cert : Rc<RefCell<Option<(String, String)>>>,
+
+    user_state : SharedUserState,
+
     pub(super) send : glib::Sender<ConnectionAction>
 
 }
@@ -403,6 +692,8 @@ impl ConnectionSet {
     pub fn new(user_state : &SharedUserState) -> Self {
         let (send, recv) = MainContext::channel::<ConnectionAction>(glib::source::PRIORITY_DEFAULT);
         let (selected, added, updated, removed) : ConnSetTypes = Default::default();
+        let pasted : Callbacks<(i32, ConnString)> = Default::default();
+        let vault : Rc<RefCell<Option<boxed::Box<dyn CredentialStore>>>> = Default::default();
         recv.attach(None, {
 
             // Holds the set of connections added by the user. This is synced to the
@@ -411,6 +702,8 @@ impl ConnectionSet {
             let mut curr_conn : Option<i32> = None;
 
             let (selected, added, updated, removed) = (selected.clone(), added.clone(), updated.clone(), removed.clone());
+            let pasted = pasted.clone();
+            let vault = vault.clone();
             let user_state = user_state.clone();
 
             move |action| {
@@ -453,19 +746,11 @@ impl ConnectionSet {
                                 if let Some(matching) = matching_conn {
                                     this_conn.security = matching.security;
                                 } else {
-                                    if this_conn.is_localhost() || this_conn.is_file() || this_conn.is_loopback() {
-                                        this_conn.security = Security::new_insecure();
-                                    } else {
-                                        this_conn.security = Security::new_secure();
-                                    }
+                                    this_conn.security = this_conn.default_security();
                                 }
 
                                 // Update engine if required
-                                if this_conn.is_file() {
-                                    this_conn.engine = Engine::SQLite;
-                                } else {
-                                    this_conn.engine = Engine::Postgres;
-                                }
+                                this_conn.engine = this_conn.infer_engine();
 
                                 updated.call((ix as i32, this_conn.clone()));
                             }
@@ -507,13 +792,82 @@ impl ConnectionSet {
                             }
                         }
                     },
+
+                    ConnectionAction::UpdateEngine(updated_engine) => {
+                        if let Some(ix) = curr_conn {
+                            let mut us = user_state.borrow_mut();
+                            let mut this_conn = &mut us.conns[ix as usize];
+
+                            if !this_conn.is_file() && this_conn.engine != updated_engine {
+                                this_conn.engine = updated_engine;
+                                updated.call((ix as i32, this_conn.clone()));
+                            }
+                        }
+                    },
                     
+                    ConnectionAction::UpdateSsh(jump) => {
+                        if let Some(ix) = curr_conn {
+                            let mut us = user_state.borrow_mut();
+                            let mut this_conn = &mut us.conns[ix as usize];
+                            if this_conn.ssh != jump {
+                                this_conn.ssh = jump;
+                                updated.call((ix as i32, this_conn.clone()));
+                            }
+                        }
+                    },
+
                     ConnectionAction::Remove(ix) => {
-                        user_state.borrow_mut().conns.remove(ix as usize);
+                        let conn = user_state.borrow_mut().conns.remove(ix as usize);
+
+                        // Removed connections should not leave their passwords behind.
+                        if let Some(vault) = vault.borrow_mut().as_mut() {
+                            if let Err(e) = vault.remove_password(&conn) {
+                                eprintln!("{}", e);
+                            }
+                        }
                         removed.call(ix);
                         selected.call(None);
                     },
 
+                    ConnectionAction::Paste(mut conn_str) => {
+                        if let Some(ix) = curr_conn {
+                            let mut us = user_state.borrow_mut();
+
+                            // All connections to the same host share the security settings, so
+                            // the settings at the string are only used for a new host.
+                            if let Some(matching) = us.conns.iter().enumerate()
+                                .find(|(i, c)| *i != ix as usize && &c.host[..] == &conn_str.info.host[..] )
+                            {
+                                conn_str.info.security = matching.1.security.clone();
+                            }
+                            // Connection strings do not carry the SSH tunnel settings.
+                            conn_str.info.ssh = us.conns[ix as usize].ssh.clone();
+                            us.conns[ix as usize] = conn_str.info.clone();
+                            drop(us);
+                            pasted.call((ix, conn_str.clone()));
+                            updated.call((ix, conn_str.info));
+                        }
+                    },
+
+                    ConnectionAction::Import(conns) => {
+                        for mut conn in conns {
+                            let mut us = user_state.borrow_mut();
+                            if us.conns.iter().any(|c| c.is_like(&conn) ) {
+                                continue;
+                            }
+                            if let Some(matching) = us.conns.iter().find(|c| &c.host[..] == &conn.host[..] ) {
+                                conn.security = matching.security.clone();
+                            }
+                            us.conns.push(conn.clone());
+
+                            // Release the state before the callbacks are called, since the
+                            // list selects the added row.
+                            drop(us);
+                            curr_conn = None;
+                            added.call(conn);
+                        }
+                    }
+
                 }
                 Continue(true)
             }
@@ -523,10 +877,19 @@ impl ConnectionSet {
             selected,
             added,
             updated,
-            removed
+            removed,
+            pasted,
+            vault,
+            pending : Default::default(),
+            pending_cert : Default::default(),
+            user_state : user_state.clone()
         }
     }
 
+    pub fn is_vault_unlocked(&self) -> bool {
+        self.vault.borrow().is_some()
+    }
+
     pub fn connect_added(&self, f : impl Fn(ConnectionInfo) + 'static) {
         self.added.bind(f);
     }
@@ -543,6 +906,10 @@ impl ConnectionSet {
         self.selected.bind(f);
     }
 
+    pub fn connect_pasted(&self, f : impl Fn((i32, ConnString)) + 'static) {
+        self.pasted.bind(f);
+    }
+
 }
 
 impl React<ConnectionBox> for ConnectionSet {
@@ -594,14 +961,227 @@ impl React<ConnectionBox> for ConnectionSet {
                 }
             }
         });
+        let engine_changed = conn_bx.engine.connect_changed({
+            let send = self.send.clone();
+            move |combo| {
+                send.send(ConnectionAction::UpdateEngine(selected_engine(combo))).unwrap();
+            }
+        });
         conn_bx.host_changed.replace(Some(host_changed));
         conn_bx.user_changed.replace(Some(user_changed));
         conn_bx.db_changed.replace(Some(db_changed));
         conn_bx.port_changed.replace(Some(port_changed));
+        conn_bx.engine_changed.replace(Some(engine_changed));
+
+        let paste = {
+            let send = self.send.clone();
+            let conn_str = conn_bx.conn_str.clone();
+            move || {
+                match ConnString::parse(&conn_str.entry.text()) {
+                    Ok(parsed) => {
+                        send.send(ConnectionAction::Paste(parsed)).unwrap();
+                    },
+                    Err(e) => {
+                        conn_str.show_message(&e, true);
+                    }
+                }
+            }
+        };
+        conn_bx.conn_str.btn_apply.connect_clicked({
+            let paste = paste.clone();
+            move |_| paste()
+        });
+        conn_bx.conn_str.entry.connect_activate({
+            let btn_apply = conn_bx.conn_str.btn_apply.clone();
+            move |_| {
+                if btn_apply.is_sensitive() {
+                    paste();
+                }
+            }
+        });
+        conn_bx.ssh.btn_apply.connect_clicked({
+            let send = self.send.clone();
+            let ssh = conn_bx.ssh.clone();
+            move |_| {
+                match ssh.jump() {
+                    Ok(jump) => {
+                        send.send(ConnectionAction::UpdateSsh(jump)).unwrap();
+                        ssh.popover.popdown();
+                    },
+                    Err(e) => {
+                        ssh.show_error(&e);
+                    }
+                }
+            }
+        });
+
+        // This is called before the connection request is built by the active connection, so
+        // the password from the vault is already at the entry by then.
+        conn_bx.switch.connect_state_set({
+            let (host_entry, port_entry, db_entry, user_entry, password_entry) = (
+                conn_bx.host.entry.clone(),
+                conn_bx.port.entry.clone(),
+                conn_bx.db.entry.clone(),
+                conn_bx.user.entry.clone(),
+                conn_bx.password.entry.clone()
+            );
+            let engine_combo = conn_bx.engine.clone();
+            let (vault, pending, user_state) = (self.vault.clone(), self.pending.clone(), self.user_state.clone());
+            let pending_cert = self.pending_cert.clone();
+            move |switch, _| {
+                if !switch.is_active() || !user_state.borrow().conn.use_vault {
+                    return Inhibit(false);
+                }
+                if let Ok(info) = extract_conn_info(&host_entry, &port_entry, &db_entry, &user_entry, &engine_combo) {
+                    if info.is_file() {
+                        return Inhibit(false);
+                    }
+
+                    // Security settings are shared by all connections to the host.
+                    let mut us = user_state.borrow_mut();
+                    for conn in us.conns.iter_mut().filter(|c| c.host == info.host ) {
+                        if let Some(vault) = vault.borrow().as_ref() {
+                            conn.security.recover_passphrase(vault.as_ref());
+                        }
+                        let cert = conn.security.client_cert.as_ref().and_then(|c| c.pkcs12_passphrase() );
+                        if let Some((path, passphrase)) = cert {
+                            pending_cert.replace(Some((path.to_string(), passphrase.to_string())));
+                        }
+                    }
+                    drop(us);
+
+                    if password_entry.text().is_empty() {
+                        if let Some(pwd) = vault.borrow().as_ref().and_then(|v| v.password(&info) ) {
+                            password_entry.set_text(&pwd);
+                        }
+                    }
+                    let pwd = password_entry.text().to_string();
+                    if !pwd.is_empty() {
+                        pending.replace(Some((info, pwd)));
+                    }
+                }
+                Inhibit(false)
+            }
+        });
+        conn_bx.conn_str.btn_import.connect_clicked({
+            let send = self.send.clone();
+            let conn_str = conn_bx.conn_str.clone();
+            move |_| {
+                match import_conn_files() {
+                    Ok(conns) => {
+                        conn_str.show_message(&format!("{} connection(s) found", conns.len()), false);
+                        send.send(ConnectionAction::Import(conns)).unwrap();
+                    },
+                    Err(e) => {
+                        conn_str.show_message(&e, true);
+                    }
+                }
+            }
+        });
     }
     
 }
 
+impl React<ActiveConnection> for ConnectionSet {
+
+    fn react(&self, conn : &ActiveConnection) {
+        conn.connect_db_connected({
+            let (vault, pending, pending_cert) = (self.vault.clone(), self.pending.clone(), self.pending_cert.clone());
+            move |_| {
+                if let Some((info, pwd)) = pending.borrow_mut().take() {
+                    if let Some(vault) = vault.borrow_mut().as_mut() {
+                        if let Err(e) = vault.set_password(&info, &pwd) {
+                            eprintln!("{}", e);
+                        }
+                    }
+                }
+                if let Some((path, passphrase)) = pending_cert.borrow_mut().take() {
+                    if let Some(vault) = vault.borrow_mut().as_mut() {
+                        if let Err(e) = vault.set_cert_passphrase(&path, &passphrase) {
+                            eprintln!("{}", e);
+                        }
+                    }
+                }
+            }
+        });
+        conn.connect_db_conn_failure({
+            let (pending, pending_cert) = (self.pending.clone(), self.pending_cert.clone());
+            move |_| {
+                pending.replace(None);
+                pending_cert.replace(None);
+            }
+        });
+    }
+
+}
+
+impl React<QueriesSettings> for ConnectionSet {
+
+    fn react(&self, settings : &QueriesSettings) {
+        settings.conn_bx.vault_entry.connect_activate({
+            let vault = self.vault.clone();
+            move |entry| {
+                let res = super::vault_path()
+                    .ok_or(format!("Unable to get data directory"))
+                    .and_then(|path| FileVault::open(&path, &entry.text()) );
+                match res {
+                    Ok(opened) => {
+                        entry.style_context().remove_class("error");
+                        entry.set_tooltip_text(Some(&format!("Vault unlocked ({} password(s))", opened.len())));
+                        vault.replace(Some(boxed::Box::new(opened)));
+                    },
+                    Err(e) => {
+                        entry.style_context().add_class("error");
+                        entry.set_tooltip_text(Some(&e));
+                    }
+                }
+                entry.set_text("");
+            }
+        });
+
+        // Disabling the vault locks it, and forgets any password waiting to be stored.
+        settings.conn_bx.vault_switch.connect_state_set({
+            let (vault, pending, pending_cert) = (self.vault.clone(), self.pending.clone(), self.pending_cert.clone());
+            let entry = settings.conn_bx.vault_entry.clone();
+            move |switch, _| {
+                if !switch.is_active() {
+                    vault.replace(None);
+                    pending.replace(None);
+                    pending_cert.replace(None);
+                    entry.set_tooltip_text(None);
+                    entry.style_context().remove_class("error");
+                }
+                Inhibit(false)
+            }
+        });
+    }
+
+}
+
+/* Reads the connections defined at the user service and password files. Missing
+files are ignored. */
+fn import_conn_files() -> Result<Vec<ConnectionInfo>, String> {
+    let mut conns = Vec::new();
+    let mut found = false;
+    if let Some(path) = ServiceFile::default_path().filter(|p| p.is_file() ) {
+        conns.extend(ServiceFile::read(&path)?.connections()?);
+        found = true;
+    }
+    if let Some(path) = PassFile::default_path().filter(|p| p.is_file() ) {
+        for conn in PassFile::read(&path)?.connections() {
+            if !conns.iter().any(|c| c.is_like(&conn) ) {
+                conns.push(conn);
+            }
+        }
+        found = true;
+    }
+    if found {
+        Ok(conns)
+    } else {
+        Err(format!("No ~/.pg_service.conf or ~/.pgpass files found"))
+    }
+}
+
 impl React<ConnectionList> for ConnectionSet {
 
     fn react(&self, conn_list : &ConnectionList) {
@@ -638,7 +1218,11 @@ impl React<ConnectionList> for ConnectionSet {
 #[derive(Debug, Clone)]
 pub struct ConnURI {
     pub info : ConnectionInfo,
-    pub uri : Url
+    pub uri : Url,
+
+    // Local port forwarded to the database host by an SSH tunnel. The URI still
+    // points to the database host, and the connection is made to this port instead.
+    pub local_port : Option<u16>
 }
 
 impl ConnURI {
@@ -659,6 +1243,20 @@ impl ConnURI {
         self.uri.scheme() == "postgresql"
     }
 
+    pub fn is_mysql(&self) -> bool {
+        self.uri.scheme() == "mysql"
+    }
+
+    // URL the driver connects to, which goes through the local end of the SSH tunnel, if any.
+    pub fn target_url(&self) -> Url {
+        let mut url = self.uri.clone();
+        if let Some(port) = self.local_port {
+            let _ = url.set_host(Some("127.0.0.1"));
+            let _ = url.set_port(Some(port));
+        }
+        url
+    }
+
     pub fn verify_integrity(&self) -> Result<(), boxed::Box<dyn Error>> {
         match self.info.engine {
             Engine::Postgres => {
@@ -671,14 +1269,24 @@ impl ConnURI {
                     return Err("Invalid URI for SQLite connection".into());
                 }
             },
-            _ => { }
+            Engine::DataFusion => {
+                if !self.is_file() {
+                    return Err("Invalid URI for DataFusion connection".into());
+                }
+            },
+            Engine::MySQL => {
+                if !self.is_mysql() {
+                    return Err("Invalid URI for MySQL connection".into());
+                }
+            }
         }
 
         if !self.is_file() {
-            if self.uri.host_str() != Some(&self.info.host[..]) {
+            if self.uri.host_str() != Some(&self.info.uri_host()[..]) {
                 return Err("Mismatch between connection host and URI domain".into());
             }
-            if self.uri.username() != &self.info.user[..] {
+            let mut expected = self.uri.clone();
+            if expected.set_username(&self.info.user).is_err() || self.uri.username() != expected.username() {
                 return Err("Mismatch between connection username and URI username".into());
             }
             if let Ok(port) = u16::from_str(&self.info.port[..]) {
@@ -704,61 +1312,71 @@ impl ConnURI {
         if info.is_file() {
             match Url::parse(&info.host) {
                 Ok(uri) => {
-                    Ok(ConnURI { info, uri })
+                    Ok(ConnURI { info, uri, local_port : None })
                 },
                 Err(e) => {
                     Err(format!("{}",e))
                 }
             }
         } else {
-            if info.user.chars().any(|c| c == ':' ) {
-                return Err(String::from("User field cannot contain ':' character"));
+            if info.is_socket() && info.engine != Engine::Postgres {
+                return Err(format!("Unix socket connections are only supported for Postgres"));
             }
 
-            let mut uri = "postgresql://".to_owned();
-            uri += &info.user;
-            uri += ":";
-
-            if password.is_empty() {
+            // Socket connections might authenticate the user by its operating
+            // system account (peer authentication), without a password.
+            if password.is_empty() && !info.is_socket() {
                 return Err(format!("Missing password"));
             }
-            uri += password;
-
-            uri += "@";
 
-            uri += &info.host;
-            uri += ":";
-            uri += &info.port;
-            uri += "/";
-            uri += &info.database;
-
-            match Url::parse(&uri) {
-                Ok(uri) => {
-                    Ok(ConnURI { info, uri })
-                },
-                Err(e) => {
-                    Err(format!("{}",e))
-                }
+            let scheme = match info.engine {
+                Engine::MySQL => "mysql",
+                _ => "postgresql"
+            };
+            let mut uri = Url::parse(&format!("{}://{}:{}/{}", scheme, info.uri_host(), info.port, info.database))
+                .map_err(|e| format!("{}", e) )?;
+
+            // User names and passwords are percent-encoded, so they might contain
+            // any character (including ':' and '@').
+            uri.set_username(&info.user)
+                .map_err(|_| format!("Invalid user: {}", info.user) )?;
+            if !password.is_empty() {
+                uri.set_password(Some(password))
+                    .map_err(|_| format!("Invalid password") )?;
             }
+            Ok(ConnURI { info, uri, local_port : None })
         }
     }
 
 }
 
+fn selected_engine(engine_combo : &ComboBoxText) -> Engine {
+    match engine_combo.active_id().as_ref().map(|id| id.as_str() ) {
+        Some("mysql") => Engine::MySQL,
+        _ => Engine::Postgres
+    }
+}
+
 /* Extract connection info from GTK widgets (except password, which is held separately 
 at the uri field of ConnURI. */
 fn extract_conn_info(
     host_entry : &Entry,
     port_entry : &Entry,
     db_entry : &Entry,
-    user_entry : &Entry
+    user_entry : &Entry,
+    engine_combo : &ComboBoxText
 ) -> Result<ConnectionInfo, String> {
     let host_s = host_entry.text().as_str().to_owned();
     if host_s.is_empty() {
         return Err(format!("Missing host"));
     }
-    let port_s = port_entry.text().as_str().to_owned();
+    let mut port_s = port_entry.text().as_str().to_owned();
     let is_file = host_s.starts_with("file://");
+
+    // The socket file name is given by the port, which defaults to the Postgres port.
+    if port_s.is_empty() && host_s.starts_with('/') {
+        port_s = String::from(PG_DEFAULT_PORT);
+    }
     if port_s.is_empty() && !is_file {
         return Err(format!("Missing port"));
     }
@@ -782,12 +1400,12 @@ fn extract_conn_info(
         if !db_s.is_empty() {
             Err("Unexpected 'Database' argument for file database".to_string())?;
         }
-        info.engine = Engine::SQLite;
+        info.engine = info.infer_engine();
     } else {
         info.port = port_s.to_string();
         info.database = db_s.to_string();
         info.user = user_s.to_string();
-        info.engine = Engine::Postgres;
+        info.engine = selected_engine(engine_combo);
     }
     Ok(info)
 }
@@ -797,10 +1415,16 @@ fn generate_conn_uri_from_entries(
     port_entry : &Entry,
     db_entry : &Entry,
     user_entry : &Entry,
-    password_entry : &PasswordEntry
+    password_entry : &PasswordEntry,
+    engine_combo : &ComboBoxText
 ) -> Result<ConnURI, String> {
-    let info = extract_conn_info(host_entry, port_entry, db_entry, user_entry)?;
-    let pwd = password_entry.text().as_str().to_owned();
+    let info = extract_conn_info(host_entry, port_entry, db_entry, user_entry, engine_combo)?;
+    let mut pwd = password_entry.text().as_str().to_owned();
+
+    // As for libpq, a missing password is looked up at the user password file.
+    if pwd.is_empty() && info.engine == Engine::Postgres {
+        pwd = super::pgpass_password(&info).unwrap_or_default();
+    }
     ConnURI::new(info, &pwd)
 }
 
@@ -814,6 +1438,9 @@ pub enum ErrorKind {
 
 }
 
+// Interval (in milliseconds) between verifications of queued notifications in notification mode.
+const NOTIFICATION_POLL_INTERVAL : u64 = 500;
+
 pub enum ActiveConnectionAction {
 
     ConnectRequest(ConnURI),
@@ -827,22 +1454,58 @@ pub enum ActiveConnectionAction {
     // Requires an arbitrary sequence of SQL commands.
     ExecutionRequest(String),
 
+    // Requires an arbitrary sequence of SQL commands, carrying the values
+    // informed by the user for its unbound placeholders.
+    ParametrizedExecutionRequest(String, HashMap<String, String>),
+
     // Requires a sigle table or view name to do a single SQL query.
     SingleQueryRequest,
 
     StartSchedule(String),
 
+    // Executes the SQL every time a notification arrives at the configured channel.
+    StartNotificationSchedule(String),
+
     EndSchedule,
 
-    ExecutionCompleted(Vec<StatementOutput>),
+    // Interrupts the statement being executed.
+    CancelRequest,
+
+    // Carries the results and the indices of the results with rows yet to be fetched.
+    ExecutionCompleted(Vec<StatementOutput>, Vec<usize>),
 
     SingleQueryCompleted(StatementOutput),
 
+    // Requests the next page of rows of the result at the given index.
+    PageRequest(usize),
+
+    // Carries the result index and its next page of rows.
+    PageCompleted(usize, Result<Table, String>),
+
     SchemaUpdate(Option<Vec<DBObject>>),
 
     ObjectSelected(Option<Vec<usize>>),
 
-    TableImport(String),
+    // Carries the file path and the options used to read it if it is a delimited text file.
+    TableImport(String, CsvOptions),
+
+    // Carries the number of rows imported so far and the total number of rows.
+    ImportProgress(usize, usize),
+
+    // Carries the file path, the destination table and the statement that will create it.
+    ImportPreview(String, String, String),
+
+    // Carries the file path and the destination table, confirmed by the user after the preview.
+    ImportConfirm(String, String),
+
+    // Requests the edition of the rows of the result of the given query.
+    EditRequest(String),
+
+    // Carries the query of the edited result and the changes reviewed by the user.
+    EditApply(String, TableEdit),
+
+    // Carries the query and its result after the changes were committed.
+    EditCompleted(String, Result<Table, String>),
 
     Error(String)
 
@@ -868,6 +1531,9 @@ pub struct ActiveConnection {
 
     on_exec_result : Callbacks<Vec<StatementOutput>>,
 
+    // Called before on_exec_result with the indices of the results with rows yet to be fetched.
+    on_paged_results : Callbacks<Vec<usize>>,
+
     // "single queries" are queries sent by interactions with the GUI
     // (Query and Report on the popover in the left schema tree). The
     // callbacks are different because the GUI should react differently
@@ -880,7 +1546,32 @@ pub struct ActiveConnection {
     
     on_schema_update : Callbacks<Option<Vec<DBObject>>>,
 
-    on_object_selected : Callbacks<Option<DBObject>>
+    on_object_selected : Callbacks<Option<DBObject>>,
+
+    // Called with the SQL and the names of placeholders that must be
+    // informed by the user before the SQL can be executed.
+    on_params_required : Callbacks<(String, Vec<String>)>,
+
+    // Called with the index of a result and its next page of rows.
+    on_page_result : Callbacks<(usize, Table)>,
+
+    // Called with the number of imported rows and the total number of rows.
+    on_import_progress : Callbacks<(usize, usize)>,
+
+    // Called with the file path, destination table and creation statement when
+    // the user imports a file into a schema.
+    on_import_preview : Callbacks<(String, String, String)>,
+
+    // Called with the query, its source table and the connection engine when the
+    // user starts editing a result.
+    on_edit_ready : Callbacks<(String, DBObject, Engine)>,
+
+    // Called with the query and its new result after the changes made by the user are committed.
+    on_edit_result : Callbacks<(String, Table)>,
+
+    // SSH tunnel of the current connection (or connection attempt), which is closed
+    // when the connection is closed or fails, and when the window is destroyed.
+    tunnel : Arc<Mutex<Option<SshTunnel>>>
 
 }
 
@@ -897,14 +1588,22 @@ impl ActiveConnection {
     pub fn new(user_state : &SharedUserState) -> Self {
         let (on_connected, on_disconnected, on_error) : ActiveConnCallbacks = Default::default();
         let on_exec_result : Callbacks<Vec<StatementOutput>> = Default::default();
+        let on_paged_results : Callbacks<Vec<usize>> = Default::default();
         let on_single_query_result : Callbacks<Table> = Default::default();
         let on_conn_failure : Callbacks<(ConnectionInfo, String)> = Default::default();
         let (send, recv) = glib::MainContext::channel::<ActiveConnectionAction>(glib::source::PRIORITY_DEFAULT);
+        let tunnel : Arc<Mutex<Option<SshTunnel>>> = Default::default();
         let on_schema_update : Callbacks<Option<Vec<DBObject>>> = Default::default();
         let on_object_selected : Callbacks<Option<DBObject>> = Default::default();
         let on_schema_invalidated : Callbacks<()> = Default::default();
         let on_schedule_start : Callbacks<()> = Default::default();
         let on_schedule_end : Callbacks<()> = Default::default();
+        let on_params_required : Callbacks<(String, Vec<String>)> = Default::default();
+        let on_page_result : Callbacks<(usize, Table)> = Default::default();
+        let on_import_progress : Callbacks<(usize, usize)> = Default::default();
+        let on_import_preview : Callbacks<(String, String, String)> = Default::default();
+        let on_edit_ready : Callbacks<(String, DBObject, Engine)> = Default::default();
+        let on_edit_result : Callbacks<(String, Table)> = Default::default();
         
         let mut schema_valid = true;
         
@@ -916,13 +1615,13 @@ impl ActiveConnection {
         // single producer).
         let mut listener = SqlListener::launch({
             let send = send.clone();
-            move |mut results, mode| {
+            move |mut results, paged, mode| {
                 match mode {
                     ExecMode::Single => {
                         send.send(ActiveConnectionAction::SingleQueryCompleted(results.remove(0))).unwrap();
                     },
                     ExecMode::Multiple => {
-                        send.send(ActiveConnectionAction::ExecutionCompleted(results)).unwrap();
+                        send.send(ActiveConnectionAction::ExecutionCompleted(results, paged)).unwrap();
                     }
                 }
             }
@@ -937,6 +1636,12 @@ impl ActiveConnection {
         /* Keeps the currently-selected object at the schema tree (might be a table, view,
         column or schema. Must necessarily be a node of the schema variable above. */
         let mut selected_obj : Option<DBObject> = None;
+
+        // Options used to read the file waiting for the user to confirm the creation of the destination table.
+        let mut import_opts = CsvOptions::default();
+
+        // Engine of the active connection, which decides how edited rows are written back.
+        let mut conn_engine : Option<Engine> = None;
         
         recv.attach(None, {
             let send = send.clone();
@@ -952,6 +1657,13 @@ impl ActiveConnection {
             let on_object_selected = on_object_selected.clone();
             let on_schema_update = on_schema_update.clone();
             let on_schema_invalidated = on_schema_invalidated.clone();
+            let on_params_required = on_params_required.clone();
+            let on_page_result = on_page_result.clone();
+            let on_paged_results = on_paged_results.clone();
+            let on_import_progress = on_import_progress.clone();
+            let on_import_preview = on_import_preview.clone();
+            let on_edit_ready = on_edit_ready.clone();
+            let on_edit_result = on_edit_result.clone();
             let user_state = (*user_state).clone();
             
             // If the user disconnects the switch when a connection is still being attempted,
@@ -959,6 +1671,12 @@ impl ActiveConnection {
             // left to die without any error messages (irrespective of whether it was successful)
             // since the user turning off the switch should mean the user gave up on the connection.
             let mut attempting_conn = false;
+
+            let tunnel = tunnel.clone();
+
+            // Set when the user disconnects, so that a tunnel that finishes opening after
+            // that is closed by the connecting thread.
+            let mut tunnel_abort : Arc<AtomicBool> = Default::default();
             
             move |action| {
                 match action {
@@ -976,13 +1694,45 @@ impl ActiveConnection {
                             return glib::Continue(true);
                         }
                         attempting_conn = true;
+                        tunnel_abort = Default::default();
                         
                         // Spawn a thread that captures the database connection URI. The URI
                         // carrying the password is forgotten when this thread dies.
                         thread::spawn({
                             let send = send.clone();
                             let us : UserState = user_state.borrow().clone();
+                            let tunnel = tunnel.clone();
+                            let tunnel_abort = tunnel_abort.clone();
                             move || {
+                                let mut uri = uri;
+                                if let Some(jump) = uri.info.ssh.clone() {
+                                    match open_tunnel(&jump, &uri.info) {
+                                        Ok(opened) => {
+                                            uri.local_port = Some(opened.local_port());
+
+                                            // The flag is read with the lock held, so either the tunnel is stored
+                                            // before a disconnection closes it, or it is dropped (closed) here.
+                                            let stored = match tunnel.lock() {
+                                                Ok(mut tunnel) if !tunnel_abort.load(Ordering::SeqCst) => {
+                                                    *tunnel = Some(opened);
+                                                    true
+                                                },
+                                                _ => false
+                                            };
+                                            if !stored {
+                                                send.send(ActiveConnectionAction::ConnectFailure(
+                                                    uri.info.clone(),
+                                                    format!("Connection attempt cancelled")
+                                                )).unwrap();
+                                                return;
+                                            }
+                                        },
+                                        Err(e) => {
+                                            send.send(ActiveConnectionAction::ConnectFailure(uri.info.clone(), e)).unwrap();
+                                            return;
+                                        }
+                                    }
+                                }
                                 match uri.info.engine {
                                     Engine::Postgres => {
                                         connect(PostgresConnection::try_new, uri.clone(), send.clone(), &us);
@@ -990,10 +1740,20 @@ impl ActiveConnection {
                                     Engine::SQLite => {
                                         connect(SqliteConnection::try_new, uri.clone(), send.clone(), &us);
                                     },
-                                    other_engine => {
+                                    Engine::MySQL => {
+                                        connect(MySqlConnection::try_new, uri.clone(), send.clone(), &us);
+                                    },
+
+                                    #[cfg(feature="arrowext")]
+                                    Engine::DataFusion => {
+                                        connect(ArrowConnection::try_new, uri.clone(), send.clone(), &us);
+                                    },
+
+                                    #[cfg(not(feature="arrowext"))]
+                                    Engine::DataFusion => {
                                         send.send(ActiveConnectionAction::ConnectFailure(
                                             uri.info.clone(),
-                                            format!("Unsupported engine: {}", other_engine)
+                                            format!("Queries was built without the arrowext feature")
                                         )).unwrap();
                                     }
                                 }
@@ -1008,26 +1768,66 @@ impl ActiveConnection {
                         schema = db_info.as_ref().map(|info| info.schema.clone() );
                         selected_obj = None;
                         let info = conn.conn_info();
+                        conn_engine = Some(info.engine);
                         if let Err(e) = listener.update_engine(conn) {
                             eprintln!("{}", e);
                         }
-                        on_connected.call((info, db_info));
-                    },
-                    
-                    ActiveConnectionAction::Disconnect => {
-                        // This means the switch has been turned off while the application
-                        // was still trying to make a connection. Send a message to ignore
-                        // this connection, so that the connecting threads does not send a
-                        // connect_accepted message back.
-                        schema = None;
-                        selected_obj = None;
-                        active_schedule.replace(false);
-                        on_disconnected.call(());
+                        on_connected.call((info, db_info));
+                    },
+                    
+                    ActiveConnectionAction::Disconnect => {
+                        // This means the switch has been turned off while the application
+                        // was still trying to make a connection. Send a message to ignore
+                        // this connection, so that the connecting threads does not send a
+                        // connect_accepted message back.
+                        schema = None;
+                        selected_obj = None;
+                        conn_engine = None;
+                        active_schedule.replace(false);
+                        tunnel_abort.store(true, Ordering::SeqCst);
+                        close_tunnel(&tunnel);
+                        on_disconnected.call(());
+                    },
+                    
+                    // When the user clicks the exec button or activates the execute action.
+                    ActiveConnectionAction::ExecutionRequest(stmts) => {
+                    
+                        if !schema_valid {
+                            on_error.call(format!("Cannot execute command right now (schema update pending)"));
+                            return glib::Continue(true);
+                        }
+
+                        if *(active_schedule.borrow()) {
+                            on_error.call(format!("Attempted to execute statement during active schedule"));
+                            return glib::Continue(true);
+                        }
+                        
+                        if listener.is_running() {
+                            // This shouldn't happen. The user is prevented from sending statements
+                            // when the engine is working.
+                            on_error.call(format!("Previous statement not completed yet."));
+                            return glib::Continue(true);
+                        }
+
+                        // Placeholders without a declared value are informed by the
+                        // user, and the statements are re-sent as a parametrized request.
+                        let unbound = crate::sql::params::unbound_params(&stmts);
+                        if unbound.len() > 0 {
+                            on_params_required.call((stmts, unbound));
+                            return glib::Continue(true);
+                        }
+
+                        let us = user_state.borrow();
+                        match listener.send_commands(stmts, HashMap::new(), us.safety(), false) {
+                            Ok(_) => { },
+                            Err(e) => {
+                                on_error.call(e.clone());
+                            }
+                        }
                     },
-                    
-                    // When the user clicks the exec button or activates the execute action.
-                    ActiveConnectionAction::ExecutionRequest(stmts) => {
-                    
+
+                    ActiveConnectionAction::ParametrizedExecutionRequest(stmts, subs) => {
+
                         if !schema_valid {
                             on_error.call(format!("Cannot execute command right now (schema update pending)"));
                             return glib::Continue(true);
@@ -1037,16 +1837,14 @@ impl ActiveConnection {
                             on_error.call(format!("Attempted to execute statement during active schedule"));
                             return glib::Continue(true);
                         }
-                        
+
                         if listener.is_running() {
-                            // This shouldn't happen. The user is prevented from sending statements
-                            // when the engine is working.
                             on_error.call(format!("Previous statement not completed yet."));
                             return glib::Continue(true);
                         }
 
                         let us = user_state.borrow();
-                        match listener.send_commands(stmts, /*HashMap::new(),*/ us.safety(), false) {
+                        match listener.send_commands(stmts, subs, us.safety(), false) {
                             Ok(_) => { },
                             Err(e) => {
                                 on_error.call(e.clone());
@@ -1120,7 +1918,7 @@ impl ActiveConnection {
                                 }
                                 let send_ans = listener.send_commands(
                                     stmts.clone(),
-                                    //HashMap::new(),
+                                    HashMap::new(),
                                     us.safety(),
                                     true
                                 );
@@ -1137,6 +1935,74 @@ impl ActiveConnection {
                         });
                         on_schedule_start.call(());
                     },
+
+                    // Execute action was clicked while execution mode is set to notification.
+                    ActiveConnectionAction::StartNotificationSchedule(stmts) => {
+
+                        if *(active_schedule.borrow()) {
+                            on_error.call(format!("Tried to start schedule twice"));
+                            return glib::Continue(true);
+                        }
+
+                        if listener.is_running() {
+                            on_error.call(format!("Previous statement not completed yet."));
+                            return glib::Continue(true);
+                        }
+
+                        let (channel, filter) = {
+                            let us = user_state.borrow();
+                            (us.execution.notification_channel.clone(), us.execution.notification_filter.clone())
+                        };
+                        if channel.trim().is_empty() {
+                            on_error.call(format!("No notification channel configured at the settings"));
+                            on_schedule_end.call(());
+                            return glib::Continue(true);
+                        }
+                        if let Err(e) = listener.listen_to_notification(&channel) {
+                            on_error.call(e);
+                            on_schedule_end.call(());
+                            return glib::Continue(true);
+                        }
+
+                        // Notifications are polled at a fixed interval. The schedule interval
+                        // does not apply here, since executions should follow the notifications closely.
+                        active_schedule.replace(true);
+                        glib::timeout_add_local(Duration::from_millis(NOTIFICATION_POLL_INTERVAL), {
+                            let active_schedule = active_schedule.clone();
+                            let listener = listener.clone();
+                            let user_state = user_state.clone();
+                            let send = send.clone();
+                            move || {
+
+                                // A statement might still be running when the schedule is ended,
+                                // so the channel is only released when the engine is free.
+                                if listener.is_running() {
+                                    return Continue(true);
+                                }
+
+                                if !*active_schedule.borrow() {
+                                    listener.clear_notifications();
+                                    return Continue(false);
+                                }
+
+                                if !listener.has_notification_queued(&channel, &filter) {
+                                    return Continue(true);
+                                }
+
+                                let us = user_state.borrow();
+                                match listener.send_commands(stmts.clone(), HashMap::new(), us.safety(), true) {
+                                    Ok(_) => {
+                                        Continue(true)
+                                    },
+                                    Err(e) => {
+                                        send.send(ActiveConnectionAction::Error(e)).unwrap();
+                                        Continue(true)
+                                    }
+                                }
+                            }
+                        });
+                        on_schedule_start.call(());
+                    },
                     
                     // Execution was un-toggled in scheduled mode.
                     ActiveConnectionAction::EndSchedule => {
@@ -1151,23 +2017,23 @@ impl ActiveConnection {
                     },
                     
                     // Table import at the schema tree.
-                    ActiveConnectionAction::TableImport(csv_path) => {
+                    ActiveConnectionAction::TableImport(csv_path, opts) => {
                         if let Some(obj) = &selected_obj {
                             match obj {
                                 DBObject::Table { name, .. } => {
-                                    let copy = Copy {
-                                        table : name.clone(),
-                                        target : CopyTarget::From,
-                                        cols : Vec::new(),
-                                        options : String::new(),
-                                        client : CopyClient::Stdio
-                                    };
+                                    spawn_table_import(&listener, &send, csv_path, opts, name.clone());
+                                },
+
+                                // Importing into a schema creates a new table named after the file,
+                                // after the user reviews the inferred column types.
+                                DBObject::Schema { name, .. } => {
+                                    let dst = import_table_name(&csv_path, &name);
                                     let send = send.clone();
-                                    listener.spawn_import_and_then(csv_path, copy, move |ans| {
+                                    import_opts = opts.clone();
+                                    listener.spawn_import_preview(csv_path.clone(), opts, dst.clone(), move |ans| {
                                         match ans {
-                                            Ok(n) => {
-                                                let msg = format!("{} row(s) imported", n);
-                                                send.send(ActiveConnectionAction::ExecutionCompleted(vec![StatementOutput::Statement(msg)])).unwrap();
+                                            Ok(sql) => {
+                                                send.send(ActiveConnectionAction::ImportPreview(csv_path.clone(), dst.clone(), sql)).unwrap();
                                             },
                                             Err(e) => {
                                                 send.send(ActiveConnectionAction::Error(e)).unwrap();
@@ -1179,9 +2045,91 @@ impl ActiveConnection {
                             }
                         }
                     },
-                    
+
+                    ActiveConnectionAction::ImportPreview(path, dst, sql) => {
+                        on_import_preview.call((path, dst, sql));
+                    },
+
+                    ActiveConnectionAction::ImportConfirm(path, dst) => {
+                        spawn_table_import(&listener, &send, path, import_opts.clone(), dst);
+                    },
+
+                    ActiveConnectionAction::ImportProgress(n, total) => {
+                        on_import_progress.call((n, total));
+                    },
+
+                    // Only results of queries over a single table (without joins) are editable.
+                    ActiveConnectionAction::EditRequest(query) => {
+                        let name = crate::sql::table_name_from_sql(&query)
+                            .filter(|(_, relation)| relation.is_empty() )
+                            .map(|(name, _)| name );
+                        let obj = match (&schema, name) {
+                            (Some(schema), Some(name)) => crate::sql::edit::find_table(&schema[..], &name).map(|obj| obj.clone() ),
+                            _ => Err(format!("Only results of queries over a single table can be edited"))
+                        };
+                        match (obj, conn_engine) {
+                            (Ok(obj), Some(engine)) => {
+                                on_edit_ready.call((query, obj, engine));
+                            },
+                            (Err(e), _) => {
+                                on_error.call(e);
+                            },
+                            _ => {
+                                on_error.call(format!("Only results of queries over a single table can be edited"));
+                            }
+                        }
+                    },
+
+                    ActiveConnectionAction::EditApply(query, edit) => {
+
+                        if !schema_valid {
+                            on_error.call(format!("Cannot execute command right now (schema update pending)"));
+                            return glib::Continue(true);
+                        }
+
+                        if *(active_schedule.borrow()) {
+                            on_error.call(format!("Attempted to execute statement during active schedule"));
+                            return glib::Continue(true);
+                        }
+
+                        if listener.is_running() {
+                            on_error.call(format!("Previous statement not completed yet."));
+                            return glib::Continue(true);
+                        }
+
+                        let safety = user_state.borrow().safety();
+                        match edit.transaction(&safety) {
+                            Ok(stmt) => {
+                                let send = send.clone();
+                                listener.spawn_edit(stmt, query.clone(), move |ans| {
+                                    send.send(ActiveConnectionAction::EditCompleted(query.clone(), ans)).unwrap();
+                                });
+                            },
+                            Err(e) => {
+                                on_error.call(e);
+                            }
+                        }
+                    },
+
+                    ActiveConnectionAction::EditCompleted(query, ans) => {
+                        match ans {
+                            Ok(tbl) => {
+                                on_edit_result.call((query, tbl));
+                            },
+                            Err(e) => {
+                                on_error.call(e);
+                            }
+                        }
+                    },
+
+                    ActiveConnectionAction::CancelRequest => {
+                        if let Err(e) = listener.cancel() {
+                            on_error.call(e);
+                        }
+                    },
+
                     // A new set of results arrived to the client.
-                    ActiveConnectionAction::ExecutionCompleted(results) => {
+                    ActiveConnectionAction::ExecutionCompleted(results, paged) => {
                         
                         let fst_error = results.iter()
                             .filter_map(|res| {
@@ -1198,7 +2146,17 @@ impl ActiveConnection {
                             }
                         
                         } else {
+                            on_paged_results.call(paged.clone());
                             on_exec_result.call(results.clone());
+
+                            // Cancelling a scheduled execution also ends the schedule.
+                            let any_cancelled = results.iter().any(|res| match res {
+                                StatementOutput::Cancelled(_) => true,
+                                _ => false
+                            });
+                            if any_cancelled && *(active_schedule.borrow()) == true {
+                                send.send(ActiveConnectionAction::EndSchedule).unwrap();
+                            }
                         }
                         
                         // This will block any new user statements until the schema information is updated.
@@ -1237,6 +2195,30 @@ impl ActiveConnection {
                         }
                     },
                     
+                    ActiveConnectionAction::PageRequest(ix) => {
+
+                        // The page is requested again when the user navigates the table after
+                        // the current statement is completed.
+                        if listener.is_running() || *(active_schedule.borrow()) {
+                            return glib::Continue(true);
+                        }
+                        let send = send.clone();
+                        listener.spawn_fetch_page(ix, move |ans| {
+                            send.send(ActiveConnectionAction::PageCompleted(ix, ans)).unwrap();
+                        });
+                    },
+
+                    ActiveConnectionAction::PageCompleted(ix, ans) => {
+                        match ans {
+                            Ok(page) => {
+                                on_page_result.call((ix, page));
+                            },
+                            Err(e) => {
+                                on_error.call(e);
+                            }
+                        }
+                    },
+
                     // Schema update after a DDL statement was executed by queries.
                     ActiveConnectionAction::SchemaUpdate(opt_schema) => {
                         schema_valid = true;
@@ -1259,6 +2241,7 @@ impl ActiveConnection {
                     
                     ActiveConnectionAction::ConnectFailure(info, e) => {
                         attempting_conn = false;
+                        close_tunnel(&tunnel);
                         on_conn_failure.call((info, e.clone()));
                     },
                     
@@ -1280,13 +2263,21 @@ impl ActiveConnection {
             on_error,
             send,
             on_exec_result,
+            on_paged_results,
             on_conn_failure,
             on_schema_update,
             on_object_selected,
             on_single_query_result,
             on_schema_invalidated,
             on_schedule_start,
-            on_schedule_end
+            on_schedule_end,
+            on_params_required,
+            on_page_result,
+            on_import_progress,
+            on_import_preview,
+            on_edit_ready,
+            on_edit_result,
+            tunnel
         }
     }
 
@@ -1371,11 +2362,60 @@ impl ActiveConnection {
         self.on_object_selected.bind(f);
     }
 
+    pub fn connect_params_required<F>(&self, f : F)
+    where
+        F : Fn((String, Vec<String>)) + 'static
+    {
+        self.on_params_required.bind(f);
+    }
+
+    pub fn connect_page_result<F>(&self, f : F)
+    where
+        F : Fn((usize, Table)) + 'static
+    {
+        self.on_page_result.bind(f);
+    }
+
+    pub fn connect_paged_results<F>(&self, f : F)
+    where
+        F : Fn(Vec<usize>) + 'static
+    {
+        self.on_paged_results.bind(f);
+    }
+
+    pub fn connect_import_progress<F>(&self, f : F)
+    where
+        F : Fn((usize, usize)) + 'static
+    {
+        self.on_import_progress.bind(f);
+    }
+
+    pub fn connect_import_preview<F>(&self, f : F)
+    where
+        F : Fn((String, String, String)) + 'static
+    {
+        self.on_import_preview.bind(f);
+    }
+
+    pub fn connect_edit_ready<F>(&self, f : F)
+    where
+        F : Fn((String, DBObject, Engine)) + 'static
+    {
+        self.on_edit_ready.bind(f);
+    }
+
+    pub fn connect_edit_result<F>(&self, f : F)
+    where
+        F : Fn((String, Table)) + 'static
+    {
+        self.on_edit_result.bind(f);
+    }
+
 }
 
 const CONN_NAME_ERR : &str = "Application name at settings contain non-alphanumeric characters";
 
-fn augment_uri_with_params(
+pub(crate) fn augment_uri_with_params(
     uri : &mut String,
     extra_args : &[String]
 ) -> Result<(), std::boxed::Box<dyn Error>> {
@@ -1428,11 +2468,10 @@ where
                 }
             };
 
-            if timeout_secs > 0 {
-                conn.configure(ConnConfig {
-                    timeout : timeout_secs as usize * 1000
-                });
-            }
+            conn.configure(ConnConfig {
+                timeout : timeout_secs as usize * 1000,
+                page_size : us.execution.page_size.max(0) as usize
+            });
 
             // From now on, the URI is forgotten (no password is kept in memory anymore), and only the
             // database info and details are sent back to the main thread.
@@ -1444,6 +2483,23 @@ where
     }
 }
 
+fn open_tunnel(jump : &SshJump, info : &ConnectionInfo) -> Result<SshTunnel, String> {
+    if info.engine != Engine::Postgres {
+        return Err(format!("SSH tunnels are only supported for Postgres connections"));
+    }
+    if info.is_socket() {
+        return Err(format!("SSH tunnels cannot be used with Unix socket connections"));
+    }
+    let port = u16::from_str(&info.port[..]).map_err(|_| format!("Invalid port value") )?;
+    SshTunnel::open(jump, &info.host, port)
+}
+
+fn close_tunnel(tunnel : &Arc<Mutex<Option<SshTunnel>>>) {
+    if let Ok(mut tunnel) = tunnel.lock() {
+        tunnel.take();
+    }
+}
+
 // Returns extra arguments to the connection string based on connection state and security settings
 pub fn get_user_state_conn_params(us : &UserState, sec : &Security) -> Result<Vec<String>, String> {
     let mut extra_args = Vec::new();
@@ -1456,14 +2512,30 @@ pub fn get_user_state_conn_params(us : &UserState, sec : &Security) -> Result<Ve
         }
     }
     extra_args.push(format!("connect_timeout={}", us.conn.timeout));
-    if sec.tls_version.is_some() {
-        extra_args.push(format!("sslmode=require"));
-    } else {
-        extra_args.push(format!("sslmode=prefer"));
-    }
+    extra_args.push(format!("sslmode={}", sec.effective_ssl_mode().url_param()));
     Ok(extra_args)
 }
 
+/* Parameters informed with the connection (e.g. at a pasted connection string) take
+precedence over the parameters set at the user settings. */
+pub fn merge_conn_params(user_params : Vec<String>, info : &ConnectionInfo) -> Vec<String> {
+    let mut params : Vec<String> = user_params.into_iter()
+        .filter(|p| !info.params.iter().any(|(k, _)| p.starts_with(&format!("{}=", k)) ) )
+        .collect();
+    params.extend(info.query_params());
+    params
+}
+
+impl React<crate::ui::QueriesWindow> for ActiveConnection {
+
+    fn react(&self, win : &crate::ui::QueriesWindow) {
+        // The ssh client is a separate process, which would outlive the application.
+        let tunnel = self.tunnel.clone();
+        win.window.connect_destroy(move |_| close_tunnel(&tunnel) );
+    }
+
+}
+
 impl React<ConnectionBox> for ActiveConnection {
 
     fn react(&self, conn_bx : &ConnectionBox) {
@@ -1474,6 +2546,7 @@ impl React<ConnectionBox> for ActiveConnection {
             conn_bx.user.entry.clone(),
             conn_bx.password.entry.clone()
         );
+        let engine_combo = conn_bx.engine.clone();
         let send = self.send.clone();
         let user_state = self.user_state.clone();
         conn_bx.switch.connect_state_set(move |switch, _state| {
@@ -1486,7 +2559,8 @@ impl React<ConnectionBox> for ActiveConnection {
                     &port_entry,
                     &db_entry,
                     &user_entry,
-                    &password_entry
+                    &password_entry,
+                    &engine_combo
                 );
                 match res_form_uri {
                     Ok(uri) => {
@@ -1503,8 +2577,10 @@ impl React<ConnectionBox> for ActiveConnection {
                             send_sqlite_conn_request(uri.clone(), &send)
                         } else if uri.is_postgres() {
                             send_postgres_conn_request(uri, &user_state, &send)
+                        } else if uri.is_mysql() {
+                            send_mysql_conn_request(uri, &user_state, &send)
                         } else {
-                            let info = extract_conn_info(&host_entry, &port_entry, &db_entry, &user_entry).unwrap_or_default();
+                            let info = extract_conn_info(&host_entry, &port_entry, &db_entry, &user_entry, &engine_combo).unwrap_or_default();
                             send.send(ActiveConnectionAction::ConnectFailure(info, "Unrecognized engine".into())).unwrap();
                             false
                         };
@@ -1514,7 +2590,7 @@ impl React<ConnectionBox> for ActiveConnection {
                         }
                     },
                     Err(e) => {
-                        let info = extract_conn_info(&host_entry, &port_entry, &db_entry, &user_entry).unwrap_or_default();
+                        let info = extract_conn_info(&host_entry, &port_entry, &db_entry, &user_entry, &engine_combo).unwrap_or_default();
                         send.send(ActiveConnectionAction::ConnectFailure(info, e)).unwrap();
                     }
                 }
@@ -1534,6 +2610,36 @@ fn send_sqlite_conn_request(
     true
 }
 
+/* MySQL connections are configured when the connection is established, so the URL
+does not carry any parameters. Only the security settings are linked to the URI. */
+fn send_mysql_conn_request(
+    mut uri : ConnURI,
+    user_state : &SharedUserState,
+    send : &glib::Sender<ActiveConnectionAction>
+) -> bool {
+    let us = user_state.borrow();
+    crate::client::assert_user_state_integrity(&us);
+    if let Some(c) = us.conns.iter().find(|c| &c.host[..] == &uri.info.host[..] ) {
+        uri.info.security = c.security.clone();
+    } else {
+        send.send(ActiveConnectionAction::ConnectFailure(
+            uri.info.clone(),
+            format!("Security not configured for this host"))
+        ).unwrap();
+        return false;
+    }
+    match uri.verify_integrity() {
+        Ok(_) => {
+            send.send(ActiveConnectionAction::ConnectRequest(uri)).unwrap();
+            true
+        },
+        Err(e) => {
+            send.send(ActiveConnectionAction::ConnectFailure(uri.info.clone(), format!("{}", e))).unwrap();
+            false
+        }
+    }
+}
+
 fn send_postgres_conn_request(
     mut uri : ConnURI,
     user_state : &SharedUserState,
@@ -1557,8 +2663,16 @@ fn send_postgres_conn_request(
     // Now link the security information to the established connection.
     uri.info.security = security;
 
+    // The connection parameters are not shown at the form, so they are taken from the
+    // saved connection with the same credentials.
+    if let Some(c) = us.conns.iter().find(|c| c.is_like(&uri.info) ) {
+        uri.info.params = c.params.clone();
+        uri.info.ssh = c.ssh.clone();
+    }
+
     match get_user_state_conn_params(&us, &uri.info.security) {
         Ok(params) => {
+            let params = merge_conn_params(params, &uri.info);
             let mut uri_str = uri.uri.as_str().to_string();
             match augment_uri_with_params(&mut uri_str, &params[..]) {
                 Ok(_) => { },
@@ -1616,6 +2730,7 @@ impl React<ExecButton> for ActiveConnection {
     fn react(&self, btn : &ExecButton) {
         let send = self.send.clone();
         let schedule_action = btn.schedule_action.clone();
+        let notification_action = btn.notification_action.clone();
         let is_scheduled = Rc::new(RefCell::new(false));
         btn.exec_action.connect_activate({
             let is_scheduled = is_scheduled.clone();
@@ -1632,10 +2747,15 @@ impl React<ExecButton> for ActiveConnection {
 
                     let stmts = param.unwrap().get::<String>().unwrap();
                     let must_schedule = schedule_action.state().unwrap().get::<bool>().unwrap();
+                    let must_listen = notification_action.state().unwrap().get::<bool>().unwrap();
                     if must_schedule {
                         exec_btn.set_icon_name("clock-app-symbolic");
                         *is_scheduled = true;
                         send.send(ActiveConnectionAction::StartSchedule(stmts)).unwrap();
+                    } else if must_listen {
+                        exec_btn.set_icon_name("clock-app-symbolic");
+                        *is_scheduled = true;
+                        send.send(ActiveConnectionAction::StartNotificationSchedule(stmts)).unwrap();
                     } else {
                         send.send(ActiveConnectionAction::ExecutionRequest(stmts)).unwrap();
                     }
@@ -1655,6 +2775,42 @@ impl React<ExecButton> for ActiveConnection {
              }
         });
 
+        btn.cancel_action.connect_activate({
+            let send = self.send.clone();
+            move |_, _| {
+                send.send(ActiveConnectionAction::CancelRequest).unwrap();
+            }
+        });
+
+        // Holds the SQL and placeholder names while the user informs the parameter values.
+        let pending_params : Rc<RefCell<Option<(String, Vec<String>)>>> = Rc::new(RefCell::new(None));
+        self.connect_params_required({
+            let form = btn.params_form.clone();
+            let pending_params = pending_params.clone();
+            move |(sql, names)| {
+                if form.update_from_params(&names[..]) {
+                    pending_params.replace(Some((sql, names)));
+                    form.dialog.show();
+                }
+            }
+        });
+
+        btn.params_form.btn_ok.connect_clicked({
+            let send = self.send.clone();
+            let form = btn.params_form.clone();
+            let pending_params = pending_params.clone();
+            move |_| {
+                if let Some((sql, names)) = pending_params.borrow_mut().take() {
+                    let subs : HashMap<String, String> = names.iter()
+                        .zip(form.entries.iter())
+                        .map(|(name, entry)| (name.clone(), entry.text().to_string()) )
+                        .collect();
+                    send.send(ActiveConnectionAction::ParametrizedExecutionRequest(sql, subs)).unwrap();
+                }
+                form.dialog.close();
+            }
+        });
+
     }
 
 }
@@ -1686,6 +2842,37 @@ impl React<QueryBuilderWindow> for ActiveConnection {
 
 }
 
+impl React<QueriesWorkspace> for ActiveConnection {
+
+    fn react(&self, ws : &QueriesWorkspace) {
+        let send = self.send.clone();
+        ws.page_action.connect_activate(move |_action, param| {
+            if let Some(ix) = param.and_then(|p| p.get::<u64>() ) {
+                send.send(ActiveConnectionAction::PageRequest(ix as usize)).unwrap();
+            }
+        });
+        ws.edit_action.connect_activate({
+            let send = self.send.clone();
+            move |_action, param| {
+                if let Some(query) = param.and_then(|p| p.get::<String>() ) {
+                    send.send(ActiveConnectionAction::EditRequest(query)).unwrap();
+                }
+            }
+        });
+        ws.edit_dialog.btn_apply.connect_clicked({
+            let send = self.send.clone();
+            let preview = ws.edit_dialog.clone();
+            move |_| {
+                if let Some((query, edit)) = preview.pending.borrow_mut().take() {
+                    send.send(ActiveConnectionAction::EditApply(query, edit)).unwrap();
+                }
+                preview.dialog.close();
+            }
+        });
+    }
+
+}
+
 impl React<SchemaTree> for ActiveConnection {
 
     fn react(&self, tree : &SchemaTree) {
@@ -1831,13 +3018,76 @@ impl React<SchemaTree> for ActiveConnection {
                 match resp {
                     ResponseType::Accept => {
                         if let Some(path) = dialog.file().and_then(|f| f.path() ) {
-                            send.send(ActiveConnectionAction::TableImport(path.to_str().unwrap().to_string())).unwrap();
+                            let opts = crate::ui::ImportDialog::csv_options(dialog);
+                            send.send(ActiveConnectionAction::TableImport(path.to_str().unwrap().to_string(), opts)).unwrap();
                         }
                     },
                     _ => { }
                 }
             }
         });
+
+        tree.import_preview_dialog.btn_import.connect_clicked({
+            let send = self.send.clone();
+            let preview = tree.import_preview_dialog.clone();
+            move |_| {
+                if let Some((path, dst)) = preview.pending.borrow_mut().take() {
+                    send.send(ActiveConnectionAction::ImportConfirm(path, dst)).unwrap();
+                }
+                preview.dialog.close();
+            }
+        });
+    }
+}
+
+fn spawn_table_import(
+    listener : &SqlListener,
+    send : &glib::Sender<ActiveConnectionAction>,
+    path : String,
+    opts : CsvOptions,
+    table : String
+) {
+    let copy = Copy {
+        table,
+        target : CopyTarget::From,
+        cols : Vec::new(),
+        options : String::new(),
+        client : CopyClient::Stdio
+    };
+    let progress_send = send.clone();
+    let send = send.clone();
+    let progress = move |n, total| {
+        progress_send.send(ActiveConnectionAction::ImportProgress(n, total)).unwrap();
+    };
+    listener.spawn_import_and_then(path, opts, copy, progress, move |ans| {
+        match ans {
+            Ok(n) => {
+                let msg = format!("{} row(s) imported", n);
+                send.send(ActiveConnectionAction::ExecutionCompleted(vec![StatementOutput::Statement(msg)], Vec::new())).unwrap();
+            },
+            Err(e) => {
+                send.send(ActiveConnectionAction::Error(e)).unwrap();
+            }
+        }
+    });
+}
+
+/* Names the table created when a file is imported into a schema after the file stem, as
+a lowercase identifier that does not need quoting. */
+fn import_table_name(path : &str, schema : &str) -> String {
+    let stem = std::path::Path::new(path).file_stem()
+        .and_then(|s| s.to_str() )
+        .unwrap_or("imported");
+    let mut name : String = stem.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' } )
+        .collect();
+    if name.is_empty() || name.starts_with(|c : char| c.is_ascii_digit() ) {
+        name = format!("t_{}", name);
+    }
+    if schema.is_empty() || schema == "public" || schema == "main" {
+        name
+    } else {
+        format!("{}.{}", schema, name)
     }
 }
 
@@ -1883,7 +3133,7 @@ fn text_to_sql_literal(entry : &Entry, ty : &DBType) -> Result<String, String> {
         // Quote literals from types in the first branch, do not
         // quote literals from types in the second branch.
         let desired_lit = match ty {
-            DBType::Text | DBType::Date | DBType::Time | DBType::Bytes |
+            DBType::Text | DBType::Date | DBType::Time | DBType::Timestamp | DBType::Bytes |
             DBType::Json | DBType::Xml | DBType::Array | DBType::Bool => {
                 format!("'{}'", entry_s.trim())
             },
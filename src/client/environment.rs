@@ -12,13 +12,14 @@ use crate::tables::table::Table;
 use stateful::React;
 use stateful::Callbacks;
 use crate::client::ActiveConnection;
-use crate::tables::table::TableSettings;
+use crate::tables::table::{TableSettings, JsonLayout};
 use crate::tables::table::Columns;
 use papyri::render::Panel;
 use crate::ui::QueriesWorkspace;
 use std::io::Write;
 use std::thread;
-use crate::ui::ExportDialog;
+use crate::ui::{ExportDialog, ImportDialog};
+use crate::tables::dialect::CsvOptions;
 use crate::client::ExecutionSettings;
 use crate::client::SharedUserState;
 use crate::ui::ExecButton;
@@ -49,7 +50,25 @@ pub enum EnvironmentAction {
 
     ChangeSetting(ExecutionSettings),
 
-    ExportError(String)
+    ExportError(String),
+
+    /// Carries the indices of the results with rows yet to be fetched. Sent before the
+    /// Update carrying the results.
+    PagedResults(Vec<usize>),
+
+    /// Carries the next page of rows of the table returned by the result at the given index.
+    AppendPage(usize, Table),
+
+    /// Request to read the .csv, .parquet or .arrow file at the given path into the workspace.
+    ImportRequest(String, CsvOptions),
+
+    /// Carries the path and the table read from it.
+    ImportCompleted(String, Table),
+
+    ImportError(String),
+
+    /// Carries a summary of a result table, which is shown at a new page after the result pages.
+    AddSummary(Table)
 
 }
 
@@ -59,7 +78,12 @@ pub struct Environment {
     
     send : glib::Sender<EnvironmentAction>,
 
-    on_tbl_update : Callbacks<Vec<Table>>,
+    // Called with the tables, the queries that returned them and the indices of the
+    // results whose remaining rows can still be fetched (None for tables holding all their rows).
+    on_tbl_update : Callbacks<(Vec<Table>, Vec<String>, Vec<Option<usize>>)>,
+
+    // Called with the index of a table and its next page of rows.
+    on_page_update : Callbacks<(usize, Table)>,
 
     on_tbl_error : Callbacks<String>,
 
@@ -73,18 +97,30 @@ impl Environment {
         let (send, recv) = glib::MainContext::channel::<EnvironmentAction>(glib::PRIORITY_DEFAULT);
         let mut tables = Tables::new();
         let mut plots = Plots::new();
-        let on_tbl_update : Callbacks<Vec<Table>> = Default::default();
+        let on_tbl_update : Callbacks<(Vec<Table>, Vec<String>, Vec<Option<usize>>)> = Default::default();
+        let on_page_update : Callbacks<(usize, Table)> = Default::default();
         let on_export_error : Callbacks<String> = Default::default();
         let on_tbl_error : Callbacks<String> = Default::default();
         let mut selected : Option<usize> = None;
+
+        // Summaries shown at the pages following the tables. Those pages are closed
+        // whenever the tables are updated.
+        let mut summaries : Vec<Table> = Vec::new();
+
+        let mut paged_results : Vec<usize> = Vec::new();
         recv.attach(None, {
             let on_tbl_update = on_tbl_update.clone();
+            let on_page_update = on_page_update.clone();
             let on_export_error = on_export_error.clone();
             let on_tbl_error = on_tbl_error.clone();
             let send = send.clone();
             move |action| {
                 match action {
+                    EnvironmentAction::PagedResults(paged) => {
+                        paged_results = paged;
+                    },
                     EnvironmentAction::Update(results) => {
+                        let paged = std::mem::take(&mut paged_results);
                         let has_error = results.iter().filter(|res| {
                             match res {
                                 StatementOutput::Invalid(_, _) => true,
@@ -92,11 +128,12 @@ impl Environment {
                             }
                         }).next().is_some();
                         if !has_error {
-                            tables.update_from_query_results(results);
+                            summaries.clear();
+                            tables.update_from_query_results(results, &paged[..]);
                             match plots.update_from_tables(&tables.tables[..]) {
                                 Ok(_) => {
                                     if tables.tables.len() >= 1 {
-                                        on_tbl_update.call(tables.tables.clone());
+                                        on_tbl_update.call((tables.tables.clone(), tables.queries.clone(), tables.pages.clone()));
                                     }
                                 },
                                 Err(e) => {
@@ -108,7 +145,8 @@ impl Environment {
                     EnvironmentAction::Restore => {
                         // Use the last state set at EnvironmentAction::Update.
                         if tables.tables.len() >= 1 {
-                            on_tbl_update.call(tables.tables.clone());
+                            summaries.clear();
+                            on_tbl_update.call((tables.tables.clone(), tables.queries.clone(), tables.pages.clone()));
                         }
                     },
 
@@ -116,15 +154,16 @@ impl Environment {
                         selected = opt_pos;
                     },
                     EnvironmentAction::ExportRequest(path) => {
-                        let item = if let Some(ix) = selected {
+                        let item = selected.and_then(|ix| {
+                            let n = tables.tables.len();
                             if let Some(plot_ix) = plots.ixs.iter().position(|i| *i == ix ) {
-                                Some(ExportItem::Panel(plots.panels[plot_ix].clone()))
-                            } else {
+                                plots.panels.get(plot_ix).cloned().map(ExportItem::Panel)
+                            } else if ix < n {
                                 Some(ExportItem::Table(tables.tables[ix].clone()))
+                            } else {
+                                summaries.get(ix - n).cloned().map(ExportItem::Table)
                             }
-                        } else {
-                            None
-                        };
+                        });
                         if let Some(item) = item {
                             thread::spawn({
                                 let send = send.clone();
@@ -139,6 +178,44 @@ impl Environment {
                     EnvironmentAction::ExportError(msg) => {
                         on_export_error.call(msg.clone());
                     },
+                    EnvironmentAction::ImportRequest(path, opts) => {
+                        thread::spawn({
+                            let send = send.clone();
+                            move || {
+                                match Table::open_with(Path::new(&path), &opts) {
+                                    Ok(tbl) => {
+                                        send.send(EnvironmentAction::ImportCompleted(path, tbl)).unwrap();
+                                    },
+                                    Err(e) => {
+                                        send.send(EnvironmentAction::ImportError(e)).unwrap();
+                                    }
+                                }
+                            }
+                        });
+                    },
+                    EnvironmentAction::ImportCompleted(path, tbl) => {
+                        summaries.clear();
+                        tables.append_external_table(path, tbl);
+                        match plots.update_from_tables(&tables.tables[..]) {
+                            Ok(_) => {
+                                on_tbl_update.call((tables.tables.clone(), tables.queries.clone(), tables.pages.clone()));
+                            },
+                            Err(e) => {
+                                on_tbl_error.call(e.clone());
+                            }
+                        }
+                    },
+                    EnvironmentAction::ImportError(msg) => {
+                        on_tbl_error.call(msg.clone());
+                    },
+                    EnvironmentAction::AppendPage(res_ix, page) => {
+                        if let Some(ix) = tables.append_page(res_ix, &page) {
+                            on_page_update.call((ix, page));
+                        }
+                    },
+                    EnvironmentAction::AddSummary(tbl) => {
+                        summaries.push(tbl);
+                    },
                     EnvironmentAction::ChangeSetting(_setting) => {
 
                     },
@@ -147,16 +224,27 @@ impl Environment {
                 Continue(true)
             }
         });
-        Self { send, on_tbl_update, on_export_error, on_tbl_error, user_state : user_state.clone() }
+        Self { send, on_tbl_update, on_page_update, on_export_error, on_tbl_error, user_state : user_state.clone() }
+    }
+
+    pub fn sender(&self) -> &glib::Sender<EnvironmentAction> {
+        &self.send
     }
 
     pub fn connect_table_update<F>(&self, f : F)
     where
-        F : Fn(Vec<Table>) + 'static
+        F : Fn((Vec<Table>, Vec<String>, Vec<Option<usize>>)) + 'static
     {
         self.on_tbl_update.bind(f);
     }
 
+    pub fn connect_page_update<F>(&self, f : F)
+    where
+        F : Fn((usize, Table)) + 'static
+    {
+        self.on_page_update.bind(f);
+    }
+
     pub fn connect_export_error<F>(&self, f : F)
     where
         F : Fn(String) + 'static
@@ -177,8 +265,20 @@ impl React<ActiveConnection> for Environment {
 
     fn react(&self, conn : &ActiveConnection) {
         let send = self.send.clone();
-        conn.connect_exec_result(move |res : Vec<StatementOutput>| {
-            send.send(EnvironmentAction::Update(res)).unwrap();
+        conn.connect_exec_result({
+            let send = send.clone();
+            move |res : Vec<StatementOutput>| {
+                send.send(EnvironmentAction::Update(res)).unwrap();
+            }
+        });
+        conn.connect_paged_results({
+            let send = send.clone();
+            move |paged| {
+                send.send(EnvironmentAction::PagedResults(paged)).unwrap();
+            }
+        });
+        conn.connect_page_result(move |(ix, page)| {
+            send.send(EnvironmentAction::AppendPage(ix, page)).unwrap();
         });
     }
 
@@ -213,6 +313,29 @@ impl React<QueriesWorkspace> for Environment {
 
 }
 
+impl React<ImportDialog> for Environment {
+
+    fn react(&self, dialog : &ImportDialog) {
+        let send = self.send.clone();
+        dialog.dialog.connect_response(move |dialog, resp| {
+            match resp {
+                ResponseType::Accept => {
+                    if let Some(path) = dialog.file().and_then(|f| f.path() ) {
+                        if let Some(p) = path.to_str() {
+                            let opts = ImportDialog::csv_options(dialog);
+                            send.send(EnvironmentAction::ImportRequest(p.to_string(), opts)).unwrap();
+                        } else {
+                            eprintln!("Path is not valid UTF-8")
+                        }
+                    }
+                },
+                _ => { }
+            }
+        });
+    }
+
+}
+
 impl React<ExportDialog> for Environment {
 
     fn react(&self, dialog : &ExportDialog) {
@@ -258,7 +381,23 @@ fn export_to_path(item : ExportItem, path : &Path) -> Result<(), String> {
                     let s = tbl.to_tex();
                     f.write_all(s.as_bytes()).map_err(|e| format!("Error writing to export file: {}", e) )
                 },
-                _ => Err(format!("Invalid file extension for table export (expected .csv, .md or .tex)"))
+                Some("json") => {
+                    let mut f = File::create(path).map_err(|e| format!("Error creating export file: {}", e) )?;
+                    let s = serde_json::to_string_pretty(&tbl.to_json(JsonLayout::Rows)).unwrap();
+                    f.write_all(s.as_bytes()).map_err(|e| format!("Error writing to export file: {}", e) )
+                },
+                Some("ndjson") => {
+                    let mut f = File::create(path).map_err(|e| format!("Error creating export file: {}", e) )?;
+                    let s = tbl.to_ndjson();
+                    f.write_all(s.as_bytes()).map_err(|e| format!("Error writing to export file: {}", e) )
+                },
+                Some("parquet") => {
+                    crate::tables::arrow::write_parquet(&tbl, path)
+                },
+                Some("arrow") => {
+                    crate::tables::arrow::write_ipc(&tbl, path)
+                },
+                _ => Err(format!("Invalid file extension for table export (expected .csv, .md, .tex, .json, .ndjson, .parquet or .arrow)"))
             }
         },
         ExportItem::Panel(mut panel) => {
@@ -358,6 +497,10 @@ pub struct Tables {
     /// Stores queries which returned successfully. 1:1 correspondence with self.tables
     queries : Vec<String>,
 
+    /// Index of the result that returned each table, if its remaining rows can still
+    /// be fetched. 1:1 correspondence with self.tables
+    pages : Vec<Option<usize>>,
+
     /// Stores message results of non-select statements that returned successfully.
     exec_results : Vec<StatementOutput>,
 
@@ -383,6 +526,7 @@ impl Tables {
             tables : Vec::new(),
             last_update : None,
             queries : Vec::new(),
+            pages : Vec::new(),
             history : vec![EnvironmentUpdate::Clear],
             exec_results : Vec::new(),
         }
@@ -392,13 +536,14 @@ impl Tables {
     /// message encountered by the database. Returns None if there
     /// is no update; Returns the Ok(result) if there is update, potentially
     /// carrying the first error the database encountered. If the update is valid,
-    /// return the update event that happened (Refresh or NewTables).
+    /// return the update event that happened (Refresh or NewTables). The paged
+    /// argument holds the indices of the results with rows yet to be fetched.
     pub fn update_from_query_results(
         &mut self,
-        results : Vec<StatementOutput>
+        results : Vec<StatementOutput>,
+        paged : &[usize]
     ) -> Option<Result<EnvironmentUpdate, ExecutionError>> {
-        self.tables.clear();
-        self.queries.clear();
+        self.clear_tables();
         self.exec_results.clear();
         if results.len() == 0 {
             self.history.push(EnvironmentUpdate::Clear);
@@ -407,16 +552,17 @@ impl Tables {
         let mut new_cols : Vec<Vec<String>> = Vec::new();
         let mut opt_err = None;
         let mut any_valid = false;
-        for r in results {
+        for (ix, r) in results.into_iter().enumerate() {
             match r {
                 StatementOutput::Valid(query, tbl) => {
                     new_cols.push(tbl.names());
                     self.tables.push(tbl);
                     self.queries.push(query.trim().to_string());
+                    self.pages.push(Some(ix).filter(|ix| paged.contains(ix) ));
                     any_valid = true;
                 },
                 StatementOutput::Invalid(msg, is_server) => {
-                    self.tables.clear();
+                    self.clear_tables();
                     self.history.push(EnvironmentUpdate::Clear);
                     opt_err = Some(ExecutionError { msg : msg.clone(), is_server });
                 },
@@ -424,9 +570,10 @@ impl Tables {
                     StatementOutput::Modification(_) | 
                     StatementOutput::Empty | 
                     StatementOutput::Committed(_, _) | 
-                    StatementOutput::RolledBack(_) => 
+                    StatementOutput::RolledBack(_) |
+                    StatementOutput::Cancelled(_) =>
                 {
-                    self.tables.clear();
+                    self.clear_tables();
                     self.exec_results.push(r.clone());
                     self.history.push(EnvironmentUpdate::Clear);
                 },
@@ -481,7 +628,8 @@ impl Tables {
         }
         if let Some(r) = results.last() {
             match r {
-                StatementOutput::Statement(s) | StatementOutput::Committed(s, _) | StatementOutput::RolledBack(s) => {
+                StatementOutput::Statement(s) | StatementOutput::Committed(s, _) |
+                StatementOutput::RolledBack(s) | StatementOutput::Cancelled(s) => {
                     Some(Ok(s.clone()))
                 },
                 StatementOutput::Invalid(e, is_server) => {
@@ -510,6 +658,19 @@ impl Tables {
         &self.queries[..]
     }
 
+    /// Appends a page of rows to the table returned by the result at index res_ix,
+    /// returning the index of the table if it is still held.
+    pub fn append_page(&mut self, res_ix : usize, page : &Table) -> Option<usize> {
+        let ix = self.pages.iter().position(|p| *p == Some(res_ix) )?;
+        match self.tables[ix].append_rows(page) {
+            Ok(_) => Some(ix),
+            Err(e) => {
+                eprintln!("{}", e);
+                None
+            }
+        }
+    }
+
     pub fn current_hist_index(&self) -> usize {
         self.history.len() - 1
     }
@@ -814,27 +975,32 @@ impl Tables {
 
     pub fn append_table_from_text(
         &mut self,
-        _name : Option<String>,
+        name : Option<String>,
         content : String
     ) -> Result<(), &'static str> {
         let t = Table::new_from_text(content)?;
-        self.append_external_table(t)?;
+        self.append_external_table(name.unwrap_or(String::new()), t);
         Ok(())
     }
 
-    // TODO receive function name and arguments
-    // to save it to history.
+    /// Appends a table that was not the result of a query. The source (e.g. the
+    /// file the table was read from) takes the place of the query.
     pub fn append_external_table(
         &mut self,
+        source : String,
         tbl : Table
-    ) -> Result<(), &'static str> {
+    ) {
         self.tables.push(tbl);
+        self.queries.push(source);
+        self.pages.push(None);
         self.history.push(EnvironmentUpdate::NewExternal);
-        Ok(())
     }
 
+    /// Clears the tables, with the queries and pages that correspond to them.
     pub fn clear_tables(&mut self) {
         self.tables.clear();
+        self.queries.clear();
+        self.pages.clear();
     }
 
 
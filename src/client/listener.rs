@@ -5,20 +5,20 @@ For a copy, see http://www.gnu.org/licenses.*/
 
 use std::thread::{self, JoinHandle};
 use crate::sql::{StatementOutput};
+use crate::sql::parsing::AnyStatement;
 use std::sync::{Arc, Mutex, mpsc::{self, Sender, Receiver}};
 use std::collections::HashMap;
-use crate::server::Connection;
+use crate::server::{Connection, Canceller};
 use crate::sql::object::{DBObject, DBInfo};
 use crate::sql::{SafetyLock};
-use std::fs::File;
-use std::io::Read;
 use crate::sql::copy::*;
 use crate::tables::table::*;
+use crate::tables::dialect::CsvOptions;
 
 #[derive(Debug, Clone)]
 pub struct ExecutionRequest {
     sql : String,
-    // subs : HashMap<String, String>,
+    subs : HashMap<String, String>,
     safety : SafetyLock,
     is_schedule : bool,
     mode : ExecMode
@@ -36,6 +36,9 @@ pub struct SqlListener {
 
     listen_channels : Arc<Mutex<Vec<String>>>,
 
+    // Kept outside the engine, which is locked while statements are executed.
+    canceller : Arc<Mutex<Option<Canceller>>>,
+
     _handle : Arc<JoinHandle<()>>
 
 }
@@ -44,6 +47,7 @@ impl SqlListener {
 
     pub fn update_engine(&mut self, engine : Box<dyn Connection>) -> Result<(), String> {
         self.listen_channels.lock().unwrap().clear();
+        *self.canceller.lock().unwrap() = engine.canceller();
         if let Ok(mut old_engine) = self.engine.lock() {
             *old_engine = Some(engine);
             Ok(())
@@ -52,62 +56,70 @@ impl SqlListener {
         }
     }
 
-    pub fn listen_to_notification(&mut self, _channel : &str, _filter : &str) {
-        /*if !self.listen_channels.lock().unwrap().iter().find(|ch| &ch[..] == channel ).is_some() {
-            // self.listener.listen_to_notification(channel);
-            self.listen_channels.push(channel.to_string());
-        }*/
-    }
-
-    pub fn clear_notifications(&self) {
-        /*match *self.engine.lock().unwrap() {
-            SqlEngine::PostgreSql { ref mut channel, .. } => {
-                *channel = None;
-            },
-            _ => { }
-        }*/
+    /// Requests the cancellation of the statement being executed. The statement (and any
+    /// statements that would be executed after it) are reported as cancelled.
+    pub fn cancel(&self) -> Result<(), String> {
+        if !self.is_running() {
+            return Err(format!("No statement being executed"));
+        }
+        match self.canceller.lock().unwrap().as_ref() {
+            Some(canceller) => canceller.cancel(),
+            None => Err(format!("Statement cancellation is unsupported for this engine"))
+        }
     }
 
-    pub fn has_notification_queued(&self, _at_channel : &str, _filter : &str) -> bool {
-        /*if at_channel.is_empty() {
-            return false;
+    /// Starts listening at the channel. Notifications received before this call are discarded.
+    pub fn listen_to_notification(&self, channel : &str) -> Result<(), String> {
+        let mut opt_engine = self.engine.lock()
+            .map_err(|_| format!("Unable to acquire lock over SQL engine"))?;
+        let engine = opt_engine.as_mut()
+            .ok_or(format!("No active connection"))?;
+        engine.listen_at_channel(channel.to_string())?;
+        engine.take_notifications();
+        let mut channels = self.listen_channels.lock().unwrap();
+        if !channels.iter().any(|ch| &ch[..] == channel ) {
+            channels.push(channel.to_string());
         }
-        match *self.engine.lock().unwrap() {
-            SqlEngine::PostgreSql { ref mut channel, .. } => {
-
-                // If there isn't a notification queued yet, queue it and wait for the next iteration.
-                if let Some(ref mut ch) = channel {
-
-                    // Use is requesting a channel different from the configured one. Re-configure
-                    // the channel and returns false, waiting for new notification.
-                    if &ch.0[..] != &at_channel[..] || &ch.1[..] != filter {
-                        ch.0 = format!("{}", at_channel);
-                        ch.1 = format!("{}", filter);
-                        return false;
-                    }
+        Ok(())
+    }
 
-                    // Sets "notification queued" status to false and return true: There
-                    // is a notification queued for now.
-                    if ch.2 {
-                        ch.2 = false;
-                        return true;
+    /// Stops listening at all channels, discarding any queued notifications.
+    pub fn clear_notifications(&self) {
+        let channels : Vec<String> = self.listen_channels.lock().unwrap().drain(..).collect();
+        if let Ok(mut opt_engine) = self.engine.lock() {
+            if let Some(engine) = opt_engine.as_mut() {
+                for ch in channels {
+                    if let Err(e) = engine.unlisten_at_channel(ch) {
+                        eprintln!("{}", e);
                     }
-
-                } else {
-                    *channel = Some((format!("{}", at_channel), format!("{}", filter), false));
                 }
-            },
-            _ => {
-                // Listen only supported on PostgreSQL engine
+                engine.take_notifications();
             }
         }
-        false*/
-        unimplemented!()
+    }
+
+    /// Verifies if a notification matching the filter arrived at the channel since the last
+    /// call. Returns false without waiting if a statement is being executed; the notifications
+    /// remain queued until the next call in this case.
+    pub fn has_notification_queued(&self, at_channel : &str, filter : &str) -> bool {
+        let channel = match crate::sql::notify::validate_channel(at_channel) {
+            Ok(channel) => channel,
+            Err(_) => return false
+        };
+        match self.engine.try_lock() {
+            Ok(mut opt_engine) => match opt_engine.as_mut() {
+                Some(engine) => {
+                    engine.take_notifications().iter().any(|n| n.matches(&channel, filter) )
+                },
+                None => false
+            },
+            Err(_) => false
+        }
     }
 
     pub fn launch<F>(result_cb : F) -> Self
     where
-        F : Fn(Vec<StatementOutput>, ExecMode) + 'static + Send
+        F : Fn(Vec<StatementOutput>, Vec<usize>, ExecMode) + 'static + Send
     {
         let (cmd_tx, cmd_rx) = mpsc::channel::<ExecutionRequest>();
         let engine : Arc<Mutex<Option<Box<dyn Connection>>>> = Arc::new(Mutex::new(None));
@@ -120,13 +132,14 @@ impl SqlListener {
             engine,
             last_cmd : Arc::new(Mutex::new(Vec::new())),
             listen_channels : Arc::new(Mutex::new(Vec::new())),
+            canceller : Arc::new(Mutex::new(None)),
             _handle : Arc::new(handle)
             
         }
     }
 
     pub fn send_single_command(&self, sql : String, safety : SafetyLock) -> Result<(), String> {
-        match self.cmd_sender.send(ExecutionRequest { sql : sql.clone(), /*subs : HashMap::new()*/ safety, is_schedule : false, mode : ExecMode::Single }) {
+        match self.cmd_sender.send(ExecutionRequest { sql : sql.clone(), subs : HashMap::new(), safety, is_schedule : false, mode : ExecMode::Single }) {
             Ok(_) => {
 
             },
@@ -142,7 +155,7 @@ impl SqlListener {
     /// are correctly parsed, send the SQL to the server. If sequence is not
     /// correctly parsed, do not send anything to the server, and return the
     /// error to the user.
-    pub fn send_commands(&self, sql : String, /*subs : HashMap<String, String>,*/ safety : SafetyLock, is_schedule : bool) -> Result<(), String> {
+    pub fn send_commands(&self, sql : String, subs : HashMap<String, String>, safety : SafetyLock, is_schedule : bool) -> Result<(), String> {
 
         // Before sending a command, it might be interesting to check if self.handle.is_running()
         // when this stabilizes at the stdlib. If it is not running (i.e. there is a panic at the
@@ -156,7 +169,7 @@ impl SqlListener {
 
         let request = ExecutionRequest { 
             sql : sql.clone(), 
-            // subs,
+            subs,
             safety, 
             is_schedule,
             mode : ExecMode::Multiple 
@@ -262,17 +275,91 @@ impl SqlListener {
         });
     }
 
+    /// Fetches the next page of rows of the result at index ix of the last execution,
+    /// executing the given closure when the page arrives.
+    pub fn spawn_fetch_page(&self, ix : usize, f : impl Fn(Result<Table, String>) + Send + 'static) {
+        let engine = self.engine.clone();
+        thread::spawn(move|| {
+            if let Ok(mut opt_engine) = engine.lock() {
+                if let Some(engine) = opt_engine.as_mut() {
+                    f(engine.fetch_page(ix));
+                } else {
+                    f(Err(String::from("No active connection to fetch rows")));
+                }
+            } else {
+                eprintln!("Unable to acquire lock over engine");
+            }
+        });
+    }
+
+    /// Applies the changes made to a table as a transaction and, when they are committed,
+    /// queries the table again, executing the given closure with the new rows.
+    pub fn spawn_edit(&self, stmt : AnyStatement, query : String, f : impl Fn(Result<Table, String>) + Send + 'static) {
+        let engine = self.engine.clone();
+        thread::spawn(move|| {
+            if let Ok(mut opt_engine) = engine.lock() {
+                if let Some(engine) = opt_engine.as_mut() {
+                    let ans = match engine.exec_edit(&stmt) {
+                        StatementOutput::Committed(_, _) => match engine.query(&query) {
+                            StatementOutput::Valid(_, tbl) => Ok(tbl),
+                            StatementOutput::Invalid(e, _) => Err(e),
+                            _ => Err(format!("Unable to query table after changes were applied"))
+                        },
+                        StatementOutput::Invalid(e, _) | StatementOutput::RolledBack(e) => Err(e),
+                        _ => Err(format!("Unexpected transaction output"))
+                    };
+                    f(ans);
+                } else {
+                    f(Err(String::from("No active connection to apply changes")));
+                }
+            } else {
+                eprintln!("Unable to acquire lock over engine");
+            }
+        });
+    }
+
+    /// Imports the table at the given file, calling progress with the number of rows
+    /// imported so far and the total number of rows.
     pub fn spawn_import_and_then(
         &self,
         path : String,
+        opts : CsvOptions,
         action : crate::sql::copy::Copy,
+        progress : impl Fn(usize, usize)->() + Send + 'static,
         f : impl Fn(Result<usize, String>)->() + Send + 'static
     ) {
         let engine = self.engine.clone();
         thread::spawn(move|| {
             if let Ok(mut opt_engine) = engine.lock() {
                 if let Some(engine) = opt_engine.as_mut() {
-                    let ans = copy_table_from_csv(path, engine.as_mut(), action);
+                    let ans = copy_table_from_file(path, &opts, engine.as_mut(), action, &progress);
+                    f(ans);
+                } else {
+                    f(Err(String::from("No active connection to complete import action")));
+                }
+            } else {
+                eprintln!("Unable to acquire lock over engine");
+            }
+        });
+    }
+
+    /// Reads the table at the given file and builds the statement that would create
+    /// the destination table at the active connection, without executing it.
+    pub fn spawn_import_preview(
+        &self,
+        path : String,
+        opts : CsvOptions,
+        dst : String,
+        f : impl Fn(Result<String, String>)->() + Send + 'static
+    ) {
+        let engine = self.engine.clone();
+        thread::spawn(move|| {
+            if let Ok(opt_engine) = engine.lock() {
+                if let Some(engine) = opt_engine.as_ref() {
+                    let ans = Table::open_with(std::path::Path::new(&path), &opts).and_then(|tbl| {
+                        tbl.sql_table_creation(&dst, &[], engine.conn_info().engine)
+                            .ok_or(format!("Unable to form create table statement"))
+                    });
                     f(ans);
                 } else {
                     f(Err(String::from("No active connection to complete import action")));
@@ -299,21 +386,25 @@ fn spawn_listener_thread<F>(
     cmd_rx : Receiver<ExecutionRequest>
 ) -> JoinHandle<()>
 where
-    F : Fn(Vec<StatementOutput>, ExecMode) + 'static + Send
+    F : Fn(Vec<StatementOutput>, Vec<usize>, ExecMode) + 'static + Send
 {
     thread::spawn(move ||  {
         loop {
             match cmd_rx.recv() {
             
-                Ok(ExecutionRequest { sql, /*subs,*/ safety, is_schedule, mode }) => {
+                Ok(ExecutionRequest { sql, subs, safety, is_schedule, mode }) => {
                 
                     let result;
+
+                    // Indices of the results with rows yet to be fetched.
+                    let mut paged = Vec::new();
                     
                     match engine.lock() {
                         Ok(mut opt_eng) => match &mut *opt_eng {
                             Some(ref mut eng) => {
-                                result = match eng.try_run(sql, /*&subs,*/ safety, is_schedule) {
+                                result = match eng.try_run(sql, &subs, safety, is_schedule) {
                                     Ok(stmt_results) => {
+                                        paged = eng.paged_results();
                                         stmt_results
                                     },
                                     Err(e) => {
@@ -337,7 +428,7 @@ where
                     /* It is important to call the result callback only after the engine mutex
                     is unlocked, so that new statements can be promptly sent after results arrive
                     (used during testing, but a good practice for ordinary use nevertheless). */
-                    result_cb(result, mode);
+                    result_cb(result, paged, mode);
                     
                 },
                 Err(_e) => {
@@ -351,35 +442,22 @@ where
     })
 }
 
-fn copy_table_from_csv(
+fn copy_table_from_file(
     path : String,
+    opts : &CsvOptions,
     conn : &mut dyn Connection,
-    action : crate::sql::copy::Copy
+    action : crate::sql::copy::Copy,
+    progress : &dyn Fn(usize, usize)
 ) -> Result<usize, String> {
     assert!(action.target == CopyTarget::From);
-    match File::open(&path) {
-        Ok(mut f) => {
-            let mut txt = String::new();
-            if let Err(e) = f.read_to_string(&mut txt) {
-                return Err(format!("{}", e));
-            }
-            match Table::new_from_text(txt) {
-                Ok(mut tbl) => {
-                    conn.import(
-                        &mut tbl,
-                        &action.table[..],
-                        &[]
-                    )
-                },
-                Err(e) => {
-                    Err(format!("Error parsing table: {}", e))
-                }
-            }
-        },
-        Err(e) => {
-            Err(format!("Error opening file to import data: {}", e))
-        }
-    }
+    let mut tbl = Table::open_with(std::path::Path::new(&path), opts)?;
+    let total = tbl.nrows();
+    conn.import_with_progress(
+        &mut tbl,
+        &action.table[..],
+        &action.cols[..],
+        &|n| progress(n, total)
+    )
 }
 
 
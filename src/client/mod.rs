@@ -69,6 +69,22 @@ mod exec;
 
 pub use exec::*;
 
+mod cli;
+
+pub use cli::*;
+
+mod connstr;
+
+pub use connstr::*;
+
+mod vault;
+
+pub use vault::*;
+
+mod tunnel;
+
+pub use tunnel::*;
+
 /* TODO enable custom logging
 
 fn _glib_logger() {
@@ -0,0 +1,168 @@
+/*Copyright (c) 2022 Diego da Silva Lima. All rights reserved.
+
+This work is licensed under the terms of the GPL v3.0 License.  
+For a copy, see http://www.gnu.org/licenses.*/
+
+use std::process::{Command, Child, Stdio};
+use std::net::{TcpListener, TcpStream, SocketAddr, Ipv4Addr};
+use std::time::{Duration, Instant};
+use std::io::Read;
+use std::thread;
+use serde::{Serialize, Deserialize};
+
+/* Databases that are only reachable through a bastion host are accessed by forwarding a
+local port to the database host with the ssh client. Authentication is left to ssh (which uses
+the informed key file, or else the agent and default identities), but it is never allowed to
+prompt the user (BatchMode), so unknown bastion host keys and passphrase-protected keys not
+loaded at the agent result in an error. */
+
+pub const SSH_PROGRAM : &str = "ssh";
+
+pub const SSH_DEFAULT_PORT : u16 = 22;
+
+// Maximum time waiting for the forwarded port to accept connections.
+const TUNNEL_TIMEOUT : Duration = Duration::from_secs(15);
+
+const TUNNEL_POLL_INTERVAL : Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SshJump {
+
+    pub host : String,
+
+    pub port : u16,
+
+    pub user : String,
+
+    // Private key used to authenticate at the bastion. If this is None, ssh
+    // uses the keys at the agent.
+    pub key_path : Option<String>
+
+}
+
+fn forward_host(host : &str) -> String {
+    if host.contains(':') {
+        format!("[{}]", host)
+    } else {
+        host.to_string()
+    }
+}
+
+impl SshJump {
+
+    /// Arguments to the ssh client to forward the local port to the database host and port
+    /// (as seen from the bastion).
+    pub fn ssh_args(&self, local_port : u16, host : &str, port : u16) -> Vec<String> {
+        let mut args = vec![
+            String::from("-N"),
+            String::from("-o"), String::from("BatchMode=yes"),
+            String::from("-o"), String::from("ExitOnForwardFailure=yes"),
+            String::from("-o"), String::from("ConnectTimeout=10"),
+            String::from("-L"), format!("127.0.0.1:{}:{}:{}", local_port, forward_host(host), port),
+            String::from("-p"), self.port.to_string()
+        ];
+        if let Some(key) = &self.key_path {
+            args.extend([String::from("-i"), key.clone(), String::from("-o"), String::from("IdentitiesOnly=yes")]);
+        }
+
+        // Options end here, so a host starting with a dash is never taken as an option.
+        args.push(String::from("--"));
+        if self.user.is_empty() {
+            args.push(self.host.clone());
+        } else {
+            args.push(format!("{}@{}", self.user, self.host));
+        }
+        args
+    }
+
+}
+
+/// A running ssh client forwarding a local port. The process is terminated when this is dropped.
+#[derive(Debug)]
+pub struct SshTunnel {
+    child : Child,
+    local_port : u16
+}
+
+fn free_local_port() -> Result<u16, String> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+        .map_err(|e| format!("Unable to find a free local port: {}", e) )?;
+    listener.local_addr()
+        .map(|addr| addr.port() )
+        .map_err(|e| format!("Unable to find a free local port: {}", e) )
+}
+
+impl SshTunnel {
+
+    pub fn open(jump : &SshJump, host : &str, port : u16) -> Result<Self, String> {
+        Self::open_with(SSH_PROGRAM, jump, host, port)
+    }
+
+    /// Starts the given ssh program, waiting until the forwarded port accepts connections.
+    pub fn open_with(program : &str, jump : &SshJump, host : &str, port : u16) -> Result<Self, String> {
+        if jump.host.is_empty() {
+            return Err(format!("Missing SSH host"));
+        }
+        let local_port = free_local_port()?;
+        let mut child = Command::new(program)
+            .args(jump.ssh_args(local_port, host, port))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Unable to start {}: {}", program, e) )?;
+        let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, local_port));
+        let start = Instant::now();
+        loop {
+            match child.try_wait() {
+                Ok(Some(status)) => {
+                    let mut msg = String::new();
+                    if let Some(mut stderr) = child.stderr.take() {
+                        let _ = stderr.read_to_string(&mut msg);
+                    }
+                    return Err(format!("SSH tunnel through {} failed ({}):\n{}", jump.host, status, msg.trim()));
+                },
+                Ok(None) => { },
+                Err(e) => {
+                    let _ = child.kill();
+                    return Err(format!("{}", e));
+                }
+            }
+            if TcpStream::connect_timeout(&addr, TUNNEL_POLL_INTERVAL).is_ok() {
+                break;
+            }
+            if start.elapsed() > TUNNEL_TIMEOUT {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("Timeout waiting for SSH tunnel through {}", jump.host));
+            }
+            thread::sleep(TUNNEL_POLL_INTERVAL);
+        }
+
+        // Keep consuming the messages of the client, so it never blocks on a full pipe.
+        if let Some(mut stderr) = child.stderr.take() {
+            thread::spawn(move || {
+                let _ = std::io::copy(&mut stderr, &mut std::io::sink());
+            });
+        }
+        Ok(SshTunnel { child, local_port })
+    }
+
+    pub fn local_port(&self) -> u16 {
+        self.local_port
+    }
+
+    pub fn is_running(&mut self) -> bool {
+        self.child.try_wait().map(|status| status.is_none() ).unwrap_or(false)
+    }
+
+}
+
+impl Drop for SshTunnel {
+
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+
+}
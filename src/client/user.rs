@@ -3,7 +3,7 @@
 This work is licensed under the terms of the GPL v3.0 License.  
 For a copy, see http://www.gnu.org/licenses.*/
 
-use super::{ConnectionInfo, OpenedScripts};
+use super::{ConnectionInfo, OpenedScripts, SslMode};
 use filecase::OpenedFile;
 use serde::{Serialize, Deserialize};
 use crate::ui::QueriesWindow;
@@ -34,7 +34,11 @@ where
 pub struct ConnSettings {
     pub timeout : i32,
     pub save_conns : bool,
-    pub app_name : String
+    pub app_name : String,
+
+    // Whether passwords are kept at the encrypted credential vault.
+    #[serde(default)]
+    pub use_vault : bool
 }
 
 impl fmt::Display for ConnSettings {
@@ -50,7 +54,8 @@ impl Default for ConnSettings {
         Self {
             timeout : 10,
             save_conns : true,
-            app_name : String::from("Queries")
+            app_name : String::from("Queries"),
+            use_vault : false
         }
     }
     
@@ -88,6 +93,10 @@ impl Default for EditorSettings {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionSettings {
     pub row_limit : i32,
+
+    // Rows fetched at a time for query results (zero fetches all rows at once)
+    #[serde(default = "default_page_size")]
+    pub page_size : i32,
     
     // pub column_limit : i32,
 
@@ -103,7 +112,15 @@ pub struct ExecutionSettings {
     // Whether to execute destructive dml statements
     pub accept_dml : bool,
     
-    pub enable_async : bool
+    pub enable_async : bool,
+
+    // Channel that triggers executions in notification mode
+    #[serde(default)]
+    pub notification_channel : String,
+
+    // JSON object the notification payload must match in notification mode (empty matches any payload)
+    #[serde(default)]
+    pub notification_filter : String
 }
 
 impl Default for ExecutionSettings {
@@ -111,16 +128,23 @@ impl Default for ExecutionSettings {
     fn default() -> Self {
         Self {
             row_limit : 500,
+            page_size : default_page_size(),
             execution_interval : 5,
             statement_timeout : 5,
             accept_ddl : false,
             accept_dml : false,
-            enable_async : false
+            enable_async : false,
+            notification_channel : String::new(),
+            notification_filter : String::new()
         }
     }
 
 }
 
+fn default_page_size() -> i32 {
+    500
+}
+
 impl fmt::Display for ExecutionSettings {
 
     fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
@@ -190,6 +214,12 @@ pub fn user_state_integrity_check(state : &UserState) -> Result<(), std::boxed::
             if a.security.verify_hostname.is_some() {
                 return Err(format!("Connections to {} are not encrypted but setting carries a verify hostname setting", a.host).into());
             }
+            if a.security.client_cert.is_some() {
+                return Err(format!("Connections to {} are not encrypted but setting carries a client certificate", a.host).into());
+            }
+            if a.security.ssl_mode.map(|m| m != SslMode::Disable ).unwrap_or(false) {
+                return Err(format!("Connections to {} are not encrypted but setting carries sslmode {}", a.host, a.security.effective_ssl_mode()).into());
+            }
         }
     }
 
@@ -305,6 +335,13 @@ impl React<crate::ui::QueriesWindow> for SharedUserState {
                 Inhibit(false)
             }
         });
+        win.settings.conn_bx.vault_switch.connect_state_set({
+            let state = self.clone();
+            move|switch, _| {
+                state.borrow_mut().conn.use_vault = switch.is_active();
+                Inhibit(false)
+            }
+        });
 
         // Execution
         win.settings.exec_bx.row_limit_spin.connect_value_changed({
@@ -313,6 +350,12 @@ impl React<crate::ui::QueriesWindow> for SharedUserState {
                 state.borrow_mut().execution.row_limit = spin.value() as i32;
             }
         });
+        win.settings.exec_bx.page_size_spin.connect_value_changed({
+            let state = self.clone();
+            move |spin| {
+                state.borrow_mut().execution.page_size = spin.value() as i32;
+            }
+        });
         win.settings.exec_bx.schedule_scale.adjustment().connect_value_changed({
             let state = self.clone();
             move |adj| {
@@ -325,6 +368,18 @@ impl React<crate::ui::QueriesWindow> for SharedUserState {
                 state.borrow_mut().execution.statement_timeout = adj.value() as i32;
             }
         });
+        win.settings.exec_bx.channel_entry.connect_changed({
+            let state = self.clone();
+            move|entry| {
+                state.borrow_mut().execution.notification_channel = entry.text().as_str().to_string();
+            }
+        });
+        win.settings.exec_bx.filter_entry.connect_changed({
+            let state = self.clone();
+            move|entry| {
+                state.borrow_mut().execution.notification_filter = entry.text().as_str().to_string();
+            }
+        });
         win.settings.exec_bx.ddl_switch.connect_state_set({
             let state = self.clone();
             move|switch, _| {
@@ -448,6 +503,7 @@ impl PersistentState<QueriesWindow> for SharedUserState {
 
                     s.conns.retain(|c| !c.is_default() && !c.host.is_empty() || c.host != crate::client::DEFAULT_HOST );
                     s.conns.iter_mut().for_each(|c| {
+                        c.security.forget_passphrase();
                         if c.port == crate::client::DEFAULT_PORT {
                             c.port.clear();
                         }
@@ -489,10 +545,14 @@ impl PersistentState<QueriesWindow> for SharedUserState {
         queries_win.settings.conn_bx.timeout_scale.adjustment().set_value(state.conn.timeout as f64);
         queries_win.settings.conn_bx.app_name_entry.set_text(&state.conn.app_name);
         queries_win.settings.conn_bx.save_switch.set_active(state.conn.save_conns);
+        queries_win.settings.conn_bx.vault_switch.set_active(state.conn.use_vault);
         
         queries_win.settings.exec_bx.row_limit_spin.adjustment().set_value(state.execution.row_limit as f64);
+        queries_win.settings.exec_bx.page_size_spin.adjustment().set_value(state.execution.page_size as f64);
         queries_win.settings.exec_bx.schedule_scale.adjustment().set_value(state.execution.execution_interval as f64);
         queries_win.settings.exec_bx.timeout_scale.adjustment().set_value(state.execution.statement_timeout as f64);
+        queries_win.settings.exec_bx.channel_entry.set_text(&state.execution.notification_channel);
+        queries_win.settings.exec_bx.filter_entry.set_text(&state.execution.notification_filter);
         queries_win.settings.exec_bx.dml_switch.set_active(state.execution.accept_dml);
         queries_win.settings.exec_bx.ddl_switch.set_active(state.execution.accept_ddl);
         queries_win.settings.exec_bx.async_switch.set_active(state.execution.enable_async);
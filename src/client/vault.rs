@@ -0,0 +1,229 @@
+/*Copyright (c) 2022 Diego da Silva Lima. All rights reserved.
+
+This work is licensed under the terms of the GPL v3.0 License.  
+For a copy, see http://www.gnu.org/licenses.*/
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use serde::{Serialize, Deserialize};
+use aes_gcm::{Aes256Gcm, KeyInit};
+use aes_gcm::aead::Aead;
+use aes_gcm::aead::generic_array::GenericArray;
+use argon2::Argon2;
+use super::conn::ConnectionInfo;
+
+/* Passwords are never saved with the connection info. When the user opts in, they are kept
+at a credential store instead, keyed by the connection credentials (engine, user, host, port and
+database). The store is consulted when a saved connection is activated with an empty password,
+and updated after a connection is established with a password typed by the user. The passphrases
+of PKCS#12 client certificates are not saved with the security settings either, and are kept
+at the store keyed by the archive path. */
+
+/// Storage for connection passwords. The file vault is the only store for now, but a
+/// keyring service might implement this trait as well.
+pub trait CredentialStore {
+
+    fn password(&self, info : &ConnectionInfo) -> Option<String>;
+
+    fn set_password(&mut self, info : &ConnectionInfo, password : &str) -> Result<(), String>;
+
+    fn remove_password(&mut self, info : &ConnectionInfo) -> Result<(), String>;
+
+    fn cert_passphrase(&self, path : &str) -> Option<String>;
+
+    fn set_cert_passphrase(&mut self, path : &str, passphrase : &str) -> Result<(), String>;
+
+}
+
+pub fn credential_key(info : &ConnectionInfo) -> String {
+    format!("{}://{}@{}:{}/{}", info.engine, info.user, info.host, info.port, info.database)
+}
+
+pub fn cert_key(path : &str) -> String {
+    format!("pkcs12://{}", path)
+}
+
+const VAULT_VERSION : u32 = 1;
+
+const KEY_LEN : usize = 32;
+
+const SALT_LEN : usize = 16;
+
+const NONCE_LEN : usize = 12;
+
+// Content of the vault file. Only the ciphertext carries the credentials.
+#[derive(Serialize, Deserialize)]
+struct VaultFile {
+    version : u32,
+    salt : String,
+    nonce : String,
+    ciphertext : String
+}
+
+/// Credentials saved at a JSON file, encrypted with AES-256-GCM by a key derived from a
+/// master passphrase (with Argon2). The file is re-written with a fresh nonce at every change.
+pub struct FileVault {
+    path : PathBuf,
+    key : [u8; KEY_LEN],
+    salt : [u8; SALT_LEN],
+    entries : BTreeMap<String, String>
+}
+
+fn derive_key(passphrase : &str, salt : &[u8]) -> Result<[u8; KEY_LEN], String> {
+    let mut key = [0u8; KEY_LEN];
+    Argon2::default().hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Unable to derive vault key: {}", e) )?;
+    Ok(key)
+}
+
+fn decode_exact<const N : usize>(s : &str, field : &str) -> Result<[u8; N], String> {
+    let bytes = base64::decode(s).map_err(|e| format!("Invalid vault {}: {}", field, e) )?;
+    if bytes.len() != N {
+        return Err(format!("Invalid vault {} length", field));
+    }
+    let mut out = [0u8; N];
+    out.copy_from_slice(&bytes[..]);
+    Ok(out)
+}
+
+impl FileVault {
+
+    /// Opens the vault at the given path, or creates an empty vault if the file does not exist.
+    pub fn open(path : &Path, passphrase : &str) -> Result<Self, String> {
+        if passphrase.is_empty() {
+            return Err(format!("Empty vault passphrase"));
+        }
+        if !path.exists() {
+            let salt : [u8; SALT_LEN] = rand::random();
+            let vault = FileVault {
+                path : path.to_owned(),
+                key : derive_key(passphrase, &salt)?,
+                salt,
+                entries : BTreeMap::new()
+            };
+            vault.save()?;
+            return Ok(vault);
+        }
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Unable to read vault: {}", e) )?;
+        let file : VaultFile = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid vault file: {}", e) )?;
+        if file.version != VAULT_VERSION {
+            return Err(format!("Unsupported vault version: {}", file.version));
+        }
+        let salt = decode_exact::<SALT_LEN>(&file.salt, "salt")?;
+        let nonce = decode_exact::<NONCE_LEN>(&file.nonce, "nonce")?;
+        let ciphertext = base64::decode(&file.ciphertext)
+            .map_err(|e| format!("Invalid vault content: {}", e) )?;
+        let key = derive_key(passphrase, &salt)?;
+        let cipher = Aes256Gcm::new(GenericArray::from_slice(&key));
+        let plaintext = cipher.decrypt(GenericArray::from_slice(&nonce), &ciphertext[..])
+            .map_err(|_| format!("Wrong passphrase or corrupted vault") )?;
+        let entries = serde_json::from_slice(&plaintext)
+            .map_err(|e| format!("Invalid vault content: {}", e) )?;
+        Ok(FileVault { path : path.to_owned(), key, salt, entries })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn insert(&mut self, key : String, value : &str) -> Result<(), String> {
+        if self.entries.get(&key).map(|v| &v[..] == value ).unwrap_or(false) {
+            return Ok(());
+        }
+        self.entries.insert(key, value.to_string());
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let nonce : [u8; NONCE_LEN] = rand::random();
+        let plaintext = serde_json::to_vec(&self.entries).map_err(|e| format!("{}", e) )?;
+        let cipher = Aes256Gcm::new(GenericArray::from_slice(&self.key));
+        let ciphertext = cipher.encrypt(GenericArray::from_slice(&nonce), &plaintext[..])
+            .map_err(|_| format!("Unable to encrypt vault") )?;
+        let file = VaultFile {
+            version : VAULT_VERSION,
+            salt : base64::encode(&self.salt),
+            nonce : base64::encode(&nonce),
+            ciphertext : base64::encode(&ciphertext)
+        };
+        let content = serde_json::to_string_pretty(&file).map_err(|e| format!("{}", e) )?;
+        write_private(&self.path, &content)
+    }
+
+}
+
+/* The content is written to a sibling file that then replaces the vault, so a failure while
+writing (crash or full disk) leaves the previous vault intact. */
+fn write_private(path : &Path, content : &str) -> Result<(), String> {
+    use std::io::Write;
+    let name = path.file_name().and_then(|n| n.to_str() )
+        .ok_or(format!("Invalid vault path"))?;
+    let tmp = path.with_file_name(format!(".{}.tmp", name));
+    let ans = create_private(&tmp)
+        .and_then(|mut f| {
+            f.write_all(content.as_bytes())?;
+            f.sync_all()
+        })
+        .and_then(|_| std::fs::rename(&tmp, path) );
+    if ans.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    ans.map_err(|e| format!("Unable to write vault: {}", e) )
+}
+
+#[cfg(unix)]
+fn create_private(path : &Path) -> std::io::Result<std::fs::File> {
+    use std::os::unix::fs::OpenOptionsExt;
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)
+}
+
+#[cfg(not(unix))]
+fn create_private(path : &Path) -> std::io::Result<std::fs::File> {
+    std::fs::File::create(path)
+}
+
+impl CredentialStore for FileVault {
+
+    fn password(&self, info : &ConnectionInfo) -> Option<String> {
+        self.entries.get(&credential_key(info)).cloned()
+    }
+
+    fn set_password(&mut self, info : &ConnectionInfo, password : &str) -> Result<(), String> {
+        self.insert(credential_key(info), password)
+    }
+
+    fn remove_password(&mut self, info : &ConnectionInfo) -> Result<(), String> {
+        if self.entries.remove(&credential_key(info)).is_some() {
+            self.save()
+        } else {
+            Ok(())
+        }
+    }
+
+    fn cert_passphrase(&self, path : &str) -> Option<String> {
+        self.entries.get(&cert_key(path)).cloned()
+    }
+
+    fn set_cert_passphrase(&mut self, path : &str, passphrase : &str) -> Result<(), String> {
+        self.insert(cert_key(path), passphrase)
+    }
+
+}
+
+/// Path of the vault file at the application data directory.
+pub fn vault_path() -> Option<PathBuf> {
+    let mut path = filecase::get_datadir(crate::APP_ID)?;
+    path.push(crate::VAULT_FILE);
+    Some(path)
+}
@@ -21,6 +21,8 @@ pub mod sql;
 
 pub const SETTINGS_FILE : &str = "user.json";
 
+pub const VAULT_FILE : &str = "vault.json";
+
 pub const APP_ID : &str = "io.github.limads.Queries";
 
 pub fn register_resources() {
@@ -38,15 +40,20 @@ fn hook_signals(
 
     client.conn_set.react(&queries_win.content.results.overview.conn_list);
     client.conn_set.react(&queries_win.content.results.overview.conn_bx);
+    client.conn_set.react(&client.active_conn);
+    client.conn_set.react(&queries_win.settings);
     client.active_conn.react(&queries_win.content.results.overview.conn_bx);
     client.active_conn.react(&queries_win.titlebar.exec_btn);
     client.active_conn.react(&queries_win.sidebar.schema_tree);
     client.active_conn.react(&queries_win.graph_win);
     client.active_conn.react(&queries_win.builder_win);
+    client.active_conn.react(&queries_win.content.results.workspace);
+    client.active_conn.react(queries_win);
 
     client.env.react(&client.active_conn);
     client.env.react(&queries_win.content.results.workspace);
     client.env.react(&queries_win.content.editor.export_dialog);
+    client.env.react(&queries_win.content.editor.import_dialog);
     client.env.react(&queries_win.titlebar.exec_btn);
 
     queries_win.content.react(&client.active_conn);
@@ -57,6 +64,7 @@ fn hook_signals(
     queries_win.content.results.overview.sec_bx.react(&client.conn_set);
     queries_win.content.results.overview.sec_bx.react(&queries_win.settings);
     queries_win.content.results.workspace.react(&client.env);
+    queries_win.content.results.workspace.react(&client.active_conn);
 
     queries_win.sidebar.schema_tree.react(&client.active_conn);
     queries_win.sidebar.file_list.react(&client.scripts);
@@ -12,6 +12,12 @@ use queries::ui::*;
 
 fn main() {
 
+    // Headless mode: queries run --conn <uri> script.sql
+    let args : Vec<String> = std::env::args().collect();
+    if args.get(1).map(|a| &a[..] ) == Some("run") {
+        std::process::exit(queries::client::run_headless(&args[2..]));
+    }
+
     queries::register_resources();
     
     if let Err(e) = gtk4::init() {
@@ -3,126 +3,226 @@
 This work is licensed under the terms of the GPL v3.0 License.  
 For a copy, see http://www.gnu.org/licenses.*/
 
-use datafusion::{self, *};
-use datafusion::datasource::TableProvider;
-use datafusion::datasource::csv::{CsvFile, CsvReadOptions};
-use datafusion::execution::physical_plan::udf::ScalarFunction;
-use arrow::datatypes::*;
-use arrow::array::*;
-use arrow::record_batch::RecordBatch;
-use datafusion::execution::context::ExecutionContext;
-use datafusion::error::ExecutionError;
-use std::sync::Arc;
+use datafusion::prelude::{SessionContext, SessionConfig, CsvReadOptions, ParquetReadOptions};
 use std::error::Error;
-use datafusion::logicalplan::Expr;
-use datafusion::logicalplan::aggregate_expr;
-use arrow::datatypes::{ArrowPrimitiveType,ArrowNativeType,ArrowNumericType};
-use crate::tables::{table::Table, column::*, nullable_column::*};
+use std::path::{Path, PathBuf};
+use std::collections::HashMap;
+use std::convert::TryInto;
+use crate::tables::table::Table;
+use crate::tables::arrow::table_from_batches;
+use crate::sql::{*, object::*};
+use crate::sql::parsing::AnyStatement;
+use crate::client::{ConnectionInfo, ConnURI, ConnConfig};
+use super::Connection;
 
 /*
 Perhaps we can implement TableProvider for a foreign PostgreSQL table,
 and let the user manipulate the data in-memory?
 */
 
-/// Copies into a column from the Array type A into Rust native type N
-fn primitive_to_column<A, N>(arr : &Arc<dyn Array>) -> Result<Column, String>
-where
-    N : Copy + ArrowNativeType,
-    A : ArrowNumericType<Native=N>,
-    Column : From<Vec<N>>
-{
-    let prim_arr = arr.as_any()
-        .downcast_ref::<PrimitiveArray<A>>()
-        .ok_or(format!("Error downcasting column"))?;
-    let mut v = Vec::new();
-    v.extend_from_slice(prim_arr.value_slice(0, prim_arr.len()));
-    Ok(Column::from(v))
+const DATA_EXTENSIONS : [&str; 2] = ["csv", "parquet"];
+
+/// Whether the path is a file that can be registered as a table by the DataFusion engine.
+pub fn is_data_file(path : &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str() )
+        .map(|ext| DATA_EXTENSIONS.iter().any(|e| &e[..] == ext ) )
+        .unwrap_or(false)
+}
+
+/// Embedded analytic engine, querying CSV and Parquet files. The files are registered
+/// as tables named after the file stem when the connection is established (a directory
+/// registers all its files). Other files can be registered with a
+/// create external table statement.
+pub struct ArrowConnection {
+
+    info : ConnectionInfo,
+
+    path : PathBuf,
+
+    ctx : SessionContext,
+
+    rt : tokio::runtime::Runtime
+
 }
 
-pub fn table_from_batch(results : &RecordBatch) -> Result<Table, String> {
-    let schema = results.schema();
-    let fields = schema.fields();
-    let mut names = Vec::new();
-    let mut cols = Vec::new();
-    for (i, arr) in results.columns().iter().enumerate() {
-        let col = match fields[i].data_type() {
-            DataType::Int8 => primitive_to_column::<Int8Type, i8>(arr)?,
-            DataType::Int16 => primitive_to_column::<Int16Type, i16>(arr)?,
-            DataType::Int32 => primitive_to_column::<Int32Type, i32>(arr)?,
-            DataType::UInt32 => primitive_to_column::<UInt32Type, u32>(arr)?,
-            DataType::Int64 => primitive_to_column::<Int64Type, i64>(arr)?,
-            DataType::Float32 => primitive_to_column::<Float32Type, f32>(arr)?,
-            DataType::Float64 => primitive_to_column::<Float64Type, f64>(arr)?,
-            DataType::Utf8 => unimplemented!(), // StringArray
-            DataType::Binary => unimplemented!(), // BinaryArray
-            _ => return Err(format!("Invalid datatype for column {}", i))
+impl ArrowConnection {
+
+    pub fn try_new(uri : ConnURI) -> Result<Self, String> {
+        let path = uri.file_path().map_err(|_| format!("Invalid path URI") )?;
+        let files : Vec<PathBuf> = if path.is_dir() {
+            let mut files = Vec::new();
+            for entry in std::fs::read_dir(&path).map_err(|e| format!("{}", e) )? {
+                let entry_path = entry.map_err(|e| format!("{}", e) )?.path();
+                if entry_path.is_file() && is_data_file(&entry_path) {
+                    files.push(entry_path);
+                }
+            }
+            files.sort();
+            files
+        } else if path.is_file() && is_data_file(&path) {
+            vec![path.clone()]
+        } else {
+            return Err(format!("Path should be a directory or a CSV or Parquet file"));
         };
-        cols.push(col);
-        names.push(fields[i].name().clone());
+        let rt = tokio::runtime::Runtime::new().map_err(|e| format!("{}", e) )?;
+        let ctx = SessionContext::with_config(SessionConfig::new().with_information_schema(true));
+        rt.block_on(async {
+            for file in files.iter() {
+                let name = file.file_stem()
+                    .and_then(|s| s.to_str() )
+                    .ok_or(format!("Invalid file name: {}", file.display()))?;
+                let file_path = file.to_str().ok_or(format!("Invalid file path: {}", file.display()))?;
+                let ans = match file.extension().and_then(|ext| ext.to_str() ) {
+                    Some("csv") => ctx.register_csv(name, file_path, CsvReadOptions::new()).await,
+                    _ => ctx.register_parquet(name, file_path, ParquetReadOptions::default()).await
+                };
+                ans.map_err(|e| format!("Error registering {}: {}", file.display(), e) )?;
+            }
+            Ok::<(), String>(())
+        })?;
+        Ok(Self { info : uri.info, path, ctx, rt })
     }
-    Table::new(names, cols).map_err(|e| format!("{}", e))
+
+    fn run_sql(&mut self, sql : &str) -> Result<Table, String> {
+        let ctx = &self.ctx;
+        self.rt.block_on(async {
+            let df = ctx.sql(sql).await.map_err(|e| format!("{}", e) )?;
+            let names : Vec<String> = df.schema().fields().iter().map(|f| f.name().clone() ).collect();
+            let batches = df.collect().await.map_err(|e| format!("{}", e) )?;
+            table_from_batches(names, &batches[..])
+        })
+    }
+
 }
 
-/*fn create_context() {
+const COLUMN_QUERY : &str = r#"
+select table_name, column_name, data_type from information_schema.columns
+where table_schema = 'public'
+order by table_name, ordinal_position;
+"#;
 
-    ctx.register_csv(
-        "my_data",
-        "test.csv",
-        CsvReadOptions::new(),
-    )?;
+/* Information schema columns carry the Arrow type names. */
+fn arrow_db_type(ty : &str) -> DBType {
+    match ty {
+        "Boolean" => DBType::Bool,
+        "Int8" | "Int16" | "UInt8" => DBType::I16,
+        "Int32" | "UInt16" => DBType::I32,
+        "Int64" | "UInt32" | "UInt64" => DBType::I64,
+        "Float32" => DBType::F32,
+        "Float64" => DBType::F64,
+        "Utf8" | "LargeUtf8" => DBType::Text,
+        "Binary" | "LargeBinary" => DBType::Bytes,
+        "Date32" | "Date64" => DBType::Date,
+        ty if ty.starts_with("Decimal") => DBType::Numeric,
+        ty if ty.starts_with("Timestamp") => DBType::Timestamp,
+        ty if ty.starts_with("Time") => DBType::Time,
+        ty if ty.starts_with("List") => DBType::Array,
+        _ => DBType::Unknown
+    }
 }
 
-fn execute() {
-    let results = ctx.sql(sql, 10)?;
-    pretty::print_batches(&results)?;
+fn string_column(tbl : &Table, ix : usize) -> Vec<String> {
+    tbl.get_column(ix)
+        .and_then(|c| c.clone().try_into().ok() )
+        .unwrap_or(Vec::new())
 }
 
-fn register_function() {
-    let f1 = Field::new("data1", DataType::Int64, true);
-    let f2 = Field::new("data2", DataType::Int64, true);
-    let sf = ScalarFunction::new("mysf", vec![f1, f2], DataType::Int64, Arc::new(sum));
-
-    let f1 = Field::new("data1", DataType::Utf8, true);
-    let f2 = Field::new("data2", DataType::Float64, true);
-    let sf2 = ScalarFunction::new("model_info", vec![f1, f2], DataType::Utf8, Arc::new(model_info));
-    ctx.register_udf(sf);
-}*/
-
-#[cfg(feature="arrowext")]
-fn query_arrow(ctx : &mut ExecutionContext, q : &str) -> QueryResult {
-    match ctx.sql(q, 10000) {
-        Ok(results) => {
-            if results.len() == 0 {
-                return QueryResult::Statement(String::from("0 Row(s) modified"));
-            } else {
-                match super::arrow::table_from_batch(&results[0]) {
-                    Ok(tbl) => QueryResult::Valid(q.to_string(), tbl),
-                    Err(e) => QueryResult::Invalid(format!("{}", e), true)
+impl Connection for ArrowConnection {
+
+    fn configure(&mut self, _cfg : ConnConfig) {
+
+    }
+
+    fn query(&mut self, q : &str) -> StatementOutput {
+        match self.run_sql(q) {
+            Ok(mut tbl) => {
+                if let Some((name, relation)) = crate::sql::table_name_from_sql(q) {
+                    tbl.set_name(Some(name));
+                    if !relation.is_empty() {
+                        tbl.set_relation(Some(relation));
+                    }
                 }
-            }
-        },
-        Err(e) => {
-            QueryResult::Invalid(format!("{}", e), true )
+                StatementOutput::Valid(q.to_string(), tbl)
+            },
+            Err(e) => StatementOutput::Invalid(crate::sql::build_error_with_stmt(&e, q), true)
         }
     }
-}
 
-#[cfg(feature="arrowext")]
-fn exec_arrow(ctx : &mut ExecutionContext, q : &str) -> QueryResult {
-    match ctx.sql(q, 10000) {
-        Ok(results) => {
-            if results.len() == 0 {
-                return QueryResult::Statement(String::from("0 Row(s) modified"));
-            } else {
-                match super::arrow::table_from_batch(&results[0]) {
-                    Ok(tbl) => QueryResult::Valid(q.to_string(), tbl),
-                    Err(e) => QueryResult::Invalid(format!("{}", e), true)
-                }
+    fn exec(&mut self, stmt : &AnyStatement) -> StatementOutput {
+        let sql = match stmt {
+            AnyStatement::Parsed(_, s) | AnyStatement::Raw(_, s, _) => s,
+            AnyStatement::ParsedTransaction { .. } => {
+                return self.exec_transaction(stmt);
+            },
+            AnyStatement::Local(_) => {
+                return StatementOutput::Invalid("Tried to execute unsupported statement".to_string(), false);
             }
-        },
-        Err(e) => {
-            QueryResult::Invalid(format!("{}", e), true )
+        };
+        match self.run_sql(sql) {
+            Ok(_) => crate::sql::build_statement_result(stmt, 0),
+            Err(e) => StatementOutput::Invalid(e, true)
         }
     }
-}
 
+    fn query_async(&mut self, _stmts : &[AnyStatement]) -> Vec<StatementOutput> {
+        vec![StatementOutput::Invalid("Asynchronous queries are unsupported in the DataFusion backend".to_string(), false)]
+    }
+
+    fn exec_transaction(&mut self, _stmt : &AnyStatement) -> StatementOutput {
+        StatementOutput::Invalid("Transactions are unsupported in the DataFusion backend".to_string(), false)
+    }
+
+    fn conn_info(&self) -> ConnectionInfo {
+        self.info.clone()
+    }
+
+    fn db_info(&mut self) -> Result<DBInfo, Box<dyn Error>> {
+        let cols = self.run_sql(COLUMN_QUERY)?;
+        let mut tbl_cols : HashMap<String, (Vec<String>, Vec<DBType>)> = HashMap::new();
+        let names = string_column(&cols, 1);
+        let tys = string_column(&cols, 2);
+        for ((tbl, name), ty) in string_column(&cols, 0).into_iter().zip(names).zip(tys) {
+            let entry = tbl_cols.entry(tbl).or_insert((Vec::new(), Vec::new()));
+            entry.0.push(name);
+            entry.1.push(arrow_db_type(&ty));
+        }
+        let mut tbl_objs : Vec<DBObject> = tbl_cols.drain()
+            .map(|(name, (col_names, col_tys))| {
+                let cols = col_names.into_iter()
+                    .zip(col_tys)
+                    .map(|(name, ty)| DBColumn { name, ty, is_pk : false })
+                    .collect();
+                DBObject::Table { schema : format!("public"), name, cols, rels : Vec::new() }
+            })
+            .collect();
+        tbl_objs.sort_by(|a, b| a.obj_name().cmp(&b.obj_name()) );
+
+        let size = if self.path.is_dir() {
+            std::fs::read_dir(&self.path)?
+                .filter_map(|entry| entry.ok() )
+                .filter(|entry| is_data_file(&entry.path()) )
+                .filter_map(|entry| entry.metadata().ok().map(|meta| meta.len() ) )
+                .sum::<u64>()
+        } else {
+            std::fs::metadata(&self.path)?.len()
+        };
+        let details = DBDetails {
+            uptime : "Unknown".to_string(),
+            server : "DataFusion".to_string(),
+            size : format!("{:.2} mb", size as f32 / 1.0e6),
+            locale : "Unknown".to_string()
+        };
+        Ok(DBInfo { schema : vec![DBObject::Schema { name : format!("public"), children : tbl_objs }], details : Some(details) })
+    }
+
+    fn import(
+        &mut self,
+        _tbl : &mut Table,
+        _dst : &str,
+        _cols : &[String],
+    ) -> Result<usize, String> {
+        Err(String::from("Inserting rows is unsupported for the DataFusion engine"))
+    }
+
+}
@@ -5,6 +5,9 @@ For a copy, see http://www.gnu.org/licenses.*/
 
 use std::collections::HashMap;
 use crate::sql::*;
+use crate::sql::copy::{Copy, CopyTarget, CopyOptions, read_from_client, write_to_client};
+use crate::sql::params::{self, Param};
+use crate::sql::notify::Notification;
 use crate::sql::object::{DBInfo};
 use crate::sql::parsing::{AnyStatement, SQLError};
 use sqlparser::ast::*;
@@ -13,6 +16,9 @@ use crate::client::ConnectionInfo;
 use crate::client::ConnConfig;
 use crate::sql::SafetyLock;
 use std::error::Error;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 /*
 
@@ -51,11 +57,55 @@ mod sqlite;
 
 pub use sqlite::*;
 
-// pub use sqlite::*;
+mod mysql;
 
-// mod arrow;
+pub use self::mysql::*;
 
-// pub use arrow::*;
+#[cfg(feature="arrowext")]
+mod arrow;
+
+#[cfg(feature="arrowext")]
+pub use self::arrow::*;
+
+/// Interrupts the statement being executed by a connection. Clones are shared with the GUI
+/// thread, which uses them while the connection is locked by the execution thread.
+#[derive(Clone)]
+pub struct Canceller {
+    requested : Arc<AtomicBool>,
+    interrupt : Arc<dyn Fn() -> Result<(), String> + Send + Sync>
+}
+
+impl Canceller {
+
+    pub fn new(interrupt : impl Fn() -> Result<(), String> + Send + Sync + 'static) -> Self {
+        Self { requested : Arc::new(AtomicBool::new(false)), interrupt : Arc::new(interrupt) }
+    }
+
+    pub fn cancel(&self) -> Result<(), String> {
+        self.requested.store(true, Ordering::SeqCst);
+        (self.interrupt)()
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+
+    pub fn reset(&self) {
+        self.requested.store(false, Ordering::SeqCst);
+    }
+
+}
+
+const CANCELLED_MSG : &str = "Statement cancelled by user";
+
+/* Statements interrupted by a cancel request fail with an engine error. Those are reported
+as cancelled, so the user does not take them for an ordinary failure. */
+fn mark_cancelled(out : StatementOutput) -> StatementOutput {
+    match out {
+        StatementOutput::Invalid(_, true) => StatementOutput::Cancelled(String::from(CANCELLED_MSG)),
+        other => other
+    }
+}
 
 pub trait Connection
 where
@@ -69,10 +119,58 @@ where
     fn exec(&mut self, stmt : &AnyStatement) -> StatementOutput;
     
     fn query_async(&mut self, stmts : &[AnyStatement]) -> Vec<StatementOutput>;
+
+    /// Executes a query, fetching only the first page of rows when a page size
+    /// was configured. The remaining rows are fetched by fetch_page, with the index
+    /// ix of the statement output informed here. The default implementation fetches all rows.
+    fn query_paged(&mut self, _ix : usize, q : &str, params : &[Param]) -> StatementOutput {
+        self.query_with_params(q, params)
+    }
+
+    /// Fetches the next page of rows of the result at index ix of the last execution.
+    /// Returns an empty table when there are no more rows.
+    fn fetch_page(&mut self, _ix : usize) -> Result<Table, String> {
+        Err(String::from("Paged results are unsupported for this engine"))
+    }
+
+    /// Indices of the results of the last execution with rows yet to be fetched.
+    fn paged_results(&self) -> Vec<usize> {
+        Vec::new()
+    }
+
+    /// Discards the rows that were not fetched for the last executed queries.
+    fn close_pages(&mut self) {
+
+    }
     
     fn exec_transaction(&mut self, stmt : &AnyStatement) -> StatementOutput;
 
-    fn listen_at_channel(&mut self, channel : String);
+    /// Executes the transaction that writes the changes made to the rows of a table. The
+    /// transaction is rolled back if any update or delete changes no rows, which means the
+    /// row was changed or removed by someone else after it was displayed.
+    fn exec_edit(&mut self, _stmt : &AnyStatement) -> StatementOutput {
+        StatementOutput::Invalid(String::from("Table edition is unsupported for this engine"), false)
+    }
+
+    /// Starts listening to notifications sent at the channel. Notifications
+    /// received afterwards are returned by take_notifications.
+    fn listen_at_channel(&mut self, _channel : String) -> Result<(), String> {
+        Err(String::from("Notifications are unsupported for this engine"))
+    }
+
+    fn unlisten_at_channel(&mut self, _channel : String) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Returns the notifications received since the last call.
+    fn take_notifications(&mut self) -> Vec<Notification> {
+        Vec::new()
+    }
+
+    /// Returns a handle to cancel the statement being executed, if the engine supports it.
+    fn canceller(&self) -> Option<Canceller> {
+        None
+    }
 
     fn conn_info(&self) -> ConnectionInfo;
 
@@ -85,27 +183,153 @@ where
         cols : &[String],
     ) -> Result<usize, String>;
 
+    /// Imports the table into the destination, calling progress with the number of rows sent
+    /// so far. The default implementation reports progress only when the import is complete.
+    fn import_with_progress(
+        &mut self,
+        tbl : &mut Table,
+        dst : &str,
+        cols : &[String],
+        progress : &dyn Fn(usize)
+    ) -> Result<usize, String> {
+        let n = self.import(tbl, dst, cols)?;
+        progress(n);
+        Ok(n)
+    }
+
+    /// Copies a table (or query result) from the database, returning its content formatted
+    /// according to the copy options. The default implementation builds the content from
+    /// the query result; engines with a copy protocol should override it.
+    fn copy_out(&mut self, copy : &Copy) -> Result<Vec<u8>, String> {
+        let opts = CopyOptions::from_str(&copy.options)?;
+        let cols = if copy.cols.len() > 0 {
+            copy.cols.join(",")
+        } else {
+            String::from("*")
+        };
+
+        // Copy from queries take the form copy (select ...) to 'file'
+        let sql = if copy.table.starts_with("(") && copy.table.ends_with(")") {
+            format!("select {} from {} as copy_query", cols, copy.table)
+        } else {
+            format!("select {} from {}", cols, copy.table)
+        };
+        match self.query(&sql) {
+            StatementOutput::Valid(_, tbl) => opts.write_table(&tbl),
+            StatementOutput::Invalid(e, _) => Err(e),
+            _ => Err(String::from("Invalid copy output"))
+        }
+    }
+
+    /// Copies the content (formatted according to the copy options) into a table,
+    /// returning the number of inserted rows. The default implementation parses the
+    /// content into a table and calls import; engines with a copy protocol should override it.
+    fn copy_in(&mut self, copy : &Copy, data : &[u8]) -> Result<usize, String> {
+        let opts = CopyOptions::from_str(&copy.options)?;
+        let mut tbl = opts.read_table(data, &copy.cols[..])?;
+        let cols = tbl.names();
+        self.import(&mut tbl, &copy.table, &cols[..])
+    }
+
+    /// Executes a copy to/from a client-side file or program.
+    fn run_copy(&mut self, copy : &Copy) -> StatementOutput {
+        match copy.target {
+            CopyTarget::To => {
+                let ans = self.copy_out(copy)
+                    .and_then(|data| write_to_client(&copy.client, &data[..]) );
+                match ans {
+                    Ok(_) => StatementOutput::Statement(format!("Copied {} to {}", copy.table, copy.client)),
+                    Err(e) => StatementOutput::Invalid(e, false)
+                }
+            },
+            CopyTarget::From => {
+                let ans = read_from_client(&copy.client)
+                    .and_then(|data| self.copy_in(copy, &data[..]) );
+                match ans {
+                    Ok(n) => StatementOutput::Statement(format!("Copied {} rows from {} to {}", n, copy.client, copy.table)),
+                    Err(e) => StatementOutput::Invalid(e, false)
+                }
+            }
+        }
+    }
+
+    /// Executes a query, binding the parameters to its named placeholders. Engines
+    /// that do not support statement parameters only accept queries without them.
+    fn query_with_params(&mut self, q : &str, params : &[Param]) -> StatementOutput {
+        if params.is_empty() {
+            self.query(q)
+        } else {
+            StatementOutput::Invalid(format!("Statement parameters are unsupported for this engine"), false)
+        }
+    }
+
+    /// Executes a statement, binding the parameters to its named placeholders. Engines
+    /// that do not support statement parameters only accept statements without them.
+    fn exec_with_params(&mut self, stmt : &AnyStatement, params : &[Param]) -> StatementOutput {
+        if params.is_empty() {
+            self.exec(stmt)
+        } else {
+            StatementOutput::Invalid(format!("Statement parameters are unsupported for this engine"), false)
+        }
+    }
+
     /// It is important that every time this method is called,
     /// at least one query result is pushed into the queue, or else
     /// the GUI will be insensitive waiting for a response.
     fn try_run(
         &mut self,
         query_seq : String,
-        // subs : &HashMap<String, String>,
+        subs : &HashMap<String, String>,
         lock : SafetyLock,
         is_schedule : bool
     ) -> Result<Vec<StatementOutput>, String> {
 
-        match crate::sql::parsing::fully_parse_sql(&query_seq) {
+        match crate::sql::parsing::fully_parse_engine_sql(&query_seq, self.conn_info().engine) {
             Ok(stmts) => {
                 
                 if stmts.len() == 0 {
                     return Err(String::from("Empty statement sequence"));
                 }
 
+                let canceller = self.canceller();
+                if let Some(canceller) = &canceller {
+                    canceller.reset();
+                }
+                let is_cancelled = || canceller.as_ref().map(|c| c.is_requested() ).unwrap_or(false);
+
+                // Results of previous executions cannot be fetched anymore.
+                self.close_pages();
+
+                // Values are bound to all placeholders before anything is executed, so that
+                // a missing value does not leave the script partially executed.
+                let all_params = params::collect_params(&stmts[..], subs);
+                let mut stmt_params = Vec::new();
+                for stmt in &stmts {
+                    match stmt {
+                        AnyStatement::Local(_) => {
+                            stmt_params.push(Vec::new());
+                        },
+                        AnyStatement::ParsedTransaction { raw, .. } => {
+                            if params::placeholder_names(raw)?.len() > 0 {
+                                return Err(String::from("Parameters inside transaction blocks are currently unsupported"));
+                            }
+                            stmt_params.push(Vec::new());
+                        },
+                        other => {
+                            stmt_params.push(params::statement_params(other.sql(), &all_params[..])?);
+                        }
+                    }
+                }
+                let has_params = stmt_params.iter().any(|ps| ps.len() > 0 );
+                let has_locals = stmts.iter().any(|stmt| match stmt {
+                    AnyStatement::Local(_) => true,
+                    _ => false
+                });
+
                 let all_queries = stmts.iter().all(|stmt| {
                     match stmt {
                         AnyStatement::Parsed(stmt, _) => crate::sql::is_like_query(&stmt),
+                        AnyStatement::Local(LocalStatement::Decl(_)) => true,
                         _ => false
                     }
                 } );
@@ -115,22 +339,26 @@ where
                 }
                 
                 // If sequence is exclusively composed of query statements, perform asysnchronous execution.
-                if all_queries && lock.enable_async {
-                    return Ok(self.query_async(&stmts[..]));
+                if all_queries && !has_params && !has_locals && lock.enable_async {
+                    let results = self.query_async(&stmts[..]);
+                    if is_cancelled() {
+                        return Ok(results.into_iter().map(mark_cancelled).collect());
+                    }
+                    return Ok(results);
                 }
                 
                 // If sequence has at least one non-query statement, default to synchronous exection.
                 let mut results = Vec::new();
                 
-                for any_stmt in stmts {
+                for (any_stmt, ps) in stmts.into_iter().zip(stmt_params) {
                     match any_stmt {
                         AnyStatement::Parsed(stmt, s) => match stmt {
                             Statement::Query(_q) => {
-                                results.push(self.query(&s, /*&subs*/));
+                                results.push(self.query_paged(results.len(), &s, &ps[..]));
                             },
                             stmt => {
                                 lock.accepts(&stmt)?;
-                                results.push(self.exec(&AnyStatement::Parsed(stmt.clone(), format!("{}", s)), /*&subs*/));
+                                results.push(self.exec_with_params(&AnyStatement::Parsed(stmt.clone(), format!("{}", s)), &ps[..]));
                             }
                         },
                         AnyStatement::ParsedTransaction { begin, middle, end, raw } => {
@@ -144,18 +372,39 @@ where
                                 raw : raw.clone() 
                             }));
                         },
+                        AnyStatement::Local(LocalStatement::Copy(copy)) => {
+                            results.push(self.run_copy(&copy));
+                        },
+                        AnyStatement::Local(LocalStatement::Decl(_)) => {
+                            // Declarations were already bound to the statement parameters.
+                        },
                         AnyStatement::Local(_local) => {
-                            // Self::run_local_statement(&local, conn, exec, &mut results)?;
                             return Err(String::from("Unsupported statement"));
                         },
                         AnyStatement::Raw(stmt_tokens, stmt_string, is_select) => {
                             if is_select {
-                                results.push(self.query(&format!("{}", stmt_string), /*&subs*/));
+                                results.push(self.query_paged(results.len(), &format!("{}", stmt_string), &ps[..]));
                             } else {
-                                results.push(self.exec(&AnyStatement::Raw(stmt_tokens, format!("{}", stmt_string), is_select), /*&subs*/));
+                                results.push(self.exec_with_params(&AnyStatement::Raw(stmt_tokens, format!("{}", stmt_string), is_select), &ps[..]));
                             }
                         }
                     }
+
+                    // Statements after a cancel request are not executed.
+                    if is_cancelled() {
+                        if let Some(last) = results.pop() {
+                            results.push(mark_cancelled(last));
+                        }
+                        if !matches!(results.last(), Some(StatementOutput::Cancelled(_))) {
+                            results.push(StatementOutput::Cancelled(String::from(CANCELLED_MSG)));
+                        }
+                        break;
+                    }
+                }
+
+                // A script with only declarations.
+                if results.is_empty() {
+                    results.push(StatementOutput::Empty);
                 }
                 
                 Ok(results)
@@ -0,0 +1,584 @@
+/*Copyright (c) 2022 Diego da Silva Lima. All rights reserved.
+
+This work is licensed under the terms of the GPL v3.0 License.  
+For a copy, see http://www.gnu.org/licenses.*/
+
+use crate::sql::{*, object::*};
+use std::error::Error;
+use crate::tables::table::{Table};
+use crate::tables::column::Column;
+use crate::tables::nullable::NullableColumn;
+use crate::sql::parsing::AnyStatement;
+use super::{Connection, Canceller};
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::str::FromStr;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+use crate::client::{ConnectionInfo, ConnURI, ConnConfig, TlsVersion, ClientCert, SslMode, Engine};
+use sqlparser::ast::Statement;
+use mysql::prelude::Queryable;
+use mysql::{Conn, Opts, OptsBuilder, SslOpts, TxOpts, ClientIdentity};
+use mysql::consts::CapabilityFlags;
+use mysql::consts::ColumnType;
+use rust_decimal::Decimal;
+use itertools::Itertools;
+
+pub struct MySqlConnection {
+
+    info : ConnectionInfo,
+
+    conn : Conn,
+
+    canceller : Canceller
+
+}
+
+// Seconds to wait for the server to accept the connection.
+const CONNECT_TIMEOUT : u64 = 10;
+
+// Character set number of binary strings, used to tell blobs from text columns.
+const BINARY_CHARSET : u16 = 63;
+
+/* MySQL kills running statements by a KILL QUERY statement sent from another session. The
+canceller keeps the connection options (including the password) to authenticate this session,
+which is opened at a separate thread so that the GUI does not wait for the server. */
+fn mysql_canceller(opts : Opts, conn_id : u32) -> Canceller {
+    Canceller::new(move || {
+        let opts = opts.clone();
+        thread::spawn(move || {
+            match Conn::new(opts) {
+                Ok(mut conn) => {
+                    if let Err(e) = conn.query_drop(format!("kill query {}", conn_id)) {
+                        eprintln!("Error sending cancel request: {}", e);
+                    }
+                },
+                Err(e) => {
+                    eprintln!("Error sending cancel request: {}", e);
+                }
+            }
+        });
+        Ok(())
+    })
+}
+
+/* Without a root certificate, the server certificate is verified against the system
+certificates for the verify-ca and verify-full modes, and is not verified otherwise. */
+fn tls_opts(uri : &ConnURI, tls_version : TlsVersion, mode : SslMode) -> Result<SslOpts, String> {
+    match tls_version {
+        TlsVersion { major : 1, minor : 0 } | TlsVersion { major : 1, minor : 1 } | TlsVersion { major : 1, minor : 2 } => { },
+        TlsVersion { major, minor } => {
+            return Err(format!("Unrecognized TLS version: {}.{}", major, minor));
+        }
+    }
+    let cert_path = if let Some(cert) = &uri.info.security.cert_path {
+        let path = PathBuf::from(cert);
+        if !path.is_file() {
+            return Err(format!("Could not read root certificate:\n{}", cert));
+        }
+        Some(path)
+    } else {
+        None
+    };
+    let accept_invalid_certs = cert_path.is_none() && !mode.verifies_certificate();
+    let client_identity = match &uri.info.security.client_cert {
+        Some(ClientCert::Pkcs12 { path, passphrase }) => {
+            if !std::path::Path::new(path).is_file() {
+                return Err(format!("Could not read client certificate:\n{}", path));
+            }
+            let identity = ClientIdentity::new(PathBuf::from(path));
+            Some(match passphrase {
+                Some(p) => identity.with_password(p.clone()),
+                None => identity
+            })
+        },
+        Some(ClientCert::Pem { .. }) => {
+            return Err(format!("MySQL connections only accept client certificates as PKCS#12 archives (.p12 or .pfx)"));
+        },
+        None => None
+    };
+    Ok(SslOpts::default()
+        .with_root_cert_path(cert_path)
+        .with_client_identity(client_identity)
+        .with_danger_accept_invalid_certs(accept_invalid_certs)
+        .with_danger_skip_domain_validation(!mode.verifies_hostname()))
+}
+
+/* The driver does not take a minimum protocol version, so the version negotiated
+with the server is verified after the connection is established. */
+fn verify_tls_version(conn : &mut Conn, min_version : TlsVersion) -> Result<(), String> {
+    let version : Option<(String, String)> = conn.query_first("show session status like 'Ssl_version'")
+        .map_err(|e| format!("{}", e) )?;
+    let version = version.map(|(_, v)| v ).unwrap_or_default();
+    let (major, minor) = version.trim_start_matches("TLSv")
+        .split('.')
+        .map(|n| usize::from_str(n).ok() )
+        .next_tuple()
+        .ok_or(format!("Connection is not encrypted"))?;
+    match (major, minor) {
+        (Some(major), Some(minor)) if (major, minor) >= (min_version.major, min_version.minor) => Ok(()),
+        _ => Err(format!("Server TLS version ({}) is below the minimum version ({})", version, min_version.to_string()))
+    }
+}
+
+impl MySqlConnection {
+
+    /* Tries to build a new connection from a ConnURI. Takes the URI
+    by value, guaranteeing that after this point, the queries client state
+    does not hold in memory any security-sensitive information (except by
+    the canceller). */
+    pub fn try_new(uri : ConnURI) -> Result<Self, String> {
+        uri.verify_integrity().map_err(|e| format!("{}",e) )?;
+        if !uri.is_mysql() {
+            return Err(format!("Invalid URL for MySQL connection"));
+        }
+        let url_opts = Opts::from_url(uri.uri.as_str())
+            .map_err(|e| format!("Invalid connection string URI: {}", e) )?;
+
+        // Updates report the rows matched instead of the rows actually changed, so that
+        // an edited row set to its current values is not taken as a missing row.
+        let builder = OptsBuilder::from_opts(url_opts)
+            .tcp_connect_timeout(Some(Duration::from_secs(CONNECT_TIMEOUT)))
+            .additional_capabilities(CapabilityFlags::CLIENT_FOUND_ROWS);
+
+        // Connections are attempted in the order given by the sslmode, with the minimum
+        // TLS version of the encrypted attempts.
+        let mode = uri.info.security.effective_ssl_mode();
+        let tls_version = uri.info.security.tls_version.unwrap_or(TlsVersion { major : 1, minor : 2 });
+        let attempts = match mode {
+            SslMode::Disable => vec![None],
+            SslMode::Allow => vec![None, Some(tls_version)],
+            SslMode::Prefer => vec![Some(tls_version), None],
+            SslMode::Require | SslMode::VerifyCa | SslMode::VerifyFull => vec![Some(tls_version)]
+        };
+        let mut last_err = String::new();
+        for attempt in attempts {
+            let ssl_opts = match attempt {
+                Some(version) => Some(tls_opts(&uri, version, mode)?),
+                None => None
+            };
+            let opts : Opts = builder.clone().ssl_opts(ssl_opts).into();
+            match Conn::new(opts.clone()) {
+                Ok(mut conn) => {
+                    if let Some(version) = attempt {
+                        verify_tls_version(&mut conn, version)?;
+                    }
+                    let canceller = mysql_canceller(opts, conn.connection_id());
+                    return Ok(Self { info : uri.info, conn, canceller });
+                },
+                Err(e) => {
+                    last_err = format!("{}", e);
+                }
+            }
+        }
+        Err(last_err)
+    }
+
+    fn query_table(&mut self, query : &str) -> StatementOutput {
+        match query_mysql_table(&mut self.conn, query) {
+            Ok(mut tbl) => {
+                if let Some((name, relation)) = crate::sql::table_name_from_sql(query) {
+                    tbl.set_name(Some(name));
+                    if !relation.is_empty() {
+                        tbl.set_relation(Some(relation));
+                    }
+                }
+                if tbl.names().iter().unique().count() == tbl.names().len() {
+                    StatementOutput::Valid(query.to_string(), tbl)
+                } else {
+                    StatementOutput::Invalid(crate::sql::build_error_with_stmt("Non-unique column names", &query), false)
+                }
+            },
+            Err((e, by_engine)) => StatementOutput::Invalid(crate::sql::build_error_with_stmt(&e, &query), by_engine)
+        }
+    }
+
+    fn run_transaction(&mut self, middle : &[Statement], end : &Statement, require_changes : bool) -> StatementOutput {
+
+        // Any early return drops the transaction, which rolls it back.
+        let mut tr = match self.conn.start_transaction(TxOpts::default()) {
+            Ok(tr) => tr,
+            Err(e) => return StatementOutput::Invalid(format!("{}", e), false)
+        };
+        let mut total_changed = 0;
+        for stmt in middle {
+            match stmt {
+                Statement::StartTransaction{ .. } |
+                    Statement::Commit { .. } |
+                    Statement::Rollback { .. } |
+                    Statement::Savepoint { .. } =>
+                {
+                    return StatementOutput::Invalid(format!("Invalid statement in the middle of transaction ({}).", stmt), false);
+                },
+                _ => {
+                    match tr.query_iter(format!("{}", stmt)) {
+                        Ok(res) => {
+                            if require_changes && res.affected_rows() == 0 && crate::sql::is_update_or_delete(stmt) {
+                                return StatementOutput::Invalid(crate::sql::no_rows_changed_msg(stmt), false);
+                            }
+                            total_changed += res.affected_rows();
+                        },
+                        Err(e) => {
+                            return StatementOutput::Invalid(e.to_string(), true);
+                        }
+                    }
+                }
+            }
+        }
+        match end {
+            Statement::Commit{ .. } => {
+                match tr.commit() {
+                    Ok(_) => StatementOutput::Committed(
+                        format!("Transaction commited ({} statements; {} total rows changed)", middle.len(), total_changed),
+                        middle.len()
+                    ),
+                    Err(e) => StatementOutput::Invalid(e.to_string(), true)
+                }
+            },
+            other => {
+                match tr.rollback() {
+                    Ok(_) => match other {
+                        Statement::Rollback { .. } => StatementOutput::RolledBack("Transaction rolled back".to_string()),
+                        _ => StatementOutput::RolledBack("Transaction rolled back (invalid end statement)".to_string())
+                    },
+                    Err(e) => StatementOutput::Invalid(e.to_string(), false)
+                }
+            }
+        }
+    }
+
+}
+
+/* Builds a table from the text protocol result of a query. Errors carry
+whether they were returned by the server. */
+fn query_mysql_table(conn : &mut Conn, query : &str) -> Result<Table, (String, bool)> {
+    let mut res = conn.query_iter(query).map_err(|e| (e.to_string(), true) )?;
+    let cols : Vec<(String, ColumnType, u16)> = res.columns()
+        .as_ref()
+        .iter()
+        .map(|c| (c.name_str().to_string(), c.column_type(), c.character_set()) )
+        .collect();
+    if cols.len() == 0 {
+        return Err((String::from("No columns available"), false));
+    }
+    let mut values : Vec<Vec<mysql::Value>> = cols.iter().map(|_| Vec::new() ).collect();
+    for row in res.by_ref() {
+        let row = row.map_err(|e| (e.to_string(), true) )?;
+        for (col, v) in values.iter_mut().zip(row.unwrap()) {
+            col.push(v);
+        }
+    }
+    let mut names = Vec::new();
+    let mut tbl_cols = Vec::new();
+    for ((name, ty, charset), col_values) in cols.into_iter().zip(values) {
+        tbl_cols.push(mysql_column(ty, charset, col_values).map_err(|e| (e, false) )?);
+        names.push(name);
+    }
+    Table::new(None, names, tbl_cols).map_err(|e| (e.to_string(), false) )
+}
+
+fn mysql_values<T>(values : Vec<mysql::Value>) -> Result<Vec<Option<T>>, String>
+where
+    T : mysql::prelude::FromValue
+{
+    values.into_iter()
+        .map(|v| mysql::from_value_opt::<Option<T>>(v).map_err(|e| format!("{}", e) ) )
+        .collect()
+}
+
+/* The text protocol returns all values as strings, which are parsed according to
+the column type. Types without a corresponding column are displayed as text. */
+fn mysql_column(ty : ColumnType, charset : u16, values : Vec<mysql::Value>) -> Result<Column, String> {
+    let col : NullableColumn = match ty {
+        ColumnType::MYSQL_TYPE_TINY | ColumnType::MYSQL_TYPE_SHORT | ColumnType::MYSQL_TYPE_YEAR => {
+            mysql_values::<i16>(values)?.into()
+        },
+        ColumnType::MYSQL_TYPE_LONG | ColumnType::MYSQL_TYPE_INT24 => {
+            mysql_values::<i32>(values)?.into()
+        },
+        ColumnType::MYSQL_TYPE_LONGLONG => {
+            mysql_values::<i64>(values)?.into()
+        },
+        ColumnType::MYSQL_TYPE_FLOAT => {
+            mysql_values::<f32>(values)?.into()
+        },
+        ColumnType::MYSQL_TYPE_DOUBLE => {
+            mysql_values::<f64>(values)?.into()
+        },
+        ColumnType::MYSQL_TYPE_DECIMAL | ColumnType::MYSQL_TYPE_NEWDECIMAL => {
+            let decs : Result<Vec<Option<Decimal>>, String> = mysql_values::<String>(values)?
+                .into_iter()
+                .map(|v| v.map(|v| Decimal::from_str(&v).map_err(|e| format!("{}", e) ) ).transpose() )
+                .collect();
+            decs?.into()
+        },
+        ColumnType::MYSQL_TYPE_JSON => {
+            let jsons : Result<Vec<Option<serde_json::Value>>, String> = mysql_values::<String>(values)?
+                .into_iter()
+                .map(|v| v.map(|v| serde_json::from_str(&v).map_err(|e| format!("{}", e) ) ).transpose() )
+                .collect();
+            jsons?.into()
+        },
+        ColumnType::MYSQL_TYPE_BIT | ColumnType::MYSQL_TYPE_GEOMETRY => {
+            mysql_values::<Vec<u8>>(values)?.into()
+        },
+        ColumnType::MYSQL_TYPE_TINY_BLOB | ColumnType::MYSQL_TYPE_MEDIUM_BLOB |
+            ColumnType::MYSQL_TYPE_LONG_BLOB | ColumnType::MYSQL_TYPE_BLOB |
+            ColumnType::MYSQL_TYPE_VARCHAR | ColumnType::MYSQL_TYPE_VAR_STRING |
+            ColumnType::MYSQL_TYPE_STRING if charset == BINARY_CHARSET =>
+        {
+            mysql_values::<Vec<u8>>(values)?.into()
+        },
+        _ => {
+            mysql_values::<String>(values)?.into()
+        }
+    };
+    Ok(Column::from(col))
+}
+
+const SCHEMA_QUERY : &str = "select database()";
+
+const TBL_QUERY : &str = r#"
+select table_name, table_type from information_schema.tables
+where table_schema = database()
+order by table_name;
+"#;
+
+const COLUMN_QUERY : &str = r#"
+select table_name, column_name, data_type, column_key from information_schema.columns
+where table_schema = database()
+order by table_name, ordinal_position;
+"#;
+
+const REL_QUERY : &str = r#"
+select table_name, column_name, referenced_table_schema, referenced_table_name, referenced_column_name
+from information_schema.key_column_usage
+where table_schema = database() and referenced_table_name is not null;
+"#;
+
+const FN_QUERY : &str = r#"
+select routine_name, routine_type, data_type from information_schema.routines
+where routine_schema = database()
+order by routine_name;
+"#;
+
+const DETAILS_QUERY : &str = r#"
+select version(), @@collation_database,
+    (select cast(coalesce(sum(data_length + index_length), 0) as signed) from information_schema.tables
+        where table_schema = database()) as size;
+"#;
+
+const UPTIME_QUERY : &str = "show global status like 'Uptime'";
+
+fn string_column(tbl : &Table, ix : usize) -> Vec<String> {
+    tbl.get_column(ix)
+        .and_then(|c| {
+            let s : Option<Vec<Option<String>>> = c.clone().try_into().ok();
+            s
+        })
+        .map(|v| v.into_iter().map(|s| s.unwrap_or_default() ).collect() )
+        .or_else(|| tbl.get_column(ix).and_then(|c| c.clone().try_into().ok() ) )
+        .unwrap_or(Vec::new())
+}
+
+fn query_db_details(conn : &mut Conn) -> Result<DBDetails, Box<dyn Error>> {
+    let mut details = DBDetails::default();
+    let row : Option<(String, String, i64)> = conn.query_first(DETAILS_QUERY)?;
+    if let Some((server, locale, size)) = row {
+        details.server = format!("MySQL {}", server);
+        details.locale = locale;
+        details.size = if size < 1_000 {
+            format!("{} bytes", size)
+        } else if size < 1_000_000 {
+            format!("{:.2} kb", size as f32 / 1.0e3)
+        } else if size < 1_000_000_000 {
+            format!("{:.2} mb", size as f32 / 1.0e6)
+        } else {
+            format!("{:.2} gb", size as f32 / 1.0e9)
+        };
+    }
+    let uptime : Option<(String, u64)> = conn.query_first(UPTIME_QUERY)?;
+    details.uptime = match uptime {
+        Some((_, secs)) => format!("{}d {}h {}m", secs / 86400, (secs % 86400) / 3600, (secs % 3600) / 60),
+        None => String::from("Unknown")
+    };
+    Ok(details)
+}
+
+impl Connection for MySqlConnection {
+
+    /* MySQL limits the execution time of select statements (in milliseconds), while MariaDB
+    limits the execution time of any statement (in seconds). */
+    fn configure(&mut self, cfg : ConnConfig) {
+        let mysql_timeout = format!("set session max_execution_time = {}", cfg.timeout);
+        if self.conn.query_drop(&mysql_timeout).is_err() {
+            let mariadb_timeout = format!("set session max_statement_time = {}", cfg.timeout as f64 / 1000.0);
+            if let Err(e) = self.conn.query_drop(&mariadb_timeout) {
+                eprintln!("Unable to set statement timeout: {}", e);
+            }
+        }
+    }
+
+    fn query(&mut self, query : &str) -> StatementOutput {
+        self.query_table(query)
+    }
+
+    fn exec(&mut self, stmt : &AnyStatement) -> StatementOutput {
+        let sql = match stmt {
+            AnyStatement::Parsed(_, s) | AnyStatement::Raw(_, s, _) => s,
+            AnyStatement::ParsedTransaction { .. } => {
+                return StatementOutput::Invalid(format!("Tried to execute transaction on single exec call."), false);
+            },
+            AnyStatement::Local(_) => {
+                return StatementOutput::Invalid("Tried to execute unsupported statement".to_string(), false);
+            }
+        };
+        match self.conn.query_iter(&sql[..]) {
+            Ok(res) => {
+                let n = res.affected_rows() as usize;
+                crate::sql::build_statement_result(&stmt, n)
+            },
+            Err(e) => StatementOutput::Invalid(e.to_string(), true)
+        }
+    }
+
+    fn query_async(&mut self, stmts : &[AnyStatement]) -> Vec<StatementOutput> {
+        stmts.iter().map(|stmt| {
+            match stmt {
+                AnyStatement::Parsed(Statement::Query(_), s) | AnyStatement::Raw(_, s, true) => self.query(s),
+                other => self.exec(other)
+            }
+        }).collect()
+    }
+
+    fn exec_transaction(&mut self, stmt : &AnyStatement) -> StatementOutput {
+        match stmt {
+            AnyStatement::ParsedTransaction { middle, end, .. } => self.run_transaction(&middle[..], end, false),
+            _ => StatementOutput::Invalid(format!("Tried to execute statement as transaction"), false)
+        }
+    }
+
+    fn exec_edit(&mut self, stmt : &AnyStatement) -> StatementOutput {
+        match stmt {
+            AnyStatement::ParsedTransaction { middle, end, .. } => self.run_transaction(&middle[..], end, true),
+            _ => StatementOutput::Invalid(format!("Tried to execute statement as transaction"), false)
+        }
+    }
+
+    fn canceller(&self) -> Option<Canceller> {
+        Some(self.canceller.clone())
+    }
+
+    fn conn_info(&self) -> ConnectionInfo {
+        self.info.clone()
+    }
+
+    /* MySQL schemas are the same as databases, so only the objects of the
+    current database are shown. */
+    fn db_info(&mut self) -> Result<DBInfo, Box<dyn Error>> {
+        let schema : Option<Option<String>> = self.conn.query_first(SCHEMA_QUERY)?;
+        let schema = schema.flatten().ok_or("No database selected")?;
+
+        let tbls = query_mysql_table(&mut self.conn, TBL_QUERY).map_err(|e| e.0 )?;
+        let cols = query_mysql_table(&mut self.conn, COLUMN_QUERY).map_err(|e| e.0 )?;
+        let rels = query_mysql_table(&mut self.conn, REL_QUERY).map_err(|e| e.0 )?;
+        let fns = query_mysql_table(&mut self.conn, FN_QUERY).map_err(|e| e.0 )?;
+
+        let (col_tbls, col_names, col_tys, col_keys) = (
+            string_column(&cols, 0),
+            string_column(&cols, 1),
+            string_column(&cols, 2),
+            string_column(&cols, 3)
+        );
+        let mut tbl_cols : HashMap<String, (Vec<String>, Vec<String>, Vec<String>)> = HashMap::new();
+        for (((tbl, name), ty), key) in col_tbls.into_iter().zip(col_names).zip(col_tys).zip(col_keys) {
+            let entry = tbl_cols.entry(tbl).or_insert((Vec::new(), Vec::new(), Vec::new()));
+            if &key[..] == "PRI" {
+                entry.2.push(name.clone());
+            }
+            entry.0.push(name);
+            entry.1.push(ty);
+        }
+
+        let mut tbl_rels : HashMap<String, Vec<Relation>> = HashMap::new();
+        let rel_cols : Vec<Vec<String>> = (0..5).map(|ix| string_column(&rels, ix) ).collect();
+        for i in 0..rels.nrows() {
+            tbl_rels.entry(rel_cols[0][i].clone()).or_insert(Vec::new()).push(Relation {
+                src_col : rel_cols[1][i].clone(),
+                tgt_schema : rel_cols[2][i].clone(),
+                tgt_tbl : rel_cols[3][i].clone(),
+                tgt_col : rel_cols[4][i].clone()
+            });
+        }
+
+        let mut tbl_objs = Vec::new();
+        let mut view_objs = Vec::new();
+        for (name, ty) in string_column(&tbls, 0).into_iter().zip(string_column(&tbls, 1)) {
+            let (names, tys, pks) = tbl_cols.remove(&name).unwrap_or((Vec::new(), Vec::new(), Vec::new()));
+            let cols = crate::sql::pack_column_types(names, tys, pks).ok().unwrap_or(Vec::new());
+            if &ty[..] == "VIEW" {
+                view_objs.push(DBObject::View { schema : schema.clone(), name, cols });
+            } else {
+                let rels = tbl_rels.remove(&name).unwrap_or(Vec::new());
+                tbl_objs.push(DBObject::Table { schema : schema.clone(), name, cols, rels });
+            }
+        }
+
+        let mut fn_objs = Vec::new();
+        for ((name, _kind), ret) in string_column(&fns, 0).into_iter().zip(string_column(&fns, 1)).zip(string_column(&fns, 2)) {
+            let ret = if ret.is_empty() { None } else { DBType::from_str(&ret).ok() };
+            fn_objs.push(DBObject::Function { schema : schema.clone(), name, args : Vec::new(), arg_names : None, ret });
+        }
+
+        if !view_objs.is_empty() {
+            tbl_objs.push(DBObject::Schema { name : format!("Views ({})", schema), children : view_objs });
+        }
+        if !fn_objs.is_empty() {
+            tbl_objs.push(DBObject::Schema { name : format!("Functions ({})", schema), children : fn_objs });
+        }
+        let top_objs = vec![DBObject::Schema { name : schema.clone(), children : tbl_objs }];
+
+        let details = match query_db_details(&mut self.conn) {
+            Ok(details) => Some(details),
+            Err(e) => {
+                eprintln!("{}", e);
+                None
+            }
+        };
+        Ok(DBInfo { schema : top_objs, details })
+    }
+
+    fn import(
+        &mut self,
+        tbl : &mut Table,
+        dst : &str,
+        cols : &[String]
+    ) -> Result<usize, String> {
+
+        // An empty column list means all table columns are inserted, in order.
+        let names = tbl.names();
+        let cols = if cols.len() == 0 {
+            names
+        } else {
+            for c in cols {
+                if !names.contains(c) {
+                    return Err(format!("Column {} not found in imported table", c));
+                }
+            }
+            cols.to_vec()
+        };
+        let sql = tbl.sql_table_insertion(&dst, &cols, Engine::MySQL)?;
+        let stmt = crate::sql::parsing::fully_parse_engine_sql(&sql, Engine::MySQL).ok()
+            .filter(|stmts| stmts.len() == 1 )
+            .and_then(|mut stmts| stmts.pop() )
+            .ok_or(String::from("Invalid insertion SQL"))?;
+        crate::sql::require_insert_n(&stmt, cols.len(), tbl.nrows())?;
+        match self.exec(&stmt) {
+            StatementOutput::Statement(_) => Ok(tbl.nrows()),
+            StatementOutput::Invalid(err, _) => Err(err),
+            _ => Err(String::from("Invalid insertion output"))
+        }
+    }
+
+}
@@ -6,9 +6,15 @@ For a copy, see http://www.gnu.org/licenses.*/
 use crate::sql::{*, object::*};
 use std::error::Error;
 use crate::tables::table::{Table};
+use crate::tables::column::Column;
+use crate::tables::nullable::NullableColumn;
+use crate::tables::temporal::{self, Interval};
+use crate::tables::network::{Inet, MacAddr};
+use crate::tables::geometry::Geometry;
+use tokio_postgres::binary_copy::BinaryCopyInWriter;
 use crate::sql::object::{DBObject, DBType, DBInfo, DBColumn};
 use crate::sql::parsing::AnyStatement;
-use super::Connection;
+use super::{Connection, Canceller};
 use std::collections::HashMap;
 use std::fs::{self};
 use std::convert::{TryFrom, TryInto};
@@ -18,8 +24,21 @@ use crate::client::ConnectionInfo;
 use crate::client::{ConnURI, ConnConfig};
 use sqlparser::ast::Statement;
 use futures::future;
+use futures::{SinkExt, TryStreamExt};
+use crate::sql::copy::{Copy, VariableType};
+use crate::sql::params::{self, Param};
+use tokio_postgres::types::{ToSql, Type};
+use tokio_postgres::{AsyncMessage, CancelToken, Socket};
+use tokio_postgres::tls::{MakeTlsConnect, TlsConnect};
+use tokio::io::{AsyncRead, AsyncWrite};
+use futures::StreamExt;
+use std::sync::mpsc::{self, Sender, Receiver};
+use crate::sql::notify::{self, Notification};
 use std::ops::Range;
 use crate::client::TlsVersion;
+use crate::client::ClientCert;
+use crate::client::SslMode;
+use crate::client::Engine;
 
 pub struct PostgresConnection {
 
@@ -29,131 +48,253 @@ pub struct PostgresConnection {
     
     rt : Option<tokio::runtime::Runtime>,
 
-}
+    // Notifications forwarded by the task that drives the connection.
+    notifications : Receiver<Notification>,
 
-const CERT_ERR : &str =
-r#"Remote connections without a root certificate
-are unsupported."#;
+    listen_channels : Vec<String>,
 
-const NOT_ENCRYPTED_ERR : &str =
-r#"Non-encrypted connections are only supported for
-hosts accessible locally or via a private network"#;
+    canceller : Canceller,
 
-const ERR_MISSING_SSL : &str =
-r#"Tried to connect without SSL mode 'require' at connection URL"#;
+    // Rows fetched at a time for query results (zero fetches all rows at once).
+    page_size : usize,
 
-async fn connect(
-    rt : &tokio::runtime::Runtime, 
-    uri : &ConnURI
-) -> Result<tokio_postgres::Client, String> {
-
-    uri.verify_integrity().map_err(|e| format!("{}",e) )?;
-    if !uri.is_postgres() {
-        return Err(format!("Invalid URL for postgres connection"));
-    }
+    // Cursors of results with rows yet to be fetched, with the index of the statement output that returned them.
+    cursors : Vec<(String, usize)>,
 
-    if let Some(tls_version) = uri.info.security.tls_version {
+    // Used to give unique names to cursors.
+    n_cursors : usize
 
-        use native_tls::{Certificate, TlsConnector};
-        use postgres_native_tls::MakeTlsConnector;
-        
-        if !uri.require_tls() {
-            return Err(format!("{}", ERR_MISSING_SSL));
-        }
-        if let Ok(cfg) = tokio_postgres::config::Config::from_str(uri.uri.as_str()) {
-            if cfg.get_ssl_mode() != tokio_postgres::config::SslMode::Require {
-                return Err(format!("{}", ERR_MISSING_SSL));
-            }
-        } else {
-            return Err(format!("Invalid connection string URI"));
-        }
+}
 
-        let cert_content = if let Some(cert) = &uri.info.security.cert_path {
-            match fs::read(cert) {
-                Ok(content) => {
-                    content
+/* Spawns the task that drives the connection. Notifications are not returned by the client
+calls, but polled from the connection, so they are forwarded to the PostgresConnection here. */
+fn spawn_connection<S, T>(
+    rt : &tokio::runtime::Runtime,
+    mut conn : tokio_postgres::Connection<S, T>,
+    notif_sender : Sender<Notification>
+)
+where
+    S : AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    T : AsyncRead + AsyncWrite + Unpin + Send + 'static
+{
+    rt.spawn(async move {
+        let mut messages = futures::stream::poll_fn(move |cx| conn.poll_message(cx) );
+        while let Some(msg) = messages.next().await {
+            match msg {
+                Ok(AsyncMessage::Notification(n)) => {
+                    let notif = Notification { channel : n.channel().to_string(), payload : n.payload().to_string() };
+                    if notif_sender.send(notif).is_err() {
+                        break;
+                    }
                 },
+                Ok(_) => { },
                 Err(e) => {
-                    return Err(format!("Could not read root certificate:\n{}", e) );
+                    eprintln!("{}", e);
+                    break;
                 }
             }
-        } else {
-            return Err(format!("{}", CERT_ERR));
-        };
+        }
+    });
+}
 
-        let min_version = match tls_version {
-            TlsVersion { major : 1, minor : 0 } => {
-                native_tls::Protocol::Tlsv10
-            },
-            TlsVersion { major : 1, minor : 1 } => {
-                native_tls::Protocol::Tlsv11
-            },
-            TlsVersion { major : 1, minor : 2 } => {
-                native_tls::Protocol::Tlsv12
-            },
-            TlsVersion { major, minor } => {
-                return Err(format!("Unrecognized TLS version: {}.{}", major, minor));
+/* Cancel requests are sent by a new connection to the server, which must use the same
+TLS setting as the original connection. The request is spawned at the connection runtime,
+so that the GUI does not wait for the server. */
+fn pg_canceller<T>(rt : &tokio::runtime::Runtime, token : CancelToken, tls : T) -> Canceller
+where
+    T : MakeTlsConnect<Socket> + Clone + Send + Sync + 'static,
+    T::Stream : Send,
+    T::TlsConnect : Send,
+    <T::TlsConnect as TlsConnect<Socket>>::Future : Send
+{
+    let handle = rt.handle().clone();
+    Canceller::new(move || {
+        let token = token.clone();
+        let tls = tls.clone();
+        handle.spawn(async move {
+            if let Err(e) = token.cancel_query(tls).await {
+                eprintln!("Error sending cancel request: {}", e);
             }
-        };
+        });
+        Ok(())
+    })
+}
 
-        let verify_hostname = if let Some(v) = uri.info.security.verify_hostname {
-            v
-        } else {
-            return Err(format!("Verify hostname setting unspecified"));
-        };
+/* Loads the certificate and private key presented to servers that authenticate clients by their
+certificates. PEM private keys should be unencrypted PKCS#8 keys (BEGIN PRIVATE KEY). */
+pub fn client_identity(client_cert : &ClientCert) -> Result<native_tls::Identity, String> {
+    match client_cert {
+        ClientCert::Pem { cert_path, key_path } => {
+            let cert = fs::read(cert_path)
+                .map_err(|e| format!("Could not read client certificate:\n{}", e) )?;
+            let key = fs::read(key_path)
+                .map_err(|e| format!("Could not read client private key:\n{}", e) )?;
+            if !String::from_utf8_lossy(&key).contains("BEGIN PRIVATE KEY") {
+                return Err(format!("Client private key should be an unencrypted PKCS#8 PEM key\n(convert it with openssl pkcs8 -topk8 -nocrypt)"));
+            }
+            native_tls::Identity::from_pkcs8(&cert, &key)
+                .map_err(|e| format!("Invalid client certificate or key:\n{}", e) )
+        },
+        ClientCert::Pkcs12 { path, passphrase } => {
+            let archive = fs::read(path)
+                .map_err(|e| format!("Could not read client certificate:\n{}", e) )?;
+            let passphrase = passphrase.as_ref().map(|p| &p[..] ).unwrap_or("");
+            native_tls::Identity::from_pkcs12(&archive, passphrase)
+                .map_err(|e| format!("Invalid client certificate archive or passphrase:\n{}", e) )
+        }
+    }
+}
+
+/* Builds the TLS connector for the sslmode of the connection. Without a root certificate,
+the server certificate is verified against the system certificates for the verify-ca and
+verify-full modes, and is not verified otherwise (as libpq does). */
+fn tls_connector(uri : &ConnURI, mode : SslMode) -> Result<HostTlsConnector, String> {
 
+    use native_tls::{Certificate, TlsConnector};
+
+    let min_version = match uri.info.security.tls_version.unwrap_or(TlsVersion { major : 1, minor : 2 }) {
+        TlsVersion { major : 1, minor : 0 } => {
+            native_tls::Protocol::Tlsv10
+        },
+        TlsVersion { major : 1, minor : 1 } => {
+            native_tls::Protocol::Tlsv11
+        },
+        TlsVersion { major : 1, minor : 2 } => {
+            native_tls::Protocol::Tlsv12
+        },
+        TlsVersion { major, minor } => {
+            return Err(format!("Unrecognized TLS version: {}.{}", major, minor));
+        }
+    };
+
+    let mut builder = TlsConnector::builder();
+    builder.use_sni(true)
+        .disable_built_in_roots(false)
+        .min_protocol_version(Some(min_version));
+    if let Some(cert_path) = &uri.info.security.cert_path {
+        let cert_content = fs::read(cert_path)
+            .map_err(|e| format!("Could not read root certificate:\n{}", e) )?;
         let cert = Certificate::from_pem(&cert_content)
             .map_err(|e| format!("{}", e) )?;
-        let connector = TlsConnector::builder()
-            .add_root_certificate(cert)
-            .use_sni(true)
-            .danger_accept_invalid_hostnames(!verify_hostname)
-            .disable_built_in_roots(false)
-            .min_protocol_version(Some(min_version))
-            .build()
-            .map_err(|e| format!("Error establishing TLS connector:\n{}", e) )?;
-        
-        let connector = MakeTlsConnector::new(connector);
-        
-        match tokio_postgres::connect(uri.uri.as_str(), connector).await {
-            Ok((cli, conn)) => {
-                rt.spawn(conn);
-                Ok(cli)
-            },
-            Err(e) => {
-                let mut e = e.to_string();
-                format_pg_string(&mut e);
-                Err(e)
-            }
+        builder.add_root_certificate(cert);
+    } else if !mode.verifies_certificate() {
+        builder.danger_accept_invalid_certs(true);
+    }
+    builder.danger_accept_invalid_hostnames(!mode.verifies_hostname());
+    if let Some(client_cert) = &uri.info.security.client_cert {
+        builder.identity(client_identity(client_cert)?);
+    }
+    let connector = builder.build()
+        .map_err(|e| format!("Error establishing TLS connector:\n{}", e) )?;
+    Ok(HostTlsConnector { connector, host : uri.info.host.clone() })
+}
+
+/* Through an SSH tunnel, the driver connects to a local port, but the server certificate
+must still be verified against the database host. */
+#[derive(Clone)]
+struct HostTlsConnector {
+    connector : native_tls::TlsConnector,
+    host : String
+}
+
+impl<S> MakeTlsConnect<S> for HostTlsConnector
+where
+    S : AsyncRead + AsyncWrite + Unpin + Send + 'static
+{
+
+    type Stream = postgres_native_tls::TlsStream<S>;
+
+    type TlsConnect = postgres_native_tls::TlsConnector;
+
+    type Error = native_tls::Error;
+
+    fn make_tls_connect(&mut self, _domain : &str) -> Result<Self::TlsConnect, Self::Error> {
+        Ok(postgres_native_tls::TlsConnector::new(self.connector.clone(), &self.host))
+    }
+
+}
+
+// Replaces the sslmode parameter of the connection URL.
+fn with_ssl_mode(uri : &ConnURI, mode : &str) -> String {
+    let mut url = uri.target_url();
+    let pairs : Vec<(String, String)> = url.query_pairs()
+        .filter(|(k, _)| k != "sslmode" )
+        .map(|(k, v)| (k.into_owned(), v.into_owned()) )
+        .collect();
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(pairs)
+        .append_pair("sslmode", mode);
+    url.to_string()
+}
+
+async fn connect_with<T>(
+    rt : &tokio::runtime::Runtime,
+    uri : &str,
+    tls : T,
+    notif_sender : Sender<Notification>
+) -> Result<(tokio_postgres::Client, Canceller), String>
+where
+    T : MakeTlsConnect<Socket> + Clone + Send + Sync + 'static,
+    T::Stream : Send + 'static,
+    T::TlsConnect : Send,
+    <T::TlsConnect as TlsConnect<Socket>>::Future : Send
+{
+    match tokio_postgres::connect(uri, tls.clone()).await {
+        Ok((cli, conn)) => {
+            spawn_connection(rt, conn, notif_sender);
+            let canceller = pg_canceller(rt, cli.cancel_token(), tls);
+            Ok((cli, canceller))
+        },
+        Err(e) => {
+            let mut e = e.to_string();
+            format_pg_string(&mut e);
+            Err(e)
         }
-     
-    } else {
-    
-        // Only connect without SSL/TLS when the client is local.
-        if uri.info.is_localhost() || uri.info.is_private_network() {
-            
-            if uri.require_tls() {
-                return Err(format!("Tried to connect without TLS, but connection requires it in its URL."));
-            }
-            
-            match tokio_postgres::connect(&uri.uri[..], tokio_postgres::NoTls{ }).await {
-                Ok((cli, conn)) => {
-                    rt.spawn(conn);
-                    Ok(cli)
-                },
-                Err(e) => {
-                    let mut e = e.to_string();
-                    format_pg_string(&mut e);
-                    Err(e)
+    }
+}
+
+async fn connect(
+    rt : &tokio::runtime::Runtime, 
+    uri : &ConnURI,
+    notif_sender : Sender<Notification>
+) -> Result<(tokio_postgres::Client, Canceller), String> {
+
+    uri.verify_integrity().map_err(|e| format!("{}",e) )?;
+    if !uri.is_postgres() {
+        return Err(format!("Invalid URL for postgres connection"));
+    }
+
+    // Unix domain sockets are never encrypted.
+    if uri.info.is_socket() {
+        return connect_with(rt, &with_ssl_mode(uri, "disable"), tokio_postgres::NoTls{ }, notif_sender).await;
+    }
+
+    let mode = uri.info.security.effective_ssl_mode();
+    match mode {
+        SslMode::Disable => {
+            connect_with(rt, &with_ssl_mode(uri, "disable"), tokio_postgres::NoTls{ }, notif_sender).await
+        },
+        SslMode::Allow => {
+            match connect_with(rt, &with_ssl_mode(uri, "disable"), tokio_postgres::NoTls{ }, notif_sender.clone()).await {
+                Ok(ans) => Ok(ans),
+                Err(_) => {
+                    connect_with(rt, &with_ssl_mode(uri, "require"), tls_connector(uri, mode)?, notif_sender).await
                 }
             }
-        } else {
-            Err(format!("{}", NOT_ENCRYPTED_ERR))
+        },
+
+        // The driver falls back to a non-encrypted connection when the server does not support TLS.
+        SslMode::Prefer => {
+            connect_with(rt, &with_ssl_mode(uri, "prefer"), tls_connector(uri, mode)?, notif_sender).await
+        },
+        SslMode::Require | SslMode::VerifyCa | SslMode::VerifyFull => {
+            connect_with(rt, &with_ssl_mode(uri, "require"), tls_connector(uri, mode)?, notif_sender).await
         }
     }
 }
 
-async fn run_transaction(client : &mut tokio_postgres::Client, any_stmt : &AnyStatement) -> StatementOutput {
+async fn run_transaction(client : &mut tokio_postgres::Client, any_stmt : &AnyStatement, require_changes : bool) -> StatementOutput {
 
     /* There is an early return for any queries or executions that fail from within
     the transactiton. We rely on the implicit rollback issued when the transaction goes out
@@ -204,6 +345,9 @@ async fn run_transaction(client : &mut tokio_postgres::Client, any_stmt : &AnySt
                             
                             _other_stmt => {
                                 match tr.execute(&format!("{}", stmt), &[]).await {
+                                    Ok(0) if require_changes && crate::sql::is_update_or_delete(stmt) => {
+                                        return StatementOutput::Invalid(crate::sql::no_rows_changed_msg(stmt), false);
+                                    },
                                     Ok(n) => {
                                         total_changed += n;
                                     },
@@ -271,18 +415,98 @@ async fn run_transaction(client : &mut tokio_postgres::Client, any_stmt : &AnySt
 
 impl PostgresConnection {
 
+    fn next_cursor_name(&mut self) -> String {
+        self.n_cursors += 1;
+        format!("queries_cursor_{}", self.n_cursors)
+    }
+
+    // Keeps the cursor open only if the page was filled (i.e. there might be more rows to fetch).
+    fn keep_or_close_cursor(&mut self, cursor : String, ix : usize, n_fetched : usize) {
+        if n_fetched >= self.page_size {
+            self.cursors.push((cursor, ix));
+        } else {
+            self.close_cursor(&cursor);
+        }
+    }
+
+    fn close_cursor(&mut self, cursor : &str) {
+        let close = format!("close {}", cursor);
+        let ans = self.rt.as_ref().unwrap().block_on(async {
+            self.client.batch_execute(&close[..]).await
+        });
+        if let Err(e) = ans {
+            eprintln!("{}", e);
+        }
+    }
+
+    /* Fetches all rows of the queries, without declaring any cursors. Used for the metadata
+    queries, which are not part of the user script and must not be truncated to a page. */
+    fn query_all(&mut self, stmts : &[AnyStatement]) -> Vec<StatementOutput> {
+        let rt = self.rt.take().unwrap();
+        let res = rt.block_on(async {
+            query_multiple(&mut self.client, stmts).await
+        });
+        self.rt = Some(rt);
+        match res {
+            Ok(vec_rows) => {
+                let mut out = Vec::new();
+                assert!(stmts.len() == vec_rows.len());
+                for i in 0..stmts.len() {
+                    out.push(build_table(&vec_rows[i], stmts[i].sql()));
+                }
+                out
+            },
+            Err(e) => {
+                let mut e = format!("{}", e);
+                format_pg_string(&mut e);
+                vec![StatementOutput::Invalid(e, false)]
+            }
+        }
+    }
+
+    fn query_async_paged(&mut self, stmts : &[AnyStatement]) -> Vec<StatementOutput> {
+        let cursors : Vec<String> = stmts.iter().map(|_| self.next_cursor_name() ).collect();
+        let page_size = self.page_size;
+        let client = &self.client;
+        let res = self.rt.as_ref().unwrap().block_on(async {
+            future::join_all(stmts.iter().zip(cursors.iter()).map(|(stmt, cursor)| {
+                declare_and_fetch(client, cursor, stmt.sql(), &[], page_size)
+            })).await
+        });
+        let mut out = Vec::new();
+        for (ix, ((stmt, cursor), ans)) in stmts.iter().zip(cursors).zip(res).enumerate() {
+            match ans {
+                Ok(rows) => {
+                    self.keep_or_close_cursor(cursor, ix, rows.len());
+                    out.push(build_table(&rows[..], stmt.sql()));
+                },
+                Err(e) => {
+                    out.push(e);
+                }
+            }
+        }
+        out
+    }
+
     /* Tries to build a new connection from a ConnURI. Takes the URI
     by value, guaranteeing that after this point, the queries client state
     does not hold in memory any security-sensitive information. */
     pub fn try_new(uri : ConnURI) -> Result<Self, String> {
         let rt = tokio::runtime::Runtime::new().unwrap();
-        let client = rt.block_on(async {
-            connect(&rt, &uri).await
+        let (notif_sender, notifications) = mpsc::channel();
+        let (client, canceller) = rt.block_on(async {
+            connect(&rt, &uri, notif_sender).await
         })?;
         Ok(Self {
             info : uri.info,
             rt : Some(rt),
             client,
+            notifications,
+            listen_channels : Vec::new(),
+            canceller,
+            page_size : 0,
+            cursors : Vec::new(),
+            n_cursors : 0
         })
     }
 
@@ -332,9 +556,127 @@ async fn query_multiple(
     future::try_join_all(query_futures).await
 }
 
+/* Paged results are fetched from cursors declared WITH HOLD, which remain open after the implicit
+commit of the declare statement, and are independent of any transactions executed afterwards. The
+trade-off is that the server runs the query to completion and materializes the whole result when the
+cursor is declared, so the first page only arrives after the full query time, and the result takes
+server storage until the cursor is closed. Only the fetched rows are kept in memory by the client.
+A cursor without hold (or a portal) would stream rows as they are produced, but would require a
+transaction to be kept open between pages, holding locks and blocking the statements the user
+executes in the meantime. */
+async fn declare_and_fetch(
+    client : &Client,
+    cursor : &str,
+    query : &str,
+    params : &[Param],
+    page_size : usize
+) -> Result<Vec<tokio_postgres::Row>, StatementOutput> {
+    let declare = format!("declare {} no scroll cursor with hold for {}", cursor, query.trim().trim_end_matches(';'));
+    if params.is_empty() {
+        client.batch_execute(&declare[..]).await.map_err(pg_error)?;
+    } else {
+        let (stmt, values) = prepare_with_params(client, &declare, params).await?;
+        let values : Vec<&(dyn ToSql + Sync)> = values.iter().map(|v| v.as_ref() ).collect();
+        client.execute(&stmt, &values[..]).await.map_err(pg_error)?;
+    }
+    fetch_from_cursor(client, cursor, page_size).await.map_err(pg_error)
+}
+
+// Rows of the fetch statement are streamed, so at most page_size rows are held at once.
+async fn fetch_from_cursor(
+    client : &Client,
+    cursor : &str,
+    page_size : usize
+) -> Result<Vec<tokio_postgres::Row>, tokio_postgres::Error> {
+    let fetch = format!("fetch forward {} from {}", page_size, cursor);
+    let stream = client.query_raw(&fetch[..], std::iter::empty::<&(dyn ToSql + Sync)>()).await?;
+    stream.try_collect().await
+}
+
+fn pg_error(e : tokio_postgres::Error) -> StatementOutput {
+    let mut e = e.to_string();
+    format_pg_string(&mut e);
+    StatementOutput::Invalid(e, true)
+}
+
+type PgParam = Box<dyn ToSql + Sync>;
+
+fn pg_type(ty : VariableType) -> Type {
+    match ty {
+        VariableType::Text => Type::TEXT,
+        VariableType::Bytea => Type::BYTEA,
+        VariableType::Integer => Type::INT8,
+        VariableType::Real => Type::FLOAT8,
+        VariableType::Bool => Type::BOOL
+    }
+}
+
+/* Converts a parameter value to the type the server expects for its placeholder. Types without
+a conversion here are sent as text, which the driver only accepts for text-like types. */
+fn pg_value(p : &Param, ty : &Type) -> Result<PgParam, String> {
+    let v = p.value.trim();
+    match *ty {
+        Type::BOOL => params::parse_bool(v).map(|b| Box::new(b) as PgParam ).ok_or(p.invalid_value()),
+        Type::INT2 => i16::from_str(v).map(|n| Box::new(n) as PgParam ).map_err(|_| p.invalid_value() ),
+        Type::INT4 => i32::from_str(v).map(|n| Box::new(n) as PgParam ).map_err(|_| p.invalid_value() ),
+        Type::INT8 => i64::from_str(v).map(|n| Box::new(n) as PgParam ).map_err(|_| p.invalid_value() ),
+        Type::FLOAT4 => f32::from_str(v).map(|n| Box::new(n) as PgParam ).map_err(|_| p.invalid_value() ),
+        Type::FLOAT8 => f64::from_str(v).map(|n| Box::new(n) as PgParam ).map_err(|_| p.invalid_value() ),
+        Type::NUMERIC => rust_decimal::Decimal::from_str(v).map(|n| Box::new(n) as PgParam ).map_err(|_| p.invalid_value() ),
+        Type::JSON | Type::JSONB => serde_json::Value::from_str(v).map(|j| Box::new(j) as PgParam ).map_err(|_| p.invalid_value() ),
+        Type::UUID => uuid::Uuid::parse_str(v).map(|u| Box::new(u) as PgParam ).map_err(|_| p.invalid_value() ),
+        Type::DATE => chrono::NaiveDate::from_str(v).map(|d| Box::new(d) as PgParam ).map_err(|_| p.invalid_value() ),
+        Type::TIMESTAMP => chrono::NaiveDateTime::parse_from_str(v, "%Y-%m-%d %H:%M:%S")
+            .or_else(|_| chrono::NaiveDateTime::from_str(v) )
+            .map(|d| Box::new(d) as PgParam )
+            .map_err(|_| p.invalid_value() ),
+        Type::TIME => temporal::parse_time(v).map(|t| Box::new(t) as PgParam ).ok_or(p.invalid_value()),
+        Type::TIMESTAMPTZ => temporal::parse_timestamptz(v).map(|t| Box::new(t) as PgParam ).ok_or(p.invalid_value()),
+        Type::INTERVAL => Interval::from_str(v).map(|i| Box::new(i) as PgParam ).map_err(|_| p.invalid_value() ),
+        Type::INET | Type::CIDR => Inet::from_str(v).map(|i| Box::new(i) as PgParam ).map_err(|_| p.invalid_value() ),
+        Type::MACADDR | Type::MACADDR8 => MacAddr::from_str(v).map(|m| Box::new(m) as PgParam ).map_err(|_| p.invalid_value() ),
+        Type::BYTEA => Ok(Box::new(p.value.as_bytes().to_vec())),
+        _ => Ok(Box::new(p.value.clone()))
+    }
+}
+
+/* Prepares a statement with named placeholders, returning it with the parameter values
+converted to the types of its positional placeholders. The declared types are informed to
+the server, which infers the types of the remaining placeholders. */
+async fn prepare_with_params(
+    client : &Client,
+    sql : &str,
+    params : &[Param]
+) -> Result<(tokio_postgres::Statement, Vec<PgParam>), StatementOutput> {
+    let (pos_sql, names) = params::positional_placeholders(sql)
+        .map_err(|e| StatementOutput::Invalid(e, false) )?;
+    let mut ordered = Vec::new();
+    for name in &names {
+        let p = params.iter().find(|p| &p.name == name )
+            .ok_or(StatementOutput::Invalid(format!("Missing value for parameter '{}'", name), false))?;
+        ordered.push(p);
+    }
+    let types : Vec<Type> = ordered.iter().map_while(|p| p.ty.map(pg_type) ).collect();
+    let stmt = client.prepare_typed(&pos_sql[..], &types[..]).await
+        .map_err(|e| {
+            let mut e = e.to_string();
+            format_pg_string(&mut e);
+            StatementOutput::Invalid(e, true)
+        })?;
+    let mut values = Vec::new();
+    for (p, ty) in ordered.iter().zip(stmt.params()) {
+        values.push(pg_value(p, ty).map_err(|e| StatementOutput::Invalid(e, false) )?);
+    }
+    Ok((stmt, values))
+}
+
 impl Connection for PostgresConnection {
 
     fn configure(&mut self, cfg : ConnConfig) {
+        self.page_size = cfg.page_size;
+        if cfg.timeout == 0 {
+            return;
+        }
         let cfg_stmt = format!("set session statement_timeout to {};", cfg.timeout);
         self.rt.as_ref().unwrap().block_on(async {
             match self.client.execute(&cfg_stmt[..], &[]).await {
@@ -346,8 +688,38 @@ impl Connection for PostgresConnection {
         });
     }
 
-    fn listen_at_channel(&mut self, _channel : String) {
+    fn listen_at_channel(&mut self, channel : String) -> Result<(), String> {
+        let channel = notify::validate_channel(&channel)?;
+        if self.listen_channels.contains(&channel) {
+            return Ok(());
+        }
+        let listen_stmt = format!("listen {};", channel);
+        self.rt.as_ref().unwrap().block_on(async {
+            self.client.batch_execute(&listen_stmt[..]).await
+        }).map_err(|e| { let mut e = e.to_string(); format_pg_string(&mut e); e })?;
+        self.listen_channels.push(channel);
+        Ok(())
+    }
 
+    fn unlisten_at_channel(&mut self, channel : String) -> Result<(), String> {
+        let channel = notify::validate_channel(&channel)?;
+        if !self.listen_channels.contains(&channel) {
+            return Ok(());
+        }
+        let unlisten_stmt = format!("unlisten {};", channel);
+        self.rt.as_ref().unwrap().block_on(async {
+            self.client.batch_execute(&unlisten_stmt[..]).await
+        }).map_err(|e| { let mut e = e.to_string(); format_pg_string(&mut e); e })?;
+        self.listen_channels.retain(|ch| ch != &channel );
+        Ok(())
+    }
+
+    fn take_notifications(&mut self) -> Vec<Notification> {
+        self.notifications.try_iter().collect()
+    }
+
+    fn canceller(&self) -> Option<Canceller> {
+        Some(self.canceller.clone())
     }
 
     fn query(&mut self, query : &str) -> StatementOutput {
@@ -365,35 +737,125 @@ impl Connection for PostgresConnection {
         })
     }
 
+    fn query_with_params(&mut self, query : &str, params : &[Param]) -> StatementOutput {
+        if params.is_empty() {
+            return self.query(query);
+        }
+        self.rt.as_ref().unwrap().block_on(async {
+            let (stmt, values) = match prepare_with_params(&self.client, query, params).await {
+                Ok(prepared) => prepared,
+                Err(out) => return out
+            };
+            let values : Vec<&(dyn ToSql + Sync)> = values.iter().map(|v| v.as_ref() ).collect();
+            match self.client.query(&stmt, &values[..]).await {
+                Ok(rows) => {
+                    build_table(&rows[..], query)
+                },
+                Err(e) => {
+                    let mut e = e.to_string();
+                    format_pg_string(&mut e);
+                    StatementOutput::Invalid(e, true)
+                }
+            }
+        })
+    }
+
+    fn query_paged(&mut self, ix : usize, query : &str, params : &[Param]) -> StatementOutput {
+        if self.page_size == 0 {
+            return self.query_with_params(query, params);
+        }
+        let cursor = self.next_cursor_name();
+        let page_size = self.page_size;
+        let ans = self.rt.as_ref().unwrap().block_on(async {
+            declare_and_fetch(&self.client, &cursor, query, params, page_size).await
+        });
+        match ans {
+            Ok(rows) => {
+                self.keep_or_close_cursor(cursor, ix, rows.len());
+                build_table(&rows[..], query)
+            },
+            Err(out) => out
+        }
+    }
+
+    fn fetch_page(&mut self, ix : usize) -> Result<Table, String> {
+        let pos = self.cursors.iter().position(|(_, cursor_ix)| *cursor_ix == ix )
+            .ok_or(String::from("Query result is no longer available (re-execute the query to fetch more rows)"))?;
+        let cursor = self.cursors[pos].0.clone();
+        let page_size = self.page_size;
+        let ans = self.rt.as_ref().unwrap().block_on(async {
+            fetch_from_cursor(&self.client, &cursor, page_size).await
+        });
+        let rows = ans.map_err(|e| { let mut e = e.to_string(); format_pg_string(&mut e); e })?;
+        if rows.len() < page_size {
+            self.cursors.remove(pos);
+            self.close_cursor(&cursor);
+        }
+        if rows.is_empty() {
+            return Ok(Table::empty(Vec::new()));
+        }
+        Table::from_rows(&rows[..]).map_err(|e| e.to_string() )
+    }
+
+    fn paged_results(&self) -> Vec<usize> {
+        self.cursors.iter().map(|(_, ix)| *ix ).collect()
+    }
+
+    fn close_pages(&mut self) {
+        let cursors : Vec<String> = self.cursors.drain(..).map(|(cursor, _)| cursor ).collect();
+        for cursor in cursors {
+            self.close_cursor(&cursor);
+        }
+    }
+
+    fn exec_with_params(&mut self, stmt : &AnyStatement, params : &[Param]) -> StatementOutput {
+        if params.is_empty() {
+            return self.exec(stmt);
+        }
+        let sql = match stmt {
+            AnyStatement::Parsed(_, s) | AnyStatement::Raw(_, s, _) => s.clone(),
+            _ => return StatementOutput::Invalid("Tried to execute unsupported statement with parameters".to_string(), false)
+        };
+        self.rt.as_ref().unwrap().block_on(async {
+            let (prepared, values) = match prepare_with_params(&self.client, &sql, params).await {
+                Ok(prepared) => prepared,
+                Err(out) => return out
+            };
+            let values : Vec<&(dyn ToSql + Sync)> = values.iter().map(|v| v.as_ref() ).collect();
+            match self.client.execute(&prepared, &values[..]).await {
+                Ok(n) => crate::sql::build_statement_result(&stmt, n as usize),
+                Err(e) => {
+                    let mut e = e.to_string();
+                    format_pg_string(&mut e);
+                    StatementOutput::Invalid(e, true)
+                }
+            }
+        })
+    }
+
     fn exec_transaction(&mut self, any_stmt : &AnyStatement) -> StatementOutput {
         let rt = self.rt.take().unwrap();
         let out = rt.block_on(async {
-            run_transaction(&mut self.client, any_stmt).await
+            run_transaction(&mut self.client, any_stmt, false).await
         });
         self.rt = Some(rt);
         out
     }
-    
-    fn query_async(&mut self, stmts : &[AnyStatement]) -> Vec<StatementOutput> {
+
+    fn exec_edit(&mut self, any_stmt : &AnyStatement) -> StatementOutput {
         let rt = self.rt.take().unwrap();
-        let res = rt.block_on(async {
-            query_multiple(&mut self.client, stmts).await
+        let out = rt.block_on(async {
+            run_transaction(&mut self.client, any_stmt, true).await
         });
         self.rt = Some(rt);
-        match res {
-            Ok(vec_rows) => {
-                let mut out = Vec::new();
-                assert!(stmts.len() == vec_rows.len());
-                for i in 0..stmts.len() {
-                    out.push(build_table(&vec_rows[i], stmts[i].sql()));
-                }
-                out
-            },
-            Err(e) => {
-                let mut e = format!("{}", e);
-                format_pg_string(&mut e);
-                vec![StatementOutput::Invalid(e, false)]
-            }
+        out
+    }
+    
+    fn query_async(&mut self, stmts : &[AnyStatement]) -> Vec<StatementOutput> {
+        if self.page_size > 0 {
+            self.query_async_paged(stmts)
+        } else {
+            self.query_all(stmts)
         }
     }
     
@@ -424,6 +886,34 @@ impl Connection for PostgresConnection {
         })
     }
 
+    fn copy_out(&mut self, copy : &Copy) -> Result<Vec<u8>, String> {
+        let copy_stmt = copy.to_string();
+        self.rt.as_ref().unwrap().block_on(async {
+            let stream = self.client.copy_out(&copy_stmt[..]).await
+                .map_err(|e| { let mut e = e.to_string(); format_pg_string(&mut e); e })?;
+            futures::pin_mut!(stream);
+            let mut data = Vec::new();
+            while let Some(chunk) = stream.try_next().await.map_err(|e| format!("Copy to stdout error: {}", e) )? {
+                data.extend_from_slice(&chunk[..]);
+            }
+            Ok(data)
+        })
+    }
+
+    fn copy_in(&mut self, copy : &Copy, data : &[u8]) -> Result<usize, String> {
+        let copy_stmt = copy.to_string();
+        self.rt.as_ref().unwrap().block_on(async {
+            let sink = self.client.copy_in(&copy_stmt[..]).await
+                .map_err(|e| { let mut e = e.to_string(); format_pg_string(&mut e); e })?;
+            futures::pin_mut!(sink);
+            sink.send(bytes::Bytes::copy_from_slice(data)).await
+                .map_err(|e| format!("Copy from stdin error: {}", e) )?;
+            let n = sink.finish().await
+                .map_err(|e| { let mut e = e.to_string(); format_pg_string(&mut e); e })?;
+            Ok(n as usize)
+        })
+    }
+
     fn conn_info(&self) -> ConnectionInfo {
         self.info.clone()
     }
@@ -470,7 +960,7 @@ impl Connection for PostgresConnection {
         debug_assert!(all_queries.len() == fn_range.end);
         
         // Send schema/tables/views queries to database
-        let out = self.query_async(&all_queries[..]);
+        let out = self.query_all(&all_queries[..]);
 
         let col_outs : Vec<&Table> = out[col_range].iter().map(|o| o.table().unwrap() ).collect();
         let pk_outs : Vec<&Table> = out[pk_range].iter().map(|o| o.table().unwrap() ).collect();
@@ -547,70 +1037,255 @@ impl Connection for PostgresConnection {
         &mut self,
         tbl : &mut Table,
         dst : &str,
-        _cols : &[String]
+        cols : &[String]
     ) -> Result<usize, String> {
-    
-        let cols = tbl.names();
-        
-        let sql = tbl.sql_table_insertion(&dst, &cols)?;
-        
-        let stmt = AnyStatement::from_sql(&sql)
-            .ok_or(String::from("Invalid insertion SQL"))?;
+        self.import_with_progress(tbl, dst, cols, &|_| { })
+    }
 
-        crate::sql::require_insert_n(&stmt, tbl.ncols(), tbl.nrows())?;
-        
-        let out = self.exec(&stmt, /*&HashMap::new()*/ );
-        match out {
-            StatementOutput::Statement(_) => {
-                Ok(tbl.nrows())
-            },
-            StatementOutput::Invalid(err, _) => {
-                Err(err)
-            },
-            _ => {
-                Err(String::from("Invalid insertion output"))
+    fn import_with_progress(
+        &mut self,
+        tbl : &mut Table,
+        dst : &str,
+        cols : &[String],
+        progress : &dyn Fn(usize)
+    ) -> Result<usize, String> {
+        let tbl = import_subset(tbl, cols)?;
+        let (dst, cols_agg) = import_names(&tbl, dst);
+        let rt = self.rt.as_ref().unwrap();
+        let client = &mut self.client;
+        rt.block_on(async {
+
+            // The table creation and the copy are done in a single transaction, so a failed
+            // copy does not leave an empty table behind (the transaction is rolled back when dropped).
+            let tx = client.transaction().await
+                .map_err(|e| { let mut e = e.to_string(); format_pg_string(&mut e); e })?;
+
+            // Destination tables that do not exist yet are created with types inferred from the table columns.
+            let exists : bool = tx.query_one("select to_regclass($1) is not null", &[&dst]).await
+                .map_err(|e| { let mut e = e.to_string(); format_pg_string(&mut e); e })?
+                .get(0);
+            if !exists {
+                let create = tbl.sql_table_creation(&dst, &[], Engine::Postgres)
+                    .ok_or(format!("Unable to form create table statement"))?;
+                tx.batch_execute(&create).await
+                    .map_err(|e| { let mut e = e.to_string(); format_pg_string(&mut e); e })?;
             }
-        }
-        
-        // TODO use copy protocol instead.
-        /*self.rt.as_ref().unwrap().block_on(async {
-            let client = &mut self.client;
-            let copy_stmt = match cols.len() {
-                0 => format!("COPY {} FROM stdin with csv header quote '\"';", dst),
-                n => {
-                    /*let mut cols_agg = String::new();
-                    for i in 0..n {
-                        cols_agg += &cols[n];
-                        if i <= n-1 {
-                            cols_agg += ",";
-                        }
+
+            // Data is copied in the binary format when the table columns can be converted to the
+            // types of the destination columns without loss. Otherwise, the server parses the values
+            // from their CSV representation.
+            let types : Vec<Type> = tx.prepare(&format!("select {} from {}", cols_agg, dst)).await
+                .map_err(|e| { let mut e = e.to_string(); format_pg_string(&mut e); e })?
+                .columns()
+                .iter()
+                .map(|c| c.type_().clone() )
+                .collect();
+            let is_binary = (0..tbl.ncols()).all(|ix| column_accepts(tbl.get_column(ix).unwrap(), &types[ix]) );
+            let copy_stmt = if is_binary {
+                format!("copy {} ({}) from stdin with (format binary)", dst, cols_agg)
+            } else {
+                format!("copy {} ({}) from stdin with (format csv)", dst, cols_agg)
+            };
+            let sink = tx.copy_in(&copy_stmt[..]).await
+                .map_err(|e| { let mut e = e.to_string(); format_pg_string(&mut e); e })?;
+            let tbl_cols : Vec<&Column> = (0..tbl.ncols()).map(|ix| tbl.get_column(ix).unwrap() ).collect();
+            let nrows = tbl.nrows();
+            let n = if is_binary {
+                let writer = BinaryCopyInWriter::new(sink, &types[..]);
+                futures::pin_mut!(writer);
+                for row in 0..nrows {
+                    let values : Vec<&(dyn ToSql + Sync)> = tbl_cols.iter().map(|c| sql_field(c, row) ).collect();
+                    writer.as_mut().write(&values[..]).await
+                        .map_err(|e| format!("Copy from stdin error: {}", e) )?;
+                    if (row + 1) % IMPORT_CHUNK_SIZE == 0 {
+                        progress(row + 1);
                     }
-                    format!("COPY {} ({}) FROM stdin with csv header quote '\"';", dst, cols_agg)*/
-                    return Err(String::from("Copying from just a few columns is unsupported"))
                 }
+                writer.finish().await
+                    .map_err(|e| { let mut e = e.to_string(); format_pg_string(&mut e); e })?
+            } else {
+                futures::pin_mut!(sink);
+                let mut start = 0;
+                while start < nrows {
+                    let end = (start + IMPORT_CHUNK_SIZE).min(nrows);
+                    let chunk = csv_chunk(&tbl_cols[..], start..end);
+                    sink.send(bytes::Bytes::from(chunk)).await
+                        .map_err(|e| format!("Copy from stdin error: {}", e) )?;
+                    if end < nrows {
+                        progress(end);
+                    }
+                    start = end;
+                }
+                sink.finish().await
+                    .map_err(|e| { let mut e = e.to_string(); format_pg_string(&mut e); e })?
             };
+            tx.commit().await
+                .map_err(|e| { let mut e = e.to_string(); format_pg_string(&mut e); e })?;
+            progress(n as usize);
+            Ok(n as usize)
+        })
+    }
 
-            // TODO filter cols
-            /*if !crate::sql::object::schema_has_table(dst, schema) {
-                let create = tbl.sql_table_creation(dst, cols).unwrap();
-                client.execute(&create[..], &[])
-                    .map_err(|e| format!("{}", e) )?;
-            } else {
+}
 
-            }*/
+// Number of rows sent to the server between progress reports.
+const IMPORT_CHUNK_SIZE : usize = 10_000;
 
-            /*let mut writer = client.copy_in(&copy_stmt[..]).await
-                .map_err(|e| format!("{}", e) )?;
-            let tbl_content = crate::tables::table::full_csv_display(tbl, cols.into());
-            writer.write_all(tbl_content.as_bytes())
-                .map_err(|e| format!("Copy from stdin error: {}", e) )?;
-            writer.finish()
-                .map_err(|e| format!("Copy from stdin error: {}", e) )?;
-            Ok(tbl.shape().0)*/
-        })*/
+/// Quoted destination table name and comma-separated list of quoted column names used by
+/// the statements that create and copy into the destination table.
+pub fn import_names(tbl : &Table, dst : &str) -> (String, String) {
+    let cols : Vec<String> = tbl.names().iter()
+        .map(|c| quote_ident(c, Engine::Postgres) )
+        .collect();
+    (quote_qualified(dst, Engine::Postgres), cols.join(", "))
+}
+
+/* Selects the columns informed as the copy target (all table columns when none are informed). */
+fn import_subset(tbl : &Table, cols : &[String]) -> Result<Table, String> {
+    if cols.is_empty() {
+        return Ok(tbl.clone());
+    }
+    let mut sub_cols = Vec::new();
+    for name in cols {
+        let col = tbl.get_column_by_name(name)
+            .ok_or(format!("Column {} not present at imported table", name))?;
+        sub_cols.push(col.clone());
+    }
+    Table::new(tbl.source().name, cols.to_vec(), sub_cols).map_err(|e| format!("{}", e) )
+}
+
+fn column_accepts(col : &Column, ty : &Type) -> bool {
+    match col {
+        Column::Bool(_) | Column::Nullable(NullableColumn::Bool(_)) => <bool as ToSql>::accepts(ty),
+        Column::I8(_) | Column::Nullable(NullableColumn::I8(_)) => <i8 as ToSql>::accepts(ty),
+        Column::I16(_) | Column::Nullable(NullableColumn::I16(_)) => <i16 as ToSql>::accepts(ty),
+        Column::I32(_) | Column::Nullable(NullableColumn::I32(_)) => <i32 as ToSql>::accepts(ty),
+        Column::U32(_) | Column::Nullable(NullableColumn::U32(_)) => <u32 as ToSql>::accepts(ty),
+        Column::I64(_) | Column::Nullable(NullableColumn::I64(_)) => <i64 as ToSql>::accepts(ty),
+        Column::F32(_) | Column::Nullable(NullableColumn::F32(_)) => <f32 as ToSql>::accepts(ty),
+        Column::F64(_) | Column::Nullable(NullableColumn::F64(_)) => <f64 as ToSql>::accepts(ty),
+        Column::Numeric(_) | Column::Nullable(NullableColumn::Numeric(_)) => <rust_decimal::Decimal as ToSql>::accepts(ty),
+        Column::Str(_) | Column::Nullable(NullableColumn::Str(_)) => <String as ToSql>::accepts(ty),
+        Column::Bytes(_) | Column::Nullable(NullableColumn::Bytes(_)) => <Vec<u8> as ToSql>::accepts(ty),
+        Column::Json(_) | Column::Nullable(NullableColumn::Json(_)) => <serde_json::Value as ToSql>::accepts(ty),
+        Column::Date(_) | Column::Nullable(NullableColumn::Date(_)) => <chrono::NaiveDate as ToSql>::accepts(ty),
+        Column::Time(_) | Column::Nullable(NullableColumn::Time(_)) => <chrono::NaiveTime as ToSql>::accepts(ty),
+        Column::Timestamp(_) | Column::Nullable(NullableColumn::Timestamp(_)) => <chrono::NaiveDateTime as ToSql>::accepts(ty),
+        Column::TimestampTz(_) | Column::Nullable(NullableColumn::TimestampTz(_)) => <chrono::DateTime<chrono::Utc> as ToSql>::accepts(ty),
+        Column::Interval(_) | Column::Nullable(NullableColumn::Interval(_)) => <Interval as ToSql>::accepts(ty),
+        Column::Uuid(_) | Column::Nullable(NullableColumn::Uuid(_)) => <uuid::Uuid as ToSql>::accepts(ty),
+        Column::Inet(_) | Column::Nullable(NullableColumn::Inet(_)) => <Inet as ToSql>::accepts(ty),
+        Column::MacAddr(_) | Column::Nullable(NullableColumn::MacAddr(_)) => <MacAddr as ToSql>::accepts(ty),
+        Column::Geometry(_) | Column::Nullable(NullableColumn::Geometry(_)) => <Geometry as ToSql>::accepts(ty)
+    }
+}
+
+fn sql_field<'a>(col : &'a Column, row : usize) -> &'a (dyn ToSql + Sync) {
+    match col {
+        Column::Bool(v) => &v[row],
+        Column::I8(v) => &v[row],
+        Column::I16(v) => &v[row],
+        Column::I32(v) => &v[row],
+        Column::U32(v) => &v[row],
+        Column::I64(v) => &v[row],
+        Column::F32(v) => &v[row],
+        Column::F64(v) => &v[row],
+        Column::Numeric(v) => &v[row],
+        Column::Str(v) => &v[row],
+        Column::Bytes(v) => &v[row],
+        Column::Json(v) => &v[row],
+        Column::Date(v) => &v[row],
+        Column::Time(v) => &v[row],
+        Column::Timestamp(v) => &v[row],
+        Column::TimestampTz(v) => &v[row],
+        Column::Interval(v) => &v[row],
+        Column::Uuid(v) => &v[row],
+        Column::Inet(v) => &v[row],
+        Column::MacAddr(v) => &v[row],
+        Column::Geometry(v) => &v[row],
+        Column::Nullable(nc) => match nc {
+            NullableColumn::Bool(v) => &v[row],
+            NullableColumn::I8(v) => &v[row],
+            NullableColumn::I16(v) => &v[row],
+            NullableColumn::I32(v) => &v[row],
+            NullableColumn::U32(v) => &v[row],
+            NullableColumn::I64(v) => &v[row],
+            NullableColumn::F32(v) => &v[row],
+            NullableColumn::F64(v) => &v[row],
+            NullableColumn::Numeric(v) => &v[row],
+            NullableColumn::Str(v) => &v[row],
+            NullableColumn::Bytes(v) => &v[row],
+            NullableColumn::Json(v) => &v[row],
+            NullableColumn::Date(v) => &v[row],
+            NullableColumn::Time(v) => &v[row],
+            NullableColumn::Timestamp(v) => &v[row],
+            NullableColumn::TimestampTz(v) => &v[row],
+            NullableColumn::Interval(v) => &v[row],
+            NullableColumn::Uuid(v) => &v[row],
+            NullableColumn::Inet(v) => &v[row],
+            NullableColumn::MacAddr(v) => &v[row],
+            NullableColumn::Geometry(v) => &v[row]
+        }
+    }
+}
+
+fn bytea_hex(b : &[u8]) -> String {
+    let mut s = String::from("\\x");
+    for byte in b {
+        s += &format!("{:02x}", byte);
     }
+    s
+}
 
+// Text representation of a field for the CSV copy format (None for nulls).
+fn csv_field(col : &Column, row : usize) -> Option<String> {
+    match col {
+        Column::Bytes(v) => Some(bytea_hex(&v[row])),
+        Column::Json(v) => Some(v[row].to_string()),
+        Column::Nullable(nc) => match nc {
+            NullableColumn::Bytes(v) => v[row].as_ref().map(|b| bytea_hex(b) ),
+            NullableColumn::Json(v) => v[row].as_ref().map(|j| j.to_string() ),
+            NullableColumn::Bool(v) => v[row].map(|e| e.to_string() ),
+            NullableColumn::I8(v) => v[row].map(|e| e.to_string() ),
+            NullableColumn::I16(v) => v[row].map(|e| e.to_string() ),
+            NullableColumn::I32(v) => v[row].map(|e| e.to_string() ),
+            NullableColumn::U32(v) => v[row].map(|e| e.to_string() ),
+            NullableColumn::I64(v) => v[row].map(|e| e.to_string() ),
+            NullableColumn::F32(v) => v[row].map(|e| e.to_string() ),
+            NullableColumn::F64(v) => v[row].map(|e| e.to_string() ),
+            NullableColumn::Numeric(v) => v[row].map(|e| e.to_string() ),
+            NullableColumn::Str(v) => v[row].clone(),
+            NullableColumn::Date(v) => v[row].map(|e| e.to_string() ),
+            NullableColumn::Time(v) => v[row].map(|e| e.to_string() ),
+            NullableColumn::Timestamp(v) => v[row].map(|e| e.to_string() ),
+            NullableColumn::TimestampTz(v) => v[row].as_ref().map(|e| temporal::timestamptz_to_string(e) ),
+            NullableColumn::Interval(v) => v[row].map(|e| e.to_string() ),
+            NullableColumn::Uuid(v) => v[row].map(|e| e.to_string() ),
+            NullableColumn::Inet(v) => v[row].map(|e| e.to_string() ),
+            NullableColumn::MacAddr(v) => v[row].as_ref().map(|e| e.to_string() ),
+            NullableColumn::Geometry(v) => v[row].as_ref().map(|e| e.to_string() )
+        },
+        other => Some(other.display_content_at_index(row, None).into_owned())
+    }
+}
 
+/* In the CSV copy format, unquoted empty fields are nulls, so all other fields are quoted. */
+fn csv_chunk(cols : &[&Column], rows : Range<usize>) -> String {
+    let mut chunk = String::new();
+    for row in rows {
+        for (ix, col) in cols.iter().enumerate() {
+            if ix >= 1 {
+                chunk += ",";
+            }
+            if let Some(field) = csv_field(col, row) {
+                chunk += "\"";
+                chunk += &field.replace("\"", "\"\"");
+                chunk += "\"";
+            }
+        }
+        chunk += "\n";
+    }
+    chunk
 }
 
 const SERVER_VERSION_QUERY : &str = "show server_version";
@@ -631,7 +1306,7 @@ from uptime;
 
 fn query_db_details(cli : &mut PostgresConnection, dbname : &str) -> Result<DBDetails, Box<dyn Error>> {
     let mut details = DBDetails::default();
-    let out = cli.query_async(&[
+    let out = cli.query_all(&[
         AnyStatement::from_sql(SERVER_VERSION_QUERY).unwrap(),
         AnyStatement::from_sql(COLLATION_QUERY).unwrap(),
         AnyStatement::from_sql(&SIZE_QUERY.replace("$DBNAME", dbname)).unwrap(),
@@ -785,9 +1460,12 @@ fn retrieve_functions(fn_info : &Table, schema : &str) -> Option<Vec<DBObject>>
                     },
                     5 => {
                         // timestamp with time zone | timestamp without time zone will have 4 splits but and a type name
-                        if split_arg[1].trim() == "time" || split_arg[1].trim() == "timestamp" {
+                        if split_arg[1].trim() == "time" {
                             func_arg_names.push(split_arg[0].to_string());
                             args.push(DBType::Time);
+                        } else if split_arg[1].trim() == "timestamp" {
+                            func_arg_names.push(split_arg[0].to_string());
+                            args.push(DBType::Timestamp);
                         }
                     },
                     _n => {
@@ -875,7 +1553,7 @@ pub struct SchemaObjs {
 /// Return HashMap of Schema->(Tables, Views)
 fn get_postgres_schemata(conn : &mut PostgresConnection) -> Result<HashMap<String, SchemaObjs>, String> {
 
-    let out = conn.query_async(&[
+    let out = conn.query_all(&[
         AnyStatement::from_sql(SCHEMATA_QUERY).unwrap(),
         AnyStatement::from_sql(TBL_QUERY).unwrap(),
         AnyStatement::from_sql(VIEW_QUERY2).unwrap()
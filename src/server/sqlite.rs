@@ -19,6 +19,10 @@ use crate::client::ConnectionInfo;
 use crate::client::ConnConfig;
 use std::error::Error;
 use crate::client::ConnURI;
+use crate::client::Engine;
+use crate::sql::copy::VariableType;
+use crate::sql::params::{self, Param};
+use std::str::FromStr;
 
 pub struct SqliteConnection {
 
@@ -26,7 +30,33 @@ pub struct SqliteConnection {
 
     conn : rusqlite::Connection,
 
-    info : ConnectionInfo
+    info : ConnectionInfo,
+
+    canceller : Canceller,
+
+    // Rows fetched at a time for query results (zero fetches all rows at once).
+    page_size : usize,
+
+    // Query results with rows yet to be fetched.
+    pages : Vec<SqlitePage>
+
+}
+
+/* Rows borrow their statement, which borrows the connection, so they cannot be kept open between
+pages. The statement is kept at the connection cache instead, and each page steps it again past the
+rows already fetched. No copy of the result is made and at most a page of rows is held in memory,
+at the cost of stepping over the earlier rows again for each page (which might also differ from
+the rows seen before if the database changed in between). */
+struct SqlitePage {
+
+    // Index of the statement output that returned the first page.
+    ix : usize,
+
+    query : String,
+
+    values : Vec<(String, Value)>,
+
+    n_fetched : usize
 
 }
 
@@ -65,10 +95,19 @@ impl SqliteConnection {
         let res_conn = rusqlite::Connection::open(&path);
         match res_conn {
             Ok(conn) => {
+                // sqlite3_interrupt makes the running statement fail with SQLITE_INTERRUPT.
+                let interrupt = conn.get_interrupt_handle();
+                let canceller = Canceller::new(move || {
+                    interrupt.interrupt();
+                    Ok(())
+                });
                 Ok(Self{
                     path : Some(path),
                     conn,
-                    info : uri.info.clone()
+                    info : uri.info.clone(),
+                    canceller,
+                    page_size : 0,
+                    pages : Vec::new()
                 })
             },
             Err(e) => Err(format!("{}", e))
@@ -86,14 +125,110 @@ impl SqliteConnection {
 
 }
 
-impl Connection for SqliteConnection {
+/* SQLite understands $name placeholders, so parameters are bound by name. Values
+are converted according to the declared type, and untyped values are bound as text
+(which SQLite converts according to the column affinity). */
+fn sqlite_values(params : &[Param]) -> Result<Vec<(String, Value)>, String> {
+    let mut values = Vec::new();
+    for p in params {
+        let v = p.value.trim();
+        let value = match p.ty {
+            Some(VariableType::Integer) => Value::Integer(i64::from_str(v).map_err(|_| p.invalid_value() )?),
+            Some(VariableType::Real) => Value::Real(f64::from_str(v).map_err(|_| p.invalid_value() )?),
+            Some(VariableType::Bool) => Value::Integer(params::parse_bool(v).ok_or(p.invalid_value())? as i64),
+            Some(VariableType::Bytea) => Value::Blob(p.value.as_bytes().to_vec()),
+            Some(VariableType::Text) | None => Value::Text(p.value.clone())
+        };
+        values.push((format!("${}", p.name), value));
+    }
+    Ok(values)
+}
 
-    fn configure(&mut self, cfg : ConnConfig) {
+fn named_params<'a>(values : &'a [(String, Value)]) -> Vec<(&'a str, &'a dyn rusqlite::ToSql)> {
+    values.iter().map(|(name, value)| (&name[..], value as &dyn rusqlite::ToSql) ).collect()
+}
 
+fn declared_columns(stmt : &rusqlite::Statement) -> (Vec<String>, Vec<String>) {
+    let col_names : Vec<String> = stmt.column_names().iter().map(|cn| cn.to_string() ).collect();
+    let mut col_tys = Vec::new();
+    for col in stmt.columns() {
+        if let Some(ty) = col.decl_type() {
+            col_tys.push(ty.to_string());
+        } else {
+            col_tys.push("unknown".to_string());
+        }
     }
+    (col_names, col_tys)
+}
 
-    fn listen_at_channel(&mut self, channel : String) {
+fn query_output(query : &str, ans : Result<Table, String>) -> StatementOutput {
+    match ans {
+        Ok(mut tbl) => {
+            if let Some((name, relation)) = crate::sql::table_name_from_sql(query) {
+                tbl.set_name(Some(name));
+                if !relation.is_empty() {
+                    tbl.set_relation(Some(relation));
+                }
+            }
+            if tbl.names().iter().unique().count() == tbl.names().len() {
+                StatementOutput::Valid(query.to_string(), tbl)
+            } else {
+                StatementOutput::Invalid(crate::sql::build_error_with_stmt("Non-unique column names", &query), false)
+            }
+        },
+        // Failures while stepping through the rows are engine errors (interrupted
+        // statements are reported as cancelled by the caller).
+        Err(e) => {
+            StatementOutput::Invalid(crate::sql::build_error_with_stmt(&e, &query), true)
+        }
+    }
+}
 
+impl SqliteConnection {
+
+    fn query_rows(
+        &mut self,
+        query : &str,
+        values : &[(String, Value)],
+        max_rows : Option<usize>
+    ) -> StatementOutput {
+        let named = named_params(values);
+        match self.conn.prepare(&query[..]) {
+            Ok(mut prep_stmt) => {
+                let (col_names, col_tys) = declared_columns(&prep_stmt);
+                match prep_stmt.query(&named[..]) {
+                    Ok(rows) => query_output(query, Table::from_sqlite_rows(col_names, &col_tys, rows, max_rows)),
+                    Err(e) => {
+                        StatementOutput::Invalid(crate::sql::build_error_with_stmt(&format!("{}", e), &query), true)
+                    }
+                }
+            },
+            Err(e) => {
+                StatementOutput::Invalid(crate::sql::build_error_with_stmt(&format!("{}", e), &query), true)
+            }
+        }
+    }
+
+    // Steps the query past the rows already fetched and reads the next page.
+    fn read_page(&self, page : &SqlitePage) -> Result<Table, String> {
+        let named = named_params(&page.values[..]);
+        let mut stmt = self.conn.prepare_cached(&page.query).map_err(|e| format!("{}", e) )?;
+        let (names, col_tys) = declared_columns(&stmt);
+        let mut rows = stmt.query(&named[..]).map_err(|e| format!("{}", e) )?;
+        for _ in 0..page.n_fetched {
+            if rows.next().map_err(|e| format!("{}", e) )?.is_none() {
+                break;
+            }
+        }
+        Table::from_sqlite_rows(names, &col_tys[..], rows, Some(self.page_size))
+    }
+
+}
+
+impl Connection for SqliteConnection {
+
+    fn configure(&mut self, cfg : ConnConfig) {
+        self.page_size = cfg.page_size;
     }
 
     fn import(
@@ -102,85 +237,142 @@ impl Connection for SqliteConnection {
         dst : &str,
         cols : &[String],
     ) -> Result<usize, String> {
-        let client = &mut self.conn;
-
-        // Auto table creation
-        /*if !crate::sql::object::schema_has_table(dst, schema) {
-            let create = tbl.sql_table_creation(dst, cols).unwrap();
-            println!("{}", create);
-            let mut create_stmt = client.prepare(&create).map_err(|e| format!("{}", e) )?;
-            create_stmt.execute(rusqlite::NO_PARAMS).map_err(|e| format!("{}", e) )?;
-        }*/
-
-        let insert = tbl.sql_table_insertion(dst, cols).map_err(|e| format!("Invalid SQL: {}",e) )?;
-        let mut insert_stmt = client.prepare(&insert).map_err(|e| format!("{}", e) )?;
-        insert_stmt.execute([]).map_err(|e| format!("{}", e) )?;
+        // The table creation and the insertion are done in a single transaction, so a failed
+        // insertion does not leave an empty table behind (the transaction is rolled back when dropped).
+        let tx = self.conn.transaction().map_err(|e| format!("{}", e) )?;
+
+        // Auto table creation. The name is compared without quotes or schema, and
+        // case-insensitively as SQLite does for identifiers.
+        let mut parts = crate::sql::split_qualified(dst, Engine::SQLite);
+        let name = parts.pop().ok_or(format!("Missing destination table"))?;
+        let master = match parts.last() {
+            Some(schema) => format!("{}.sqlite_master", quote_ident(schema, Engine::SQLite)),
+            None => String::from("sqlite_master")
+        };
+        let exists : bool = tx.query_row(
+            &format!("select count(*) > 0 from {} where type = 'table' and lower(name) = lower(?1)", master),
+            [&name],
+            |row| row.get(0)
+        ).map_err(|e| format!("{}", e) )?;
+        if !exists {
+            let create = tbl.sql_table_creation(dst, cols, Engine::SQLite)
+                .ok_or(format!("Unable to form create table statement"))?;
+            tx.execute(&create, []).map_err(|e| format!("{}", e) )?;
+        }
+
+        let insert = tbl.sql_table_insertion(dst, cols, Engine::SQLite).map_err(|e| format!("Invalid SQL: {}",e) )?;
+        tx.execute(&insert, []).map_err(|e| format!("{}", e) )?;
+        tx.commit().map_err(|e| format!("{}", e) )?;
         Ok(tbl.shape().0)
     }
 
     fn query(&mut self, query : &str) -> StatementOutput {
-        // let query = substitute_if_required(q, subs);
+        self.query_with_params(query, &[])
+    }
 
-        match self.conn.prepare(&query[..]) {
-            Ok(mut prep_stmt) => {
-                let col_names : Vec<String> = prep_stmt.column_names().iter().map(|cn| cn.to_string() ).collect();
-                let mut col_tys = Vec::new();
-                for col in prep_stmt.columns() {
-                    if let Some(ty) = col.decl_type() {
-                        col_tys.push(ty.to_string());
-                    } else {
-                        col_tys.push("unknown".to_string());
-                    }
-                }
-                if col_names.len() != col_tys.len() {
-                    return StatementOutput::Invalid("Invalid column set".to_string(), false);
-                }
-                match prep_stmt.query([]) {
-                    Ok(rows) => {
-                        match Table::from_sqlite_rows(col_names, &col_tys, rows) {
-                            Ok(mut tbl) => {
-                                if let Some((name, relation)) = crate::sql::table_name_from_sql(query) {
-                                    tbl.set_name(Some(name));
-                                    if !relation.is_empty() {
-                                        tbl.set_relation(Some(relation));
-                                    }
-                                }
-                                if tbl.names().iter().unique().count() == tbl.names().len() {
-                                    StatementOutput::Valid(query.to_string(), tbl)
-                                } else {
-                                    StatementOutput::Invalid(crate::sql::build_error_with_stmt("Non-unique column names", &query), false)
-                                }
-                            },
-                            Err(e) => {
-                                StatementOutput::Invalid(crate::sql::build_error_with_stmt(&e, &query), false)
-                            }
-                        }
-                    },
-                    Err(e) => {
-                        StatementOutput::Invalid(crate::sql::build_error_with_stmt(&format!("{}", e), &query), true)
-                    }
-                }
-            },
-            Err(e) => {
-                StatementOutput::Invalid(crate::sql::build_error_with_stmt(&format!("{}", e), &query), true)
+    fn query_with_params(&mut self, query : &str, params : &[Param]) -> StatementOutput {
+        match sqlite_values(params) {
+            Ok(values) => self.query_rows(query, &values[..], None),
+            Err(e) => StatementOutput::Invalid(e, false)
+        }
+    }
+
+    fn query_paged(&mut self, ix : usize, query : &str, params : &[Param]) -> StatementOutput {
+        if self.page_size == 0 {
+            return self.query_with_params(query, params);
+        }
+        let values = match sqlite_values(params) {
+            Ok(values) => values,
+            Err(e) => return StatementOutput::Invalid(e, false)
+        };
+        let mut page = SqlitePage { ix, query : query.to_string(), values, n_fetched : 0 };
+        let ans = self.read_page(&page);
+        if let Ok(tbl) = &ans {
+            if tbl.nrows() >= self.page_size {
+                page.n_fetched = tbl.nrows();
+                self.pages.push(page);
             }
         }
+        query_output(query, ans)
+    }
+
+    fn fetch_page(&mut self, ix : usize) -> Result<Table, String> {
+        let pos = self.pages.iter().position(|page| page.ix == ix )
+            .ok_or(String::from("Query result is no longer available (re-execute the query to fetch more rows)"))?;
+        let tbl = self.read_page(&self.pages[pos])?;
+        if tbl.nrows() < self.page_size {
+            self.pages.remove(pos);
+        } else {
+            self.pages[pos].n_fetched += tbl.nrows();
+        }
+        Ok(tbl)
+    }
+
+    fn paged_results(&self) -> Vec<usize> {
+        self.pages.iter().map(|page| page.ix ).collect()
+    }
+
+    fn close_pages(&mut self) {
+        self.pages.clear();
     }
 
     fn exec_transaction(&mut self, _stmt : &AnyStatement) -> StatementOutput {
         StatementOutput::Invalid("Transactions are unsupported in the SQLite backend".to_string(), false)
     }
+
+    fn exec_edit(&mut self, stmt : &AnyStatement) -> StatementOutput {
+        let middle = match stmt {
+            AnyStatement::ParsedTransaction { middle, .. } => middle,
+            _ => return StatementOutput::Invalid(format!("Tried to execute statement as transaction"), false)
+        };
+
+        // Any early return drops the transaction, which rolls it back.
+        let tr = match self.conn.transaction() {
+            Ok(tr) => tr,
+            Err(e) => return StatementOutput::Invalid(e.to_string(), false)
+        };
+        let mut total_changed = 0;
+        for stmt in middle {
+            match tr.execute(&format!("{}", stmt), []) {
+                Ok(0) if crate::sql::is_update_or_delete(stmt) => {
+                    return StatementOutput::Invalid(crate::sql::no_rows_changed_msg(stmt), false);
+                },
+                Ok(n) => {
+                    total_changed += n;
+                },
+                Err(e) => {
+                    return StatementOutput::Invalid(e.to_string(), true);
+                }
+            }
+        }
+        match tr.commit() {
+            Ok(_) => StatementOutput::Committed(
+                format!("Transaction commited ({} statements; {} total rows changed)", middle.len(), total_changed),
+                middle.len()
+            ),
+            Err(e) => StatementOutput::Invalid(e.to_string(), true)
+        }
+    }
     
     fn query_async(&mut self, _stmts : &[AnyStatement]) -> Vec<StatementOutput> {
         vec![StatementOutput::Invalid("Asynchronous queries are unsupported in the SQLite backend".to_string(), false)]
     }
     
-    fn exec(&mut self, stmt : &AnyStatement, /*subs : &HashMap<String, String>*/) -> StatementOutput {
+    fn exec(&mut self, stmt : &AnyStatement) -> StatementOutput {
+        self.exec_with_params(stmt, &[])
+    }
+
+    fn exec_with_params(&mut self, stmt : &AnyStatement, params : &[Param]) -> StatementOutput {
+        let values = match sqlite_values(params) {
+            Ok(values) => values,
+            Err(e) => return StatementOutput::Invalid(e, false)
+        };
+        let named = named_params(&values[..]);
         let ans = match stmt {
             AnyStatement::Parsed(_, raw) | AnyStatement::ParsedTransaction{ raw, .. } => {
-                self.conn.execute(&raw, [])
+                self.conn.execute(&raw, &named[..])
             },
-            AnyStatement::Raw(_, s, _) => self.conn.execute(&s, []),
+            AnyStatement::Raw(_, s, _) => self.conn.execute(&s, &named[..]),
             AnyStatement::Local(_) => panic!("Tried to execute local statement remotely")
         };
         match ans {
@@ -189,6 +381,10 @@ impl Connection for SqliteConnection {
         }
     }
 
+    fn canceller(&self) -> Option<Canceller> {
+        Some(self.canceller.clone())
+    }
+
     fn conn_info(&self) -> ConnectionInfo {
         self.info.clone()
     }
@@ -569,15 +765,18 @@ impl Display for SqliteColumn {
 impl SqliteColumn {
 
     pub fn new(decl_type : &str) -> Result<Self, &'static str> {
-        match decl_type {
-            "integer" | "int" | "INTEGER" | "INT" => Ok(SqliteColumn::I64(Vec::new())),
-            "real" | "REAL" => Ok(SqliteColumn::F64(Vec::new())),
-            "text" | "TEXT" => Ok(SqliteColumn::Str(Vec::new())),
-            "blob" | "BLOB" => Ok(SqliteColumn::Bytes(Vec::new())),
+        match &decl_type.to_lowercase()[..] {
+            "integer" | "int" => Ok(SqliteColumn::I64(Vec::new())),
+            "real" => Ok(SqliteColumn::F64(Vec::new())),
+            "text" => Ok(SqliteColumn::Str(Vec::new())),
+            "blob" => Ok(SqliteColumn::Bytes(Vec::new())),
+
+            // SQLite has no temporal storage class, so those values are stored as text.
+            "date" | "time" | "datetime" | "timestamp" => Ok(SqliteColumn::Str(Vec::new())),
 
             // Used by the Queries application itself, when column.decl_type()
             // does not have a declared type.
-            "unknown" | "Unknown" => Ok(SqliteColumn::Bytes(Vec::new())),
+            "unknown" => Ok(SqliteColumn::Bytes(Vec::new())),
 
             _ => { Err("Invalid column type") }
         }
@@ -706,12 +905,12 @@ pub fn copy_table_to_sqlite(
     // TODO filter cols
 
     if !crate::sql::object::schema_has_table(dst, schema) {
-        let create = tbl.sql_table_creation(dst, cols).ok_or(String::from("Invalid SQL"))?;
+        let create = tbl.sql_table_creation(dst, cols, Engine::SQLite).ok_or(String::from("Invalid SQL"))?;
         let mut create_stmt = client.prepare(&create).map_err(|e| format!("{}", e) )?;
         create_stmt.execute([]).map_err(|e| format!("{}", e) )?;
     }
 
-    let insert = tbl.sql_table_insertion(dst, cols)?;
+    let insert = tbl.sql_table_insertion(dst, cols, Engine::SQLite)?;
     let mut insert_stmt = client.prepare(&insert).map_err(|e| format!("{}", e) )?;
     insert_stmt.execute([]).map_err(|e| format!("{}", e) )?;
     Ok(())
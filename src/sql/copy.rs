@@ -10,7 +10,10 @@ use sqlparser::tokenizer::{Token};
 use sqlparser::dialect::keywords::Keyword;
 use std::str::FromStr;
 use regex::Regex;
+use std::io::Write;
+use std::process::{Command, Stdio};
 use super::*;
+use crate::tables::dialect::{CsvOptions, CsvEncoding};
 
 // Supported syntax for now:
 // copy patients to file '/home/diego/Downloads/patients.csv';
@@ -41,6 +44,19 @@ pub enum CopyClient {
     Stdio
 }
 
+impl std::fmt::Display for CopyClient {
+
+    fn fmt(&self, f : &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CopyClient::Program(prog) => write!(f, "program '{}'", prog),
+            CopyClient::File(path) => write!(f, "file '{}'", path),
+            CopyClient::Variable(var) => write!(f, "variable {}", var),
+            CopyClient::Stdio => write!(f, "stdio")
+        }
+    }
+
+}
+
 // Ideally, we want to support a copy in/out version, something like:
 // copy tbl to program 'myprogram' then copy out_tbl from stdout;
 // So that users can write executable filters that process text or binary copy
@@ -148,6 +164,277 @@ impl ToString for Copy {
     }
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CopyFormat {
+    Text,
+    Csv,
+    Binary
+}
+
+/// Interpretation of the 'with' clause of a copy statement, accepting both the
+/// current (format csv, header true, delimiter ';') and the legacy (csv header delimiter ';')
+/// syntaxes. Those are forwarded verbatim to PostgreSQL, but must be interpreted at
+/// client-side for engines without a copy protocol.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CopyOptions {
+    pub format : CopyFormat,
+    pub header : bool,
+    pub delimiter : u8,
+    pub quote : u8,
+    pub escape : Option<u8>,
+    pub encoding : Option<CsvEncoding>,
+    pub null : String
+}
+
+impl Default for CopyOptions {
+
+    fn default() -> Self {
+        Self {
+            format : CopyFormat::Text,
+            header : false,
+            delimiter : b'\t',
+            quote : b'"',
+            escape : None,
+            encoding : None,
+            null : String::from("\\N")
+        }
+    }
+
+}
+
+fn single_byte_option(name : &str, tk : Option<&Token>) -> Result<u8, String> {
+    match tk {
+        Some(Token::SingleQuotedString(s)) if s.len() == 1 => Ok(s.as_bytes()[0]),
+        _ => Err(format!("Copy option {} must be a single one-byte character", name))
+    }
+}
+
+impl FromStr for CopyOptions {
+
+    type Err = String;
+
+    fn from_str(s : &str) -> Result<Self, String> {
+        let mut opts = CopyOptions::default();
+        let mut delimiter = None;
+        let mut null = None;
+        let tokens = parsing::extract_postgres_tokens(s)?;
+        let mut token_iter = tokens.iter()
+            .filter(|tk| !parsing::is_token_whitespace(tk) && **tk != Token::LParen && **tk != Token::RParen && **tk != Token::Comma )
+            .peekable();
+        while let Some(tk) = token_iter.next() {
+            let w = match tk {
+                Token::Word(w) => w.value.to_uppercase(),
+                other => return Err(format!("Invalid copy option: {}", other))
+            };
+            match &w[..] {
+                "FORMAT" => {
+                    opts.format = match token_iter.next() {
+                        Some(Token::Word(f)) => match &f.value.to_uppercase()[..] {
+                            "CSV" => CopyFormat::Csv,
+                            "TEXT" => CopyFormat::Text,
+                            "BINARY" => CopyFormat::Binary,
+                            other => return Err(format!("Invalid copy format: {}", other))
+                        },
+                        _ => return Err(format!("Missing copy format"))
+                    };
+                },
+                "CSV" => {
+                    opts.format = CopyFormat::Csv;
+                },
+                "BINARY" => {
+                    opts.format = CopyFormat::Binary;
+                },
+                "HEADER" => {
+                    opts.header = true;
+                    if let Some(Token::Word(v)) = token_iter.peek() {
+                        match &v.value.to_uppercase()[..] {
+                            "TRUE" | "ON" | "1" => {
+                                token_iter.next();
+                            },
+                            "FALSE" | "OFF" | "0" => {
+                                opts.header = false;
+                                token_iter.next();
+                            },
+                            _ => { }
+                        }
+                    }
+                },
+                "DELIMITER" => {
+                    token_iter.next_if(|tk| match tk { Token::Word(w) => w.keyword == Keyword::AS, _ => false });
+                    delimiter = Some(single_byte_option("delimiter", token_iter.next())?);
+                },
+                "QUOTE" => {
+                    token_iter.next_if(|tk| match tk { Token::Word(w) => w.keyword == Keyword::AS, _ => false });
+                    opts.quote = single_byte_option("quote", token_iter.next())?;
+                },
+                "ESCAPE" => {
+                    token_iter.next_if(|tk| match tk { Token::Word(w) => w.keyword == Keyword::AS, _ => false });
+                    opts.escape = Some(single_byte_option("escape", token_iter.next())?);
+                },
+                "ENCODING" => {
+                    match token_iter.next() {
+                        Some(Token::SingleQuotedString(s)) => {
+                            opts.encoding = Some(CsvEncoding::from_str(s)?);
+                        },
+                        _ => return Err(format!("Invalid encoding for copy"))
+                    }
+                },
+                "NULL" => {
+                    token_iter.next_if(|tk| match tk { Token::Word(w) => w.keyword == Keyword::AS, _ => false });
+                    match token_iter.next() {
+                        Some(Token::SingleQuotedString(s)) => {
+                            null = Some(s.clone());
+                        },
+                        _ => return Err(format!("Invalid null string for copy"))
+                    }
+                },
+                other => {
+                    return Err(format!("Unsupported copy option: {}", other));
+                }
+            }
+        }
+
+        // Defaults depend on the format, and are only applied when not informed by the user.
+        if opts.format == CopyFormat::Csv {
+            opts.delimiter = b',';
+            opts.null = String::new();
+        }
+        if let Some(delimiter) = delimiter {
+            opts.delimiter = delimiter;
+        }
+        if let Some(null) = null {
+            opts.null = null;
+        }
+        Ok(opts)
+    }
+
+}
+
+impl CopyOptions {
+
+    /* Writes the table content in the format informed by the options. Used by engines that
+    do not support a copy protocol. */
+    pub fn write_table(&self, tbl : &Table) -> Result<Vec<u8>, String> {
+        if self.format == CopyFormat::Binary {
+            return Err(format!("Binary copy is only supported for PostgreSQL connections"));
+        }
+        let mut builder = ::csv::WriterBuilder::new();
+        builder.delimiter(self.delimiter)
+            .quote(self.quote)
+            .quote_style(if self.format == CopyFormat::Csv { ::csv::QuoteStyle::Necessary } else { ::csv::QuoteStyle::Never });
+        if let Some(escape) = self.escape.filter(|e| *e != self.quote ) {
+            builder.escape(escape).double_quote(false);
+        }
+        let mut wtr = builder.from_writer(Vec::new());
+        for (row_ix, row) in tbl.text_rows(None, None, self.header, 0).into_iter().enumerate() {
+            let is_header = self.header && row_ix == 0;
+            let fields : Vec<String> = row.map(|field| {
+                if !is_header && field == crate::tables::nullable::NullableColumn::NULL {
+                    self.null.clone()
+                } else {
+                    field.to_string()
+                }
+            }).collect();
+            wtr.write_record(&fields).map_err(|e| format!("{}", e) )?;
+        }
+        wtr.into_inner().map_err(|e| format!("{}", e) )
+    }
+
+    /// Options to read delimited text equivalent to the copy options. The encoding is
+    /// detected from the content when not informed.
+    pub fn csv_options(&self) -> CsvOptions {
+        CsvOptions {
+            delimiter : Some(self.delimiter),
+            quote : Some(self.quote),
+            escape : self.escape.filter(|e| *e != self.quote ),
+            header : Some(self.header),
+            encoding : self.encoding,
+            null : Some(self.null.clone()),
+            decimal_comma : Some(false),
+            skip_rows : 0,
+            column_types : std::collections::HashMap::new()
+        }
+    }
+
+    /* Reads a table from content informed in the format given by the options. Used by engines
+    that do not support a copy protocol. */
+    pub fn read_table(&self, data : &[u8], cols : &[String]) -> Result<Table, String> {
+        if self.format == CopyFormat::Binary {
+            return Err(format!("Binary copy is only supported for PostgreSQL connections"));
+        }
+        let mut tbl = Table::new_from_delimited(data, &self.csv_options())
+            .map_err(|e| format!("Error parsing table: {}", e) )?;
+        if cols.len() > 0 {
+            if cols.len() != tbl.ncols() {
+                return Err(format!("Copy informed {} columns, but content has {}", cols.len(), tbl.ncols()));
+            }
+            tbl.set_names(cols.to_vec());
+        }
+        Ok(tbl)
+    }
+
+}
+
+/// Reads the content to be copied into a table from the client (file, program output).
+pub fn read_from_client(client : &CopyClient) -> Result<Vec<u8>, String> {
+    match client {
+        CopyClient::File(path) => {
+            std::fs::read(path).map_err(|e| format!("Error opening file {}: {}", path, e) )
+        },
+        CopyClient::Program(prog) => {
+            let out = Command::new("sh").arg("-c").arg(prog)
+                .stdin(Stdio::null())
+                .stderr(Stdio::piped())
+                .stdout(Stdio::piped())
+                .output()
+                .map_err(|e| format!("Error launching program: {}", e) )?;
+            if out.status.success() {
+                Ok(out.stdout)
+            } else {
+                Err(format!("Program exited with error:\n{}", String::from_utf8_lossy(&out.stderr)))
+            }
+        },
+        CopyClient::Variable(_) => {
+            Err(format!("Copy from variables is currently unsupported"))
+        },
+        CopyClient::Stdio => {
+            Err(format!("Copy from stdin is unsupported (use copy from file or program)"))
+        }
+    }
+}
+
+/// Writes the content copied from a table to the client (file, program input).
+pub fn write_to_client(client : &CopyClient, data : &[u8]) -> Result<(), String> {
+    match client {
+        CopyClient::File(path) => {
+            std::fs::write(path, data).map_err(|e| format!("Error writing file {}: {}", path, e) )
+        },
+        CopyClient::Program(prog) => {
+            let mut child = Command::new("sh").arg("-c").arg(prog)
+                .stdin(Stdio::piped())
+                .stderr(Stdio::piped())
+                .stdout(Stdio::null())
+                .spawn()
+                .map_err(|e| format!("Error launching program: {}", e) )?;
+            if let Some(mut stdin) = child.stdin.take() {
+                stdin.write_all(data).map_err(|e| format!("Error writing to program: {}", e) )?;
+            }
+            let out = child.wait_with_output().map_err(|e| format!("{}", e) )?;
+            if out.status.success() {
+                Ok(())
+            } else {
+                Err(format!("Program exited with error:\n{}", String::from_utf8_lossy(&out.stderr)))
+            }
+        },
+        CopyClient::Variable(_) => {
+            Err(format!("Copy to variables is currently unsupported"))
+        },
+        CopyClient::Stdio => {
+            Err(format!("Copy to stdout is unsupported (use copy to file or program)"))
+        }
+    }
+}
+
 const COPY_REGEX : &str =
     r"(copy|COPY)\s+.*\s+(from|FROM|to|TO)\s+((program|PROGRAM|variable|VARIABLE)\s)?('.*'|\$\$.*\$\$|stdin|STDIN|stdout|STDOUT)(\s+with.*)?;";
 
@@ -181,15 +468,51 @@ impl FromStr for Execute {
 
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum VariableType {
     Text,
-    Bytea
+    Bytea,
+    Integer,
+    Real,
+    Bool
+}
+
+impl VariableType {
+
+    pub fn from_type_name(name : &str) -> Option<Self> {
+        match &name.to_uppercase()[..] {
+            "TEXT" | "VARCHAR" => Some(VariableType::Text),
+            "BYTEA" => Some(VariableType::Bytea),
+            "INT" | "INTEGER" | "BIGINT" | "SMALLINT" => Some(VariableType::Integer),
+            "REAL" | "FLOAT" | "DOUBLE" | "NUMERIC" => Some(VariableType::Real),
+            "BOOL" | "BOOLEAN" => Some(VariableType::Bool),
+            _ => None
+        }
+    }
+
+}
+
+impl std::fmt::Display for VariableType {
+
+    fn fmt(&self, f : &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            VariableType::Text => write!(f, "text"),
+            VariableType::Bytea => write!(f, "bytea"),
+            VariableType::Integer => write!(f, "integer"),
+            VariableType::Real => write!(f, "real"),
+            VariableType::Bool => write!(f, "boolean")
+        }
+    }
+
 }
 
+/* Declares variables that can be referenced by $name or :name placeholders in the
+statements that follow. Variables with a default value (declare x integer = 10)
+are bound directly; the remaining must be informed by the user before execution. */
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Declare {
     pub names : Vec<String>,
-    pub types : Vec<VariableType>
+    pub types : Vec<VariableType>,
+    pub values : Vec<Option<String>>
 }
 
@@ -0,0 +1,332 @@
+/*Copyright (c) 2022 Diego da Silva Lima. All rights reserved.
+
+This work is licensed under the terms of the GPL v3.0 License.  
+For a copy, see http://www.gnu.org/licenses.*/
+
+use std::collections::BTreeMap;
+use sqlparser::dialect::{Dialect, PostgreSqlDialect, MySqlDialect};
+use sqlparser::parser::{Parser, ParserError};
+use sqlparser::ast::Statement;
+use crate::client::Engine;
+use crate::tables::table::Table;
+use crate::tables::field::Field;
+use super::object::{DBObject, DBColumn, DBType};
+use super::parsing::AnyStatement;
+use super::{SafetyLock, quote_ident};
+
+/* Rows are identified by the values of their primary key columns, so that changes can be
+recorded independently of the order in which the rows are shown (after the user sorts or
+filters the table). The values are kept as SQL literals built from the typed values of the
+result, since the displayed content might be rounded or truncated. */
+
+/// SQL literals of the primary key columns of a row.
+pub type RowKey = Vec<String>;
+
+/// Changes to the rows of a table with a primary key, written back to the database
+/// as a single transaction.
+#[derive(Debug, Clone)]
+pub struct TableEdit {
+
+    // Schema-qualified, quoted table name.
+    table : String,
+
+    engine : Engine,
+
+    // Columns of the result table, in the order they are displayed.
+    cols : Vec<DBColumn>,
+
+    // Indices of the primary key columns among cols.
+    pks : Vec<usize>,
+
+    // New values of existing rows, by row key and column index (None for null).
+    updates : BTreeMap<(RowKey, usize), Option<String>>,
+
+    deleted : Vec<RowKey>,
+
+    // Inserted rows. Columns left as None take their default values.
+    inserted : Vec<Vec<Option<String>>>
+
+}
+
+/// Searches the table of the given name, which might be qualified by its schema. Unqualified
+/// names that match tables at more than one schema are rejected, since the search path of the
+/// connection would be required to tell which table the query refers to.
+pub fn find_table<'a>(objs : &'a [DBObject], name : &str) -> Result<&'a DBObject, String> {
+    let name = name.replace('"', "").replace('`', "");
+    let mut found = Vec::new();
+    collect_tables(objs, &name, &mut found);
+    match found.len() {
+        0 => Err(format!("Table {} not found", name)),
+        1 => Ok(found[0]),
+        _ => Err(format!("Table {} exists at more than one schema\n(qualify the table name with its schema to edit the result)", name))
+    }
+}
+
+fn collect_tables<'a>(objs : &'a [DBObject], name : &str, found : &mut Vec<&'a DBObject>) {
+    for obj in objs {
+        match obj {
+            DBObject::Schema { children, .. } => {
+                collect_tables(&children[..], name, found);
+            },
+            DBObject::Table { schema, name : tbl_name, .. } => {
+                if &tbl_name[..] == name || format!("{}.{}", schema, tbl_name) == name {
+                    found.push(obj);
+                }
+            },
+            _ => { }
+        }
+    }
+}
+
+fn quote_literal(v : &str) -> String {
+    format!("'{}'", v.replace('\'', "''"))
+}
+
+/// Writes a value read from a query result as an SQL literal. Returns None for values that
+/// cannot identify a row (NaN, JSON and geometries).
+pub fn key_literal(field : &Field, engine : Engine) -> Option<String> {
+    match field {
+        Field::Bool(v) => Some(v.to_string()),
+        Field::I8(v) => Some(v.to_string()),
+        Field::I16(v) => Some(v.to_string()),
+        Field::I32(v) => Some(v.to_string()),
+        Field::U32(v) => Some(v.to_string()),
+        Field::I64(v) => Some(v.to_string()),
+
+        // The shortest representation that reads back as the same value.
+        Field::F32(v) if v.is_finite() => Some(v.to_string()),
+        Field::F64(v) if v.is_finite() => Some(v.to_string()),
+        Field::Numeric(v) => Some(v.to_string()),
+        Field::Str(v) => Some(quote_literal(v)),
+        Field::Bytes(v) => {
+            let hex : String = v.iter().map(|b| format!("{:02x}", b) ).collect();
+            match engine {
+                Engine::Postgres => Some(format!("'\\x{}'", hex)),
+                _ => Some(format!("X'{}'", hex))
+            }
+        },
+        Field::Date(v) => Some(quote_literal(&v.to_string())),
+        Field::Time(v) => Some(quote_literal(&v.to_string())),
+        Field::Timestamp(v) => Some(quote_literal(&v.to_string())),
+        Field::TimestampTz(v) => match engine {
+            Engine::Postgres => Some(quote_literal(&v.to_rfc3339())),
+            _ => Some(quote_literal(&v.naive_utc().to_string()))
+        },
+        Field::Interval(v) if engine == Engine::Postgres => Some(quote_literal(&v.to_string())),
+        Field::Uuid(v) => Some(quote_literal(&v.to_string())),
+        Field::Inet(v) => Some(quote_literal(&v.to_string())),
+        Field::MacAddr(v) => Some(quote_literal(&v.to_string())),
+        _ => None
+    }
+}
+
+// Whether the text is a plain decimal number ([+-]digits[.digits][e[+-]digits]). Values such as
+// NaN or infinity are accepted by f64::from_str, but must be quoted in SQL.
+fn is_number_literal(s : &str) -> bool {
+    let s = s.strip_prefix(|c| c == '+' || c == '-' ).unwrap_or(s);
+    let (mantissa, exp) = match s.find(|c| c == 'e' || c == 'E' ) {
+        Some(ix) => (&s[..ix], Some(&s[ix+1..])),
+        None => (s, None)
+    };
+    let mut parts = mantissa.splitn(2, '.');
+    let int = parts.next().unwrap_or("");
+    let frac = parts.next().unwrap_or("");
+    let digits = |s : &str| s.chars().all(|c| c.is_ascii_digit() );
+    let valid_exp = match exp {
+        Some(e) => {
+            let e = e.strip_prefix(|c| c == '+' || c == '-' ).unwrap_or(e);
+            !e.is_empty() && digits(e)
+        },
+        None => true
+    };
+    !(int.is_empty() && frac.is_empty()) && digits(int) && digits(frac) && valid_exp
+}
+
+fn literal(value : &Option<String>, ty : DBType) -> String {
+    match value {
+        Some(v) => match ty {
+            DBType::I16 | DBType::I32 | DBType::I64 | DBType::F32 | DBType::F64 | DBType::Numeric if is_number_literal(v.trim()) => {
+                v.trim().to_string()
+            },
+            DBType::Bool if v.trim().parse::<bool>().is_ok() => {
+                v.trim().to_string()
+            },
+            _ => format!("'{}'", v.replace('\'', "''"))
+        },
+        None => String::from("NULL")
+    }
+}
+
+impl TableEdit {
+
+    /// Prepares the edition of the rows of a query result, which should hold all primary key
+    /// columns of the table, and only columns of the table.
+    pub fn new(names : &[String], obj : &DBObject, engine : Engine) -> Result<Self, String> {
+        let (schema, name, tbl_cols) = match obj {
+            DBObject::Table { schema, name, cols, .. } => (schema, name, cols),
+            _ => return Err(format!("Only tables can be edited"))
+        };
+        let mut cols = Vec::new();
+        for n in names {
+            match tbl_cols.iter().find(|c| &c.name[..] == &n[..] ) {
+                Some(col) => cols.push(col.clone()),
+                None => return Err(format!("Column {} is not a column of table {}", n, name))
+            }
+        }
+        if !tbl_cols.iter().any(|c| c.is_pk ) {
+            return Err(format!("Table {} has no primary key", name));
+        }
+        if let Some(missing) = tbl_cols.iter().find(|c| c.is_pk && !names.iter().any(|n| &n[..] == &c.name[..] ) ) {
+            return Err(format!("Primary key column {} should be selected to edit table {}", missing.name, name));
+        }
+        let pks = cols.iter().enumerate().filter(|(_, c)| c.is_pk ).map(|(ix, _)| ix ).collect();
+
+        // SQLite tables are reported under a "public" schema, which is not a valid qualifier.
+        let table = match engine {
+            Engine::SQLite => quote_ident(name, engine),
+            _ => format!("{}.{}", quote_ident(schema, engine), quote_ident(name, engine))
+        };
+        Ok(Self {
+            table,
+            engine,
+            cols,
+            pks,
+            updates : BTreeMap::new(),
+            deleted : Vec::new(),
+            inserted : Vec::new()
+        })
+    }
+
+    /// Reads the key of the row at the given index of a table with the same columns as the edited table.
+    pub fn row_key(&self, tbl : &Table, row : usize) -> Option<RowKey> {
+        self.pks.iter().map(|pk| {
+            let col = tbl.get_column(*pk)?;
+            if col.is_null(row) {
+                return None;
+            }
+            key_literal(&col.at(row, None)?, self.engine)
+        }).collect()
+    }
+
+    pub fn is_pk(&self, col : usize) -> bool {
+        self.pks.contains(&col)
+    }
+
+    pub fn update(&mut self, key : RowKey, col : usize, value : Option<String>) {
+        if !self.deleted.contains(&key) {
+            self.updates.insert((key, col), value);
+        }
+    }
+
+    pub fn delete(&mut self, key : RowKey) {
+        self.updates.retain(|(k, _), _| k != &key );
+        if !self.deleted.contains(&key) {
+            self.deleted.push(key);
+        }
+    }
+
+    pub fn is_deleted(&self, key : &RowKey) -> bool {
+        self.deleted.contains(key)
+    }
+
+    /// Appends an empty row, returning its index among the inserted rows.
+    pub fn insert(&mut self) -> usize {
+        self.inserted.push(vec![None; self.cols.len()]);
+        self.inserted.len() - 1
+    }
+
+    pub fn update_inserted(&mut self, row : usize, col : usize, value : Option<String>) {
+        if let Some(r) = self.inserted.get_mut(row) {
+            if col < r.len() {
+                r[col] = value;
+            }
+        }
+    }
+
+    pub fn remove_inserted(&mut self, row : usize) {
+        if row < self.inserted.len() {
+            self.inserted.remove(row);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.updates.is_empty() && self.deleted.is_empty() && self.inserted.iter().all(|r| r.iter().all(|v| v.is_none() ) )
+    }
+
+    pub fn clear(&mut self) {
+        self.updates.clear();
+        self.deleted.clear();
+        self.inserted.clear();
+    }
+
+    fn key_filter(&self, key : &RowKey) -> String {
+        let conds : Vec<_> = self.pks.iter().zip(key.iter())
+            .map(|(pk, v)| format!("{} = {}", quote_ident(&self.cols[*pk].name, self.engine), v) )
+            .collect();
+        conds.join(" AND ")
+    }
+
+    /// Builds the update, delete and insert statements, in this order.
+    pub fn statements(&self) -> Vec<String> {
+        let mut stmts = Vec::new();
+        let mut assignments : Vec<(&RowKey, Vec<String>)> = Vec::new();
+        for ((key, col), value) in self.updates.iter() {
+            let c = &self.cols[*col];
+            let assign = format!("{} = {}", quote_ident(&c.name, self.engine), literal(value, c.ty));
+            match assignments.last_mut() {
+                Some((last_key, assigns)) if *last_key == key => assigns.push(assign),
+                _ => assignments.push((key, vec![assign]))
+            }
+        }
+        for (key, assigns) in assignments {
+            stmts.push(format!("UPDATE {} SET {} WHERE {};", self.table, assigns.join(", "), self.key_filter(key)));
+        }
+        for key in self.deleted.iter() {
+            stmts.push(format!("DELETE FROM {} WHERE {};", self.table, self.key_filter(key)));
+        }
+        for row in self.inserted.iter() {
+            let (names, values) : (Vec<_>, Vec<_>) = row.iter().enumerate()
+                .filter(|(_, v)| v.is_some() )
+                .map(|(ix, v)| (quote_ident(&self.cols[ix].name, self.engine), literal(v, self.cols[ix].ty)) )
+                .unzip();
+            if !names.is_empty() {
+                stmts.push(format!("INSERT INTO {} ({}) VALUES ({});", self.table, names.join(", "), values.join(", ")));
+            }
+        }
+        stmts
+    }
+
+    /// Parses the statements and wraps them in a transaction, verifying each one is accepted by
+    /// the safety lock.
+    pub fn transaction(&self, safety : &SafetyLock) -> Result<AnyStatement, String> {
+        let stmts = self.statements();
+        if stmts.is_empty() {
+            return Err(format!("No changes to apply"));
+        }
+        let raw = format!("BEGIN;\n{}\nCOMMIT;\n", stmts.join("\n"));
+        let mut parsed = match self.engine {
+            Engine::MySQL => parse_with(&MySqlDialect {}, &raw)?,
+            _ => parse_with(&PostgreSqlDialect {}, &raw)?
+        };
+        if parsed.len() != stmts.len() + 2 {
+            return Err(format!("Invalid number of statements in transaction"));
+        }
+        let end = parsed.remove(parsed.len()-1);
+        let begin = parsed.remove(0);
+        for stmt in parsed.iter() {
+            safety.accepts(stmt)?;
+        }
+        Ok(AnyStatement::ParsedTransaction { begin, middle : parsed, end, raw })
+    }
+
+}
+
+fn parse_with(dialect : &dyn Dialect, sql : &str) -> Result<Vec<Statement>, String> {
+    Parser::parse_sql(dialect, sql)
+        .map_err(|e| {
+            match e {
+                ParserError::TokenizerError(s) => s,
+                ParserError::ParserError(s) => s
+            }
+        })
+}
@@ -8,6 +8,7 @@ use std::fmt;
 use std::error::Error;
 use crate::tables::table::*;
 use crate::sql::object::{DBType, DBColumn};
+use crate::client::Engine;
 use std::collections::HashMap;
 use std::string::ToString;
 use std::cmp::{PartialEq, Eq};
@@ -45,8 +46,14 @@ pub mod parsing;
 /// Locally-parsed and executed copy statements.
 pub mod copy;
 
-// PostgreSQL notifications
-// pub mod notify;
+/// Named statement parameters.
+pub mod params;
+
+/// PostgreSQL notifications.
+pub mod notify;
+
+/// Write-back of changes made to the rows of a table.
+pub mod edit;
 
 // Wraps thread that listen to SQL commands.
 // pub mod listener;
@@ -232,6 +239,9 @@ pub enum StatementOutput {
     // or client (if false), carrying its error message.
     Invalid(String, bool),
 
+    // Returns a statement interrupted by a cancel request from the user.
+    Cancelled(String),
+
     // Resulting from a local command invocation
     Empty
 
@@ -296,7 +306,7 @@ pub fn condense_statement_outputs(stmts : &[StatementOutput]) -> Option<String>
                 StatementOutput::Committed(msg, n_stmts) => {
                     Some((msg.clone(), *n_stmts))
                 },
-                StatementOutput::RolledBack(msg) => {
+                StatementOutput::RolledBack(msg) | StatementOutput::Cancelled(msg) => {
                     Some((msg.clone(), 0))
                 },
                 _ => None
@@ -435,6 +445,17 @@ pub fn require_insert_n(stmt : &AnyStatement, ncols : usize, nrows : usize) -> R
     }
 }
 
+pub fn is_update_or_delete(stmt : &Statement) -> bool {
+    match stmt {
+        Statement::Update { .. } | Statement::Delete { .. } => true,
+        _ => false
+    }
+}
+
+pub fn no_rows_changed_msg(stmt : &Statement) -> String {
+    format!("No rows changed by statement ({}).\nThe row might have been changed or removed since it was displayed.", stmt)
+}
+
 pub fn build_statement_result(any_stmt : &AnyStatement, n : usize) -> StatementOutput {
     match any_stmt {
         AnyStatement::Parsed(stmt, _) => match stmt {
@@ -618,6 +639,54 @@ pub fn pack_column_types(
 }
 
 // TODO maybe return Cow here?
+/// Quotes an identifier, so it might hold upper-case letters, spaces, quotes or reserved words.
+pub fn quote_ident(name : &str, engine : Engine) -> String {
+    match engine {
+        Engine::MySQL => format!("`{}`", name.replace('`', "``")),
+        _ => format!("\"{}\"", name.replace('"', "\"\""))
+    }
+}
+
+/// Quotes each part of a (possibly schema-qualified) name. Parts that are already
+/// quoted are kept as they are.
+pub fn quote_qualified(name : &str, engine : Engine) -> String {
+    split_qualified(name, engine).iter()
+        .map(|p| quote_ident(p, engine) )
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Splits a (possibly schema-qualified) name into its parts, removing the quotes of quoted parts.
+pub fn split_qualified(name : &str, engine : Engine) -> Vec<String> {
+    let quote = match engine {
+        Engine::MySQL => '`',
+        _ => '"'
+    };
+    let mut parts = vec![String::new()];
+    let mut in_quote = false;
+    for c in name.chars() {
+        if c == quote {
+            in_quote = !in_quote;
+        }
+        if c == '.' && !in_quote {
+            parts.push(String::new());
+        } else {
+            parts.last_mut().unwrap().push(c);
+        }
+    }
+    parts.iter()
+        .map(|p| {
+            let p = p.trim();
+            if p.len() >= 2 && p.starts_with(quote) && p.ends_with(quote) {
+                let doubled : String = [quote, quote].iter().collect();
+                p[1..p.len()-1].replace(&doubled[..], &quote.to_string()[..])
+            } else {
+                p.to_string()
+            }
+        })
+        .collect()
+}
+
 pub fn substitute_if_required(q : &str, subs : &HashMap<String, String>) -> String {
     let mut txt = q.to_string();
     if !subs.is_empty() {
@@ -34,6 +34,7 @@ pub enum DBType {
     Text,
     Date,
     Time,
+    Timestamp,
     Bytes,
     Json,
     Xml,
@@ -57,6 +58,17 @@ impl FromStr for DBType {
         match s {
             "boolean" | "bool" | "BOOL" => Ok(Self::Bool),
             "bigint" | "bigserial" | "int8" => Ok(Self::I64),
+
+            // Types named as in MySQL's information_schema.
+            "tinyint" | "year" => Ok(Self::I16),
+            "mediumint" => Ok(Self::I32),
+            "float" => Ok(Self::F32),
+            "double" => Ok(Self::F64),
+            "decimal" => Ok(Self::Numeric),
+            "varchar" | "tinytext" | "mediumtext" | "longtext" | "enum" | "set" => Ok(Self::Text),
+            "datetime" | "timestamp" => Ok(Self::Timestamp),
+            "tinyblob" | "mediumblob" | "longblob" | "binary" | "varbinary" => Ok(Self::Bytes),
+
             "bit" | "bit varying" | "character" | "character varying" | "text" | "name" | "char" | "cstring" |
                 "CHAR" | "CHARACTER" | "TEXT" =>
             {
@@ -71,8 +83,8 @@ impl FromStr for DBType {
             "real" | "REAL" | "float4" => Ok(Self::F32),
             "dp" | "double precision" | "float8" => Ok(Self::F64),
             "blob" | "BLOB" | "bytea" | "BYTEA" => Ok(Self::Bytes),
-            "time" | "time with time zone" | "time without time zone" |
-            "timestamp with time zone" | "timestamp without time zone" => Ok(Self::Time),
+            "time" | "time with time zone" | "time without time zone" => Ok(Self::Time),
+            "timestamp with time zone" | "timestamp without time zone" | "timestamptz" => Ok(Self::Timestamp),
             "xml" => Ok(Self::Xml),
             "anyarray" | "array" | "ARRAY" => Ok(Self::Array),
             "trigger" => Ok(Self::Trigger),
@@ -96,6 +108,7 @@ impl fmt::Display for DBType {
             Self::Text => "text",
             Self::Date => "date",
             Self::Time => "time",
+            Self::Timestamp => "timestamp",
             Self::Bytes => "bytea",
             Self::Json => "json",
             Self::Xml => "xml",
@@ -0,0 +1,219 @@
+/*Copyright (c) 2022 Diego da Silva Lima. All rights reserved.
+
+This work is licensed under the terms of the GPL v3.0 License.  
+For a copy, see http://www.gnu.org/licenses.*/
+
+use std::collections::HashMap;
+use sqlparser::tokenizer::Token;
+use super::LocalStatement;
+use super::copy::VariableType;
+use super::parsing::{AnyStatement, extract_postgres_tokens};
+
+/*
+Named placeholders ($name or :name) let the same script be executed with different values.
+The values come either from declare statements (declare x integer = 10;) or are informed
+by the user before execution, and are always bound as statement parameters: they are never
+interpolated into the SQL text sent to the server.
+*/
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Param {
+
+    pub name : String,
+
+    // Type informed at a declare statement. Parameters without a declared type are bound
+    // as text (SQLite) or according to the type inferred by the server (Postgres).
+    pub ty : Option<VariableType>,
+
+    pub value : String
+
+}
+
+impl Param {
+
+    pub fn invalid_value(&self) -> String {
+        match self.ty {
+            Some(ty) => format!("Invalid value for parameter '{}' ({}): {}", self.name, ty, self.value),
+            None => format!("Invalid value for parameter '{}': {}", self.name, self.value)
+        }
+    }
+
+}
+
+pub fn parse_bool(s : &str) -> Option<bool> {
+    match &s.trim().to_lowercase()[..] {
+        "true" | "t" | "1" => Some(true),
+        "false" | "f" | "0" => Some(false),
+        _ => None
+    }
+}
+
+/// Returns the name of a named placeholder ($name), or None if this is a positional ($1)
+/// or anonymous ($, ?) placeholder.
+pub fn placeholder_name(pl : &str) -> Option<&str> {
+    let name = pl.strip_prefix('$')?;
+    let first = name.chars().next()?;
+    if first.is_alphabetic() || first == '_' {
+        Some(name)
+    } else {
+        None
+    }
+}
+
+/// Re-writes :name placeholders (lexed as a colon followed by a word) as $name placeholders,
+/// returning whether any placeholder was re-written. Colons within brackets are array slices,
+/// and are left as they are.
+pub fn normalize_placeholders(tokens : Vec<Token>) -> (Vec<Token>, bool) {
+    let mut out = Vec::with_capacity(tokens.len());
+    let mut bracket_depth = 0;
+    let mut normalized = false;
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(tk) = tokens.next() {
+        match tk {
+            Token::LBracket => {
+                bracket_depth += 1;
+            },
+            Token::RBracket => {
+                if bracket_depth > 0 {
+                    bracket_depth -= 1;
+                }
+            },
+            Token::Colon if bracket_depth == 0 => {
+                let placeholder = match tokens.peek() {
+                    Some(Token::Word(w)) if w.quote_style.is_none() => Some(format!("${}", w.value)),
+                    _ => None
+                };
+                if let Some(placeholder) = placeholder {
+                    tokens.next();
+                    out.push(Token::Placeholder(placeholder));
+                    normalized = true;
+                    continue;
+                }
+            },
+            _ => { }
+        }
+        out.push(tk);
+    }
+    (out, normalized)
+}
+
+/// Re-builds the SQL from a token sequence. Single-quoted strings are
+/// escaped again, since the tokenizer removes the escaping.
+pub fn tokens_to_sql(tokens : &[Token]) -> String {
+    let mut sql = String::new();
+    for tk in tokens {
+        match tk {
+            Token::SingleQuotedString(s) => {
+                sql += &format!("'{}'", s.replace('\'', "''"))[..];
+            },
+            other => {
+                sql += &other.to_string()[..];
+            }
+        }
+    }
+    sql
+}
+
+/// Returns the distinct placeholder names, in the order they first appear at the SQL.
+pub fn placeholder_names(sql : &str) -> Result<Vec<String>, String> {
+    let (tokens, _) = normalize_placeholders(extract_postgres_tokens(sql)?);
+    let mut names : Vec<String> = Vec::new();
+    for tk in tokens.iter() {
+        if let Token::Placeholder(pl) = tk {
+            if let Some(name) = placeholder_name(pl) {
+                if !names.iter().any(|n| &n[..] == name ) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+    }
+    Ok(names)
+}
+
+/// Re-writes the named placeholders as positional placeholders ($1, $2, ...) as expected
+/// by the Postgres driver, returning the placeholder names in the order of their positions.
+pub fn positional_placeholders(sql : &str) -> Result<(String, Vec<String>), String> {
+    let (mut tokens, _) = normalize_placeholders(extract_postgres_tokens(sql)?);
+    let mut names : Vec<String> = Vec::new();
+    for tk in tokens.iter_mut() {
+        if let Token::Placeholder(pl) = tk {
+            let name = placeholder_name(pl)
+                .ok_or(format!("Unsupported SQL token: '{}'", pl))?
+                .to_string();
+            let pos = match names.iter().position(|n| n == &name ) {
+                Some(pos) => pos,
+                None => {
+                    names.push(name);
+                    names.len() - 1
+                }
+            };
+            *tk = Token::Placeholder(format!("${}", pos + 1));
+        }
+    }
+    Ok((tokens_to_sql(&tokens[..]), names))
+}
+
+/// Collects the parameters bound by declare statements. The values informed by the
+/// user (subs) take precedence over the default values of the declarations, and are
+/// untyped when informed for placeholders that were not declared.
+pub fn collect_params(stmts : &[AnyStatement], subs : &HashMap<String, String>) -> Vec<Param> {
+    let mut params : Vec<Param> = Vec::new();
+    for stmt in stmts {
+        if let AnyStatement::Local(LocalStatement::Decl(decl)) = stmt {
+            for i in 0..decl.names.len() {
+                let value = subs.get(&decl.names[i]).or(decl.values[i].as_ref());
+                if let Some(value) = value {
+                    params.retain(|p| p.name != decl.names[i] );
+                    params.push(Param { name : decl.names[i].clone(), ty : Some(decl.types[i]), value : value.clone() });
+                }
+            }
+        }
+    }
+    for (name, value) in subs.iter() {
+        if !params.iter().any(|p| &p.name == name ) {
+            params.push(Param { name : name.clone(), ty : None, value : value.clone() });
+        }
+    }
+    params
+}
+
+/// Returns the parameters for the placeholders of a single statement, in the order
+/// they first appear.
+pub fn statement_params(sql : &str, params : &[Param]) -> Result<Vec<Param>, String> {
+    let mut stmt_params = Vec::new();
+    for name in placeholder_names(sql)? {
+        match params.iter().find(|p| p.name == name ) {
+            Some(p) => stmt_params.push(p.clone()),
+            None => return Err(format!("Missing value for parameter '{}'", name))
+        }
+    }
+    Ok(stmt_params)
+}
+
+/// Returns the names of the placeholders (and declared variables) without a value, which
+/// must be informed by the user before execution. Returns an empty vector if the script could
+/// not be parsed, since the parsing error is reported at execution.
+pub fn unbound_params(sql : &str) -> Vec<String> {
+    let stmts = match super::parsing::fully_parse_sql(sql) {
+        Ok(stmts) => stmts,
+        Err(_) => return Vec::new()
+    };
+    let bound : Vec<String> = collect_params(&stmts[..], &HashMap::new())
+        .into_iter()
+        .map(|p| p.name )
+        .collect();
+    let mut unbound : Vec<String> = Vec::new();
+    for stmt in &stmts {
+        let names = match stmt {
+            AnyStatement::Local(LocalStatement::Decl(decl)) => decl.names.clone(),
+            AnyStatement::Local(_) => Vec::new(),
+            other => placeholder_names(other.sql()).unwrap_or_default()
+        };
+        for name in names {
+            if !bound.contains(&name) && !unbound.contains(&name) {
+                unbound.push(name);
+            }
+        }
+    }
+    unbound
+}
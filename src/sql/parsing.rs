@@ -4,7 +4,7 @@ This work is licensed under the terms of the GPL v3.0 License.
 For a copy, see http://www.gnu.org/licenses.*/
 
 use super::*;
-use sqlparser::dialect::{PostgreSqlDialect};
+use sqlparser::dialect::{Dialect, PostgreSqlDialect, MySqlDialect};
 use sqlparser::ast::{Statement, SetExpr, SelectItem};
 use sqlparser::parser::{Parser, ParserError};
 use sqlparser::dialect::keywords::Keyword;
@@ -383,16 +383,98 @@ fn close_transaction(mut stmts : Vec<Statement>) -> Result<AnyStatement, SQLErro
     Ok(AnyStatement::ParsedTransaction { begin, end, middle : stmts, raw })
 }
 
+/* Splits the SQL into segments of standard SQL (to be parsed by sqlparser) and client-side
+statements: copy to/from file, program or variable; and variable declarations. Copies from
+stdin/to stdout are left to sqlparser (and rejected later), since they require data inlined
+in the script. Declarations that aren't understood as variables (e.g. cursor declarations)
+are also left to sqlparser. */
+fn split_local_statements(sql : &str, tokens : Vec<Token>) -> Result<Vec<Either<String, LocalStatement>>, SQLError> {
+    let has_local = tokens.iter().any(|tk| match tk {
+        Token::Word(w) => w.keyword == Keyword::COPY || w.keyword == Keyword::DECLARE,
+        _ => false
+    });
+    if !has_local {
+        return Ok(vec![Either::Left(sql.to_string())]);
+    }
+    let mut segments = Vec::new();
+    let mut curr_sql = String::new();
+    for group in split_statement_tokens(tokens).map_err(|e| SQLError::Lexing(e) )? {
+        let mut token_iter = group.iter().skip_while(|tk| is_token_whitespace(tk) ).peekable();
+        let first_kw = match token_iter.peek() {
+            Some(Token::Word(w)) => w.keyword,
+            _ => Keyword::NoKeyword
+        };
+        let local = match first_kw {
+            Keyword::COPY => {
+                local_statement_or_tokens(&mut token_iter).map_err(|e| SQLError::Parsing(e) )?
+            },
+            Keyword::DECLARE => {
+                local_statement_or_tokens(&mut token_iter).ok().flatten()
+            },
+            _ => None
+        };
+        match local {
+            Some(Either::Left(LocalStatement::Copy(copy))) if copy.client != copy::CopyClient::Stdio => {
+                if !curr_sql.trim().is_empty() {
+                    segments.push(Either::Left(std::mem::take(&mut curr_sql)));
+                }
+                segments.push(Either::Right(LocalStatement::Copy(copy)));
+            },
+            Some(Either::Left(LocalStatement::Decl(decl))) => {
+                if !curr_sql.trim().is_empty() {
+                    segments.push(Either::Left(std::mem::take(&mut curr_sql)));
+                }
+                segments.push(Either::Right(LocalStatement::Decl(decl)));
+            },
+            _ => {
+                curr_sql += &params::tokens_to_sql(&group[..])[..];
+            }
+        }
+    }
+    if !curr_sql.trim().is_empty() {
+        segments.push(Either::Left(curr_sql));
+    }
+    Ok(segments)
+}
+
 pub fn fully_parse_sql(
     sql : &str
 ) -> Result<Vec<AnyStatement>, SQLError> {
+    fully_parse_sql_with(sql, &PostgreSqlDialect{})
+}
 
-    let tokens = extract_postgres_tokens(&sql)
+/// Parses the script with the dialect of the engine it will be sent to (MySQL scripts
+/// use backtick-quoted identifiers and other syntax the PostgreSQL dialect rejects).
+pub fn fully_parse_engine_sql(
+    sql : &str,
+    engine : Engine
+) -> Result<Vec<AnyStatement>, SQLError> {
+    match engine {
+        Engine::MySQL => fully_parse_sql_with(sql, &MySqlDialect{}),
+        _ => fully_parse_sql_with(sql, &PostgreSqlDialect{})
+    }
+}
+
+pub fn fully_parse_sql_with(
+    sql : &str,
+    dialect : &dyn Dialect
+) -> Result<Vec<AnyStatement>, SQLError> {
+
+    let tokens = extract_tokens(&sql, dialect)
         .map_err(|e| SQLError::Lexing(e) )?;
 
-    // It is important to reject queries with placeholder tokens, because
-    // the postgres driver panics on any placeholder/argument mismatch, and
-    // the call never passes any statement arguments.
+    // :name placeholders are re-written as $name, so that named placeholders have
+    // a single representation from this point on.
+    let (tokens, normalized) = params::normalize_placeholders(tokens);
+    let sql = if normalized {
+        params::tokens_to_sql(&tokens[..])
+    } else {
+        sql.to_string()
+    };
+
+    // It is important to reject queries with positional placeholder tokens, because
+    // the postgres driver panics on any placeholder/argument mismatch. Named placeholders
+    // ($name) are accepted, and bound to the declared/informed values at execution.
     for (ix, tk) in tokens.iter().enumerate() {
         match tk {
             Token::Placeholder(pl) => {
@@ -406,74 +488,90 @@ pub fn fully_parse_sql(
                     }
                 }
                 
-                return Err(SQLError::Unsupported(format!("Unsupported SQL token: '{}'", pl)));
+                if params::placeholder_name(pl).is_none() {
+                    return Err(SQLError::Unsupported(format!("Unsupported SQL token: '{}'", pl)));
+                }
             },
             _ => { }
         }
     }
     
-    let dialect = dialect::PostgreSqlDialect{};
     let mut any_stmts = Vec::new();
    
-    match Parser::parse_sql(&dialect, sql) {
-        
-        Ok(mut stmts) => {
-            
-            let mut curr_transaction = None;
-            while stmts.len() > 0 {
-                
-                match stmts.remove(0) {
-                    Statement::Copy{ .. } => {
-                        return Err(SQLError::Unsupported(format!("Unsupported statement (copy)")));
-                    },
-                    Statement::Savepoint { .. } => {
-                        return Err(SQLError::Unsupported(format!("Unsupported feature (Transaction SAVEPOINT)")));
-                    },
-                    Statement::StartTransaction { modes }  => {
-                        if curr_transaction.is_some() {
-                            return Err(SQLError::Unsupported(format!("Nested transactions are currently unsupported.")));
-                        }
-                        curr_transaction = Some(vec![Statement::StartTransaction { modes }]);
-                    },
-                    Statement::Rollback { chain } => {
-                        if let Some(mut ct) = curr_transaction.take() {
-                            ct.push(Statement::Rollback { chain });
-                            let tr = close_transaction(ct)?;
-                            any_stmts.push(tr);
-                        } else {
-                            return Err(SQLError::Parsing(format!("ROLLBACK without any open transactions (missing BEGIN)")));
-                        }
-                    },
-                    Statement::Commit { chain }  => {
-                        if let Some(mut ct) = curr_transaction.take() {
-                            ct.push(Statement::Commit { chain });
-                            let tr = close_transaction(ct)?;
-                            any_stmts.push(tr);
-                        } else {
-                            return Err(SQLError::Parsing(format!("COMMIT statement without any open transactions (missing BEGIN)")));
-                        }
-                    },
-                    other_stmt => { 
-                        if let Some(ref mut curr_t) = curr_transaction {
-                            curr_t.push(other_stmt);
-                        } else {
-                            let orig = format!("{}", other_stmt);
-                            any_stmts.push(AnyStatement::Parsed(other_stmt.clone(), orig));
-                        }
+    // Client-side copies and declarations are not understood by sqlparser, so they are parsed
+    // separately, and the SQL between them is parsed by sqlparser as usual.
+    let mut stmts = Vec::new();
+    for segment in split_local_statements(&sql, tokens)? {
+        match segment {
+            Either::Left(sql) => {
+                match Parser::parse_sql(dialect, &sql) {
+                    Ok(parsed) => stmts.extend(parsed.into_iter().map(|stmt| Either::Left(stmt) )),
+                    Err(e) => {
+                        return Err(SQLError::Parsing(format!("{}", e)));
                     }
                 }
+            },
+            Either::Right(local) => {
+                stmts.push(Either::Right(local));
             }
-            
-            if curr_transaction.is_some() {
-                return Err(SQLError::Parsing(format!("Unfinished transaction block\n(expected COMMIT or ROLLBACK)")));
-            }
-        },
+        }
+    }
+
+    let mut curr_transaction = None;
+    while stmts.len() > 0 {
         
-        Err(e) => {
-            return Err(SQLError::Parsing(format!("{}", e)));
+        match stmts.remove(0) {
+            Either::Right(local) => {
+                if curr_transaction.is_some() {
+                    return Err(SQLError::Unsupported(format!("Client-side statements inside transaction blocks are currently unsupported.")));
+                }
+                any_stmts.push(AnyStatement::Local(local));
+            },
+            Either::Left(Statement::Copy{ .. }) => {
+                return Err(SQLError::Unsupported(format!("Unsupported statement (copy)")));
+            },
+            Either::Left(Statement::Savepoint { .. }) => {
+                return Err(SQLError::Unsupported(format!("Unsupported feature (Transaction SAVEPOINT)")));
+            },
+            Either::Left(Statement::StartTransaction { modes })  => {
+                if curr_transaction.is_some() {
+                    return Err(SQLError::Unsupported(format!("Nested transactions are currently unsupported.")));
+                }
+                curr_transaction = Some(vec![Statement::StartTransaction { modes }]);
+            },
+            Either::Left(Statement::Rollback { chain }) => {
+                if let Some(mut ct) = curr_transaction.take() {
+                    ct.push(Statement::Rollback { chain });
+                    let tr = close_transaction(ct)?;
+                    any_stmts.push(tr);
+                } else {
+                    return Err(SQLError::Parsing(format!("ROLLBACK without any open transactions (missing BEGIN)")));
+                }
+            },
+            Either::Left(Statement::Commit { chain })  => {
+                if let Some(mut ct) = curr_transaction.take() {
+                    ct.push(Statement::Commit { chain });
+                    let tr = close_transaction(ct)?;
+                    any_stmts.push(tr);
+                } else {
+                    return Err(SQLError::Parsing(format!("COMMIT statement without any open transactions (missing BEGIN)")));
+                }
+            },
+            Either::Left(other_stmt) => { 
+                if let Some(ref mut curr_t) = curr_transaction {
+                    curr_t.push(other_stmt);
+                } else {
+                    let orig = format!("{}", other_stmt);
+                    any_stmts.push(AnyStatement::Parsed(other_stmt.clone(), orig));
+                }
+            }
         }
     }
     
+    if curr_transaction.is_some() {
+        return Err(SQLError::Parsing(format!("Unfinished transaction block\n(expected COMMIT or ROLLBACK)")));
+    }
+    
     Ok(any_stmts)
 }
 
@@ -718,12 +816,22 @@ where
                 } else {
                     Err(format!("Invalid copy client specification"))
                 }
+            } else if &w.value[..] == "VARIABLE" || &w.value[..] == "variable" {
+                match take_while_not_whitespace(token_iter) {
+                    Some(Token::Word(var)) => Ok(copy::CopyClient::Variable(var.value.to_string())),
+                    _ => Err(format!("Invalid variable name"))
+                }
+            } else if ["STDIN", "stdin", "STDOUT", "stdout"].iter().any(|s| &w.value[..] == &s[..] ) {
+                Ok(copy::CopyClient::Stdio)
             } else {
                 Err(format!("Invalid copy client specification"))
             }
         },
-        Some(Token::SingleQuotedString(s)) => {
-            Err(format!("Invalid client copy specification: {}", s))
+
+        // copy tbl to 'file.csv' is executed at the client (unlike psql, which
+        // would ask the server to write to this path).
+        Some(Token::SingleQuotedString(file)) => {
+            Ok(copy::CopyClient::File(file.to_string()))
         },
         Some(other) => {
             Err(format!("Invalid client copy specification: {}", other))
@@ -741,15 +849,20 @@ where
     let mut options = String::new();
     if let Some(Token::Word(w)) = take_while_not_whitespace(token_iter) {
         if w.keyword == Keyword::WITH {
+
+            // Keep the clause as informed (including quoted delimiters and the
+            // parenthesized form), since it is forwarded to the server by Copy::to_string.
             while let Some(tk) = token_iter.next() {
                 match tk {
-                    Token::Word(w) => {
-                        options += &w.to_string()[..];
-                        options += " ";
+                    Token::SemiColon => {
+                        break;
                     },
-                    _ => { }
+                    tk => {
+                        options += &tk.to_string()[..];
+                    }
                 }
             }
+            options = options.trim().to_string();
         }
     }
 
@@ -765,8 +878,11 @@ where
 }
 
 pub fn extract_postgres_tokens(stmt : &str) -> Result<Vec<Token>, String> {
-    let dialect = PostgreSqlDialect{};
-    let mut tokenizer = Tokenizer::new(&dialect, stmt);
+    extract_tokens(stmt, &PostgreSqlDialect{})
+}
+
+pub fn extract_tokens(stmt : &str, dialect : &dyn Dialect) -> Result<Vec<Token>, String> {
+    let mut tokenizer = Tokenizer::new(dialect, stmt);
     tokenizer.tokenize().map_err(|e| format!("{}", e) )
 }
 
@@ -876,7 +992,32 @@ pub fn split_unparsed_statements(sql_text : String) -> Result<Vec<AnyStatement>,
     Ok(unparsed_stmts)
 }
 
-pub fn parse_declare_items<'a, I>(token_iter : &mut I, names : &mut Vec<String>, types : &mut Vec<VariableType>) -> Result<(), String>
+/* Parses the value following '=' or 'default' at a variable declaration. Numbers might be preceded
+by a minus sign, and words (true, false) are taken verbatim. */
+fn parse_declare_value<'a, I>(token_iter : &mut I) -> Result<String, String>
+where
+    I : Iterator<Item=&'a Token>
+{
+    match take_while_not_whitespace(token_iter) {
+        Some(Token::SingleQuotedString(s)) => Ok(s.to_string()),
+        Some(Token::Number(n, _)) => Ok(n.to_string()),
+        Some(Token::Minus) => {
+            match take_while_not_whitespace(token_iter) {
+                Some(Token::Number(n, _)) => Ok(format!("-{}", n)),
+                _ => Err(String::from("Invalid declare statement (expected number after '-')"))
+            }
+        },
+        Some(Token::Word(w)) if w.quote_style.is_none() => Ok(w.value.to_string()),
+        _ => Err(String::from("Invalid declare statement (expected literal value after '=')"))
+    }
+}
+
+pub fn parse_declare_items<'a, I>(
+    token_iter : &mut I,
+    names : &mut Vec<String>,
+    types : &mut Vec<VariableType>,
+    values : &mut Vec<Option<String>>
+) -> Result<(), String>
 where
     I : Iterator<Item=&'a Token>
 {
@@ -884,24 +1025,29 @@ where
         if w.keyword == Keyword::NoKeyword {
             let name = w.value.to_string();
             if let Some(Token::Word(w)) = take_while_not_whitespace(token_iter) {
-                let ty = match w.keyword {
-                    Keyword::TEXT => {
-                        VariableType::Text
-                    },
-                    Keyword::BYTEA => {
-                        VariableType::Bytea
-                    },
-                    _ => return Err(String::from("Invalid declare statement (expected declare 'var' 'text|bytea';"))
+                let ty = VariableType::from_type_name(&w.value)
+                    .ok_or(String::from("Invalid declare statement (expected declare 'var' 'text|bytea|integer|real|boolean';"))?;
+                let mut next = take_while_not_whitespace(token_iter);
+                let is_default = match next {
+                    Some(Token::Eq) => true,
+                    Some(Token::Word(w)) => w.keyword == Keyword::DEFAULT,
+                    _ => false
                 };
-                match take_while_not_whitespace(token_iter) {
+                let value = if is_default {
+                    let value = parse_declare_value(token_iter)?;
+                    next = take_while_not_whitespace(token_iter);
+                    Some(value)
+                } else {
+                    None
+                };
+                names.push(name);
+                types.push(ty);
+                values.push(value);
+                match next {
                     Some(Token::Comma) => {
-                        names.push(name);
-                        types.push(ty);
-                        parse_declare_items(token_iter, names, types)
+                        parse_declare_items(token_iter, names, types, values)
                     },
-                    Some(Token::SemiColon) => {
-                        names.push(name);
-                        types.push(ty);
+                    Some(Token::SemiColon) | None => {
                         Ok(())
                     },
                     _ => Err(String::from("Invalid declare statement (expected ',' or ';')"))
@@ -921,9 +1067,9 @@ pub fn parse_remaining_declare_tokens<'a, I>(token_iter : &mut I) -> Result<Decl
 where
     I : Iterator<Item=&'a Token>
 {
-    let (mut names, mut types) = (Vec::new(), Vec::new());
-    parse_declare_items(token_iter, &mut names, &mut types)?;
-    Ok(Declare{ names, types })
+    let (mut names, mut types, mut values) = (Vec::new(), Vec::new(), Vec::new());
+    parse_declare_items(token_iter, &mut names, &mut types, &mut values)?;
+    Ok(Declare{ names, types, values })
 }
 
 impl FromStr for Declare {
@@ -0,0 +1,340 @@
+/*Copyright (c) 2022 Diego da Silva Lima. All rights reserved.
+
+This work is licensed under the terms of the GPL v3.0 License.  
+For a copy, see http://www.gnu.org/licenses.*/
+
+use std::str::FromStr;
+use std::fmt::{self, Display};
+use std::collections::HashMap;
+use rust_decimal::Decimal;
+use super::table::{Table, Columns};
+use super::column::Column;
+use super::nullable::NullableColumn;
+use super::view::{TableView, SortKey, NullOrder};
+
+/* Group-by and pivot operations are calculated in memory over the rows of a result table.
+Rows are grouped by the displayed content of their key columns, and null values are ignored by
+all aggregates (as in SQL). The minimum and maximum keep the type of the aggregated column, and
+decimal columns are summed as decimals, so no precision is lost. The resulting tables are sorted
+by their key columns. */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Count,
+    Sum,
+    Mean,
+    Min,
+    Max,
+    Median,
+    StdDev
+}
+
+impl Aggregate {
+
+    pub const ALL : [Aggregate; 7] = [
+        Aggregate::Count,
+        Aggregate::Sum,
+        Aggregate::Mean,
+        Aggregate::Min,
+        Aggregate::Max,
+        Aggregate::Median,
+        Aggregate::StdDev
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Aggregate::Count => "count",
+            Aggregate::Sum => "sum",
+            Aggregate::Mean => "mean",
+            Aggregate::Min => "min",
+            Aggregate::Max => "max",
+            Aggregate::Median => "median",
+            Aggregate::StdDev => "stddev"
+        }
+    }
+
+    /// Calculates the aggregate over non-null values. Returns None when there are no values
+    /// to aggregate (or less than two values for the standard deviation).
+    pub fn calculate(&self, vals : &[f64]) -> Option<f64> {
+        let n = vals.len();
+        if n == 0 && *self != Aggregate::Count {
+            return None;
+        }
+        match self {
+            Aggregate::Count => Some(n as f64),
+            Aggregate::Sum => Some(vals.iter().sum()),
+            Aggregate::Mean => Some(vals.iter().sum::<f64>() / n as f64),
+            Aggregate::Min => vals.iter().cloned().fold(None, |m : Option<f64>, v| Some(m.map(|m| m.min(v) ).unwrap_or(v)) ),
+            Aggregate::Max => vals.iter().cloned().fold(None, |m : Option<f64>, v| Some(m.map(|m| m.max(v) ).unwrap_or(v)) ),
+            Aggregate::Median => {
+                let mut sorted = vals.to_vec();
+                sorted.sort_by(|a, b| a.total_cmp(b) );
+                if n % 2 == 0 {
+                    Some((sorted[n / 2 - 1] + sorted[n / 2]) / 2.)
+                } else {
+                    Some(sorted[n / 2])
+                }
+            },
+            Aggregate::StdDev => {
+                if n < 2 {
+                    return None;
+                }
+                let mean = vals.iter().sum::<f64>() / n as f64;
+                let ss : f64 = vals.iter().map(|v| (v - mean).powi(2) ).sum();
+                Some((ss / (n - 1) as f64).sqrt())
+            }
+        }
+    }
+
+}
+
+impl Display for Aggregate {
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+
+}
+
+impl FromStr for Aggregate {
+
+    type Err = String;
+
+    fn from_str(s : &str) -> Result<Self, String> {
+        Aggregate::ALL.iter()
+            .find(|agg| agg.name() == s )
+            .cloned()
+            .ok_or(format!("Invalid aggregate: {}", s))
+    }
+
+}
+
+fn check_col(tbl : &Table, col : usize) -> Result<(), String> {
+    if col < tbl.ncols() {
+        Ok(())
+    } else {
+        Err(format!("Invalid column index: {}", col))
+    }
+}
+
+fn cell_key(tbl : &Table, col : usize, row : usize) -> String {
+    if tbl[col].is_null(row) {
+        String::from(NullableColumn::NULL)
+    } else {
+        tbl[col].display_content_at_index(row, None).to_string()
+    }
+}
+
+// Indices of the rows of each group, in the order groups first appear at the table.
+fn groups(tbl : &Table, keys : &[usize]) -> Vec<Vec<usize>> {
+    let mut groups : Vec<Vec<usize>> = Vec::new();
+    let mut positions : HashMap<Vec<String>, usize> = HashMap::new();
+    for row in 0..tbl.nrows() {
+        let key : Vec<String> = keys.iter().map(|k| cell_key(tbl, *k, row) ).collect();
+        match positions.get(&key) {
+            Some(pos) => groups[*pos].push(row),
+            None => {
+                positions.insert(key, groups.len());
+                groups.push(vec![row]);
+            }
+        }
+    }
+    groups
+}
+
+fn is_temporal(col : &Column) -> bool {
+    match col {
+        Column::Date(_) | Column::Time(_) | Column::Timestamp(_) |
+            Column::TimestampTz(_) | Column::Interval(_) => true,
+        Column::Nullable(NullableColumn::Date(_)) | Column::Nullable(NullableColumn::Time(_)) |
+            Column::Nullable(NullableColumn::Timestamp(_)) | Column::Nullable(NullableColumn::TimestampTz(_)) |
+            Column::Nullable(NullableColumn::Interval(_)) => true,
+        _ => false
+    }
+}
+
+// Non-null values of a column at the given rows.
+fn valid_values(col : &Column, rows : &[usize]) -> Column {
+    match col.rearranged(rows) {
+        Column::Nullable(n) => n.pack(),
+        other => other
+    }
+}
+
+// Aggregates the non-null values of a column at the given rows.
+fn aggregate_rows(col : &Column, rows : &[usize], agg : Aggregate, name : &str) -> Result<Option<f64>, String> {
+    let valid = valid_values(col, rows);
+    let cols = Columns::new().take_and_push(name, &valid, 0);
+    let vals = cols.try_numeric(0)
+        .map_err(|e| format!("Cannot calculate {} of column {} ({})", agg, name, e) )?;
+    Ok(agg.calculate(&vals[..]))
+}
+
+// Sum, mean and median of decimal values, calculated without conversion to floats.
+fn aggregate_decimals(vals : &[Decimal], agg : Aggregate, name : &str) -> Result<Option<Decimal>, String> {
+    let n = vals.len();
+    if n == 0 {
+        return Ok(None);
+    }
+    let overflow = || format!("Overflow when calculating {} of column {}", agg, name);
+    let sum = || vals.iter().try_fold(Decimal::ZERO, |s, v| s.checked_add(*v) ).ok_or_else(overflow);
+    match agg {
+        Aggregate::Sum => sum().map(Some),
+        Aggregate::Mean => sum()?.checked_div(Decimal::from(n)).ok_or_else(overflow).map(Some),
+        Aggregate::Median => {
+            let mut sorted = vals.to_vec();
+            sorted.sort();
+            if n % 2 == 0 {
+                sorted[n / 2 - 1].checked_add(sorted[n / 2])
+                    .and_then(|s| s.checked_div(Decimal::TWO) )
+                    .ok_or_else(overflow)
+                    .map(Some)
+            } else {
+                Ok(Some(sorted[n / 2]))
+            }
+        },
+        _ => Err(format!("Cannot calculate {} of decimal column {}", agg, name))
+    }
+}
+
+// Rows holding the minimum (or maximum) value of each group, which is taken in the column type.
+fn extreme_values(col : &Column, groups : &[Vec<usize>], max : bool, name : &str) -> Result<Column, String> {
+    let tbl = Table::new(None, vec![name.to_string()], vec![col.clone()])
+        .map_err(|e| format!("{}", e) )?;
+    let mut view = TableView::default();
+    view.then_by(SortKey { col : 0, ascending : true, nulls : NullOrder::Last });
+    let mut rank = vec![0; col.len()];
+    for (pos, row) in view.indices(&tbl)?.iter().enumerate() {
+        rank[*row] = pos;
+    }
+
+    // Groups without valid values point to an extra null row.
+    let mut nc = col.to_nullable();
+    let null_row = nc.len();
+    nc.push_null();
+    let rows : Vec<usize> = groups.iter()
+        .map(|g| {
+            let valid = g.iter().filter(|r| !col.is_null(**r) );
+            let row = if max {
+                valid.max_by_key(|r| rank[**r] )
+            } else {
+                valid.min_by_key(|r| rank[**r] )
+            };
+            row.cloned().unwrap_or(null_row)
+        })
+        .collect();
+    Ok(Column::from(nc.rearranged(&rows[..])))
+}
+
+// Calculates the aggregate of a column for each group of rows.
+fn aggregate_groups(col : &Column, groups : &[Vec<usize>], agg : Aggregate, name : &str) -> Result<Column, String> {
+    match agg {
+        Aggregate::Count => {
+            let counts : Vec<i64> = groups.iter().map(|g| valid_values(col, &g[..]).len() as i64 ).collect();
+            return Ok(Column::from(counts));
+        },
+        Aggregate::Min | Aggregate::Max => {
+            return extreme_values(col, groups, agg == Aggregate::Max, name);
+        },
+        _ => { }
+    }
+    if is_temporal(col) {
+        return Err(format!("Cannot calculate {} of temporal column {}", agg, name));
+    }
+    let is_decimal = match col {
+        Column::Numeric(_) | Column::Nullable(NullableColumn::Numeric(_)) => true,
+        _ => false
+    };
+    if is_decimal && agg != Aggregate::StdDev {
+        let mut vals = Vec::new();
+        for g in groups {
+            let dec = match valid_values(col, &g[..]) {
+                Column::Numeric(v) => aggregate_decimals(&v[..], agg, name)?,
+                _ => None
+            };
+            vals.push(dec);
+        }
+        return Ok(Column::from(NullableColumn::from(vals)));
+    }
+    let mut vals = Vec::new();
+    for g in groups {
+        vals.push(aggregate_rows(col, &g[..], agg, name)?);
+    }
+    Ok(Column::from(NullableColumn::from(vals)))
+}
+
+fn sorted_by_keys(tbl : Table, nkeys : usize) -> Result<Table, String> {
+    let mut view = TableView::default();
+    for col in 0..nkeys {
+        view.then_by(SortKey { col, ascending : true, nulls : NullOrder::Last });
+    }
+    view.apply(&tbl)
+}
+
+impl Table {
+
+    /// Groups the rows by the values of the key columns, calculating the aggregates of
+    /// the given columns for each group. The result holds the key columns followed by
+    /// one column per aggregate.
+    pub fn grouped_by(&self, keys : &[usize], aggs : &[(usize, Aggregate)]) -> Result<Table, String> {
+        if keys.is_empty() && aggs.is_empty() {
+            return Err(format!("No columns to group by or aggregate"));
+        }
+        for col in keys.iter().chain(aggs.iter().map(|(col, _)| col ) ) {
+            check_col(self, *col)?;
+        }
+        let names = self.names();
+        let groups = if keys.is_empty() {
+            vec![(0..self.nrows()).collect()]
+        } else {
+            groups(self, keys)
+        };
+        let firsts : Vec<usize> = groups.iter().filter_map(|g| g.first().cloned() ).collect();
+        let mut out_names = Vec::new();
+        let mut out_cols = Vec::new();
+        for k in keys {
+            out_names.push(names[*k].clone());
+            out_cols.push(self[*k].rearranged(&firsts[..]));
+        }
+        for (col, agg) in aggs {
+            out_names.push(format!("{}({})", agg, names[*col]));
+            out_cols.push(aggregate_groups(&self[*col], &groups[..], *agg, &names[*col])?);
+        }
+        let tbl = Table::new(None, out_names, out_cols).map_err(|e| format!("{}", e) )?;
+        sorted_by_keys(tbl, keys.len())
+    }
+
+    /// Builds a cross-tabulation with one row per distinct value of the row column and one
+    /// column per distinct value of the pivot column, holding the aggregate of the value
+    /// column for the rows with both values.
+    pub fn pivoted(&self, row : usize, col : usize, value : usize, agg : Aggregate) -> Result<Table, String> {
+        for c in [row, col, value] {
+            check_col(self, c)?;
+        }
+        let names = self.names();
+        let row_groups = groups(self, &[row]);
+
+        // Pivot columns are sorted by their values.
+        let pivot_col = Table::new(None, vec![names[col].clone()], vec![self[col].clone()])
+            .map_err(|e| format!("{}", e) )?;
+        let pivot_col = sorted_by_keys(pivot_col, 1)?;
+        let pivot_names : Vec<String> = groups(&pivot_col, &[0]).iter()
+            .map(|g| cell_key(&pivot_col, 0, g[0]) )
+            .collect();
+
+        let firsts : Vec<usize> = row_groups.iter().map(|g| g[0] ).collect();
+        let mut out_names = vec![names[row].clone()];
+        let mut out_cols = vec![self[row].rearranged(&firsts[..])];
+        for pivot in pivot_names.iter() {
+            let cells : Vec<Vec<usize>> = row_groups.iter()
+                .map(|g| g.iter().filter(|r| cell_key(self, col, **r) == *pivot ).cloned().collect() )
+                .collect();
+            out_names.push(pivot.clone());
+            out_cols.push(aggregate_groups(&self[value], &cells[..], agg, &names[value])?);
+        }
+        let tbl = Table::new(None, out_names, out_cols).map_err(|e| format!("{}", e) )?;
+        sorted_by_keys(tbl, 1)
+    }
+
+}
+
@@ -0,0 +1,380 @@
+/*Copyright (c) 2022 Diego da Silva Lima. All rights reserved.
+
+This work is licensed under the terms of the GPL v3.0 License.  
+For a copy, see http://www.gnu.org/licenses.*/
+
+use arrow::datatypes::*;
+use arrow::array::*;
+use arrow::record_batch::RecordBatch;
+use arrow::compute::cast;
+use arrow::error::ArrowError;
+use arrow::ipc;
+use parquet::arrow::ArrowWriter;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use rust_decimal::Decimal;
+use std::sync::Arc;
+use std::fs::File;
+use std::path::Path;
+use std::collections::HashMap;
+use super::table::Table;
+use super::column::*;
+use super::nullable::*;
+use super::temporal::Interval;
+use chrono::{NaiveDate, NaiveTime, NaiveDateTime, DateTime, Utc, Timelike, TimeZone};
+
+/* Arrow has no JSON type, so JSON columns are written as Utf8 columns, and their
+names are kept at the schema metadata so they can be parsed back when the file is read. */
+const JSON_COLUMNS_KEY : &str = "queries:json";
+
+/// Copies into a column from the Array type A into Rust native type A::Native
+fn primitive_to_column<A>(arr : &ArrayRef) -> Result<Column, String>
+where
+    A : ArrowPrimitiveType,
+    NullableColumn : From<Vec<Option<A::Native>>>
+{
+    let prim_arr = arr.as_any()
+        .downcast_ref::<PrimitiveArray<A>>()
+        .ok_or(format!("Error downcasting column"))?;
+    let v : Vec<Option<A::Native>> = prim_arr.iter().collect();
+    Ok(Column::from(NullableColumn::from(v)))
+}
+
+fn string_to_column(arr : &ArrayRef, is_json : bool) -> Result<Column, String> {
+    let str_arr = arr.as_any()
+        .downcast_ref::<StringArray>()
+        .ok_or(format!("Error downcasting column"))?;
+    if is_json {
+        let mut v : Vec<Option<serde_json::Value>> = Vec::with_capacity(str_arr.len());
+        for s in str_arr.iter() {
+            match s {
+                Some(s) => v.push(Some(serde_json::from_str(s).map_err(|e| format!("Invalid JSON value: {}", e) )?)),
+                None => v.push(None)
+            }
+        }
+        Ok(Column::from(NullableColumn::from(v)))
+    } else {
+        let v : Vec<Option<String>> = str_arr.iter().map(|s| s.map(|s| s.to_string() ) ).collect();
+        Ok(Column::from(NullableColumn::from(v)))
+    }
+}
+
+fn binary_to_column(arr : &ArrayRef) -> Result<Column, String> {
+    let bin_arr = arr.as_any()
+        .downcast_ref::<BinaryArray>()
+        .ok_or(format!("Error downcasting column"))?;
+    let v : Vec<Option<Vec<u8>>> = bin_arr.iter().map(|b| b.map(|b| b.to_vec() ) ).collect();
+    Ok(Column::from(NullableColumn::from(v)))
+}
+
+fn bool_to_column(arr : &ArrayRef) -> Result<Column, String> {
+    let bool_arr = arr.as_any()
+        .downcast_ref::<BooleanArray>()
+        .ok_or(format!("Error downcasting column"))?;
+    let v : Vec<Option<bool>> = bool_arr.iter().collect();
+    Ok(Column::from(NullableColumn::from(v)))
+}
+
+fn decimal_to_column(arr : &ArrayRef, scale : i8) -> Result<Column, String> {
+    let dec_arr = arr.as_any()
+        .downcast_ref::<Decimal128Array>()
+        .ok_or(format!("Error downcasting column"))?;
+    let mut v : Vec<Option<Decimal>> = Vec::with_capacity(dec_arr.len());
+    for d in dec_arr.iter() {
+        match d {
+            Some(d) => {
+                let d = Decimal::try_from_i128_with_scale(d, scale.max(0) as u32)
+                    .map_err(|e| format!("Invalid decimal value: {}", e) )?;
+                v.push(Some(d));
+            },
+            None => v.push(None)
+        }
+    }
+    Ok(Column::from(NullableColumn::from(v)))
+}
+
+const MICROS_PER_SEC : i64 = 1_000_000;
+
+fn epoch() -> NaiveDate {
+    NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()
+}
+
+fn micros_to_timestamp(us : i64) -> Option<NaiveDateTime> {
+    NaiveDateTime::from_timestamp_opt(us.div_euclid(MICROS_PER_SEC), (us.rem_euclid(MICROS_PER_SEC) * 1000) as u32)
+}
+
+fn timestamp_to_micros(t : &NaiveDateTime) -> i64 {
+    t.timestamp() * MICROS_PER_SEC + (t.timestamp_subsec_micros() as i64)
+}
+
+fn time_to_micros(t : &NaiveTime) -> i64 {
+    t.num_seconds_from_midnight() as i64 * MICROS_PER_SEC + (t.nanosecond() / 1000) as i64
+}
+
+/* Temporal arrays are cast to a single unit per type (days for dates and microseconds for
+times and timestamps) before being read. Timestamps carrying a time zone are read as UTC. */
+fn temporal_to_column(arr : &ArrayRef, ty : &DataType) -> Result<Column, String> {
+    match ty {
+        DataType::Date32 | DataType::Date64 => {
+            let arr = cast(arr, &DataType::Date32).map_err(cast_error)?;
+            let arr = arr.as_any().downcast_ref::<Date32Array>().ok_or(format!("Error downcasting column"))?;
+            let v : Vec<Option<NaiveDate>> = arr.iter()
+                .map(|d| d.map(|d| epoch() + chrono::Duration::days(d as i64) ) )
+                .collect();
+            Ok(Column::from(NullableColumn::from(v)))
+        },
+        DataType::Time32(_) | DataType::Time64(_) => {
+            let arr = cast(arr, &DataType::Time64(TimeUnit::Microsecond)).map_err(cast_error)?;
+            let arr = arr.as_any().downcast_ref::<Time64MicrosecondArray>().ok_or(format!("Error downcasting column"))?;
+            let v : Vec<Option<NaiveTime>> = arr.iter()
+                .map(|t| t.and_then(|t| {
+                    NaiveTime::from_num_seconds_from_midnight_opt((t / MICROS_PER_SEC) as u32, ((t % MICROS_PER_SEC) * 1000) as u32)
+                }))
+                .collect();
+            Ok(Column::from(NullableColumn::from(v)))
+        },
+        DataType::Timestamp(_, tz) => {
+            let arr = cast(arr, &DataType::Timestamp(TimeUnit::Microsecond, tz.clone())).map_err(cast_error)?;
+            let arr = arr.as_any().downcast_ref::<TimestampMicrosecondArray>().ok_or(format!("Error downcasting column"))?;
+            let v : Vec<Option<NaiveDateTime>> = arr.iter().map(|t| t.and_then(micros_to_timestamp) ).collect();
+            if tz.is_some() {
+                let v : Vec<Option<DateTime<Utc>>> = v.iter().map(|t| t.map(|t| Utc.from_utc_datetime(&t) ) ).collect();
+                Ok(Column::from(NullableColumn::from(v)))
+            } else {
+                Ok(Column::from(NullableColumn::from(v)))
+            }
+        },
+        DataType::Interval(IntervalUnit::MonthDayNano) => {
+            let arr = arr.as_any().downcast_ref::<IntervalMonthDayNanoArray>().ok_or(format!("Error downcasting column"))?;
+            let v : Vec<Option<Interval>> = arr.iter()
+                .map(|i| i.map(|i| {
+                    let (months, days, nanos) = IntervalMonthDayNanoType::to_parts(i);
+                    Interval::new(months, days, nanos / 1000)
+                }))
+                .collect();
+            Ok(Column::from(NullableColumn::from(v)))
+        },
+        other => Err(format!("Invalid temporal type: {:?}", other))
+    }
+}
+
+fn cast_error(e : ArrowError) -> String {
+    format!("{}", e)
+}
+
+pub fn table_from_batch(results : &RecordBatch) -> Result<Table, String> {
+    let schema = results.schema();
+    let json_cols : Vec<String> = schema.metadata().get(JSON_COLUMNS_KEY)
+        .and_then(|s| serde_json::from_str(s).ok() )
+        .unwrap_or(Vec::new());
+    let fields = schema.fields();
+    let mut names = Vec::new();
+    let mut cols = Vec::new();
+    for (i, arr) in results.columns().iter().enumerate() {
+        let is_json = json_cols.iter().any(|c| c == fields[i].name() );
+        let col = match fields[i].data_type() {
+            DataType::Boolean => bool_to_column(arr)?,
+            DataType::Int8 => primitive_to_column::<Int8Type>(arr)?,
+            DataType::Int16 => primitive_to_column::<Int16Type>(arr)?,
+            DataType::Int32 => primitive_to_column::<Int32Type>(arr)?,
+            DataType::UInt32 => primitive_to_column::<UInt32Type>(arr)?,
+            DataType::Int64 => primitive_to_column::<Int64Type>(arr)?,
+            DataType::Float32 => primitive_to_column::<Float32Type>(arr)?,
+            DataType::Float64 => primitive_to_column::<Float64Type>(arr)?,
+            DataType::Decimal128(_, scale) => decimal_to_column(arr, *scale)?,
+            DataType::Utf8 => string_to_column(arr, is_json)?,
+            DataType::Binary => binary_to_column(arr)?,
+            DataType::LargeUtf8 | DataType::Dictionary(_, _) => {
+                string_to_column(&cast(arr, &DataType::Utf8).map_err(cast_error)?, is_json)?
+            },
+            DataType::LargeBinary => {
+                binary_to_column(&cast(arr, &DataType::Binary).map_err(cast_error)?)?
+            },
+            DataType::UInt8 | DataType::UInt16 => {
+                primitive_to_column::<Int32Type>(&cast(arr, &DataType::Int32).map_err(cast_error)?)?
+            },
+
+            DataType::Date32 | DataType::Date64 | DataType::Time32(_) | DataType::Time64(_) |
+            DataType::Timestamp(_, _) | DataType::Interval(IntervalUnit::MonthDayNano) => {
+                temporal_to_column(arr, fields[i].data_type())?
+            },
+
+            // Other intervals, durations and unsigned 64-bit integers are displayed as text.
+            other => {
+                string_to_column(&cast(arr, &DataType::Utf8)
+                    .map_err(|_| format!("Invalid datatype for column {} ({:?})", i, other) )?, false)?
+            }
+        };
+        cols.push(col);
+        names.push(fields[i].name().clone());
+    }
+    Table::new(None, names, cols).map_err(|e| format!("{}", e))
+}
+
+/// Builds a single table from a sequence of record batches.
+pub fn table_from_batches(names : Vec<String>, batches : &[RecordBatch]) -> Result<Table, String> {
+    let mut tbl : Option<Table> = None;
+    for batch in batches.iter().filter(|b| b.num_rows() > 0 ) {
+        let batch_tbl = table_from_batch(batch)?;
+        match tbl.as_mut() {
+            Some(tbl) => tbl.append_rows(&batch_tbl)?,
+            None => tbl = Some(batch_tbl)
+        }
+    }
+    Ok(tbl.unwrap_or(Table::empty(names)))
+}
+
+fn nullable_column(col : &Column) -> NullableColumn {
+    match col {
+        Column::Bool(v) => NullableColumn::Bool(v.iter().map(|v| Some(*v) ).collect()),
+        Column::I8(v) => NullableColumn::I8(v.iter().map(|v| Some(*v) ).collect()),
+        Column::I16(v) => NullableColumn::I16(v.iter().map(|v| Some(*v) ).collect()),
+        Column::I32(v) => NullableColumn::I32(v.iter().map(|v| Some(*v) ).collect()),
+        Column::U32(v) => NullableColumn::U32(v.iter().map(|v| Some(*v) ).collect()),
+        Column::I64(v) => NullableColumn::I64(v.iter().map(|v| Some(*v) ).collect()),
+        Column::F32(v) => NullableColumn::F32(v.iter().map(|v| Some(*v) ).collect()),
+        Column::F64(v) => NullableColumn::F64(v.iter().map(|v| Some(*v) ).collect()),
+        Column::Numeric(v) => NullableColumn::Numeric(v.iter().map(|v| Some(*v) ).collect()),
+        Column::Str(v) => NullableColumn::Str(v.iter().map(|v| Some(v.clone()) ).collect()),
+        Column::Bytes(v) => NullableColumn::Bytes(v.iter().map(|v| Some(v.clone()) ).collect()),
+        Column::Json(v) => NullableColumn::Json(v.iter().map(|v| Some(v.clone()) ).collect()),
+        Column::Date(v) => NullableColumn::Date(v.iter().map(|v| Some(*v) ).collect()),
+        Column::Time(v) => NullableColumn::Time(v.iter().map(|v| Some(*v) ).collect()),
+        Column::Timestamp(v) => NullableColumn::Timestamp(v.iter().map(|v| Some(*v) ).collect()),
+        Column::TimestampTz(v) => NullableColumn::TimestampTz(v.iter().map(|v| Some(*v) ).collect()),
+        Column::Interval(v) => NullableColumn::Interval(v.iter().map(|v| Some(*v) ).collect()),
+        Column::Uuid(v) => NullableColumn::Uuid(v.iter().map(|v| Some(*v) ).collect()),
+        Column::Inet(v) => NullableColumn::Inet(v.iter().map(|v| Some(*v) ).collect()),
+        Column::MacAddr(v) => NullableColumn::MacAddr(v.iter().map(|v| Some(v.clone()) ).collect()),
+        Column::Geometry(v) => NullableColumn::Geometry(v.iter().map(|v| Some(v.clone()) ).collect()),
+        Column::Nullable(nc) => nc.clone()
+    }
+}
+
+fn decimal_array(v : &[Option<Decimal>]) -> Result<ArrayRef, String> {
+
+    // Arrow decimals have a single scale per column, so all values are rescaled to the largest one.
+    let scale = v.iter().filter_map(|d| d.map(|d| d.scale() ) ).max().unwrap_or(0);
+    let mantissas : Vec<Option<i128>> = v.iter()
+        .map(|d| d.map(|mut d| { d.rescale(scale); d.mantissa() }) )
+        .collect();
+    let arr = Decimal128Array::from(mantissas)
+        .with_precision_and_scale(38, scale as i8)
+        .map_err(cast_error)?;
+    Ok(Arc::new(arr))
+}
+
+fn column_to_array(col : &Column) -> Result<(DataType, ArrayRef), String> {
+    let arr : ArrayRef = match nullable_column(col) {
+        NullableColumn::Bool(v) => Arc::new(BooleanArray::from(v)),
+        NullableColumn::I8(v) => Arc::new(Int8Array::from(v)),
+        NullableColumn::I16(v) => Arc::new(Int16Array::from(v)),
+        NullableColumn::I32(v) => Arc::new(Int32Array::from(v)),
+        NullableColumn::U32(v) => Arc::new(UInt32Array::from(v)),
+        NullableColumn::I64(v) => Arc::new(Int64Array::from(v)),
+        NullableColumn::F32(v) => Arc::new(Float32Array::from(v)),
+        NullableColumn::F64(v) => Arc::new(Float64Array::from(v)),
+        NullableColumn::Numeric(v) => decimal_array(&v[..])?,
+        NullableColumn::Str(v) => Arc::new(v.iter().map(|s| s.as_ref().map(|s| &s[..] ) ).collect::<StringArray>()),
+        NullableColumn::Bytes(v) => Arc::new(v.iter().map(|b| b.as_ref().map(|b| &b[..] ) ).collect::<BinaryArray>()),
+        NullableColumn::Json(v) => Arc::new(v.iter().map(|j| j.as_ref().map(|j| j.to_string() ) ).collect::<StringArray>()),
+        NullableColumn::Date(v) => {
+            Arc::new(Date32Array::from(v.iter().map(|d| d.map(|d| (d - epoch()).num_days() as i32 ) ).collect::<Vec<_>>()))
+        },
+        NullableColumn::Time(v) => {
+            Arc::new(Time64MicrosecondArray::from(v.iter().map(|t| t.as_ref().map(time_to_micros) ).collect::<Vec<_>>()))
+        },
+        NullableColumn::Timestamp(v) => {
+            Arc::new(TimestampMicrosecondArray::from(v.iter().map(|t| t.as_ref().map(timestamp_to_micros) ).collect::<Vec<_>>()))
+        },
+        NullableColumn::TimestampTz(v) => {
+            let micros : Vec<Option<i64>> = v.iter().map(|t| t.as_ref().map(|t| timestamp_to_micros(&t.naive_utc()) ) ).collect();
+            Arc::new(TimestampMicrosecondArray::from(micros).with_timezone(String::from("UTC")))
+        },
+        NullableColumn::Interval(v) => {
+            Arc::new(v.iter()
+                .map(|i| i.map(|i| IntervalMonthDayNanoType::make_value(i.months, i.days, i.micros * 1000) ) )
+                .collect::<IntervalMonthDayNanoArray>())
+        },
+
+        // Arrow has no native network or geometry types, so these are written as text (WKT for geometries).
+        NullableColumn::Uuid(v) => Arc::new(v.iter().map(|u| u.map(|u| u.to_string() ) ).collect::<StringArray>()),
+        NullableColumn::Inet(v) => Arc::new(v.iter().map(|i| i.map(|i| i.to_string() ) ).collect::<StringArray>()),
+        NullableColumn::MacAddr(v) => Arc::new(v.iter().map(|m| m.as_ref().map(|m| m.to_string() ) ).collect::<StringArray>()),
+        NullableColumn::Geometry(v) => Arc::new(v.iter().map(|g| g.as_ref().map(|g| g.to_string() ) ).collect::<StringArray>())
+    };
+    Ok((arr.data_type().clone(), arr))
+}
+
+pub fn table_to_batch(tbl : &Table) -> Result<RecordBatch, String> {
+    let mut fields = Vec::new();
+    let mut arrs = Vec::new();
+    let mut json_cols = Vec::new();
+    for (ix, name) in tbl.names().into_iter().enumerate() {
+        let col = tbl.get_column(ix).ok_or(format!("Invalid column index"))?;
+        let is_json = match col {
+            Column::Json(_) | Column::Nullable(NullableColumn::Json(_)) => true,
+            _ => false
+        };
+        let (ty, arr) = column_to_array(col)?;
+        if is_json {
+            json_cols.push(name.clone());
+        }
+        fields.push(Field::new(&name, ty, arr.null_count() > 0));
+        arrs.push(arr);
+    }
+    let mut metadata = HashMap::new();
+    if json_cols.len() > 0 {
+        metadata.insert(JSON_COLUMNS_KEY.to_string(), serde_json::to_string(&json_cols).unwrap());
+    }
+    let schema = Schema::new_with_metadata(fields, metadata);
+    RecordBatch::try_new(Arc::new(schema), arrs).map_err(cast_error)
+}
+
+pub fn write_parquet(tbl : &Table, path : &Path) -> Result<(), String> {
+    let batch = table_to_batch(tbl)?;
+    let f = File::create(path).map_err(|e| format!("Error creating export file: {}", e) )?;
+    let mut writer = ArrowWriter::try_new(f, batch.schema(), None)
+        .map_err(|e| format!("Error writing to export file: {}", e) )?;
+    writer.write(&batch).map_err(|e| format!("Error writing to export file: {}", e) )?;
+    writer.close().map_err(|e| format!("Error writing to export file: {}", e) )?;
+    Ok(())
+}
+
+pub fn write_ipc(tbl : &Table, path : &Path) -> Result<(), String> {
+    let batch = table_to_batch(tbl)?;
+    let f = File::create(path).map_err(|e| format!("Error creating export file: {}", e) )?;
+    let mut writer = ipc::writer::FileWriter::try_new(f, &batch.schema())
+        .map_err(|e| format!("Error writing to export file: {}", e) )?;
+    writer.write(&batch).map_err(|e| format!("Error writing to export file: {}", e) )?;
+    writer.finish().map_err(|e| format!("Error writing to export file: {}", e) )?;
+    Ok(())
+}
+
+fn schema_names(schema : &Schema) -> Vec<String> {
+    schema.fields().iter().map(|f| f.name().clone() ).collect()
+}
+
+pub fn read_parquet(path : &Path) -> Result<Table, String> {
+    let f = File::open(path).map_err(|e| format!("Error opening file: {}", e) )?;
+    let builder = ParquetRecordBatchReaderBuilder::try_new(f)
+        .map_err(|e| format!("Error reading parquet file: {}", e) )?;
+    let names = schema_names(builder.schema());
+    let reader = builder.build().map_err(|e| format!("Error reading parquet file: {}", e) )?;
+    let mut batches = Vec::new();
+    for batch in reader {
+        batches.push(batch.map_err(|e| format!("Error reading parquet file: {}", e) )?);
+    }
+    table_from_batches(names, &batches[..])
+}
+
+pub fn read_ipc(path : &Path) -> Result<Table, String> {
+    let f = File::open(path).map_err(|e| format!("Error opening file: {}", e) )?;
+    let reader = ipc::reader::FileReader::try_new(f, None)
+        .map_err(|e| format!("Error reading arrow file: {}", e) )?;
+    let names = schema_names(&reader.schema());
+    let mut batches = Vec::new();
+    for batch in reader {
+        batches.push(batch.map_err(|e| format!("Error reading arrow file: {}", e) )?);
+    }
+    table_from_batches(names, &batches[..])
+}
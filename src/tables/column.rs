@@ -13,7 +13,13 @@ use itertools::Itertools;
 use std::cmp::{PartialOrd, PartialEq, Ordering};
 
 use std::str::FromStr;
+use serde::{Serialize, Deserialize};
 use std::fmt::Write;
+use chrono::{NaiveDate, NaiveTime, NaiveDateTime, DateTime, Utc};
+use super::temporal::{self, Interval};
+use super::network::{Inet, MacAddr};
+use super::geometry::Geometry;
+use uuid::Uuid;
 
 /// Densely packed column, where each variant is a vector of some
 /// element that implements postgres::types::ToSql.
@@ -31,6 +37,15 @@ pub enum Column {
     Str(Vec<String>),
     Bytes(Vec<Vec<u8>>),
     Json(Vec<Value>),
+    Date(Vec<NaiveDate>),
+    Time(Vec<NaiveTime>),
+    Timestamp(Vec<NaiveDateTime>),
+    TimestampTz(Vec<DateTime<Utc>>),
+    Interval(Vec<Interval>),
+    Uuid(Vec<Uuid>),
+    Inet(Vec<Inet>),
+    MacAddr(Vec<MacAddr>),
+    Geometry(Vec<Geometry>),
     Nullable(NullableColumn)
 }
 
@@ -105,7 +120,16 @@ impl<'a> Column {
             NullableColumn::Numeric(mut s) => Column::from(s.drain(..).filter_map(|s| s ).collect::<Vec<_>>()),
             NullableColumn::Str(mut s) => Column::from(s.drain(..).filter_map(|s| s ).collect::<Vec<_>>()),
             NullableColumn::Bytes(mut s) => Column::from(s.drain(..).filter_map(|s| s ).collect::<Vec<_>>()),
-            NullableColumn::Json(mut s) => Column::from(s.drain(..).filter_map(|s| s ).collect::<Vec<_>>())
+            NullableColumn::Json(mut s) => Column::from(s.drain(..).filter_map(|s| s ).collect::<Vec<_>>()),
+            NullableColumn::Date(mut s) => Column::from(s.drain(..).filter_map(|s| s ).collect::<Vec<_>>()),
+            NullableColumn::Time(mut s) => Column::from(s.drain(..).filter_map(|s| s ).collect::<Vec<_>>()),
+            NullableColumn::Timestamp(mut s) => Column::from(s.drain(..).filter_map(|s| s ).collect::<Vec<_>>()),
+            NullableColumn::TimestampTz(mut s) => Column::from(s.drain(..).filter_map(|s| s ).collect::<Vec<_>>()),
+            NullableColumn::Interval(mut s) => Column::from(s.drain(..).filter_map(|s| s ).collect::<Vec<_>>()),
+            NullableColumn::Uuid(mut s) => Column::from(s.drain(..).filter_map(|s| s ).collect::<Vec<_>>()),
+            NullableColumn::Inet(mut s) => Column::from(s.drain(..).filter_map(|s| s ).collect::<Vec<_>>()),
+            NullableColumn::MacAddr(mut s) => Column::from(s.drain(..).filter_map(|s| s ).collect::<Vec<_>>()),
+            NullableColumn::Geometry(mut s) => Column::from(s.drain(..).filter_map(|s| s ).collect::<Vec<_>>())
         };
         if col.len() == n {
             Ok(col)
@@ -162,6 +186,43 @@ impl<'a> Column {
             Column::Json(_vs) => {
                 None
             },
+            Column::Date(vs) => {
+                Some(filtered(&vs[..], temporal::parse_date(val)?))
+            },
+            Column::Time(vs) => {
+                Some(filtered(&vs[..], temporal::parse_time(val)?))
+            },
+            Column::Timestamp(vs) => {
+                Some(filtered(&vs[..], temporal::parse_timestamp(val)?))
+            },
+            Column::TimestampTz(vs) => {
+                Some(filtered(&vs[..], temporal::parse_timestamptz(val)?))
+            },
+            Column::Interval(vs) => {
+                Some(filtered(&vs[..], Interval::from_str(val).ok()?))
+            },
+            Column::Uuid(vs) => {
+                Some(filtered(&vs[..], Uuid::from_str(val).ok()?))
+            },
+            Column::Inet(vs) => {
+                // Keeps addresses within the informed network (or equal to the informed address).
+                let net = Inet::from_str(val).ok()?;
+                let (ixs, vec) : (Vec<usize>, Vec<Inet>) = vs.iter().cloned()
+                    .enumerate()
+                    .filter(|(_, a)| net.contains(a) )
+                    .unzip();
+                Some((ixs, Column::from(vec)))
+            },
+            Column::MacAddr(vs) => {
+                Some(filtered(&vs[..], MacAddr::from_str(val).ok()?))
+            },
+            Column::Geometry(vs) => {
+                let (ixs, vec) : (Vec<usize>, Vec<Geometry>) = vs.iter().cloned()
+                    .enumerate()
+                    .filter(|(_, a)| a.to_string().matches(val).next().is_some() )
+                    .unzip();
+                Some((ixs, Column::from(vec)))
+            },
             Column::Nullable(_vs) => {
                 None
             }
@@ -206,6 +267,33 @@ impl<'a> Column {
             Column::Json(_vs) => {
                 Column::from(self.display_content(None)).rearranged(ixs)
             },
+            Column::Date(vs) => {
+                rearrange(&vs[..], ixs)
+            },
+            Column::Time(vs) => {
+                rearrange(&vs[..], ixs)
+            },
+            Column::Timestamp(vs) => {
+                rearrange(&vs[..], ixs)
+            },
+            Column::TimestampTz(vs) => {
+                rearrange(&vs[..], ixs)
+            },
+            Column::Interval(vs) => {
+                rearrange(&vs[..], ixs)
+            },
+            Column::Uuid(vs) => {
+                rearrange(&vs[..], ixs)
+            },
+            Column::Inet(vs) => {
+                rearrange(&vs[..], ixs)
+            },
+            Column::MacAddr(vs) => {
+                rearrange(&vs[..], ixs)
+            },
+            Column::Geometry(vs) => {
+                rearrange(&vs[..], ixs)
+            },
             Column::Nullable(vs) => {
                 let vals : NullableColumn = vs.rearranged(ixs);
                 Column::Nullable(vals)
@@ -252,6 +340,35 @@ impl<'a> Column {
             Column::Json(_vs) => {
                 Column::from(self.display_content(None)).sorted(ascending)
             },
+            Column::Date(vs) => {
+                sorted(&vs[..], ascending)
+            },
+            Column::Time(vs) => {
+                sorted(&vs[..], ascending)
+            },
+            Column::Timestamp(vs) => {
+                sorted(&vs[..], ascending)
+            },
+            Column::TimestampTz(vs) => {
+                sorted(&vs[..], ascending)
+            },
+            Column::Interval(vs) => {
+                sorted(&vs[..], ascending)
+            },
+            Column::Uuid(vs) => {
+                sorted(&vs[..], ascending)
+            },
+            Column::Inet(vs) => {
+                sorted(&vs[..], ascending)
+            },
+            Column::MacAddr(vs) => {
+                sorted(&vs[..], ascending)
+            },
+            Column::Geometry(_vs) => {
+                let (ixs, _) = Column::from(self.display_content(None)).sorted(ascending);
+                let col = self.rearranged(&ixs);
+                (ixs, col)
+            },
             Column::Nullable(vs) => {
                 let (ixs, vals) : (_, NullableColumn) = vs.sorted(ascending);
                 (ixs, Column::Nullable(vals))
@@ -266,6 +383,35 @@ impl<'a> Column {
         vec.into()
     }
 
+    /// Converts the column into JSON values, keeping numbers as numbers, nulls as nulls and
+    /// nested JSON as-is. Binary values are base64-encoded strings.
+    pub fn json_values(&self) -> Vec<Value> {
+        match self {
+            Column::Bool(v) => v.iter().map(|e| Value::from(*e) ).collect(),
+            Column::I8(v) => v.iter().map(|e| Value::from(*e) ).collect(),
+            Column::I16(v) => v.iter().map(|e| Value::from(*e) ).collect(),
+            Column::I32(v) => v.iter().map(|e| Value::from(*e) ).collect(),
+            Column::U32(v) => v.iter().map(|e| Value::from(*e) ).collect(),
+            Column::I64(v) => v.iter().map(|e| Value::from(*e) ).collect(),
+            Column::F32(v) => v.iter().map(|e| Value::from(*e as f64) ).collect(),
+            Column::F64(v) => v.iter().map(|e| Value::from(*e) ).collect(),
+            Column::Numeric(v) => v.iter().map(|d| decimal_to_json(d) ).collect(),
+            Column::Str(v) => v.iter().map(|e| Value::from(e.clone()) ).collect(),
+            Column::Bytes(v) => v.iter().map(|e| Value::from(base64::encode(e)) ).collect(),
+            Column::Json(v) => v.clone(),
+            Column::Date(v) => v.iter().map(|e| Value::from(e.to_string()) ).collect(),
+            Column::Time(v) => v.iter().map(|e| Value::from(e.to_string()) ).collect(),
+            Column::Timestamp(v) => v.iter().map(|e| Value::from(e.format("%Y-%m-%dT%H:%M:%S%.f").to_string()) ).collect(),
+            Column::TimestampTz(v) => v.iter().map(|e| Value::from(e.format("%Y-%m-%dT%H:%M:%S%.fZ").to_string()) ).collect(),
+            Column::Interval(v) => v.iter().map(|e| Value::from(e.to_string()) ).collect(),
+            Column::Uuid(v) => v.iter().map(|e| Value::from(e.to_string()) ).collect(),
+            Column::Inet(v) => v.iter().map(|e| Value::from(e.to_string()) ).collect(),
+            Column::MacAddr(v) => v.iter().map(|e| Value::from(e.to_string()) ).collect(),
+            Column::Geometry(v) => v.iter().map(|e| e.to_geojson() ).collect(),
+            Column::Nullable(col) => col.json_values()
+        }
+    }
+
     /// If this column contains a single JSON element, return it.
     pub fn single_json_row(&self) -> Option<serde_json::Value> {
         match self {
@@ -348,6 +494,15 @@ impl<'a> Column {
             Column::Str(v) => v.get(ix).map(|f| Field::Str(f.clone()) ),
             Column::Json(v) => v.get(ix).map(|f| Field::Json(f.clone()) ),
             Column::Bytes(v) => v.get(ix).map(|f| Field::Bytes(f.clone()) ),
+            Column::Date(v) => v.get(ix).map(|f| Field::Date(*f) ),
+            Column::Time(v) => v.get(ix).map(|f| Field::Time(*f) ),
+            Column::Timestamp(v) => v.get(ix).map(|f| Field::Timestamp(*f) ),
+            Column::TimestampTz(v) => v.get(ix).map(|f| Field::TimestampTz(*f) ),
+            Column::Interval(v) => v.get(ix).map(|f| Field::Interval(*f) ),
+            Column::Uuid(v) => v.get(ix).map(|f| Field::Uuid(*f) ),
+            Column::Inet(v) => v.get(ix).map(|f| Field::Inet(*f) ),
+            Column::MacAddr(v) => v.get(ix).map(|f| Field::MacAddr(f.clone()) ),
+            Column::Geometry(v) => v.get(ix).map(|f| Field::Geometry(f.clone()) ),
             Column::Nullable(col) => col.at(ix, missing)
         }
     }
@@ -366,10 +521,26 @@ impl<'a> Column {
             Column::Str(v) => v.len(),
             Column::Bytes(v) => v.len(),
             Column::Json(v) => v.len(),
+            Column::Date(v) => v.len(),
+            Column::Time(v) => v.len(),
+            Column::Timestamp(v) => v.len(),
+            Column::TimestampTz(v) => v.len(),
+            Column::Interval(v) => v.len(),
+            Column::Uuid(v) => v.len(),
+            Column::Inet(v) => v.len(),
+            Column::MacAddr(v) => v.len(),
+            Column::Geometry(v) => v.len(),
             Column::Nullable(col) => col.len()
         }
     }
 
+    pub fn is_null(&self, ix : usize) -> bool {
+        match self {
+            Column::Nullable(col) => col.is_null(ix),
+            _ => false
+        }
+    }
+
     /*pub fn ref_content(&'a self) -> Vec<&(dyn ToSql + Sync)> {
         match self {
             Column::Bool(v) => Self::to_ref_dyn(v),
@@ -490,6 +661,15 @@ impl<'a> Column {
                 Cow::Owned(json_to_string(&v[row_ix]))
             },
             Column::Bytes(v) => Cow::Owned(display_binary(&v[row_ix])),
+            Column::Date(v) => Cow::Owned(v[row_ix].to_string()),
+            Column::Time(v) => Cow::Owned(v[row_ix].to_string()),
+            Column::Timestamp(v) => Cow::Owned(v[row_ix].to_string()),
+            Column::TimestampTz(v) => Cow::Owned(temporal::timestamptz_to_string(&v[row_ix])),
+            Column::Interval(v) => Cow::Owned(v[row_ix].to_string()),
+            Column::Uuid(v) => Cow::Owned(v[row_ix].to_string()),
+            Column::Inet(v) => Cow::Owned(v[row_ix].to_string()),
+            Column::MacAddr(v) => Cow::Owned(v[row_ix].to_string()),
+            Column::Geometry(v) => Cow::Owned(v[row_ix].to_string()),
             Column::Nullable(col) => col.display_content_at_index(row_ix, prec)
         }
     }
@@ -512,6 +692,15 @@ impl<'a> Column {
             Column::Str(v) => v.iter().skip(fst_row_ix).take(max_rows).for_each(|e| write_str(&mut buffer, &e[..]) ),
             Column::Json(v) => v.iter().skip(fst_row_ix).take(max_rows).for_each(|e| write_str(&mut buffer, &json_to_string(&e) ) ),
             Column::Bytes(v) => v.iter().skip(fst_row_ix).take(max_rows).for_each(|e| write_binary(&mut buffer, &e[..]) ),
+            Column::Date(v) => v.iter().skip(fst_row_ix).take(max_rows).for_each(|e| writeln!(&mut buffer, "{}", e).unwrap() ),
+            Column::Time(v) => v.iter().skip(fst_row_ix).take(max_rows).for_each(|e| writeln!(&mut buffer, "{}", e).unwrap() ),
+            Column::Timestamp(v) => v.iter().skip(fst_row_ix).take(max_rows).for_each(|e| writeln!(&mut buffer, "{}", e).unwrap() ),
+            Column::TimestampTz(v) => v.iter().skip(fst_row_ix).take(max_rows).for_each(|e| writeln!(&mut buffer, "{}", temporal::timestamptz_to_string(e)).unwrap() ),
+            Column::Interval(v) => v.iter().skip(fst_row_ix).take(max_rows).for_each(|e| writeln!(&mut buffer, "{}", e).unwrap() ),
+            Column::Uuid(v) => v.iter().skip(fst_row_ix).take(max_rows).for_each(|e| writeln!(&mut buffer, "{}", e).unwrap() ),
+            Column::Inet(v) => v.iter().skip(fst_row_ix).take(max_rows).for_each(|e| writeln!(&mut buffer, "{}", e).unwrap() ),
+            Column::MacAddr(v) => v.iter().skip(fst_row_ix).take(max_rows).for_each(|e| writeln!(&mut buffer, "{}", e).unwrap() ),
+            Column::Geometry(v) => v.iter().skip(fst_row_ix).take(max_rows).for_each(|e| writeln!(&mut buffer, "{}", e).unwrap() ),
             Column::Nullable(col) => { buffer = col.display_lines(prec, Some(fst_row), Some(max_rows)); }
         }
 
@@ -537,10 +726,147 @@ impl<'a> Column {
             Column::Str(v) => v.clone(),
             Column::Json(v) => v.iter().map(|e| json_to_string(e) ).collect(),
             Column::Bytes(v) => v.iter().map(|e| display_binary(&e) ).collect(),
+            Column::Date(v) => v.iter().map(|e| e.to_string() ).collect(),
+            Column::Time(v) => v.iter().map(|e| e.to_string() ).collect(),
+            Column::Timestamp(v) => v.iter().map(|e| e.to_string() ).collect(),
+            Column::TimestampTz(v) => v.iter().map(|e| temporal::timestamptz_to_string(e) ).collect(),
+            Column::Interval(v) => v.iter().map(|e| e.to_string() ).collect(),
+            Column::Uuid(v) => v.iter().map(|e| e.to_string() ).collect(),
+            Column::Inet(v) => v.iter().map(|e| e.to_string() ).collect(),
+            Column::MacAddr(v) => v.iter().map(|e| e.to_string() ).collect(),
+            Column::Geometry(v) => v.iter().map(|e| e.to_string() ).collect(),
             Column::Nullable(col) => col.display_content(prec)
         }
     }
 
+    pub fn to_nullable(&self) -> NullableColumn {
+        match self {
+            Column::Bool(v) => NullableColumn::from(v.iter().cloned().map(Some).collect::<Vec<_>>()),
+            Column::I8(v) => NullableColumn::from(v.iter().cloned().map(Some).collect::<Vec<_>>()),
+            Column::I16(v) => NullableColumn::from(v.iter().cloned().map(Some).collect::<Vec<_>>()),
+            Column::I32(v) => NullableColumn::from(v.iter().cloned().map(Some).collect::<Vec<_>>()),
+            Column::U32(v) => NullableColumn::from(v.iter().cloned().map(Some).collect::<Vec<_>>()),
+            Column::I64(v) => NullableColumn::from(v.iter().cloned().map(Some).collect::<Vec<_>>()),
+            Column::F32(v) => NullableColumn::from(v.iter().cloned().map(Some).collect::<Vec<_>>()),
+            Column::F64(v) => NullableColumn::from(v.iter().cloned().map(Some).collect::<Vec<_>>()),
+            Column::Numeric(v) => NullableColumn::from(v.iter().cloned().map(Some).collect::<Vec<_>>()),
+            Column::Str(v) => NullableColumn::from(v.iter().cloned().map(Some).collect::<Vec<_>>()),
+            Column::Bytes(v) => NullableColumn::from(v.iter().cloned().map(Some).collect::<Vec<_>>()),
+            Column::Json(v) => NullableColumn::from(v.iter().cloned().map(Some).collect::<Vec<_>>()),
+            Column::Date(v) => NullableColumn::from(v.iter().cloned().map(Some).collect::<Vec<_>>()),
+            Column::Time(v) => NullableColumn::from(v.iter().cloned().map(Some).collect::<Vec<_>>()),
+            Column::Timestamp(v) => NullableColumn::from(v.iter().cloned().map(Some).collect::<Vec<_>>()),
+            Column::TimestampTz(v) => NullableColumn::from(v.iter().cloned().map(Some).collect::<Vec<_>>()),
+            Column::Interval(v) => NullableColumn::from(v.iter().cloned().map(Some).collect::<Vec<_>>()),
+            Column::Uuid(v) => NullableColumn::from(v.iter().cloned().map(Some).collect::<Vec<_>>()),
+            Column::Inet(v) => NullableColumn::from(v.iter().cloned().map(Some).collect::<Vec<_>>()),
+            Column::MacAddr(v) => NullableColumn::from(v.iter().cloned().map(Some).collect::<Vec<_>>()),
+            Column::Geometry(v) => NullableColumn::from(v.iter().cloned().map(Some).collect::<Vec<_>>()),
+            Column::Nullable(col) => col.clone()
+        }
+    }
+
+    /// Returns the values of this column followed by the values of the other column. Columns
+    /// of different types (which SQLite might return for distinct pages of the same query) are
+    /// appended as text.
+    pub fn appended(&self, other : &Column) -> Column {
+        let nc = match (self.to_nullable(), other.to_nullable()) {
+            (NullableColumn::Bool(mut a), NullableColumn::Bool(b)) => { a.extend(b); NullableColumn::Bool(a) },
+            (NullableColumn::I8(mut a), NullableColumn::I8(b)) => { a.extend(b); NullableColumn::I8(a) },
+            (NullableColumn::I16(mut a), NullableColumn::I16(b)) => { a.extend(b); NullableColumn::I16(a) },
+            (NullableColumn::I32(mut a), NullableColumn::I32(b)) => { a.extend(b); NullableColumn::I32(a) },
+            (NullableColumn::U32(mut a), NullableColumn::U32(b)) => { a.extend(b); NullableColumn::U32(a) },
+            (NullableColumn::I64(mut a), NullableColumn::I64(b)) => { a.extend(b); NullableColumn::I64(a) },
+            (NullableColumn::F32(mut a), NullableColumn::F32(b)) => { a.extend(b); NullableColumn::F32(a) },
+            (NullableColumn::F64(mut a), NullableColumn::F64(b)) => { a.extend(b); NullableColumn::F64(a) },
+            (NullableColumn::Numeric(mut a), NullableColumn::Numeric(b)) => { a.extend(b); NullableColumn::Numeric(a) },
+            (NullableColumn::Str(mut a), NullableColumn::Str(b)) => { a.extend(b); NullableColumn::Str(a) },
+            (NullableColumn::Bytes(mut a), NullableColumn::Bytes(b)) => { a.extend(b); NullableColumn::Bytes(a) },
+            (NullableColumn::Json(mut a), NullableColumn::Json(b)) => { a.extend(b); NullableColumn::Json(a) },
+            (NullableColumn::Date(mut a), NullableColumn::Date(b)) => { a.extend(b); NullableColumn::Date(a) },
+            (NullableColumn::Time(mut a), NullableColumn::Time(b)) => { a.extend(b); NullableColumn::Time(a) },
+            (NullableColumn::Timestamp(mut a), NullableColumn::Timestamp(b)) => { a.extend(b); NullableColumn::Timestamp(a) },
+            (NullableColumn::TimestampTz(mut a), NullableColumn::TimestampTz(b)) => { a.extend(b); NullableColumn::TimestampTz(a) },
+            (NullableColumn::Interval(mut a), NullableColumn::Interval(b)) => { a.extend(b); NullableColumn::Interval(a) },
+            (NullableColumn::Uuid(mut a), NullableColumn::Uuid(b)) => { a.extend(b); NullableColumn::Uuid(a) },
+            (NullableColumn::Inet(mut a), NullableColumn::Inet(b)) => { a.extend(b); NullableColumn::Inet(a) },
+            (NullableColumn::MacAddr(mut a), NullableColumn::MacAddr(b)) => { a.extend(b); NullableColumn::MacAddr(a) },
+            (NullableColumn::Geometry(mut a), NullableColumn::Geometry(b)) => { a.extend(b); NullableColumn::Geometry(a) },
+            (a, b) => {
+                let mut txt = a.display_opt_content(None);
+                txt.extend(b.display_opt_content(None));
+                NullableColumn::from(txt)
+            }
+        };
+        Column::from(nc)
+    }
+
+    /// Postgres type used when creating a table to hold this column. Text columns
+    /// are typed by a sample of their values.
+    pub fn postgres_type(&self) -> String {
+        let ty = match self {
+            Column::Bool(_) | Column::Nullable(NullableColumn::Bool(_)) => "boolean",
+            Column::I8(_) | Column::Nullable(NullableColumn::I8(_)) => "smallint",
+            Column::I16(_) | Column::Nullable(NullableColumn::I16(_)) => "smallint",
+            Column::I32(_) | Column::Nullable(NullableColumn::I32(_)) => "integer",
+            Column::U32(_) | Column::Nullable(NullableColumn::U32(_)) => "bigint",
+            Column::I64(_) | Column::Nullable(NullableColumn::I64(_)) => "bigint",
+            Column::F32(_) | Column::Nullable(NullableColumn::F32(_)) => "real",
+            Column::F64(_) | Column::Nullable(NullableColumn::F64(_)) => "double precision",
+            Column::Numeric(_) | Column::Nullable(NullableColumn::Numeric(_)) => "numeric",
+            Column::Bytes(_) | Column::Nullable(NullableColumn::Bytes(_)) => "bytea",
+            Column::Json(_) | Column::Nullable(NullableColumn::Json(_)) => "jsonb",
+            Column::Date(_) | Column::Nullable(NullableColumn::Date(_)) => "date",
+            Column::Time(_) | Column::Nullable(NullableColumn::Time(_)) => "time",
+            Column::Timestamp(_) | Column::Nullable(NullableColumn::Timestamp(_)) => "timestamp",
+            Column::TimestampTz(_) | Column::Nullable(NullableColumn::TimestampTz(_)) => "timestamptz",
+            Column::Interval(_) | Column::Nullable(NullableColumn::Interval(_)) => "interval",
+            Column::Uuid(_) | Column::Nullable(NullableColumn::Uuid(_)) => "uuid",
+            Column::Inet(_) | Column::Nullable(NullableColumn::Inet(_)) => "inet",
+            Column::MacAddr(_) | Column::Nullable(NullableColumn::MacAddr(_)) => "macaddr",
+            // PostGIS might not be installed at the destination, so geometries are written as WKT.
+            Column::Geometry(_) | Column::Nullable(NullableColumn::Geometry(_)) => "text",
+            Column::Str(v) => infer_text_type(v.iter().map(|s| &s[..] )).postgres_type(),
+            Column::Nullable(NullableColumn::Str(v)) => {
+                infer_text_type(v.iter().filter_map(|s| s.as_ref().map(|s| &s[..] ) )).postgres_type()
+            }
+        };
+        ty.to_string()
+    }
+
+    /// SQLite type affinity used when creating a table to hold this column.
+    pub fn sqlite3_creation_type(&self) -> String {
+        let ty = match self {
+            Column::Bool(_) | Column::I8(_) | Column::I16(_) | Column::I32(_) |
+            Column::U32(_) | Column::I64(_) => "INTEGER",
+            Column::F32(_) | Column::F64(_) => "REAL",
+            Column::Numeric(_) => "NUMERIC",
+            Column::Bytes(_) => "BLOB",
+            Column::Json(_) => "TEXT",
+            Column::Date(_) => "DATE",
+            Column::Time(_) => "TIME",
+            Column::Timestamp(_) | Column::TimestampTz(_) => "TIMESTAMP",
+            Column::Interval(_) | Column::Uuid(_) | Column::Inet(_) |
+            Column::MacAddr(_) | Column::Geometry(_) => "TEXT",
+            Column::Str(v) => infer_text_type(v.iter().map(|s| &s[..] )).sqlite3_type(),
+            Column::Nullable(NullableColumn::Str(v)) => {
+                infer_text_type(v.iter().filter_map(|s| s.as_ref().map(|s| &s[..] ) )).sqlite3_type()
+            },
+            Column::Nullable(nc) => match nc {
+                NullableColumn::Bool(_) | NullableColumn::I8(_) | NullableColumn::I16(_) |
+                NullableColumn::I32(_) | NullableColumn::U32(_) | NullableColumn::I64(_) => "INTEGER",
+                NullableColumn::F32(_) | NullableColumn::F64(_) => "REAL",
+                NullableColumn::Numeric(_) => "NUMERIC",
+                NullableColumn::Bytes(_) => "BLOB",
+                NullableColumn::Date(_) => "DATE",
+                NullableColumn::Time(_) => "TIME",
+                NullableColumn::Timestamp(_) | NullableColumn::TimestampTz(_) => "TIMESTAMP",
+                _ => "TEXT"
+            }
+        };
+        ty.to_string()
+    }
+
     pub fn sqlite3_type(&self) -> String {
         match self {
             Column::I32(_) | Column::I64(_) => String::from("INT"),
@@ -610,6 +936,182 @@ pub fn write_str(buffer : &mut String, s : &str) {
     }
 }
 
+/* Decimals are written as JSON numbers with their exact digits (serde_json keeps the
+number text with the arbitrary_precision feature), so converting them to f64 does not
+lose their precision or scale. */
+pub fn decimal_to_json(d : &Decimal) -> Value {
+    match serde_json::Number::from_str(&d.to_string()) {
+        Ok(n) => Value::Number(n),
+        Err(_) => Value::String(d.to_string())
+    }
+}
+
+/// Type recognized from the values of a text column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextType {
+    Bool,
+    Integer,
+    Float,
+    Numeric,
+    Date,
+    Time,
+    Timestamp,
+    Json,
+    Text
+}
+
+impl TextType {
+
+    pub fn postgres_type(&self) -> &'static str {
+        match self {
+            TextType::Bool => "boolean",
+            TextType::Integer => "bigint",
+            TextType::Float => "double precision",
+            TextType::Numeric => "numeric",
+            TextType::Date => "date",
+            TextType::Time => "time",
+            TextType::Timestamp => "timestamp",
+            TextType::Json => "jsonb",
+            TextType::Text => "text"
+        }
+    }
+
+    pub fn sqlite3_type(&self) -> &'static str {
+        match self {
+            TextType::Bool | TextType::Integer => "INTEGER",
+            TextType::Float => "REAL",
+            TextType::Numeric => "NUMERIC",
+            TextType::Date => "DATE",
+            TextType::Time => "TIME",
+            TextType::Timestamp => "TIMESTAMP",
+            _ => "TEXT"
+        }
+    }
+
+}
+
+impl FromStr for TextType {
+
+    type Err = String;
+
+    fn from_str(s : &str) -> Result<Self, String> {
+        match &s.to_lowercase()[..] {
+            "bool" | "boolean" => Ok(TextType::Bool),
+            "int" | "integer" | "bigint" => Ok(TextType::Integer),
+            "float" | "double" | "real" => Ok(TextType::Float),
+            "numeric" | "decimal" => Ok(TextType::Numeric),
+            "date" => Ok(TextType::Date),
+            "time" => Ok(TextType::Time),
+            "timestamp" => Ok(TextType::Timestamp),
+            "json" | "jsonb" => Ok(TextType::Json),
+            "text" => Ok(TextType::Text),
+            other => Err(format!("Unknown column type: {}", other))
+        }
+    }
+
+}
+
+// Significant digits that can be represented by a double-precision float without loss.
+const F64_DIGITS : usize = 15;
+
+fn parse_text_bool(s : &str) -> Option<bool> {
+    match &s.to_lowercase()[..] {
+        "true" | "t" => Some(true),
+        "false" | "f" => Some(false),
+        _ => None
+    }
+}
+
+// Codes such as "007" are kept as text, since the leading zeros would be lost as numbers.
+fn has_leading_zeros(s : &str) -> bool {
+    let digits = s.trim_start_matches(|c| c == '+' || c == '-' );
+    digits.len() > 1 && digits.starts_with('0') && digits[1..].starts_with(|c : char| c.is_ascii_digit() )
+}
+
+fn significant_digits(s : &str) -> usize {
+    let mantissa = s.split(|c| c == 'e' || c == 'E' ).next().unwrap_or(s);
+    mantissa.chars()
+        .filter(|c| c.is_ascii_digit() )
+        .skip_while(|c| *c == '0' )
+        .count()
+}
+
+/// Decides the narrowest type that can represent all non-empty text values.
+pub fn infer_text_type<'a>(vals : impl Iterator<Item=&'a str>) -> TextType {
+    let date = regex::Regex::new(r"^\d{4}-\d{2}-\d{2}$").unwrap();
+    let time = regex::Regex::new(r"^\d{2}:\d{2}(:\d{2}(\.\d+)?)?$").unwrap();
+    let timestamp = regex::Regex::new(r"^\d{4}-\d{2}-\d{2}[ T]\d{2}:\d{2}(:\d{2}(\.\d+)?)?$").unwrap();
+    let mut candidates = vec![
+        TextType::Bool,
+        TextType::Integer,
+        TextType::Float,
+        TextType::Date,
+        TextType::Time,
+        TextType::Timestamp,
+        TextType::Json
+    ];
+    let mut n = 0;
+    let mut exceeds_f64 = false;
+    for v in vals.map(|v| v.trim() ).filter(|v| !v.is_empty() ) {
+        let leading_zeros = has_leading_zeros(v);
+        candidates.retain(|ty| {
+            match ty {
+                TextType::Bool => parse_text_bool(v).is_some(),
+                TextType::Integer => !leading_zeros && v.parse::<i64>().is_ok(),
+                TextType::Float => !leading_zeros && v.parse::<f64>().map(|f| f.is_finite() ).unwrap_or(false),
+                TextType::Date => date.is_match(v),
+                TextType::Time => time.is_match(v),
+                TextType::Timestamp => timestamp.is_match(v),
+                TextType::Json => v.parse::<Value>().is_ok(),
+                _ => true
+            }
+        });
+        if candidates.contains(&TextType::Float) && significant_digits(v) > F64_DIGITS {
+            exceeds_f64 = true;
+        }
+        n += 1;
+        if candidates.is_empty() {
+            break;
+        }
+    }
+    match candidates.get(0) {
+        _ if n == 0 => TextType::Text,
+
+        // Decimal values that would lose precision as floats are kept as numeric.
+        Some(TextType::Float) if exceeds_f64 => TextType::Numeric,
+        Some(ty) => *ty,
+        None => TextType::Text
+    }
+}
+
+/// Converts text values to a column of the given type. Empty values are nulls for all types except
+/// text, and the column is nullable if any nulls are found. If any other value cannot be parsed as
+/// the given type, the column is kept as text, so no content is lost.
+pub fn column_from_text(values : Vec<Option<String>>, ty : TextType) -> Column {
+    fn parse_all<T>(values : &[Option<String>], f : impl Fn(&str)->Option<T>) -> Option<Vec<Option<T>>> {
+        values.iter()
+            .map(|v| match v.as_ref().map(|s| s.trim() ).filter(|s| !s.is_empty() ) {
+                Some(s) => f(s).map(Some),
+                None => Some(None)
+            })
+            .collect()
+    }
+    let parsed = match ty {
+        TextType::Bool => parse_all(&values, parse_text_bool).map(NullableColumn::from),
+        TextType::Integer => parse_all(&values, |s| s.parse::<i64>().ok() ).map(NullableColumn::from),
+        TextType::Float => parse_all(&values, |s| s.parse::<f64>().ok() ).map(NullableColumn::from),
+        TextType::Numeric => parse_all(&values, |s| {
+            Decimal::from_str(s).or_else(|_| Decimal::from_scientific(s) ).ok()
+        }).map(NullableColumn::from),
+        TextType::Json => parse_all(&values, |s| s.parse::<Value>().ok() ).map(NullableColumn::from),
+        TextType::Date => parse_all(&values, temporal::parse_date).map(NullableColumn::from),
+        TextType::Time => parse_all(&values, temporal::parse_time).map(NullableColumn::from),
+        TextType::Timestamp => parse_all(&values, temporal::parse_timestamp).map(NullableColumn::from),
+        TextType::Text => None
+    };
+    Column::from(parsed.unwrap_or_else(|| NullableColumn::from(values) ))
+}
+
 pub mod from {
 
     use super::*;
@@ -718,6 +1220,60 @@ pub mod from {
         }
     }
 
+    impl From<Vec<NaiveDate>> for Column {
+        fn from(value: Vec<NaiveDate>) -> Self {
+            Self::Date(value)
+        }
+    }
+
+    impl From<Vec<NaiveTime>> for Column {
+        fn from(value: Vec<NaiveTime>) -> Self {
+            Self::Time(value)
+        }
+    }
+
+    impl From<Vec<NaiveDateTime>> for Column {
+        fn from(value: Vec<NaiveDateTime>) -> Self {
+            Self::Timestamp(value)
+        }
+    }
+
+    impl From<Vec<DateTime<Utc>>> for Column {
+        fn from(value: Vec<DateTime<Utc>>) -> Self {
+            Self::TimestampTz(value)
+        }
+    }
+
+    impl From<Vec<Interval>> for Column {
+        fn from(value: Vec<Interval>) -> Self {
+            Self::Interval(value)
+        }
+    }
+
+    impl From<Vec<Uuid>> for Column {
+        fn from(value: Vec<Uuid>) -> Self {
+            Self::Uuid(value)
+        }
+    }
+
+    impl From<Vec<Inet>> for Column {
+        fn from(value: Vec<Inet>) -> Self {
+            Self::Inet(value)
+        }
+    }
+
+    impl From<Vec<MacAddr>> for Column {
+        fn from(value: Vec<MacAddr>) -> Self {
+            Self::MacAddr(value)
+        }
+    }
+
+    impl From<Vec<Geometry>> for Column {
+        fn from(value: Vec<Geometry>) -> Self {
+            Self::Geometry(value)
+        }
+    }
+
     /*impl<T> From<Vec<Vec<T>>> for Column
     where
         Column : From<Vec<T>>
@@ -909,6 +1465,123 @@ pub mod try_into {
         }
 
     }
+
+    impl TryFrom<Column> for Vec<NaiveDate> {
+
+        type Error = &'static str;
+
+        fn try_from(col : Column) -> Result<Self, Self::Error> {
+            match col {
+                Column::Date(v) => Ok(v),
+                _ => Err("Invalid column type")
+            }
+        }
+
+    }
+
+    impl TryFrom<Column> for Vec<NaiveTime> {
+
+        type Error = &'static str;
+
+        fn try_from(col : Column) -> Result<Self, Self::Error> {
+            match col {
+                Column::Time(v) => Ok(v),
+                _ => Err("Invalid column type")
+            }
+        }
+
+    }
+
+    impl TryFrom<Column> for Vec<NaiveDateTime> {
+
+        type Error = &'static str;
+
+        fn try_from(col : Column) -> Result<Self, Self::Error> {
+            match col {
+                Column::Timestamp(v) => Ok(v),
+                _ => Err("Invalid column type")
+            }
+        }
+
+    }
+
+    impl TryFrom<Column> for Vec<DateTime<Utc>> {
+
+        type Error = &'static str;
+
+        fn try_from(col : Column) -> Result<Self, Self::Error> {
+            match col {
+                Column::TimestampTz(v) => Ok(v),
+                _ => Err("Invalid column type")
+            }
+        }
+
+    }
+
+    impl TryFrom<Column> for Vec<Interval> {
+
+        type Error = &'static str;
+
+        fn try_from(col : Column) -> Result<Self, Self::Error> {
+            match col {
+                Column::Interval(v) => Ok(v),
+                _ => Err("Invalid column type")
+            }
+        }
+
+    }
+
+    impl TryFrom<Column> for Vec<Uuid> {
+
+        type Error = &'static str;
+
+        fn try_from(col : Column) -> Result<Self, Self::Error> {
+            match col {
+                Column::Uuid(v) => Ok(v),
+                _ => Err("Invalid column type")
+            }
+        }
+
+    }
+
+    impl TryFrom<Column> for Vec<Inet> {
+
+        type Error = &'static str;
+
+        fn try_from(col : Column) -> Result<Self, Self::Error> {
+            match col {
+                Column::Inet(v) => Ok(v),
+                _ => Err("Invalid column type")
+            }
+        }
+
+    }
+
+    impl TryFrom<Column> for Vec<MacAddr> {
+
+        type Error = &'static str;
+
+        fn try_from(col : Column) -> Result<Self, Self::Error> {
+            match col {
+                Column::MacAddr(v) => Ok(v),
+                _ => Err("Invalid column type")
+            }
+        }
+
+    }
+
+    impl TryFrom<Column> for Vec<Geometry> {
+
+        type Error = &'static str;
+
+        fn try_from(col : Column) -> Result<Self, Self::Error> {
+            match col {
+                Column::Geometry(v) => Ok(v),
+                _ => Err("Invalid column type")
+            }
+        }
+
+    }
     
     impl<T> TryFrom<Column> for Vec<Option<T>>
     where
@@ -923,7 +1596,16 @@ pub mod try_into {
         NullableColumn : From<Vec<Option<Decimal>>>,
         NullableColumn : From<Vec<Option<String>>>,
         NullableColumn : From<Vec<Option<serde_json::Value>>>,
-        NullableColumn : From<Vec<Option<Vec<u8>>>>
+        NullableColumn : From<Vec<Option<Vec<u8>>>>,
+        NullableColumn : From<Vec<Option<NaiveDate>>>,
+        NullableColumn : From<Vec<Option<NaiveTime>>>,
+        NullableColumn : From<Vec<Option<NaiveDateTime>>>,
+        NullableColumn : From<Vec<Option<DateTime<Utc>>>>,
+        NullableColumn : From<Vec<Option<Interval>>>,
+        NullableColumn : From<Vec<Option<Uuid>>>,
+        NullableColumn : From<Vec<Option<Inet>>>,
+        NullableColumn : From<Vec<Option<MacAddr>>>,
+        NullableColumn : From<Vec<Option<Geometry>>>
     {
     
         type Error = &'static str;
@@ -949,6 +1631,15 @@ pub mod try_into {
                 Column::Str(mut v) => Vec::<Option<T>>::try_from(NullableColumn::from(v.drain(..).map(|v| Some(v) ).collect::<Vec<_>>())).map_err(|_| "" ),
                 Column::Json(mut v) => Vec::<Option<T>>::try_from(NullableColumn::from(v.drain(..).map(|v| Some(v) ).collect::<Vec<_>>())).map_err(|_| "" ),
                 Column::Bytes(mut v) => Vec::<Option<T>>::try_from(NullableColumn::from(v.drain(..).map(|v| Some(v) ).collect::<Vec<_>>())).map_err(|_| "" ),
+                Column::Date(mut v) => Vec::<Option<T>>::try_from(NullableColumn::from(v.drain(..).map(|v| Some(v) ).collect::<Vec<_>>())).map_err(|_| "" ),
+                Column::Time(mut v) => Vec::<Option<T>>::try_from(NullableColumn::from(v.drain(..).map(|v| Some(v) ).collect::<Vec<_>>())).map_err(|_| "" ),
+                Column::Timestamp(mut v) => Vec::<Option<T>>::try_from(NullableColumn::from(v.drain(..).map(|v| Some(v) ).collect::<Vec<_>>())).map_err(|_| "" ),
+                Column::TimestampTz(mut v) => Vec::<Option<T>>::try_from(NullableColumn::from(v.drain(..).map(|v| Some(v) ).collect::<Vec<_>>())).map_err(|_| "" ),
+                Column::Interval(mut v) => Vec::<Option<T>>::try_from(NullableColumn::from(v.drain(..).map(|v| Some(v) ).collect::<Vec<_>>())).map_err(|_| "" ),
+                Column::Uuid(mut v) => Vec::<Option<T>>::try_from(NullableColumn::from(v.drain(..).map(|v| Some(v) ).collect::<Vec<_>>())).map_err(|_| "" ),
+                Column::Inet(mut v) => Vec::<Option<T>>::try_from(NullableColumn::from(v.drain(..).map(|v| Some(v) ).collect::<Vec<_>>())).map_err(|_| "" ),
+                Column::MacAddr(mut v) => Vec::<Option<T>>::try_from(NullableColumn::from(v.drain(..).map(|v| Some(v) ).collect::<Vec<_>>())).map_err(|_| "" ),
+                Column::Geometry(mut v) => Vec::<Option<T>>::try_from(NullableColumn::from(v.drain(..).map(|v| Some(v) ).collect::<Vec<_>>())).map_err(|_| "" ),
             }
         }
     }
@@ -0,0 +1,280 @@
+/*Copyright (c) 2022 Diego da Silva Lima. All rights reserved.
+
+This work is licensed under the terms of the GPL v3.0 License.  
+For a copy, see http://www.gnu.org/licenses.*/
+
+use std::str::FromStr;
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use super::column::TextType;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CsvEncoding {
+    Utf8,
+    Latin1
+}
+
+impl FromStr for CsvEncoding {
+
+    type Err = String;
+
+    fn from_str(s : &str) -> Result<Self, String> {
+        match &s.to_lowercase().replace("_", "-")[..] {
+            "utf8" | "utf-8" => Ok(CsvEncoding::Utf8),
+            "latin1" | "latin-1" | "iso-8859-1" | "iso8859-1" => Ok(CsvEncoding::Latin1),
+            other => Err(format!("Unsupported encoding: {}", other))
+        }
+    }
+
+}
+
+/// Options used to read delimited text. Fields left as None are detected from the
+/// content by CsvOptions::sniff.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct CsvOptions {
+
+    pub delimiter : Option<u8>,
+
+    pub quote : Option<u8>,
+
+    // Character escaping quotes inside quoted fields. Quotes are escaped by doubling them when None.
+    pub escape : Option<u8>,
+
+    pub header : Option<bool>,
+
+    pub encoding : Option<CsvEncoding>,
+
+    // Fields matching this string are read as nulls.
+    pub null : Option<String>,
+
+    // Whether numeric fields use a comma as the decimal separator (1,5 instead of 1.5).
+    pub decimal_comma : Option<bool>,
+
+    // Number of lines ignored before the header (or the first record).
+    pub skip_rows : usize,
+
+    // Types of columns that should not be inferred from their values.
+    pub column_types : HashMap<String, TextType>
+
+}
+
+const DELIMITERS : [u8; 4] = [b',', b';', b'\t', b'|'];
+
+const QUOTES : [u8; 2] = [b'"', b'\''];
+
+// Number of lines inspected when detecting the dialect.
+const SNIFF_LINES : usize = 100;
+
+impl CsvOptions {
+
+    /// Options for content known to follow the given delimiter and header convention.
+    pub fn with_delimiter(delimiter : u8, header : bool) -> Self {
+        CsvOptions {
+            delimiter : Some(delimiter),
+            quote : Some(b'"'),
+            header : Some(header),
+            encoding : Some(CsvEncoding::Utf8),
+            decimal_comma : Some(false),
+            ..Default::default()
+        }
+    }
+
+    /// Decodes the content according to the encoding (or as UTF-8 if it is valid UTF-8 and
+    /// Latin-1 otherwise when the encoding is unknown), removing any byte order mark and the
+    /// skipped lines.
+    pub fn decode(&self, content : &[u8]) -> Result<String, String> {
+        let content = content.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(content);
+        let txt = match self.encoding {
+            Some(CsvEncoding::Utf8) => {
+                String::from_utf8(content.to_vec()).map_err(|e| format!("Content is not valid UTF-8: {}", e) )?
+            },
+            Some(CsvEncoding::Latin1) => content.iter().map(|b| *b as char ).collect(),
+            None => match std::str::from_utf8(content) {
+                Ok(s) => s.to_string(),
+                Err(_) => content.iter().map(|b| *b as char ).collect()
+            }
+        };
+        if self.skip_rows == 0 {
+            Ok(txt)
+        } else {
+            Ok(txt.split_inclusive('\n').skip(self.skip_rows).collect())
+        }
+    }
+
+    /// Whether any option that can be detected from the content was left unset.
+    pub fn is_partial(&self) -> bool {
+        self.delimiter.is_none() || self.quote.is_none() || self.header.is_none() ||
+            self.encoding.is_none() || self.decimal_comma.is_none()
+    }
+
+    /// Returns a copy of the options with all unset fields detected from the content.
+    pub fn sniff(&self, content : &[u8]) -> Self {
+        let mut opts = self.clone();
+        if opts.encoding.is_none() {
+            let content = content.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(content);
+            opts.encoding = Some(if std::str::from_utf8(content).is_ok() { CsvEncoding::Utf8 } else { CsvEncoding::Latin1 });
+        }
+        let txt = opts.decode(content).unwrap_or(String::new());
+        let lines : Vec<&str> = txt.lines()
+            .filter(|l| !l.trim().is_empty() )
+            .take(SNIFF_LINES)
+            .collect();
+        if opts.quote.is_none() {
+            opts.quote = Some(sniff_quote(&lines[..]));
+        }
+        let quote = opts.quote.unwrap();
+        if opts.delimiter.is_none() {
+            opts.delimiter = Some(sniff_delimiter(&lines[..], quote));
+        }
+        let delimiter = opts.delimiter.unwrap();
+        let records = split_records(&lines[..], delimiter, quote);
+        if opts.decimal_comma.is_none() {
+            opts.decimal_comma = Some(delimiter != b',' && sniff_decimal_comma(&records[..]));
+        }
+        if opts.header.is_none() {
+            opts.header = Some(sniff_header(&records[..], opts.decimal_comma.unwrap(), opts.null.as_ref()));
+        }
+        opts
+    }
+
+    /// Replaces the decimal comma of numeric fields by a point.
+    pub fn normalize_decimal(&self, s : String) -> String {
+        if self.decimal_comma.unwrap_or(false) && !s.contains('.') && s.matches(',').count() == 1 {
+            let replaced = s.replace(',', ".");
+            if replaced.trim().parse::<f64>().is_ok() {
+                return replaced;
+            }
+        }
+        s
+    }
+
+    pub fn is_null(&self, s : &str) -> bool {
+        self.null.as_ref().map(|n| &n[..] == s ).unwrap_or(false)
+    }
+
+}
+
+fn parse_number(s : &str, decimal_comma : bool) -> Option<f64> {
+    if decimal_comma && !s.contains('.') && s.matches(',').count() == 1 {
+        s.replace(',', ".").parse::<f64>().ok()
+    } else {
+        s.parse::<f64>().ok()
+    }
+}
+
+/* The quote is the character that more often opens a field (at the start of a line or right after
+a possible delimiter). Apostrophes inside words are not counted. */
+fn sniff_quote(lines : &[&str]) -> u8 {
+    let mut counts = [0; 2];
+    for line in lines {
+        let bytes = line.as_bytes();
+        for (ix, b) in bytes.iter().enumerate() {
+            if let Some(qix) = QUOTES.iter().position(|q| q == b ) {
+                if ix == 0 || DELIMITERS.iter().any(|d| *d == bytes[ix-1] ) {
+                    counts[qix] += 1;
+                }
+            }
+        }
+    }
+    if counts[1] > counts[0] {
+        QUOTES[1]
+    } else {
+        QUOTES[0]
+    }
+}
+
+fn count_unquoted(line : &str, delimiter : u8, quote : u8) -> usize {
+    let mut quoted = false;
+    let mut n = 0;
+    for b in line.bytes() {
+        if b == quote {
+            quoted = !quoted;
+        } else if b == delimiter && !quoted {
+            n += 1;
+        }
+    }
+    n
+}
+
+// Whether all commas are between digits, and therefore are likely decimal separators.
+fn commas_within_numbers(lines : &[&str]) -> bool {
+    lines.iter().all(|l| {
+        let bytes = l.as_bytes();
+        bytes.iter().enumerate()
+            .filter(|(_, b)| **b == b',' )
+            .all(|(ix, _)| ix > 0 && ix < bytes.len() - 1 && bytes[ix-1].is_ascii_digit() && bytes[ix+1].is_ascii_digit() )
+    })
+}
+
+/* The delimiter is the candidate that splits all lines into the same number of fields. Among
+those, the one yielding more fields is preferred. When no candidate is consistent, the candidate
+most frequently found across lines is used. Commas found only between digits are taken as decimal
+separators when another candidate is consistent. */
+fn sniff_delimiter(lines : &[&str], quote : u8) -> u8 {
+    let mut best : Option<(u8, usize)> = None;
+    let mut most_frequent : (u8, usize) = (b',', 0);
+    let decimal_commas = commas_within_numbers(lines);
+    for delim in DELIMITERS.iter() {
+        let counts : Vec<usize> = lines.iter().map(|l| count_unquoted(l, *delim, quote) ).collect();
+        let total : usize = counts.iter().sum();
+        if total > most_frequent.1 {
+            most_frequent = (*delim, total);
+        }
+        if let Some(fst) = counts.get(0) {
+            if *fst > 0 && counts.iter().all(|c| c == fst ) {
+                let replaces_best = match best {
+                    Some((b',', _)) if decimal_commas => true,
+                    Some((_, n)) => *fst > n && !(*delim == b',' && decimal_commas),
+                    None => true
+                };
+                if replaces_best {
+                    best = Some((*delim, *fst));
+                }
+            }
+        }
+    }
+    best.map(|(d, _)| d ).unwrap_or(most_frequent.0)
+}
+
+fn split_records(lines : &[&str], delimiter : u8, quote : u8) -> Vec<Vec<String>> {
+    let content = lines.join("\n");
+    let mut reader = ::csv::ReaderBuilder::new()
+        .has_headers(false)
+        .delimiter(delimiter)
+        .quote(quote)
+        .flexible(true)
+        .trim(::csv::Trim::All)
+        .from_reader(content.as_bytes());
+    reader.records()
+        .filter_map(|rec| rec.ok() )
+        .map(|rec| rec.iter().map(|f| f.to_string() ).collect() )
+        .collect()
+}
+
+fn sniff_decimal_comma(records : &[Vec<String>]) -> bool {
+    let comma = regex::Regex::new(r"^[+-]?\d+,\d+$").unwrap();
+    let point = regex::Regex::new(r"^[+-]?\d+\.\d+$").unwrap();
+    let fields = || records.iter().flat_map(|rec| rec.iter() );
+    fields().any(|f| comma.is_match(f) ) && !fields().any(|f| point.is_match(f) )
+}
+
+/* The first record is a header when none of its fields are numeric. Content where all fields
+are text is assumed to have a header. */
+fn sniff_header(records : &[Vec<String>], decimal_comma : bool, null : Option<&String>) -> bool {
+    let is_number = |s : &str| parse_number(s, decimal_comma).is_some();
+    let fst = match records.get(0) {
+        Some(fst) => fst,
+        None => return true
+    };
+    if fst.iter().any(|f| is_number(f) ) {
+        return false;
+    }
+    let is_value = |s : &str| !s.is_empty() && null.map(|n| &n[..] != s ).unwrap_or(true);
+    if fst.iter().any(|f| !is_value(f) ) && records.len() > 1 {
+
+        // Data fields are frequently empty, so an empty field at the first record only
+        // suggests a header (with an unnamed column) when later records have no empty fields.
+        return records[1..].iter().all(|rec| rec.iter().all(|f| is_value(f) ) );
+    }
+    true
+}
@@ -5,6 +5,11 @@ For a copy, see http://www.gnu.org/licenses.*/
 
 use rust_decimal::Decimal;
 use serde_json::Value;
+use chrono::{NaiveDate, NaiveTime, NaiveDateTime, DateTime, Utc};
+use super::temporal::{self, Interval};
+use super::network::{Inet, MacAddr};
+use super::geometry::Geometry;
+use uuid::Uuid;
 
 
 #[derive(Debug, Clone)]
@@ -20,7 +25,16 @@ pub enum Field {
     Numeric(Decimal),
     Str(String),
     Json(Value),
-    Bytes(Vec<u8>)
+    Bytes(Vec<u8>),
+    Date(NaiveDate),
+    Time(NaiveTime),
+    Timestamp(NaiveDateTime),
+    TimestampTz(DateTime<Utc>),
+    Interval(Interval),
+    Uuid(Uuid),
+    Inet(Inet),
+    MacAddr(MacAddr),
+    Geometry(Geometry)
 }
 
 impl Field {
@@ -38,7 +52,16 @@ impl Field {
             Field::Numeric(f) => f.to_string(),
             Field::Str(f) => f.clone(),
             Field::Json(f) => f.to_string(),
-            Field::Bytes(_f) => format!("(Binary)")
+            Field::Bytes(_f) => format!("(Binary)"),
+            Field::Date(f) => f.to_string(),
+            Field::Time(f) => f.to_string(),
+            Field::Timestamp(f) => f.to_string(),
+            Field::TimestampTz(f) => temporal::timestamptz_to_string(f),
+            Field::Interval(f) => f.to_string(),
+            Field::Uuid(f) => f.to_string(),
+            Field::Inet(f) => f.to_string(),
+            Field::MacAddr(f) => f.to_string(),
+            Field::Geometry(f) => f.to_string()
         }
     }
 
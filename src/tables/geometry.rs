@@ -0,0 +1,391 @@
+/*Copyright (c) 2022 Diego da Silva Lima. All rights reserved.
+
+This work is licensed under the terms of the GPL v3.0 License.  
+For a copy, see http://www.gnu.org/licenses.*/
+
+use std::fmt::{self, Display};
+use std::error::Error;
+use serde_json::{Value, json};
+use tokio_postgres::types::{FromSql, ToSql, Type, IsNull, to_sql_checked};
+use bytes::{BytesMut, BufMut};
+
+pub type Coord = (f64, f64);
+
+/// Two-dimensional geometry, decoded from PostGIS geometries or from the Postgres
+/// point, lseg, path, box and polygon types. Z and M coordinates are ignored.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Geometry {
+    Point(Coord),
+    LineString(Vec<Coord>),
+    Polygon(Vec<Vec<Coord>>),
+    MultiPoint(Vec<Coord>),
+    MultiLineString(Vec<Vec<Coord>>),
+    MultiPolygon(Vec<Vec<Vec<Coord>>>),
+    Collection(Vec<Geometry>)
+}
+
+impl Geometry {
+
+    /// All vertices of this geometry, in order.
+    pub fn coords(&self) -> Vec<Coord> {
+        match self {
+            Geometry::Point(c) => vec![*c],
+            Geometry::LineString(cs) | Geometry::MultiPoint(cs) => cs.clone(),
+            Geometry::Polygon(rings) | Geometry::MultiLineString(rings) => rings.iter().flatten().cloned().collect(),
+            Geometry::MultiPolygon(polys) => polys.iter().flatten().flatten().cloned().collect(),
+            Geometry::Collection(geoms) => geoms.iter().flat_map(|g| g.coords() ).collect()
+        }
+    }
+
+    pub fn to_geojson(&self) -> Value {
+        fn pos(c : &Coord) -> Value {
+            json!([c.0, c.1])
+        }
+        fn line(cs : &[Coord]) -> Value {
+            Value::Array(cs.iter().map(pos).collect())
+        }
+        fn poly(rings : &[Vec<Coord>]) -> Value {
+            Value::Array(rings.iter().map(|r| line(r) ).collect())
+        }
+        match self {
+            Geometry::Point(c) => json!({ "type" : "Point", "coordinates" : pos(c) }),
+            Geometry::LineString(cs) => json!({ "type" : "LineString", "coordinates" : line(cs) }),
+            Geometry::Polygon(rings) => json!({ "type" : "Polygon", "coordinates" : poly(rings) }),
+            Geometry::MultiPoint(cs) => json!({ "type" : "MultiPoint", "coordinates" : line(cs) }),
+            Geometry::MultiLineString(ls) => json!({ "type" : "MultiLineString", "coordinates" : poly(ls) }),
+            Geometry::MultiPolygon(ps) => {
+                let coords : Vec<Value> = ps.iter().map(|p| poly(p) ).collect();
+                json!({ "type" : "MultiPolygon", "coordinates" : coords })
+            },
+            Geometry::Collection(gs) => {
+                let geoms : Vec<Value> = gs.iter().map(|g| g.to_geojson() ).collect();
+                json!({ "type" : "GeometryCollection", "geometries" : geoms })
+            }
+        }
+    }
+
+}
+
+fn write_coords(f : &mut fmt::Formatter<'_>, cs : &[Coord]) -> fmt::Result {
+    write!(f, "(")?;
+    for (ix, c) in cs.iter().enumerate() {
+        if ix >= 1 {
+            write!(f, ",")?;
+        }
+        write!(f, "{} {}", c.0, c.1)?;
+    }
+    write!(f, ")")
+}
+
+fn write_rings(f : &mut fmt::Formatter<'_>, rings : &[Vec<Coord>]) -> fmt::Result {
+    write!(f, "(")?;
+    for (ix, r) in rings.iter().enumerate() {
+        if ix >= 1 {
+            write!(f, ",")?;
+        }
+        write_coords(f, r)?;
+    }
+    write!(f, ")")
+}
+
+impl Display for Geometry {
+
+    /// Writes the geometry as well-known text (WKT).
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Geometry::Point(c) => write!(f, "POINT({} {})", c.0, c.1),
+            Geometry::LineString(cs) => { write!(f, "LINESTRING")?; write_coords(f, cs) },
+            Geometry::Polygon(rings) => { write!(f, "POLYGON")?; write_rings(f, rings) },
+            Geometry::MultiPoint(cs) => {
+                let pts : Vec<Vec<Coord>> = cs.iter().map(|c| vec![*c] ).collect();
+                write!(f, "MULTIPOINT")?;
+                write_rings(f, &pts)
+            },
+            Geometry::MultiLineString(ls) => { write!(f, "MULTILINESTRING")?; write_rings(f, ls) },
+            Geometry::MultiPolygon(ps) => {
+                write!(f, "MULTIPOLYGON(")?;
+                for (ix, p) in ps.iter().enumerate() {
+                    if ix >= 1 {
+                        write!(f, ",")?;
+                    }
+                    write_rings(f, p)?;
+                }
+                write!(f, ")")
+            },
+            Geometry::Collection(gs) => {
+                write!(f, "GEOMETRYCOLLECTION(")?;
+                for (ix, g) in gs.iter().enumerate() {
+                    if ix >= 1 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", g)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+
+}
+
+// EWKB flags set at the geometry type for extra dimensions and an embedded SRID.
+const EWKB_Z : u32 = 0x80000000;
+
+const EWKB_M : u32 = 0x40000000;
+
+const EWKB_SRID : u32 = 0x20000000;
+
+struct Reader<'a> {
+    buf : &'a [u8],
+    pos : usize,
+    little_endian : bool
+}
+
+impl<'a> Reader<'a> {
+
+    fn take<const N : usize>(&mut self) -> Result<[u8; N], String> {
+        let bytes = self.buf.get(self.pos..self.pos + N).ok_or(format!("Unexpected end of geometry"))?;
+        self.pos += N;
+        let mut arr = [0u8; N];
+        arr.copy_from_slice(bytes);
+        Ok(arr)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let b = self.take::<4>()?;
+        Ok(if self.little_endian { u32::from_le_bytes(b) } else { u32::from_be_bytes(b) })
+    }
+
+    fn f64(&mut self) -> Result<f64, String> {
+        let b = self.take::<8>()?;
+        Ok(if self.little_endian { f64::from_le_bytes(b) } else { f64::from_be_bytes(b) })
+    }
+
+    fn coord(&mut self, extra_dims : usize) -> Result<Coord, String> {
+        let c = (self.f64()?, self.f64()?);
+        for _ in 0..extra_dims {
+            self.f64()?;
+        }
+        Ok(c)
+    }
+
+    fn coords(&mut self, extra_dims : usize) -> Result<Vec<Coord>, String> {
+        let n = self.u32()?;
+        (0..n).map(|_| self.coord(extra_dims) ).collect()
+    }
+
+    fn rings(&mut self, extra_dims : usize) -> Result<Vec<Vec<Coord>>, String> {
+        let n = self.u32()?;
+        (0..n).map(|_| self.coords(extra_dims) ).collect()
+    }
+
+    fn geometries(&mut self) -> Result<Vec<Geometry>, String> {
+        let n = self.u32()?;
+        (0..n).map(|_| self.geometry() ).collect()
+    }
+
+    /* Reads a geometry in either the PostGIS extended (EWKB) or the ISO WKB format, which
+    mark extra dimensions with high-order flags or with thousands added to the type code. */
+    fn geometry(&mut self) -> Result<Geometry, String> {
+        self.little_endian = self.u8()? == 1;
+        let code = self.u32()?;
+        if code & EWKB_SRID != 0 {
+            self.u32()?;
+        }
+        let iso_dims = (code & 0x0FFFFFFF) / 1000;
+        let has_z = code & EWKB_Z != 0 || iso_dims == 1 || iso_dims == 3;
+        let has_m = code & EWKB_M != 0 || iso_dims == 2 || iso_dims == 3;
+        let extra = has_z as usize + has_m as usize;
+        match (code & 0x0FFFFFFF) % 1000 {
+            1 => Ok(Geometry::Point(self.coord(extra)?)),
+            2 => Ok(Geometry::LineString(self.coords(extra)?)),
+            3 => Ok(Geometry::Polygon(self.rings(extra)?)),
+            4 => {
+                let pts = self.geometries()?.iter().flat_map(|g| g.coords() ).collect();
+                Ok(Geometry::MultiPoint(pts))
+            },
+            5 => {
+                let lines = self.geometries()?.iter().map(|g| g.coords() ).collect();
+                Ok(Geometry::MultiLineString(lines))
+            },
+            6 => {
+                let polys = self.geometries()?.into_iter().map(|g| match g {
+                    Geometry::Polygon(rings) => rings,
+                    other => vec![other.coords()]
+                }).collect();
+                Ok(Geometry::MultiPolygon(polys))
+            },
+            7 => Ok(Geometry::Collection(self.geometries()?)),
+            other => Err(format!("Unsupported geometry type: {}", other))
+        }
+    }
+
+}
+
+/// Parses a geometry in the (extended) well-known binary format.
+pub fn from_wkb(buf : &[u8]) -> Result<Geometry, String> {
+    Reader { buf, pos : 0, little_endian : true }.geometry()
+}
+
+fn put_coords(out : &mut BytesMut, cs : &[Coord]) {
+    out.put_u32_le(cs.len() as u32);
+    for c in cs {
+        out.put_f64_le(c.0);
+        out.put_f64_le(c.1);
+    }
+}
+
+fn put_rings(out : &mut BytesMut, rings : &[Vec<Coord>]) {
+    out.put_u32_le(rings.len() as u32);
+    for r in rings {
+        put_coords(out, r);
+    }
+}
+
+/// Writes a geometry in the little-endian well-known binary format.
+pub fn to_wkb(geom : &Geometry, out : &mut BytesMut) {
+    out.put_u8(1);
+    match geom {
+        Geometry::Point(c) => {
+            out.put_u32_le(1);
+            out.put_f64_le(c.0);
+            out.put_f64_le(c.1);
+        },
+        Geometry::LineString(cs) => {
+            out.put_u32_le(2);
+            put_coords(out, cs);
+        },
+        Geometry::Polygon(rings) => {
+            out.put_u32_le(3);
+            put_rings(out, rings);
+        },
+        Geometry::MultiPoint(cs) => {
+            out.put_u32_le(4);
+            out.put_u32_le(cs.len() as u32);
+            cs.iter().for_each(|c| to_wkb(&Geometry::Point(*c), out) );
+        },
+        Geometry::MultiLineString(ls) => {
+            out.put_u32_le(5);
+            out.put_u32_le(ls.len() as u32);
+            ls.iter().for_each(|l| to_wkb(&Geometry::LineString(l.clone()), out) );
+        },
+        Geometry::MultiPolygon(ps) => {
+            out.put_u32_le(6);
+            out.put_u32_le(ps.len() as u32);
+            ps.iter().for_each(|p| to_wkb(&Geometry::Polygon(p.clone()), out) );
+        },
+        Geometry::Collection(gs) => {
+            out.put_u32_le(7);
+            out.put_u32_le(gs.len() as u32);
+            gs.iter().for_each(|g| to_wkb(g, out) );
+        }
+    }
+}
+
+fn is_postgis(ty : &Type) -> bool {
+    ty.name() == "geometry"
+}
+
+// Repeats the first vertex at the end, since WKT rings are explicitly closed.
+fn closed(mut cs : Vec<Coord>) -> Vec<Coord> {
+    if let Some(fst) = cs.first().cloned() {
+        if cs.last() != Some(&fst) {
+            cs.push(fst);
+        }
+    }
+    cs
+}
+
+fn unclosed(cs : &[Coord]) -> &[Coord] {
+    if cs.len() > 1 && cs.first() == cs.last() {
+        &cs[..cs.len()-1]
+    } else {
+        cs
+    }
+}
+
+impl<'a> FromSql<'a> for Geometry {
+
+    // The built-in geometric types use big-endian coordinates without the WKB header.
+    fn from_sql(ty : &Type, raw : &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        if is_postgis(ty) {
+            return Ok(from_wkb(raw)?);
+        }
+        let mut r = Reader { buf : raw, pos : 0, little_endian : false };
+        match *ty {
+            Type::POINT => Ok(Geometry::Point(r.coord(0)?)),
+            Type::LSEG => Ok(Geometry::LineString(vec![r.coord(0)?, r.coord(0)?])),
+            Type::PATH => {
+                let is_closed = r.u8()? != 0;
+                let cs = r.coords(0)?;
+                if is_closed {
+                    Ok(Geometry::Polygon(vec![closed(cs)]))
+                } else {
+                    Ok(Geometry::LineString(cs))
+                }
+            },
+            Type::BOX => {
+                let (high, low) = (r.coord(0)?, r.coord(0)?);
+                Ok(Geometry::Polygon(vec![vec![low, (high.0, low.1), high, (low.0, high.1), low]]))
+            },
+            Type::POLYGON => Ok(Geometry::Polygon(vec![closed(r.coords(0)?)])),
+            _ => Err(format!("Unsupported geometry type: {}", ty).into())
+        }
+    }
+
+    fn accepts(ty : &Type) -> bool {
+        is_postgis(ty) || [Type::POINT, Type::LSEG, Type::PATH, Type::BOX, Type::POLYGON].contains(ty)
+    }
+
+}
+
+impl ToSql for Geometry {
+
+    fn to_sql(&self, ty : &Type, out : &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        if is_postgis(ty) {
+            to_wkb(self, out);
+            return Ok(IsNull::No);
+        }
+        let put = |out : &mut BytesMut, cs : &[Coord]| cs.iter().for_each(|c| { out.put_f64(c.0); out.put_f64(c.1); });
+        match (ty, self) {
+            (&Type::POINT, Geometry::Point(c)) => put(out, &[*c]),
+            (&Type::LSEG, Geometry::LineString(cs)) if cs.len() == 2 => put(out, &cs[..]),
+            (&Type::PATH, Geometry::LineString(cs)) => {
+                out.put_u8(0);
+                out.put_i32(cs.len() as i32);
+                put(out, &cs[..]);
+            },
+            (&Type::PATH, Geometry::Polygon(rings)) if rings.len() == 1 => {
+                let cs = unclosed(&rings[0]);
+                out.put_u8(1);
+                out.put_i32(cs.len() as i32);
+                put(out, cs);
+            },
+            (&Type::POLYGON, Geometry::Polygon(rings)) if rings.len() == 1 => {
+                let cs = unclosed(&rings[0]);
+                out.put_i32(cs.len() as i32);
+                put(out, cs);
+            },
+            (&Type::BOX, Geometry::Polygon(rings)) if rings.len() == 1 => {
+                let cs = &rings[0];
+                let xs = cs.iter().map(|c| c.0 );
+                let ys = cs.iter().map(|c| c.1 );
+                let high = (xs.clone().fold(f64::MIN, f64::max), ys.clone().fold(f64::MIN, f64::max));
+                let low = (xs.fold(f64::MAX, f64::min), ys.fold(f64::MAX, f64::min));
+                put(out, &[high, low]);
+            },
+            _ => return Err(format!("Geometry {} cannot be written as {}", self, ty).into())
+        }
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty : &Type) -> bool {
+        <Geometry as FromSql<'_>>::accepts(ty)
+    }
+
+    to_sql_checked!();
+
+}
@@ -15,3 +15,18 @@ pub mod nullable;
 
 pub mod report;
 
+pub mod arrow;
+
+pub mod dialect;
+
+pub mod temporal;
+
+pub mod network;
+
+pub mod geometry;
+
+pub mod pgtype;
+
+pub mod view;
+
+pub mod aggregate;
@@ -0,0 +1,215 @@
+/*Copyright (c) 2022 Diego da Silva Lima. All rights reserved.
+
+This work is licensed under the terms of the GPL v3.0 License.  
+For a copy, see http://www.gnu.org/licenses.*/
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use std::error::Error;
+use tokio_postgres::types::{FromSql, ToSql, Type, IsNull, to_sql_checked};
+use bytes::{BytesMut, BufMut};
+
+// Address families as encoded by Postgres (AF_INET and AF_INET + 1).
+const PGSQL_AF_INET : u8 = 2;
+
+const PGSQL_AF_INET6 : u8 = 3;
+
+/// Host or network address (Postgres inet and cidr types).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Inet {
+    pub addr : IpAddr,
+    pub netmask : u8,
+
+    // Whether the value came from a cidr column, in which case the netmask is always shown.
+    pub cidr : bool
+}
+
+fn max_bits(addr : &IpAddr) -> u8 {
+    match addr {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128
+    }
+}
+
+fn addr_bits(addr : &IpAddr) -> u128 {
+    match addr {
+        IpAddr::V4(a) => u32::from(*a) as u128,
+        IpAddr::V6(a) => u128::from(*a)
+    }
+}
+
+impl Inet {
+
+    pub fn new(addr : IpAddr, netmask : u8) -> Self {
+        Inet { addr, netmask, cidr : false }
+    }
+
+    /// Whether the address (or network) is within this network. Addresses of
+    /// distinct families are never contained.
+    pub fn contains(&self, other : &Inet) -> bool {
+        let bits = max_bits(&self.addr);
+        if bits != max_bits(&other.addr) || other.netmask < self.netmask {
+            return false;
+        }
+        let shift = (bits - self.netmask) as u32;
+        addr_bits(&self.addr).checked_shr(shift).unwrap_or(0) == addr_bits(&other.addr).checked_shr(shift).unwrap_or(0)
+    }
+
+}
+
+impl Display for Inet {
+
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.cidr || self.netmask != max_bits(&self.addr) {
+            write!(f, "{}/{}", self.addr, self.netmask)
+        } else {
+            write!(f, "{}", self.addr)
+        }
+    }
+
+}
+
+impl FromStr for Inet {
+
+    type Err = String;
+
+    fn from_str(s : &str) -> Result<Self, String> {
+        let err = || format!("Invalid network address: {}", s);
+        let mut parts = s.trim().splitn(2, '/');
+        let addr = IpAddr::from_str(parts.next().ok_or_else(err)?).map_err(|_| err() )?;
+        let netmask = match parts.next() {
+            Some(n) => n.parse::<u8>().map_err(|_| err() )?,
+            None => max_bits(&addr)
+        };
+        if netmask > max_bits(&addr) {
+            return Err(err());
+        }
+        Ok(Inet::new(addr, netmask))
+    }
+
+}
+
+impl<'a> FromSql<'a> for Inet {
+
+    // Binary layout: family, netmask bits, cidr flag, address length and address bytes.
+    fn from_sql(ty : &Type, raw : &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        if raw.len() < 4 || raw.len() != 4 + raw[3] as usize {
+            return Err(format!("Invalid {} value", ty).into());
+        }
+        let bytes = &raw[4..];
+        let addr = match (raw[0], bytes.len()) {
+            (PGSQL_AF_INET, 4) => IpAddr::V4(Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3])),
+            (PGSQL_AF_INET6, 16) => {
+                let mut octets = [0u8; 16];
+                octets.copy_from_slice(bytes);
+                IpAddr::V6(Ipv6Addr::from(octets))
+            },
+            _ => return Err(format!("Invalid {} address family", ty).into())
+        };
+        Ok(Inet { addr, netmask : raw[1], cidr : *ty == Type::CIDR })
+    }
+
+    fn accepts(ty : &Type) -> bool {
+        *ty == Type::INET || *ty == Type::CIDR
+    }
+
+}
+
+impl ToSql for Inet {
+
+    fn to_sql(&self, ty : &Type, out : &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        let is_cidr = *ty == Type::CIDR;
+        match self.addr {
+            IpAddr::V4(a) => {
+                out.put_slice(&[PGSQL_AF_INET, self.netmask, is_cidr as u8, 4]);
+                out.put_slice(&a.octets());
+            },
+            IpAddr::V6(a) => {
+                out.put_slice(&[PGSQL_AF_INET6, self.netmask, is_cidr as u8, 16]);
+                out.put_slice(&a.octets());
+            }
+        }
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty : &Type) -> bool {
+        *ty == Type::INET || *ty == Type::CIDR
+    }
+
+    to_sql_checked!();
+
+}
+
+/// MAC address (Postgres macaddr with 6 bytes or macaddr8 with 8 bytes).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MacAddr(pub Vec<u8>);
+
+impl Display for MacAddr {
+
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        let octets : Vec<String> = self.0.iter().map(|b| format!("{:02x}", b) ).collect();
+        write!(f, "{}", octets.join(":"))
+    }
+
+}
+
+impl FromStr for MacAddr {
+
+    type Err = String;
+
+    fn from_str(s : &str) -> Result<Self, String> {
+        let err = || format!("Invalid MAC address: {}", s);
+        let octets : Result<Vec<u8>, _> = s.trim()
+            .split(|c| c == ':' || c == '-' )
+            .map(|o| u8::from_str_radix(o, 16) )
+            .collect();
+        let octets = octets.map_err(|_| err() )?;
+        if octets.len() == 6 || octets.len() == 8 {
+            Ok(MacAddr(octets))
+        } else {
+            Err(err())
+        }
+    }
+
+}
+
+impl<'a> FromSql<'a> for MacAddr {
+
+    fn from_sql(ty : &Type, raw : &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        match (ty, raw.len()) {
+            (&Type::MACADDR, 6) | (&Type::MACADDR8, 8) => Ok(MacAddr(raw.to_vec())),
+            _ => Err(format!("Invalid {} value", ty).into())
+        }
+    }
+
+    fn accepts(ty : &Type) -> bool {
+        *ty == Type::MACADDR || *ty == Type::MACADDR8
+    }
+
+}
+
+impl ToSql for MacAddr {
+
+    fn to_sql(&self, ty : &Type, out : &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        match (ty, self.0.len()) {
+            (&Type::MACADDR, 6) | (&Type::MACADDR8, 8) => out.put_slice(&self.0[..]),
+
+            // 6-byte addresses are stored as macaddr8 by inserting FF:FE at the middle.
+            (&Type::MACADDR8, 6) => {
+                out.put_slice(&self.0[..3]);
+                out.put_slice(&[0xff, 0xfe]);
+                out.put_slice(&self.0[3..]);
+            },
+            _ => return Err(format!("Invalid {} value: {}", ty, self).into())
+        }
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty : &Type) -> bool {
+        *ty == Type::MACADDR || *ty == Type::MACADDR8
+    }
+
+    to_sql_checked!();
+
+}
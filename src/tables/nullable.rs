@@ -9,6 +9,11 @@ use crate::tables::field::Field;
 use rust_decimal::Decimal;
 use serde_json::Value;
 use std::fmt::Write;
+use chrono::{NaiveDate, NaiveTime, NaiveDateTime, DateTime, Utc};
+use super::temporal::{self, Interval};
+use super::network::{Inet, MacAddr};
+use super::geometry::Geometry;
+use uuid::Uuid;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum NullableColumn {
@@ -23,12 +28,21 @@ pub enum NullableColumn {
     Numeric(Vec<Option<Decimal>>),
     Str(Vec<Option<String>>),
     Bytes(Vec<Option<Vec<u8>>>),
-    Json(Vec<Option<Value>>)
+    Json(Vec<Option<Value>>),
+    Date(Vec<Option<NaiveDate>>),
+    Time(Vec<Option<NaiveTime>>),
+    Timestamp(Vec<Option<NaiveDateTime>>),
+    TimestampTz(Vec<Option<DateTime<Utc>>>),
+    Interval(Vec<Option<Interval>>),
+    Uuid(Vec<Option<Uuid>>),
+    Inet(Vec<Option<Inet>>),
+    MacAddr(Vec<Option<MacAddr>>),
+    Geometry(Vec<Option<Geometry>>)
 }
 
 impl<'a> NullableColumn {
 
-    const NULL : &'a str = "NULL";
+    pub const NULL : &'a str = "NULL";
 
     fn write_datum_or_null<T>(buffer : &mut String, e : &Option<T>)
     where
@@ -82,7 +96,18 @@ impl<'a> NullableColumn {
                     Some(e) => write_binary(&mut buffer, &e),
                     None => write!(&mut buffer, "{}\n", Self::NULL ).unwrap()
                 }
-            })
+            }),
+            NullableColumn::Date(v) => v.iter().skip(fst_row_ix).take(max_rows).for_each(|e| Self::write_datum_or_null(&mut buffer,e) ),
+            NullableColumn::Time(v) => v.iter().skip(fst_row_ix).take(max_rows).for_each(|e| Self::write_datum_or_null(&mut buffer,e) ),
+            NullableColumn::Timestamp(v) => v.iter().skip(fst_row_ix).take(max_rows).for_each(|e| Self::write_datum_or_null(&mut buffer,e) ),
+            NullableColumn::TimestampTz(v) => v.iter().skip(fst_row_ix).take(max_rows).for_each(|e| {
+                Self::write_datum_or_null(&mut buffer, &e.as_ref().map(|e| temporal::timestamptz_to_string(e) ))
+            }),
+            NullableColumn::Interval(v) => v.iter().skip(fst_row_ix).take(max_rows).for_each(|e| Self::write_datum_or_null(&mut buffer,e) ),
+            NullableColumn::Uuid(v) => v.iter().skip(fst_row_ix).take(max_rows).for_each(|e| Self::write_datum_or_null(&mut buffer,e) ),
+            NullableColumn::Inet(v) => v.iter().skip(fst_row_ix).take(max_rows).for_each(|e| Self::write_datum_or_null(&mut buffer,e) ),
+            NullableColumn::MacAddr(v) => v.iter().skip(fst_row_ix).take(max_rows).for_each(|e| Self::write_datum_or_null(&mut buffer,e) ),
+            NullableColumn::Geometry(v) => v.iter().skip(fst_row_ix).take(max_rows).for_each(|e| Self::write_datum_or_null(&mut buffer,e) )
         }
         buffer
     }
@@ -124,6 +149,33 @@ impl<'a> NullableColumn {
             },
             NullableColumn::Json(_vs) => {
                 NullableColumn::from(self.display_opt_content(None)).rearranged(ixs)
+            },
+            NullableColumn::Date(vs) => {
+                rearrange(&vs[..], ixs)
+            },
+            NullableColumn::Time(vs) => {
+                rearrange(&vs[..], ixs)
+            },
+            NullableColumn::Timestamp(vs) => {
+                rearrange(&vs[..], ixs)
+            },
+            NullableColumn::TimestampTz(vs) => {
+                rearrange(&vs[..], ixs)
+            },
+            NullableColumn::Interval(vs) => {
+                rearrange(&vs[..], ixs)
+            },
+            NullableColumn::Uuid(vs) => {
+                rearrange(&vs[..], ixs)
+            },
+            NullableColumn::Inet(vs) => {
+                rearrange(&vs[..], ixs)
+            },
+            NullableColumn::MacAddr(vs) => {
+                rearrange(&vs[..], ixs)
+            },
+            NullableColumn::Geometry(vs) => {
+                rearrange(&vs[..], ixs)
             }
         }
     }
@@ -165,6 +217,35 @@ impl<'a> NullableColumn {
             },
             NullableColumn::Json(_vs) => {
                 NullableColumn::from(self.display_opt_content(None)).sorted(ascending)
+            },
+            NullableColumn::Date(vs) => {
+                sorted(&vs[..], ascending)
+            },
+            NullableColumn::Time(vs) => {
+                sorted(&vs[..], ascending)
+            },
+            NullableColumn::Timestamp(vs) => {
+                sorted(&vs[..], ascending)
+            },
+            NullableColumn::TimestampTz(vs) => {
+                sorted(&vs[..], ascending)
+            },
+            NullableColumn::Interval(vs) => {
+                sorted(&vs[..], ascending)
+            },
+            NullableColumn::Uuid(vs) => {
+                sorted(&vs[..], ascending)
+            },
+            NullableColumn::Inet(vs) => {
+                sorted(&vs[..], ascending)
+            },
+            NullableColumn::MacAddr(vs) => {
+                sorted(&vs[..], ascending)
+            },
+            NullableColumn::Geometry(_vs) => {
+                let (ixs, _) = NullableColumn::from(self.display_opt_content(None)).sorted(ascending);
+                let col = self.rearranged(&ixs);
+                (ixs, col)
             }
         }
     }
@@ -182,7 +263,16 @@ impl<'a> NullableColumn {
             NullableColumn::Numeric(v) => Column::from(v.iter().filter_map(|v| v.clone() ).collect::<Vec<_>>()),
             NullableColumn::Str(v) => Column::from(v.iter().filter_map(|v| v.clone() ).collect::<Vec<_>>()),
             NullableColumn::Bytes(v) => Column::from(v.iter().filter_map(|v| v.clone() ).collect::<Vec<_>>()),
-            NullableColumn::Json(v) => Column::from(v.iter().filter_map(|v| v.clone() ).collect::<Vec<_>>())
+            NullableColumn::Json(v) => Column::from(v.iter().filter_map(|v| v.clone() ).collect::<Vec<_>>()),
+            NullableColumn::Date(v) => Column::from(v.iter().filter_map(|v| v.clone() ).collect::<Vec<_>>()),
+            NullableColumn::Time(v) => Column::from(v.iter().filter_map(|v| v.clone() ).collect::<Vec<_>>()),
+            NullableColumn::Timestamp(v) => Column::from(v.iter().filter_map(|v| v.clone() ).collect::<Vec<_>>()),
+            NullableColumn::TimestampTz(v) => Column::from(v.iter().filter_map(|v| v.clone() ).collect::<Vec<_>>()),
+            NullableColumn::Interval(v) => Column::from(v.iter().filter_map(|v| v.clone() ).collect::<Vec<_>>()),
+            NullableColumn::Uuid(v) => Column::from(v.iter().filter_map(|v| v.clone() ).collect::<Vec<_>>()),
+            NullableColumn::Inet(v) => Column::from(v.iter().filter_map(|v| v.clone() ).collect::<Vec<_>>()),
+            NullableColumn::MacAddr(v) => Column::from(v.iter().filter_map(|v| v.clone() ).collect::<Vec<_>>()),
+            NullableColumn::Geometry(v) => Column::from(v.iter().filter_map(|v| v.clone() ).collect::<Vec<_>>())
         }
     }
 
@@ -199,7 +289,16 @@ impl<'a> NullableColumn {
             NullableColumn::Numeric(v) => v.iter().filter(|v| v.is_some()).count(),
             NullableColumn::Str(v) => v.iter().filter(|v| v.is_some()).count(),
             NullableColumn::Bytes(v) => v.iter().filter(|v| v.is_some()).count(),
-            NullableColumn::Json(v) => v.iter().filter(|v| v.is_some()).count()
+            NullableColumn::Json(v) => v.iter().filter(|v| v.is_some()).count(),
+            NullableColumn::Date(v) => v.iter().filter(|v| v.is_some()).count(),
+            NullableColumn::Time(v) => v.iter().filter(|v| v.is_some()).count(),
+            NullableColumn::Timestamp(v) => v.iter().filter(|v| v.is_some()).count(),
+            NullableColumn::TimestampTz(v) => v.iter().filter(|v| v.is_some()).count(),
+            NullableColumn::Interval(v) => v.iter().filter(|v| v.is_some()).count(),
+            NullableColumn::Uuid(v) => v.iter().filter(|v| v.is_some()).count(),
+            NullableColumn::Inet(v) => v.iter().filter(|v| v.is_some()).count(),
+            NullableColumn::MacAddr(v) => v.iter().filter(|v| v.is_some()).count(),
+            NullableColumn::Geometry(v) => v.iter().filter(|v| v.is_some()).count()
         }
     }
 
@@ -217,7 +316,16 @@ impl<'a> NullableColumn {
             NullableColumn::Numeric(v) => v.get(ix).cloned().map(|f| f.map(|f| Field::Numeric(f.clone()) ).unwrap_or(Field::Str(String::from(Self::NULL))) ),
             NullableColumn::Str(v) => v.get(ix).cloned().map(|f| f.map(|f| Field::Str(f.clone()) ).unwrap_or(Field::Str(String::from(Self::NULL))) ),
             NullableColumn::Json(v) => v.get(ix).cloned().map(|f| f.map(|f| Field::Json(f.clone()) ).unwrap_or(Field::Str(String::from(Self::NULL))) ),
-            NullableColumn::Bytes(v) => v.get(ix).cloned().map(|f| f.map(|f| Field::Bytes(f.clone()) ).unwrap_or(Field::Str(String::from(Self::NULL))) )
+            NullableColumn::Bytes(v) => v.get(ix).cloned().map(|f| f.map(|f| Field::Bytes(f.clone()) ).unwrap_or(Field::Str(String::from(Self::NULL))) ),
+            NullableColumn::Date(v) => v.get(ix).cloned().map(|f| f.map(|f| Field::Date(f) ).unwrap_or(Field::Str(String::from(Self::NULL))) ),
+            NullableColumn::Time(v) => v.get(ix).cloned().map(|f| f.map(|f| Field::Time(f) ).unwrap_or(Field::Str(String::from(Self::NULL))) ),
+            NullableColumn::Timestamp(v) => v.get(ix).cloned().map(|f| f.map(|f| Field::Timestamp(f) ).unwrap_or(Field::Str(String::from(Self::NULL))) ),
+            NullableColumn::TimestampTz(v) => v.get(ix).cloned().map(|f| f.map(|f| Field::TimestampTz(f) ).unwrap_or(Field::Str(String::from(Self::NULL))) ),
+            NullableColumn::Interval(v) => v.get(ix).cloned().map(|f| f.map(|f| Field::Interval(f) ).unwrap_or(Field::Str(String::from(Self::NULL))) ),
+            NullableColumn::Uuid(v) => v.get(ix).cloned().map(|f| f.map(|f| Field::Uuid(f) ).unwrap_or(Field::Str(String::from(Self::NULL))) ),
+            NullableColumn::Inet(v) => v.get(ix).cloned().map(|f| f.map(|f| Field::Inet(f) ).unwrap_or(Field::Str(String::from(Self::NULL))) ),
+            NullableColumn::MacAddr(v) => v.get(ix).cloned().map(|f| f.map(|f| Field::MacAddr(f) ).unwrap_or(Field::Str(String::from(Self::NULL))) ),
+            NullableColumn::Geometry(v) => v.get(ix).cloned().map(|f| f.map(|f| Field::Geometry(f) ).unwrap_or(Field::Str(String::from(Self::NULL))) )
         }
     }
 
@@ -234,7 +342,98 @@ impl<'a> NullableColumn {
             NullableColumn::Numeric(v) => v.len(),
             NullableColumn::Str(v) => v.len(),
             NullableColumn::Bytes(v) => v.len(),
-            NullableColumn::Json(v) => v.len()
+            NullableColumn::Json(v) => v.len(),
+            NullableColumn::Date(v) => v.len(),
+            NullableColumn::Time(v) => v.len(),
+            NullableColumn::Timestamp(v) => v.len(),
+            NullableColumn::TimestampTz(v) => v.len(),
+            NullableColumn::Interval(v) => v.len(),
+            NullableColumn::Uuid(v) => v.len(),
+            NullableColumn::Inet(v) => v.len(),
+            NullableColumn::MacAddr(v) => v.len(),
+            NullableColumn::Geometry(v) => v.len()
+        }
+    }
+
+    /// Appends a null value at the end of the column.
+    pub fn push_null(&mut self) {
+        match self {
+            NullableColumn::Bool(v) => v.push(None),
+            NullableColumn::I8(v) => v.push(None),
+            NullableColumn::I16(v) => v.push(None),
+            NullableColumn::I32(v) => v.push(None),
+            NullableColumn::U32(v) => v.push(None),
+            NullableColumn::I64(v) => v.push(None),
+            NullableColumn::F32(v) => v.push(None),
+            NullableColumn::F64(v) => v.push(None),
+            NullableColumn::Numeric(v) => v.push(None),
+            NullableColumn::Str(v) => v.push(None),
+            NullableColumn::Bytes(v) => v.push(None),
+            NullableColumn::Json(v) => v.push(None),
+            NullableColumn::Date(v) => v.push(None),
+            NullableColumn::Time(v) => v.push(None),
+            NullableColumn::Timestamp(v) => v.push(None),
+            NullableColumn::TimestampTz(v) => v.push(None),
+            NullableColumn::Interval(v) => v.push(None),
+            NullableColumn::Uuid(v) => v.push(None),
+            NullableColumn::Inet(v) => v.push(None),
+            NullableColumn::MacAddr(v) => v.push(None),
+            NullableColumn::Geometry(v) => v.push(None)
+        }
+    }
+
+    pub fn is_null(&self, ix : usize) -> bool {
+        match self {
+            NullableColumn::Bool(v) => v.get(ix).map(|v| v.is_none() ).unwrap_or(false),
+            NullableColumn::I8(v) => v.get(ix).map(|v| v.is_none() ).unwrap_or(false),
+            NullableColumn::I16(v) => v.get(ix).map(|v| v.is_none() ).unwrap_or(false),
+            NullableColumn::I32(v) => v.get(ix).map(|v| v.is_none() ).unwrap_or(false),
+            NullableColumn::U32(v) => v.get(ix).map(|v| v.is_none() ).unwrap_or(false),
+            NullableColumn::I64(v) => v.get(ix).map(|v| v.is_none() ).unwrap_or(false),
+            NullableColumn::F32(v) => v.get(ix).map(|v| v.is_none() ).unwrap_or(false),
+            NullableColumn::F64(v) => v.get(ix).map(|v| v.is_none() ).unwrap_or(false),
+            NullableColumn::Numeric(v) => v.get(ix).map(|v| v.is_none() ).unwrap_or(false),
+            NullableColumn::Str(v) => v.get(ix).map(|v| v.is_none() ).unwrap_or(false),
+            NullableColumn::Bytes(v) => v.get(ix).map(|v| v.is_none() ).unwrap_or(false),
+            NullableColumn::Json(v) => v.get(ix).map(|v| v.is_none() ).unwrap_or(false),
+            NullableColumn::Date(v) => v.get(ix).map(|v| v.is_none() ).unwrap_or(false),
+            NullableColumn::Time(v) => v.get(ix).map(|v| v.is_none() ).unwrap_or(false),
+            NullableColumn::Timestamp(v) => v.get(ix).map(|v| v.is_none() ).unwrap_or(false),
+            NullableColumn::TimestampTz(v) => v.get(ix).map(|v| v.is_none() ).unwrap_or(false),
+            NullableColumn::Interval(v) => v.get(ix).map(|v| v.is_none() ).unwrap_or(false),
+            NullableColumn::Uuid(v) => v.get(ix).map(|v| v.is_none() ).unwrap_or(false),
+            NullableColumn::Inet(v) => v.get(ix).map(|v| v.is_none() ).unwrap_or(false),
+            NullableColumn::MacAddr(v) => v.get(ix).map(|v| v.is_none() ).unwrap_or(false),
+            NullableColumn::Geometry(v) => v.get(ix).map(|v| v.is_none() ).unwrap_or(false)
+        }
+    }
+
+    pub fn json_values(&self) -> Vec<Value> {
+        fn or_null<T>(e : &Option<T>, f : impl Fn(&T)->Value) -> Value {
+            e.as_ref().map(f).unwrap_or(Value::Null)
+        }
+        match self {
+            NullableColumn::Bool(v) => v.iter().map(|e| or_null(e, |e| Value::from(*e) ) ).collect(),
+            NullableColumn::I8(v) => v.iter().map(|e| or_null(e, |e| Value::from(*e) ) ).collect(),
+            NullableColumn::I16(v) => v.iter().map(|e| or_null(e, |e| Value::from(*e) ) ).collect(),
+            NullableColumn::I32(v) => v.iter().map(|e| or_null(e, |e| Value::from(*e) ) ).collect(),
+            NullableColumn::U32(v) => v.iter().map(|e| or_null(e, |e| Value::from(*e) ) ).collect(),
+            NullableColumn::I64(v) => v.iter().map(|e| or_null(e, |e| Value::from(*e) ) ).collect(),
+            NullableColumn::F32(v) => v.iter().map(|e| or_null(e, |e| Value::from(*e as f64) ) ).collect(),
+            NullableColumn::F64(v) => v.iter().map(|e| or_null(e, |e| Value::from(*e) ) ).collect(),
+            NullableColumn::Numeric(v) => v.iter().map(|e| or_null(e, |d| decimal_to_json(d) ) ).collect(),
+            NullableColumn::Str(v) => v.iter().map(|e| or_null(e, |e| Value::from(e.clone()) ) ).collect(),
+            NullableColumn::Bytes(v) => v.iter().map(|e| or_null(e, |e| Value::from(base64::encode(e)) ) ).collect(),
+            NullableColumn::Json(v) => v.iter().map(|e| e.clone().unwrap_or(Value::Null) ).collect(),
+            NullableColumn::Date(v) => v.iter().map(|e| or_null(e, |e| Value::from(e.to_string()) ) ).collect(),
+            NullableColumn::Time(v) => v.iter().map(|e| or_null(e, |e| Value::from(e.to_string()) ) ).collect(),
+            NullableColumn::Timestamp(v) => v.iter().map(|e| or_null(e, |e| Value::from(e.format("%Y-%m-%dT%H:%M:%S%.f").to_string()) ) ).collect(),
+            NullableColumn::TimestampTz(v) => v.iter().map(|e| or_null(e, |e| Value::from(e.format("%Y-%m-%dT%H:%M:%S%.fZ").to_string()) ) ).collect(),
+            NullableColumn::Interval(v) => v.iter().map(|e| or_null(e, |e| Value::from(e.to_string()) ) ).collect(),
+            NullableColumn::Uuid(v) => v.iter().map(|e| or_null(e, |e| Value::from(e.to_string()) ) ).collect(),
+            NullableColumn::Inet(v) => v.iter().map(|e| or_null(e, |e| Value::from(e.to_string()) ) ).collect(),
+            NullableColumn::MacAddr(v) => v.iter().map(|e| or_null(e, |e| Value::from(e.to_string()) ) ).collect(),
+            NullableColumn::Geometry(v) => v.iter().map(|e| or_null(e, |e| e.to_geojson() ) ).collect()
         }
     }
 
@@ -252,6 +451,15 @@ impl<'a> NullableColumn {
             NullableColumn::Str(v) => v.iter().map(|e| e.as_ref().map(|e| e.to_string() )).collect(),
             NullableColumn::Json(v) => v.iter().map(|e| e.as_ref().map(|e| json_to_string(&e) )).collect(),
             NullableColumn::Bytes(v) => v.iter().map(|e| e.as_ref().map(|e| display_binary(&e) )).collect(),
+            NullableColumn::Date(v) => v.iter().map(|e| e.as_ref().map(|e| e.to_string() )).collect(),
+            NullableColumn::Time(v) => v.iter().map(|e| e.as_ref().map(|e| e.to_string() )).collect(),
+            NullableColumn::Timestamp(v) => v.iter().map(|e| e.as_ref().map(|e| e.to_string() )).collect(),
+            NullableColumn::TimestampTz(v) => v.iter().map(|e| e.as_ref().map(|e| temporal::timestamptz_to_string(e) )).collect(),
+            NullableColumn::Interval(v) => v.iter().map(|e| e.as_ref().map(|e| e.to_string() )).collect(),
+            NullableColumn::Uuid(v) => v.iter().map(|e| e.as_ref().map(|e| e.to_string() )).collect(),
+            NullableColumn::Inet(v) => v.iter().map(|e| e.as_ref().map(|e| e.to_string() )).collect(),
+            NullableColumn::MacAddr(v) => v.iter().map(|e| e.as_ref().map(|e| e.to_string() )).collect(),
+            NullableColumn::Geometry(v) => v.iter().map(|e| e.as_ref().map(|e| e.to_string() )).collect(),
         }
     }
 
@@ -269,6 +477,15 @@ impl<'a> NullableColumn {
             NullableColumn::Str(v) => v.iter().map(|e| e.as_ref().map(|e| e.to_string() ).unwrap_or(String::from(Self::NULL) )).collect(),
             NullableColumn::Json(v) => v.iter().map(|e| e.as_ref().map(|e| json_to_string(&e) ).unwrap_or(String::from(Self::NULL) )).collect(),
             NullableColumn::Bytes(v) => v.iter().map(|e| e.as_ref().map(|e| display_binary(&e) ).unwrap_or(String::from(Self::NULL) )).collect(),
+            NullableColumn::Date(v) => v.iter().map(|e| e.as_ref().map(|e| e.to_string() ).unwrap_or(String::from(Self::NULL) )).collect(),
+            NullableColumn::Time(v) => v.iter().map(|e| e.as_ref().map(|e| e.to_string() ).unwrap_or(String::from(Self::NULL) )).collect(),
+            NullableColumn::Timestamp(v) => v.iter().map(|e| e.as_ref().map(|e| e.to_string() ).unwrap_or(String::from(Self::NULL) )).collect(),
+            NullableColumn::TimestampTz(v) => v.iter().map(|e| e.as_ref().map(|e| temporal::timestamptz_to_string(e) ).unwrap_or(String::from(Self::NULL) )).collect(),
+            NullableColumn::Interval(v) => v.iter().map(|e| e.as_ref().map(|e| e.to_string() ).unwrap_or(String::from(Self::NULL) )).collect(),
+            NullableColumn::Uuid(v) => v.iter().map(|e| e.as_ref().map(|e| e.to_string() ).unwrap_or(String::from(Self::NULL) )).collect(),
+            NullableColumn::Inet(v) => v.iter().map(|e| e.as_ref().map(|e| e.to_string() ).unwrap_or(String::from(Self::NULL) )).collect(),
+            NullableColumn::MacAddr(v) => v.iter().map(|e| e.as_ref().map(|e| e.to_string() ).unwrap_or(String::from(Self::NULL) )).collect(),
+            NullableColumn::Geometry(v) => v.iter().map(|e| e.as_ref().map(|e| e.to_string() ).unwrap_or(String::from(Self::NULL) )).collect(),
         }
     }
 
@@ -295,6 +512,15 @@ impl<'a> NullableColumn {
                     .map(|v| Cow::Owned(display_binary(&v)) )
                     .unwrap_or(Cow::Borrowed(Self::NULL))
             },
+            NullableColumn::Date(ref v) => v[row_ix].as_ref().map(|v| Cow::Owned(v.to_string()) ).unwrap_or(Cow::Borrowed(Self::NULL)),
+            NullableColumn::Time(ref v) => v[row_ix].as_ref().map(|v| Cow::Owned(v.to_string()) ).unwrap_or(Cow::Borrowed(Self::NULL)),
+            NullableColumn::Timestamp(ref v) => v[row_ix].as_ref().map(|v| Cow::Owned(v.to_string()) ).unwrap_or(Cow::Borrowed(Self::NULL)),
+            NullableColumn::TimestampTz(ref v) => v[row_ix].as_ref().map(|v| Cow::Owned(temporal::timestamptz_to_string(v)) ).unwrap_or(Cow::Borrowed(Self::NULL)),
+            NullableColumn::Interval(ref v) => v[row_ix].as_ref().map(|v| Cow::Owned(v.to_string()) ).unwrap_or(Cow::Borrowed(Self::NULL)),
+            NullableColumn::Uuid(ref v) => v[row_ix].as_ref().map(|v| Cow::Owned(v.to_string()) ).unwrap_or(Cow::Borrowed(Self::NULL)),
+            NullableColumn::Inet(ref v) => v[row_ix].as_ref().map(|v| Cow::Owned(v.to_string()) ).unwrap_or(Cow::Borrowed(Self::NULL)),
+            NullableColumn::MacAddr(ref v) => v[row_ix].as_ref().map(|v| Cow::Owned(v.to_string()) ).unwrap_or(Cow::Borrowed(Self::NULL)),
+            NullableColumn::Geometry(ref v) => v[row_ix].as_ref().map(|v| Cow::Owned(v.to_string()) ).unwrap_or(Cow::Borrowed(Self::NULL)),
         }
     }
 
@@ -378,6 +604,60 @@ pub mod from {
         }
     }
 
+    impl From<Vec<Option<NaiveDate>>> for NullableColumn {
+        fn from(value: Vec<Option<NaiveDate>>) -> Self {
+            Self::Date(value)
+        }
+    }
+
+    impl From<Vec<Option<NaiveTime>>> for NullableColumn {
+        fn from(value: Vec<Option<NaiveTime>>) -> Self {
+            Self::Time(value)
+        }
+    }
+
+    impl From<Vec<Option<NaiveDateTime>>> for NullableColumn {
+        fn from(value: Vec<Option<NaiveDateTime>>) -> Self {
+            Self::Timestamp(value)
+        }
+    }
+
+    impl From<Vec<Option<DateTime<Utc>>>> for NullableColumn {
+        fn from(value: Vec<Option<DateTime<Utc>>>) -> Self {
+            Self::TimestampTz(value)
+        }
+    }
+
+    impl From<Vec<Option<Interval>>> for NullableColumn {
+        fn from(value: Vec<Option<Interval>>) -> Self {
+            Self::Interval(value)
+        }
+    }
+
+    impl From<Vec<Option<Uuid>>> for NullableColumn {
+        fn from(value: Vec<Option<Uuid>>) -> Self {
+            Self::Uuid(value)
+        }
+    }
+
+    impl From<Vec<Option<Inet>>> for NullableColumn {
+        fn from(value: Vec<Option<Inet>>) -> Self {
+            Self::Inet(value)
+        }
+    }
+
+    impl From<Vec<Option<MacAddr>>> for NullableColumn {
+        fn from(value: Vec<Option<MacAddr>>) -> Self {
+            Self::MacAddr(value)
+        }
+    }
+
+    impl From<Vec<Option<Geometry>>> for NullableColumn {
+        fn from(value: Vec<Option<Geometry>>) -> Self {
+            Self::Geometry(value)
+        }
+    }
+
 }
 
 pub mod try_into {
@@ -540,4 +820,121 @@ pub mod try_into {
         }
 
     }
+
+    impl TryFrom<NullableColumn> for Vec<Option<NaiveDate>> {
+
+        type Error = &'static str;
+
+        fn try_from(col : NullableColumn) -> Result<Self, Self::Error> {
+            match col {
+                NullableColumn::Date(v) => Ok(v),
+                _ => Err("Invalid column type")
+            }
+        }
+
+    }
+
+    impl TryFrom<NullableColumn> for Vec<Option<NaiveTime>> {
+
+        type Error = &'static str;
+
+        fn try_from(col : NullableColumn) -> Result<Self, Self::Error> {
+            match col {
+                NullableColumn::Time(v) => Ok(v),
+                _ => Err("Invalid column type")
+            }
+        }
+
+    }
+
+    impl TryFrom<NullableColumn> for Vec<Option<NaiveDateTime>> {
+
+        type Error = &'static str;
+
+        fn try_from(col : NullableColumn) -> Result<Self, Self::Error> {
+            match col {
+                NullableColumn::Timestamp(v) => Ok(v),
+                _ => Err("Invalid column type")
+            }
+        }
+
+    }
+
+    impl TryFrom<NullableColumn> for Vec<Option<DateTime<Utc>>> {
+
+        type Error = &'static str;
+
+        fn try_from(col : NullableColumn) -> Result<Self, Self::Error> {
+            match col {
+                NullableColumn::TimestampTz(v) => Ok(v),
+                _ => Err("Invalid column type")
+            }
+        }
+
+    }
+
+    impl TryFrom<NullableColumn> for Vec<Option<Interval>> {
+
+        type Error = &'static str;
+
+        fn try_from(col : NullableColumn) -> Result<Self, Self::Error> {
+            match col {
+                NullableColumn::Interval(v) => Ok(v),
+                _ => Err("Invalid column type")
+            }
+        }
+
+    }
+
+    impl TryFrom<NullableColumn> for Vec<Option<Uuid>> {
+
+        type Error = &'static str;
+
+        fn try_from(col : NullableColumn) -> Result<Self, Self::Error> {
+            match col {
+                NullableColumn::Uuid(v) => Ok(v),
+                _ => Err("Invalid column type")
+            }
+        }
+
+    }
+
+    impl TryFrom<NullableColumn> for Vec<Option<Inet>> {
+
+        type Error = &'static str;
+
+        fn try_from(col : NullableColumn) -> Result<Self, Self::Error> {
+            match col {
+                NullableColumn::Inet(v) => Ok(v),
+                _ => Err("Invalid column type")
+            }
+        }
+
+    }
+
+    impl TryFrom<NullableColumn> for Vec<Option<MacAddr>> {
+
+        type Error = &'static str;
+
+        fn try_from(col : NullableColumn) -> Result<Self, Self::Error> {
+            match col {
+                NullableColumn::MacAddr(v) => Ok(v),
+                _ => Err("Invalid column type")
+            }
+        }
+
+    }
+
+    impl TryFrom<NullableColumn> for Vec<Option<Geometry>> {
+
+        type Error = &'static str;
+
+        fn try_from(col : NullableColumn) -> Result<Self, Self::Error> {
+            match col {
+                NullableColumn::Geometry(v) => Ok(v),
+                _ => Err("Invalid column type")
+            }
+        }
+
+    }
 }
@@ -0,0 +1,192 @@
+/*Copyright (c) 2022 Diego da Silva Lima. All rights reserved.
+
+This work is licensed under the terms of the GPL v3.0 License.  
+For a copy, see http://www.gnu.org/licenses.*/
+
+/* Decoding of Postgres arrays, enums, domains and composite types. The driver resolves
+the OIDs of types it does not know by looking them up at pg_type when preparing a statement,
+so the column types carry their kind (element type for arrays, labels for enums, base type for
+domains and fields for composites), which is used to decode the binary values recursively. */
+
+use std::error::Error;
+use std::convert::TryInto;
+use serde_json::{Value, Map};
+use tokio_postgres::types::{FromSql, Type, Kind};
+use tokio_postgres::row;
+use rust_decimal::Decimal;
+use super::column::decimal_to_json;
+use super::nullable::NullableColumn;
+use super::temporal::Interval;
+use super::network::{Inet, MacAddr};
+use super::geometry::Geometry;
+
+type DecodeError = Box<dyn Error + Sync + Send>;
+
+/// Value of any Postgres type, decoded as JSON. Arrays are nested according to their
+/// dimensions, and composites are decoded as objects keyed by field name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgValue(pub Value);
+
+impl<'a> FromSql<'a> for PgValue {
+
+    fn from_sql(ty : &Type, raw : &'a [u8]) -> Result<Self, DecodeError> {
+        Ok(PgValue(decode_value(ty, raw)?))
+    }
+
+    fn accepts(ty : &Type) -> bool {
+        is_nested(ty)
+    }
+
+}
+
+/// Whether this type is decoded here rather than into a dedicated column type.
+pub fn is_nested(ty : &Type) -> bool {
+    match ty.kind() {
+        Kind::Array(_) | Kind::Enum(_) | Kind::Domain(_) | Kind::Composite(_) => true,
+        _ => false
+    }
+}
+
+struct Buffer<'a> {
+    buf : &'a [u8],
+    pos : usize
+}
+
+impl<'a> Buffer<'a> {
+
+    fn bytes(&mut self, n : usize) -> Result<&'a [u8], DecodeError> {
+        let bytes = self.buf.get(self.pos..self.pos + n).ok_or("Unexpected end of value")?;
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    fn i32(&mut self) -> Result<i32, DecodeError> {
+        Ok(i32::from_be_bytes(self.bytes(4)?.try_into()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, DecodeError> {
+        Ok(u32::from_be_bytes(self.bytes(4)?.try_into()?))
+    }
+
+    // Reads a length-prefixed value, where a length of -1 marks a null.
+    fn value(&mut self, ty : &Type) -> Result<Value, DecodeError> {
+        let len = self.i32()?;
+        if len < 0 {
+            Ok(Value::Null)
+        } else {
+            decode_value(ty, self.bytes(len as usize)?)
+        }
+    }
+
+}
+
+pub fn decode_value(ty : &Type, raw : &[u8]) -> Result<Value, DecodeError> {
+    match ty.kind() {
+        Kind::Array(elem) => decode_array(elem, raw),
+        Kind::Domain(base) => decode_value(base, raw),
+        Kind::Enum(_) => Ok(Value::from(std::str::from_utf8(raw)?)),
+        Kind::Composite(fields) => {
+            let mut buf = Buffer { buf : raw, pos : 0 };
+            let n = buf.i32()?;
+            if n as usize != fields.len() {
+                return Err(format!("Invalid number of fields for {}", ty).into());
+            }
+            let mut obj = Map::new();
+            for f in fields {
+                buf.u32()?;
+                obj.insert(f.name().to_string(), buf.value(f.type_())?);
+            }
+            Ok(Value::Object(obj))
+        },
+        _ => decode_scalar(ty, raw)
+    }
+}
+
+/* Binary array layout: number of dimensions, a flag for the presence of nulls and the element
+OID, followed by the length and lower bound of each dimension and the elements in row-major order. */
+fn decode_array(elem : &Type, raw : &[u8]) -> Result<Value, DecodeError> {
+    let mut buf = Buffer { buf : raw, pos : 0 };
+    let ndim = buf.i32()?;
+    buf.i32()?;
+    buf.u32()?;
+    let mut dims = Vec::new();
+    for _ in 0..ndim {
+        dims.push(buf.i32()?.max(0) as usize);
+        buf.i32()?;
+    }
+    if dims.is_empty() {
+        return Ok(Value::Array(Vec::new()));
+    }
+    nest(&dims[..], elem, &mut buf)
+}
+
+fn nest(dims : &[usize], elem : &Type, buf : &mut Buffer) -> Result<Value, DecodeError> {
+    let mut vals = Vec::with_capacity(dims[0]);
+    for _ in 0..dims[0] {
+        if dims.len() == 1 {
+            vals.push(buf.value(elem)?);
+        } else {
+            vals.push(nest(&dims[1..], elem, buf)?);
+        }
+    }
+    Ok(Value::Array(vals))
+}
+
+fn decode_scalar(ty : &Type, raw : &[u8]) -> Result<Value, DecodeError> {
+    let val = match *ty {
+        Type::BOOL => Value::from(bool::from_sql(ty, raw)?),
+        Type::CHAR => Value::from(i8::from_sql(ty, raw)?),
+        Type::INT2 => Value::from(i16::from_sql(ty, raw)?),
+        Type::INT4 => Value::from(i32::from_sql(ty, raw)?),
+        Type::INT8 => Value::from(i64::from_sql(ty, raw)?),
+        Type::OID => Value::from(u32::from_sql(ty, raw)?),
+        Type::FLOAT4 => Value::from(f32::from_sql(ty, raw)? as f64),
+        Type::FLOAT8 => Value::from(f64::from_sql(ty, raw)?),
+        Type::NUMERIC => decimal_to_json(&Decimal::from_sql(ty, raw)?),
+        Type::JSON | Type::JSONB => Value::from_sql(ty, raw)?,
+        Type::BYTEA => Value::from(base64::encode(raw)),
+        Type::DATE => Value::from(chrono::NaiveDate::from_sql(ty, raw)?.to_string()),
+        Type::TIME => Value::from(chrono::NaiveTime::from_sql(ty, raw)?.to_string()),
+        Type::TIMESTAMP => {
+            Value::from(chrono::NaiveDateTime::from_sql(ty, raw)?.format("%Y-%m-%dT%H:%M:%S%.f").to_string())
+        },
+        Type::TIMESTAMPTZ => {
+            Value::from(chrono::DateTime::<chrono::Utc>::from_sql(ty, raw)?.format("%Y-%m-%dT%H:%M:%S%.fZ").to_string())
+        },
+        Type::INTERVAL => Value::from(Interval::from_sql(ty, raw)?.to_string()),
+        Type::UUID => Value::from(uuid::Uuid::from_sql(ty, raw)?.to_string()),
+        Type::INET | Type::CIDR => Value::from(Inet::from_sql(ty, raw)?.to_string()),
+        Type::MACADDR | Type::MACADDR8 => Value::from(MacAddr::from_sql(ty, raw)?.to_string()),
+        _ if <Geometry as FromSql>::accepts(ty) => Geometry::from_sql(ty, raw)?.to_geojson(),
+        _ if <String as FromSql>::accepts(ty) => Value::from(String::from_sql(ty, raw)?),
+        _ => return Err(format!("Unsupported type: {}", ty).into())
+    };
+    Ok(val)
+}
+
+/// Decodes a column of arrays, enums, domains or composites. Enums and domains over
+/// scalars are packed into a column of the corresponding scalar type.
+pub fn nullable_from_nested(rows : &[row::Row], ix : usize) -> Result<NullableColumn, &'static str> {
+    let mut vals = Vec::new();
+    for r in rows.iter() {
+        let v = r.try_get::<usize, Option<PgValue>>(ix)
+            .map_err(|_e| { "Unable to parse column" })?;
+        vals.push(v.map(|v| v.0 ).filter(|v| !v.is_null() ));
+    }
+    Ok(scalar_or_json(vals))
+}
+
+fn scalar_or_json(vals : Vec<Option<Value>>) -> NullableColumn {
+    let valid = || vals.iter().filter_map(|v| v.as_ref() );
+    if valid().all(|v| v.is_string() ) {
+        NullableColumn::from(vals.iter().map(|v| v.as_ref().and_then(|v| v.as_str() ).map(|v| v.to_string() ) ).collect::<Vec<_>>())
+    } else if valid().all(|v| v.is_boolean() ) {
+        NullableColumn::from(vals.iter().map(|v| v.as_ref().and_then(|v| v.as_bool() ) ).collect::<Vec<_>>())
+    } else if valid().all(|v| v.is_i64() ) {
+        NullableColumn::from(vals.iter().map(|v| v.as_ref().and_then(|v| v.as_i64() ) ).collect::<Vec<_>>())
+    } else if valid().all(|v| v.is_number() ) {
+        NullableColumn::from(vals.iter().map(|v| v.as_ref().and_then(|v| v.as_f64() ) ).collect::<Vec<_>>())
+    } else {
+        NullableColumn::from(vals)
+    }
+}
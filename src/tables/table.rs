@@ -23,8 +23,14 @@ use std::cmp::{Eq, PartialEq};
 use quick_xml::Reader;
 use quick_xml::events::{Event };
 use crate::tables::nullable::NullableColumn;
+use crate::client::Engine;
+use crate::tables::dialect::CsvOptions;
 use std::ops::Index;
 use std::collections::HashMap;
+use crate::tables::temporal::{self, Interval};
+use crate::tables::network::{Inet, MacAddr};
+use crate::tables::geometry::Geometry;
+use crate::tables::pgtype;
 
 #[derive(Debug, Clone)]
 pub struct TableSource {
@@ -195,6 +201,21 @@ impl Table {
         Ok(self)
     }
 
+    /// Appends the rows of a table with the same columns (such as the next page of a query result).
+    pub fn append_rows(&mut self, other : &Table) -> Result<(), String> {
+        if other.nrows == 0 {
+            return Ok(());
+        }
+        if self.names != other.names {
+            return Err(format!("Cannot append rows of a table with different columns"));
+        }
+        for (col, other_col) in self.cols.iter_mut().zip(other.cols.iter()) {
+            *col = col.appended(other_col);
+        }
+        self.nrows += other.nrows;
+        Ok(())
+    }
+
     pub fn is_empty(&self) -> bool {
         self.cols.len() == 0 || self.cols[0].len() == 0
     }
@@ -211,11 +232,15 @@ impl Table {
         }
     }
     
+    /// Builds a table by stepping through the rows. At most max_rows are read, if informed.
+    /// Errors while stepping (such as an interrupted statement) are returned instead of a
+    /// partial table.
     pub fn from_sqlite_rows(
         names : Vec<String>,
         col_tys : &[String],
-        mut rows : rusqlite::Rows
-    ) -> Result<Table, &'static str>
+        mut rows : rusqlite::Rows,
+        max_rows : Option<usize>
+    ) -> Result<Table, String>
     where
         NullableColumn : From<Vec<Option<i64>>>,
         NullableColumn : From<Vec<Option<f64>>>,
@@ -231,11 +256,15 @@ impl Table {
         }).collect();
         let empty_cols : Vec<_> = empty_cols?;
         if names.len() == 0 {
-            return Err("No columns available");
+            return Err(String::from("No columns available"));
         }
         let mut sqlite_cols : Vec<SqliteColumn> = Vec::new();
         let mut curr_row = 0;
-        while let Ok(row) = rows.next() {
+        loop {
+            if max_rows.map(|max| curr_row >= max ).unwrap_or(false) {
+                break;
+            }
+            let row = rows.next().map_err(|e| format!("{}", e) )?;
             match row {
                 Some(r) => {
                     if curr_row == 0 {
@@ -257,16 +286,18 @@ impl Table {
             }
         }
         if curr_row == 0 {
-            Ok(Table::new(None, names, empty_cols)?)
+            let cols = empty_cols.into_iter().zip(col_tys.iter()).map(|(c, ty)| sqlite_temporal(c, ty) ).collect();
+            Ok(Table::new(None, names, cols)?)
         } else {
             let mut null_cols : Vec<NullableColumn> = sqlite_cols
                 .drain(0..sqlite_cols.len())
                 .map(|c| c.into() ).collect();
             if null_cols.len() == 0 {
-                return Err("Too few columns");
+                return Err(String::from("Too few columns"));
             }
             let cols : Vec<Column> = null_cols.drain(0..null_cols.len())
-                .map(|nc| Column::from(nc) )
+                .zip(col_tys.iter())
+                .map(|(nc, ty)| sqlite_temporal(Column::from(nc), ty) )
                 .collect();
             Ok(Table::new(None, names, cols)?)
         }
@@ -309,42 +340,21 @@ impl Table {
             let is_timestamp_tz = col_types[i] == &Type::TIMESTAMPTZ;
             let is_date = col_types[i] == &Type::DATE;
             let is_time = col_types[i] == &Type::TIME;
+            let is_interval = col_types[i] == &Type::INTERVAL;
             let is_numeric = col_types[i] == &Type::NUMERIC;
             // let is_money = col_types[i] == &Type::MONEY;
             let is_uuid = col_types[i] == &Type::UUID;
             let is_json = col_types[i] == &Type::JSON || col_types[i] == &Type::JSONB;
-            let is_point = col_types[i] == &Type::POINT;
+            let is_inet = col_types[i] == &Type::INET || col_types[i] == &Type::CIDR;
+            let is_macaddr = col_types[i] == &Type::MACADDR || col_types[i] == &Type::MACADDR8;
+
+            // Built-in point, lseg, path, box and polygon types, or PostGIS geometries.
             // let is_line = col_types[i] == &Type::LINE;
-            let is_box = col_types[i] == &Type::BOX;
-            let is_path = col_types[i] == &Type::PATH;
-            let is_text_arr = col_types[i] == &Type::TEXT_ARRAY;
-            let is_real_arr = col_types[i] == &Type::FLOAT4_ARRAY;
-            let is_dp_arr = col_types[i] == &Type::FLOAT8_ARRAY;
-            let is_smallint_arr = col_types[i] == &Type::INT2_ARRAY;
-            let is_int_arr = col_types[i] == &Type::INT4_ARRAY;
-            let is_decimal_arr = col_types[i] == &Type::NUMERIC_ARRAY;
-            let is_bigint_arr = col_types[i] == &Type::INT8_ARRAY;
+            let is_geometry = <Geometry as FromSql>::accepts(col_types[i]);
             let _is_xml = col_types[i] == &Type::XML;
-            let is_json_arr = col_types[i] == &Type::JSON_ARRAY;
-            let array_ty = if is_text_arr {
-                Some(ArrayType::Text)
-            } else if is_real_arr {
-                Some(ArrayType::Float4)
-            } else if is_dp_arr {
-                Some(ArrayType::Float8)
-            } else if is_smallint_arr {
-                Some(ArrayType::Int2)
-            } else if is_int_arr {
-                Some(ArrayType::Int4)
-            } else if is_bigint_arr {
-                Some(ArrayType::Int8)
-            } else if is_json_arr {
-                Some(ArrayType::Json)
-            } else if is_decimal_arr {
-                Some(ArrayType::Decimal)
-            } else {
-                None
-            };
+
+            // Arrays, enums, domains and composites (user-defined types included).
+            let is_nested = pgtype::is_nested(col_types[i]);
             if is_bool {
                 null_cols.push(nullable_from_rows::<bool>(rows, i)?);
             } else if is_bytea {
@@ -366,27 +376,29 @@ impl Table {
             } else if is_oid {
                 null_cols.push(nullable_from_rows::<u32>(rows, i)?);
             } else if is_timestamp {
-                null_cols.push(as_nullable_text::<chrono::NaiveDateTime>(rows, i)?);
+                null_cols.push(nullable_from_rows::<chrono::NaiveDateTime>(rows, i)?);
             } else if is_timestamp_tz {
-                null_cols.push(as_nullable_text::<chrono::DateTime<chrono::Utc>>(rows, i)?);
+                null_cols.push(nullable_from_rows::<chrono::DateTime<chrono::Utc>>(rows, i)?);
             } else if is_date {
-                null_cols.push(as_nullable_text::<chrono::NaiveDate>(rows, i)?);
+                null_cols.push(nullable_from_rows::<chrono::NaiveDate>(rows, i)?);
             } else if is_time {
-                null_cols.push(as_nullable_text::<chrono::NaiveTime>(rows, i)?);
+                null_cols.push(nullable_from_rows::<chrono::NaiveTime>(rows, i)?);
+            } else if is_interval {
+                null_cols.push(nullable_from_rows::<Interval>(rows, i)?);
             } else if is_uuid {
-                null_cols.push(as_nullable_text::<uuid::Uuid>(rows, i)?);
-            } else if is_point {
-                null_cols.push(as_nullable_json_value::<geo_types::Point<f64>>(rows, i)?);
-            } else if is_path {
-               null_cols.push(as_nullable_json_value::<geo_types::LineString<f64>>(rows, i)?);
-            } else if is_box {
-                null_cols.push(as_nullable_json_value::<geo_types::Rect<f64>>(rows, i)?);
+                null_cols.push(nullable_from_rows::<uuid::Uuid>(rows, i)?);
+            } else if is_inet {
+                null_cols.push(nullable_from_rows::<Inet>(rows, i)?);
+            } else if is_macaddr {
+                null_cols.push(nullable_from_rows::<MacAddr>(rows, i)?);
+            } else if is_geometry {
+                null_cols.push(nullable_from_rows::<Geometry>(rows, i)?);
             } else if is_numeric {
                 null_cols.push(nullable_from_rows::<Decimal>(rows, i)?);
             } else if is_json {
                 null_cols.push(nullable_from_rows::<Value>(rows, i)?);
-            } else if let Some(ty) = array_ty {
-                null_cols.push(nullable_from_arr(rows, i, ty)?);
+            } else if is_nested {
+                null_cols.push(pgtype::nullable_from_nested(rows, i)?);
             } else {
                 null_cols.push(nullable_unable_to_parse(rows, col_types[i]));
             }
@@ -528,56 +540,82 @@ impl Table {
         self.relation = relation;
     }
 
+    pub fn set_names(&mut self, names : Vec<String>) {
+        assert!(names.len() == self.cols.len());
+        self.names = names;
+    }
+
+    /// Parses a table from delimited text, detecting its dialect.
     pub fn new_from_text(
         source : String
     ) -> Result<Self, &'static str> {
-        match csv::parse_csv_as_text_cols(&source.clone()) {
-            Ok(mut cols) => {
-                let mut parsed_cols = Vec::new();
-                let mut names = Vec::new();
-                for (name, values) in cols.drain(0..) {
-                    let mut parsed_int = Vec::new();
-                    let mut parsed_float = Vec::new();
-                    let mut parsed_json = Vec::new();
-                    let mut all_int = true;
-                    let mut all_float = true;
-                    let mut all_json = true;
-                    for s in values.iter() {
-                        if all_int {
-                            if let Ok(int) = s.parse::<i64>() {
-                                parsed_int.push(int);
-                            } else {
-                                all_int = false;
-                            }
-                        }
-                        if all_float {
-                            if let Ok(float) = s.parse::<f64>() {
-                                parsed_float.push(float);
-                            } else {
-                                all_float = false;
-                            }
-                        }
-                        if all_json {
-                            if let Ok(json) = s.parse::<Value>() {
-                                parsed_json.push(json);
-                            } else {
-                                all_json = false;
-                            }
-                        }
-                    }
-                    match (all_int, all_float, all_json) {
-                        (true, _, _) => parsed_cols.push(Column::I64(parsed_int)),
-                        (false, true, _) => parsed_cols.push(Column::F64(parsed_float)),
-                        (false, false, true) => parsed_cols.push(Column::Json(parsed_json)),
-                        _ => parsed_cols.push(Column::Str(values))
-                    }
-                    names.push(name);
-                }
-                Ok(Table::new(None, names, parsed_cols)?)
+        Self::new_from_delimited(source.as_bytes(), &CsvOptions::default())
+            .map_err(|_| "Could not parse CSV content" )
+    }
+
+    /// Reads a table from a .csv, .parquet or .arrow (Arrow IPC) file. The table is named after
+    /// the file stem.
+    pub fn open(path : &std::path::Path) -> Result<Self, String> {
+        Self::open_with(path, &CsvOptions::default())
+    }
+
+    /// Reads a table from a file, using the informed options for delimited text files.
+    pub fn open_with(path : &std::path::Path, opts : &CsvOptions) -> Result<Self, String> {
+        let mut tbl = match path.extension().and_then(|ext| ext.to_str() ) {
+            Some("csv") | Some("tsv") | Some("txt") => {
+                let content = std::fs::read(path).map_err(|e| format!("Error opening file: {}", e) )?;
+                Self::new_from_delimited(&content[..], opts).map_err(|e| format!("Error parsing table: {}", e) )?
             },
-            Err(_e) => {
-                Err("Could not parse CSV content")
-            }
+            Some("parquet") => super::arrow::read_parquet(path)?,
+            Some("arrow") => super::arrow::read_ipc(path)?,
+            _ => return Err(format!("Invalid file extension for table import (expected .csv, .tsv, .txt, .parquet or .arrow)"))
+        };
+        tbl.set_name(path.file_stem().and_then(|s| s.to_str() ).map(|s| s.to_string() ));
+        Ok(tbl)
+    }
+
+    /// Parses a table from delimited text. If the content has no header,
+    /// columns are named column1, column2, ... columnN.
+    pub fn new_from_csv(
+        source : String,
+        delimiter : u8,
+        has_header : bool
+    ) -> Result<Self, &'static str> {
+        Self::new_from_delimited(source.as_bytes(), &CsvOptions::with_delimiter(delimiter, has_header))
+            .map_err(|_| "Could not parse CSV content" )
+    }
+
+    /// Parses a table from delimited text with the informed options. Options
+    /// not set are detected from the content.
+    pub fn new_from_delimited(content : &[u8], opts : &CsvOptions) -> Result<Self, String> {
+        let opts = if opts.is_partial() { opts.sniff(content) } else { opts.clone() };
+        let source = opts.decode(content)?;
+        let mut names = Vec::new();
+        let mut cols = Vec::new();
+        for (name, values) in csv::parse_csv_as_text_cols(&source, &opts)? {
+            let values : Vec<Option<String>> = values.into_iter()
+                .map(|s| if opts.is_null(&s) { None } else { Some(opts.normalize_decimal(s)) })
+                .collect();
+            cols.push(Column::from(NullableColumn::from(values)));
+            names.push(name);
+        }
+        let mut tbl = Table::new(None, names, cols)?;
+        tbl.infer_types(&opts.column_types);
+        Ok(tbl)
+    }
+
+    /// Converts text columns to the type recognized from their values, or to the type
+    /// informed for the column name.
+    pub fn infer_types(&mut self, types : &HashMap<String, TextType>) {
+        for (name, col) in self.names.iter().zip(self.cols.iter_mut()) {
+            let values : Vec<Option<String>> = match col {
+                Column::Str(v) => v.drain(..).map(Some).collect(),
+                Column::Nullable(NullableColumn::Str(v)) => v.drain(..).collect(),
+                _ => continue
+            };
+            let ty = types.get(name).cloned()
+                .unwrap_or_else(|| infer_text_type(values.iter().filter_map(|v| v.as_deref() )) );
+            *col = column_from_text(values, ty);
         }
     }
 
@@ -649,8 +687,8 @@ impl Table {
     /// TODO check if SQL is valid (maybe external to the struct). SQL can
     /// be invalid if there are reserved keywords as column names.
     pub fn sql_string(&self, name : &str) -> Result<String, String> {
-        if let Some(mut creation) = self.sql_table_creation(name, &[]) {
-            creation += &self.sql_table_insertion(name, &[])?;
+        if let Some(mut creation) = self.sql_table_creation(name, &[], Engine::SQLite) {
+            creation += &self.sql_table_insertion(name, &[], Engine::SQLite)?;
             /*match crate::sql::parsing::parse_sql(&creation[..], &HashMap::new()) {
                 Ok(_) => Ok(creation),
                 Err(e) => Err(format!("{}", e))
@@ -669,29 +707,41 @@ impl Table {
         self.cols[col_ix].display_lines(self.format.prec, fst_row, max_rows)
     }
 
-    pub fn sql_table_creation(&self, name : &str, _cols : &[String]) -> Option<String> {
-        let mut query = format!("CREATE TABLE {}(", name);
-        for (i, (name, col)) in self.names.iter().zip(self.cols.iter()).enumerate() {
-            let name = match name.chars().find(|c| *c == ' ') {
-                Some(_) => String::from("\"") + &name[..] + "\"",
-                None => name.clone()
+    /// Builds the statement that creates a table able to hold the informed columns
+    /// (or all columns if none are informed) at the given engine.
+    pub fn sql_table_creation(&self, name : &str, cols : &[String], engine : Engine) -> Option<String> {
+        let order : Vec<usize> = if cols.len() == 0 {
+            (0..self.names.len()).collect()
+        } else {
+            cols.iter().map(|c| self.names.iter().position(|n| &n[..] == &c[..] ) ).collect::<Option<Vec<_>>>()?
+        };
+        if order.is_empty() {
+            return None;
+        }
+        let mut query = format!("create table {} (\n", crate::sql::quote_qualified(name, engine));
+        for (i, ix) in order.iter().enumerate() {
+            let col_name = crate::sql::quote_ident(&self.names[*ix], engine);
+            let ty = match engine {
+                Engine::Postgres => self.cols[*ix].postgres_type(),
+                _ => self.cols[*ix].sqlite3_creation_type()
             };
-            query += &format!("{} {}", name, col.sqlite3_type());
-            if i < self.cols.len() - 1 {
-                query += ","
+            query += &format!("    {} {}", col_name, ty);
+            if i < order.len() - 1 {
+                query += ",\n"
             } else {
-                query += ");\n"
+                query += "\n);\n"
             }
         }
         Some(query)
     }
 
-    /// Always successful, but query might be empty if there is no data on the columns.
-    pub fn sql_table_insertion(&self, name : &str, cols : &[String]) -> Result<String, String> {
+    /// Always successful, but query might be empty if there is no data on the columns. The
+    /// table and column names are quoted as they are at sql_table_creation.
+    pub fn sql_table_insertion(&self, name : &str, cols : &[String], engine : Engine) -> Result<String, String> {
         let mut stmt = String::new();
         let nrows = self.nrows();
         
-        for i in 0..(cols.len()-1) {
+        for i in 0..cols.len().saturating_sub(1) {
             for j in (i+1)..cols.len() {
                 if &cols[i][..] == &cols[j][..] {
                     return Err(String::from("Duplicated columns"));
@@ -699,10 +749,12 @@ impl Table {
             }
         }
         
+        let name = crate::sql::quote_qualified(name, engine);
         if cols.len() == 0 {
             stmt += &format!("insert into {} values ", name)[..];
         } else {
-            let tuple = insertion_tuple(&cols);
+            let quoted : Vec<String> = cols.iter().map(|c| crate::sql::quote_ident(c, engine) ).collect();
+            let tuple = insertion_tuple(&quoted);
             stmt += &format!("insert into {} {} values ", name, tuple)[..];
         }
         
@@ -777,6 +829,45 @@ impl Table {
         content
     }
 
+    /// Represents the table as JSON. The columnar layout is the inverse of
+    /// the TryFrom<serde_json::Value> implementation.
+    pub fn to_json(&self, layout : JsonLayout) -> serde_json::Value {
+        let shown : Vec<usize> = (0..self.cols.len()).filter(|ix| self.show_column(*ix) ).collect();
+        let mut values : Vec<Vec<Value>> = shown.iter().map(|ix| self.cols[*ix].json_values() ).collect();
+        match layout {
+            JsonLayout::Rows => {
+                let mut rows = Vec::with_capacity(self.nrows);
+                for row_ix in 0..self.nrows {
+                    let mut obj = serde_json::Map::new();
+                    for (i, col_ix) in shown.iter().enumerate() {
+                        obj.insert(self.names[*col_ix].clone(), std::mem::take(&mut values[i][row_ix]));
+                    }
+                    rows.push(Value::Object(obj));
+                }
+                Value::Array(rows)
+            },
+            JsonLayout::Columns => {
+                let mut obj = serde_json::Map::new();
+                for (col_ix, vals) in shown.iter().zip(values.drain(..)) {
+                    obj.insert(self.names[*col_ix].clone(), Value::Array(vals));
+                }
+                Value::Object(obj)
+            }
+        }
+    }
+
+    /// Newline-delimited JSON, with one object per row.
+    pub fn to_ndjson(&self) -> String {
+        let mut content = String::new();
+        if let Value::Array(rows) = self.to_json(JsonLayout::Rows) {
+            for row in rows {
+                content += &row.to_string();
+                content += "\n";
+            }
+        }
+        content
+    }
+
     pub fn to_tex(&self) -> String {
         let mut rows = self.text_rows(None, None, true, 0);
         let mut tex = String::new();
@@ -1111,6 +1202,23 @@ impl<'a> Columns<'a> {
             }
             return Ok(cvt);
         }
+
+        // Temporal values are positioned along plot axes as seconds.
+        if let Some(date) = self.try_access::<chrono::NaiveDate>(ix) {
+            return Ok(date.iter().map(|d| temporal::date_axis(d) ).collect());
+        }
+        if let Some(time) = self.try_access::<chrono::NaiveTime>(ix) {
+            return Ok(time.iter().map(|t| temporal::time_axis(t) ).collect());
+        }
+        if let Some(ts) = self.try_access::<chrono::NaiveDateTime>(ix) {
+            return Ok(ts.iter().map(|t| temporal::timestamp_axis(t) ).collect());
+        }
+        if let Some(ts) = self.try_access::<chrono::DateTime<chrono::Utc>>(ix) {
+            return Ok(ts.iter().map(|t| temporal::timestamptz_axis(t) ).collect());
+        }
+        if let Some(intv) = self.try_access::<Interval>(ix) {
+            return Ok(intv.iter().map(|i| temporal::interval_axis(i) ).collect());
+        }
         match self.cols.get(ix) {
             Some(Column::Nullable(_)) => Err(NotNumericErr::HasNull),
             Some(_) => Err(NotNumericErr::IsNot),
@@ -1234,6 +1342,17 @@ impl Default for TableSettings {
 
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonLayout {
+
+    /// Array of objects, one per row.
+    Rows,
+
+    /// Object mapping each column name to an array of values.
+    Columns
+
+}
+
 pub fn full_csv_display(tbl : &mut Table, cols : Vec<String>) -> String {
     let show = if cols.len() == 0 {
         None
@@ -1256,6 +1375,7 @@ pub fn full_csv_display(tbl : &mut Table, cols : Vec<String>) -> String {
 mod csv {
 
     use ::csv;
+    use crate::tables::dialect::CsvOptions;
 
     fn parse_header(
         csv_reader : &mut csv::Reader<&[u8]>
@@ -1301,17 +1421,29 @@ mod csv {
 
     /// Given a textual content as CSV, return a HashMap of its columns as strings.
     pub fn parse_csv_as_text_cols(
-        content : &String
+        content : &str,
+        opts : &CsvOptions
     ) -> Result<Vec<(String, Vec<String>)>, String> {
+        let has_header = opts.header.unwrap_or(true);
         let mut csv_reader = csv::ReaderBuilder::new()
-            .has_headers(true)
+            .has_headers(has_header)
+            .delimiter(opts.delimiter.unwrap_or(b','))
             .flexible(false)
             .trim(csv::Trim::All)
-            .quote(b'"')
+            .quote(opts.quote.unwrap_or(b'"'))
             .quoting(true)
+            .escape(opts.escape)
+            .double_quote(opts.escape.is_none())
             .from_reader(content.as_bytes());
-        let header : Vec<String> = parse_header(&mut csv_reader)
-            .ok_or("No CSV header at informed file".to_string())?;
+        let header : Vec<String> = if has_header {
+            parse_header(&mut csv_reader)
+                .ok_or("No CSV header at informed file".to_string())?
+        } else {
+            // The reader does not consume the first record when reading it to count the columns.
+            let ncols = parse_header(&mut csv_reader)
+                .ok_or("Empty CSV content".to_string())?.len();
+            (1..=ncols).map(|i| format!("column{}", i) ).collect()
+        };
         
         let data_keys = header.clone();
         let mut data_vec : Vec<(String, Vec<String>)> = Vec::new();
@@ -1477,6 +1609,32 @@ pub fn hstore_to_value(
     Ok(NullableColumn::from(vals))
 }
 
+/* SQLite stores temporal values as text. Text columns declared with a temporal type are
+converted when all their values can be parsed, and are kept as text otherwise. */
+fn sqlite_temporal(col : Column, decl_type : &str) -> Column {
+    let ty = match &decl_type.to_lowercase()[..] {
+        "date" => TextType::Date,
+        "time" => TextType::Time,
+        "datetime" | "timestamp" => TextType::Timestamp,
+        _ => return col
+    };
+    let values : Vec<Option<String>> = match &col {
+        Column::Str(v) => v.iter().cloned().map(Some).collect(),
+        Column::Nullable(NullableColumn::Str(v)) => v.clone(),
+        _ => return col
+    };
+    let converted = column_from_text(values, ty);
+    let n_valid = |c : &Column| match c {
+        Column::Nullable(nc) => nc.count_valid(),
+        other => other.len()
+    };
+    if n_valid(&converted) == n_valid(&col) {
+        converted
+    } else {
+        col
+    }
+}
+
 pub fn nullable_from_rows<'a, T>(
     rows : &'a [row::Row],
     ix : usize
@@ -1526,17 +1684,6 @@ pub fn as_nullable_json_value<'a, T>(
     Ok(NullableColumn::from(val_data?))
 }
 
-pub enum ArrayType {
-    Float4,
-    Float8,
-    Text,
-    Int2,
-    Int4,
-    Int8,
-    Decimal,
-    Json
-}
-
 pub fn nullable_unable_to_parse<'a>(rows : &'a [row::Row], ty_name : &types::Type) -> NullableColumn {
     let unable_to_parse : Vec<Option<String>> = rows.iter()
         .map(|_| Some(format!("Unable to parse ({})", ty_name)))
@@ -1555,45 +1702,6 @@ where
     }
 }
 
-pub fn nullable_from_arr<'a>(
-    rows : &'a [row::Row],
-    ix : usize,
-    ty : ArrayType
-) -> Result<NullableColumn, &'static str> {
-    let data : Vec<Option<serde_json::Value>> = match ty {
-        ArrayType::Float4 => {
-            col_as_opt_vec::<Vec<f32>>(rows, ix)?.drain(..).map(|v| json_value_or_null(v) ).collect()
-        },
-        ArrayType::Float8 => {
-            col_as_opt_vec::<Vec<f64>>(rows, ix)?.drain(..).map(|v| json_value_or_null(v) ).collect()
-        },
-        ArrayType::Int2 => {
-            col_as_opt_vec::<Vec<i16>>(rows, ix)?.drain(..).map(|v| json_value_or_null(v) ).collect()
-        },
-        ArrayType::Int4 => {
-            col_as_opt_vec::<Vec<i32>>(rows, ix)?.drain(..).map(|v| json_value_or_null(v) ).collect()
-        },
-        ArrayType::Int8 => {
-            col_as_opt_vec::<Vec<i64>>(rows, ix)?.drain(..).map(|v| json_value_or_null(v) ).collect()
-        },
-        ArrayType::Text => {
-            col_as_opt_vec::<Vec<String>>(rows, ix)?.drain(..).map(|v| json_value_or_null(v) ).collect()
-        },
-        ArrayType::Json => {
-            col_as_opt_vec::<Vec<serde_json::Value>>(rows, ix)?.drain(..).map(|v| json_value_or_null(v) ).collect()
-        },
-        ArrayType::Decimal => {
-            let mut col = col_as_opt_vec::<Vec<Decimal>>(rows, ix)?;
-            let mut fcol : Vec<Option<Vec<f64>>> = col.drain(..).map(|v| match v {
-                Some(v) => Some(v.clone().drain(..).map(|v| v.to_f64().unwrap_or(std::f64::NAN) ).collect::<Vec<_>>()),
-                None => None
-            }).collect();
-            fcol.drain(..).map(|v| json_value_or_null(v) ).collect()
-        }
-    };
-    Ok(NullableColumn::from(data))
-}
-
 // cargo test -- sorted_table --nocapture
 #[test]
 fn sorted_table() {
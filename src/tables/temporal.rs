@@ -0,0 +1,300 @@
+/*Copyright (c) 2022 Diego da Silva Lima. All rights reserved.
+
+This work is licensed under the terms of the GPL v3.0 License.  
+For a copy, see http://www.gnu.org/licenses.*/
+
+use chrono::{NaiveDate, NaiveTime, NaiveDateTime, DateTime, Utc, Local, Offset, Timelike, TimeZone};
+use std::fmt::{self, Display};
+use std::cmp::Ordering;
+use std::str::FromStr;
+use std::error::Error;
+use std::convert::TryInto;
+use serde::{Serialize, Deserialize};
+use tokio_postgres::types::{FromSql, ToSql, Type, IsNull, to_sql_checked};
+use bytes::{BytesMut, BufMut};
+
+const MICROS_PER_SEC : i64 = 1_000_000;
+
+const SECS_PER_DAY : i64 = 86_400;
+
+/// Postgres interval, kept as separate month, day and microsecond counts since
+/// months and days do not have a fixed duration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct Interval {
+    pub months : i32,
+    pub days : i32,
+    pub micros : i64
+}
+
+impl Interval {
+
+    pub fn new(months : i32, days : i32, micros : i64) -> Self {
+        Interval { months, days, micros }
+    }
+
+    /// Approximate duration in seconds, taking months as 30 days.
+    pub fn seconds(&self) -> f64 {
+        (self.months as i64 * 30 + self.days as i64) as f64 * SECS_PER_DAY as f64 +
+            self.micros as f64 / MICROS_PER_SEC as f64
+    }
+
+    /// Exact duration in microseconds, taking months as 30 days and days as 24 hours (the
+    /// same normalization Postgres uses to compare intervals).
+    pub fn span(&self) -> i128 {
+        (self.months as i128 * 30 + self.days as i128) * (SECS_PER_DAY * MICROS_PER_SEC) as i128 + self.micros as i128
+    }
+
+}
+
+/* Intervals are ordered by their normalized span, so that 1 mon 1 day is greater than 30 days. Intervals
+with the same span (1 mon and 30 days) are ordered by their fields to keep the order consistent with
+equality. */
+impl Ord for Interval {
+
+    fn cmp(&self, other : &Self) -> Ordering {
+        self.span().cmp(&other.span())
+            .then_with(|| (self.months, self.days, self.micros).cmp(&(other.months, other.days, other.micros)) )
+    }
+
+}
+
+impl PartialOrd for Interval {
+
+    fn partial_cmp(&self, other : &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+
+}
+
+fn plural(n : i32, unit : &str) -> String {
+    if n == 1 || n == -1 {
+        format!("{} {}", n, unit)
+    } else {
+        format!("{} {}s", n, unit)
+    }
+}
+
+// Writes a microsecond count as [-]hh:mm:ss[.ffffff].
+fn write_clock(f : &mut fmt::Formatter<'_>, micros : i64) -> fmt::Result {
+    let sign = if micros < 0 { "-" } else { "" };
+    let micros = micros.abs();
+    let secs = micros / MICROS_PER_SEC;
+    let frac = micros % MICROS_PER_SEC;
+    write!(f, "{}{:02}:{:02}:{:02}", sign, secs / 3600, (secs % 3600) / 60, secs % 60)?;
+    if frac > 0 {
+        write!(f, ".{}", format!("{:06}", frac).trim_end_matches('0'))?;
+    }
+    Ok(())
+}
+
+impl Display for Interval {
+
+    /// Writes the interval in the Postgres output style (e.g. 1 year 2 mons 3 days 04:05:06).
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if self.months / 12 != 0 {
+            parts.push(plural(self.months / 12, "year"));
+        }
+        if self.months % 12 != 0 {
+            parts.push(plural(self.months % 12, "mon"));
+        }
+        if self.days != 0 {
+            parts.push(plural(self.days, "day"));
+        }
+        write!(f, "{}", parts.join(" "))?;
+        if self.micros != 0 || parts.is_empty() {
+            if !parts.is_empty() {
+                write!(f, " ")?;
+            }
+            write_clock(f, self.micros)?;
+        }
+        Ok(())
+    }
+
+}
+
+/* Fractions of a unit are carried to the smaller units as Postgres does (1.5 mons is 1 mon 15 days
+and 1.5 days is 1 day 12:00:00). */
+impl Interval {
+
+    fn add_months(&mut self, n : f64) {
+        self.months += n.trunc() as i32;
+        self.add_days(n.fract() * 30.0);
+    }
+
+    fn add_days(&mut self, n : f64) {
+        self.days += n.trunc() as i32;
+        self.add_micros(n.fract() * (SECS_PER_DAY * MICROS_PER_SEC) as f64);
+    }
+
+    fn add_micros(&mut self, n : f64) {
+        self.micros += n.round() as i64;
+    }
+
+}
+
+fn parse_clock(s : &str) -> Option<i64> {
+    let (neg, s) = match s.strip_prefix('-') {
+        Some(s) => (true, s),
+        None => (false, s.strip_prefix('+').unwrap_or(s))
+    };
+    let mut fields = s.split(':');
+    let hours = fields.next()?.parse::<i64>().ok()?;
+    let mins = fields.next()?.parse::<i64>().ok()?;
+    let secs = match fields.next() {
+        Some(secs) => secs.parse::<f64>().ok()?,
+        None => 0.0
+    };
+    if fields.next().is_some() {
+        return None;
+    }
+    let micros = (hours * 3600 + mins * 60) * MICROS_PER_SEC + (secs * MICROS_PER_SEC as f64).round() as i64;
+    Some(if neg { -micros } else { micros })
+}
+
+impl FromStr for Interval {
+
+    type Err = String;
+
+    /// Parses the Postgres interval style (e.g. 1 year 2 mons 3 days 04:05:06 or 3 hours 20 minutes).
+    fn from_str(s : &str) -> Result<Self, String> {
+        let err = || format!("Invalid interval: {}", s);
+        let mut intv = Interval::default();
+        let mut tokens = s.split_whitespace().peekable();
+        if tokens.peek().is_none() {
+            return Err(err());
+        }
+        while let Some(tk) = tokens.next() {
+            if tk.contains(':') {
+                intv.micros += parse_clock(tk).ok_or_else(err)?;
+                continue;
+            }
+            let n = tk.parse::<f64>().map_err(|_| err() )?;
+            let unit = tokens.next().ok_or_else(err)?.to_lowercase();
+            match unit.trim_end_matches('s') {
+                "year" => intv.add_months(n * 12.0),
+                "mon" | "month" => intv.add_months(n),
+                "week" => intv.add_days(n * 7.0),
+                "day" => intv.add_days(n),
+                "hour" => intv.add_micros(n * 3600.0 * MICROS_PER_SEC as f64),
+                "min" | "minute" => intv.add_micros(n * 60.0 * MICROS_PER_SEC as f64),
+                "sec" | "second" => intv.add_micros(n * MICROS_PER_SEC as f64),
+                _ => return Err(err())
+            }
+        }
+        Ok(intv)
+    }
+
+}
+
+impl<'a> FromSql<'a> for Interval {
+
+    // Binary layout: microseconds (i64), days (i32) and months (i32).
+    fn from_sql(_ty : &Type, raw : &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        if raw.len() != 16 {
+            return Err(format!("Invalid interval length: {}", raw.len()).into());
+        }
+        let micros = i64::from_be_bytes(raw[0..8].try_into()?);
+        let days = i32::from_be_bytes(raw[8..12].try_into()?);
+        let months = i32::from_be_bytes(raw[12..16].try_into()?);
+        Ok(Interval { months, days, micros })
+    }
+
+    fn accepts(ty : &Type) -> bool {
+        *ty == Type::INTERVAL
+    }
+
+}
+
+impl ToSql for Interval {
+
+    fn to_sql(&self, _ty : &Type, out : &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        out.put_i64(self.micros);
+        out.put_i32(self.days);
+        out.put_i32(self.months);
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty : &Type) -> bool {
+        *ty == Type::INTERVAL
+    }
+
+    to_sql_checked!();
+
+}
+
+/// Text representation of a timestamp with time zone at the local offset (e.g. 2022-01-01 09:00:00-03),
+/// with the offset minutes written only when not zero.
+pub fn timestamptz_to_string(t : &DateTime<Utc>) -> String {
+    let local = t.with_timezone(&Local);
+    let offset = local.offset().fix().local_minus_utc();
+    let sign = if offset < 0 { "-" } else { "+" };
+    let mins = offset.abs() / 60;
+    if mins % 60 == 0 {
+        format!("{}{}{:02}", local.naive_local(), sign, mins / 60)
+    } else {
+        format!("{}{}{:02}:{:02}", local.naive_local(), sign, mins / 60, mins % 60)
+    }
+}
+
+pub fn parse_date(s : &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").ok()
+}
+
+pub fn parse_time(s : &str) -> Option<NaiveTime> {
+    let s = s.trim();
+    NaiveTime::parse_from_str(s, "%H:%M:%S%.f")
+        .or_else(|_| NaiveTime::parse_from_str(s, "%H:%M") )
+        .ok()
+}
+
+pub fn parse_timestamp(s : &str) -> Option<NaiveDateTime> {
+    let s = s.trim();
+    ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"].iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(s, fmt).ok() )
+}
+
+/// Parses a timestamp with an explicit offset (+hh, +hh:mm, Z or UTC). Timestamps without
+/// offset are taken to be at UTC.
+pub fn parse_timestamptz(s : &str) -> Option<DateTime<Utc>> {
+    let s = s.trim();
+    if let Some(naive) = s.strip_suffix(" UTC").or_else(|| s.strip_suffix('Z') ).and_then(parse_timestamp) {
+        return Some(Utc.from_utc_datetime(&naive));
+    }
+    if let Some(naive) = parse_timestamp(s) {
+        return Some(Utc.from_utc_datetime(&naive));
+    }
+    let ix = s.rfind(|c| c == '+' || c == '-' )?;
+    let naive = parse_timestamp(&s[..ix])?;
+    let mut offset = s[ix+1..].split(':');
+    let hours = offset.next()?.parse::<i64>().ok()?;
+    let mins = match offset.next() {
+        Some(m) => m.parse::<i64>().ok()?,
+        None => 0
+    };
+    let secs = (hours * 3600 + mins * 60) * if &s[ix..ix+1] == "-" { -1 } else { 1 };
+    Some(Utc.from_utc_datetime(&(naive - chrono::Duration::seconds(secs))))
+}
+
+/// Position of a date along a plot axis, in seconds since the Unix epoch.
+pub fn date_axis(d : &NaiveDate) -> f64 {
+    timestamp_axis(&d.and_hms_opt(0, 0, 0).unwrap())
+}
+
+/// Position of a time along a plot axis, in seconds since midnight.
+pub fn time_axis(t : &NaiveTime) -> f64 {
+    t.num_seconds_from_midnight() as f64 + t.nanosecond() as f64 / 1.0e9
+}
+
+/// Position of a timestamp along a plot axis, in seconds since the Unix epoch.
+pub fn timestamp_axis(t : &NaiveDateTime) -> f64 {
+    t.timestamp() as f64 + t.timestamp_subsec_nanos() as f64 / 1.0e9
+}
+
+pub fn timestamptz_axis(t : &DateTime<Utc>) -> f64 {
+    timestamp_axis(&t.naive_utc())
+}
+
+pub fn interval_axis(i : &Interval) -> f64 {
+    i.seconds()
+}
@@ -0,0 +1,381 @@
+/*Copyright (c) 2022 Diego da Silva Lima. All rights reserved.
+
+This work is licensed under the terms of the GPL v3.0 License.  
+For a copy, see http://www.gnu.org/licenses.*/
+
+use std::cmp::Ordering;
+use std::str::FromStr;
+use rust_decimal::Decimal;
+use regex::Regex;
+use chrono::{NaiveDate, NaiveTime, NaiveDateTime, DateTime, Utc};
+use uuid::Uuid;
+use super::table::Table;
+use super::column::Column;
+use super::nullable::NullableColumn;
+use super::temporal::{self, Interval};
+use super::network::{Inet, MacAddr};
+
+/* A view is a sequence of sort keys and a filter over the columns of a table. The rows of the
+view are calculated as indices into the original table, so that the original table is kept
+as-is when the user changes the view. */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NullOrder {
+    First,
+    Last
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    pub col : usize,
+    pub ascending : bool,
+    pub nulls : NullOrder
+}
+
+/// Predicates compare the values of a column with literals parsed into the column type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Predicate {
+    Equal(String),
+    NotEqual(String),
+    Less(String),
+    LessOrEqual(String),
+    Greater(String),
+    GreaterOrEqual(String),
+
+    // Inclusive range.
+    Between(String, String),
+
+    // Substring of the displayed value.
+    Contains(String),
+
+    // Regular expression matched against the displayed value.
+    Matches(String),
+
+    IsNull,
+    IsNotNull
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    Condition { col : usize, pred : Predicate },
+    And(Vec<Filter>),
+    Or(Vec<Filter>)
+}
+
+impl Filter {
+
+    /// Combines this filter with another filter, flattening nested conjunctions (or disjunctions).
+    pub fn and(self, other : Filter) -> Filter {
+        match self {
+            Filter::And(mut fs) => {
+                fs.push(other);
+                Filter::And(fs)
+            },
+            f => Filter::And(vec![f, other])
+        }
+    }
+
+    pub fn or(self, other : Filter) -> Filter {
+        match self {
+            Filter::Or(mut fs) => {
+                fs.push(other);
+                Filter::Or(fs)
+            },
+            f => Filter::Or(vec![f, other])
+        }
+    }
+
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TableView {
+    pub sort : Vec<SortKey>,
+    pub filter : Option<Filter>
+}
+
+// Comparable value of a table cell. Integer types are compared as i64, and values
+// without a natural order (binary, JSON and geometries) are compared by their displayed content.
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+enum Key {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Numeric(Decimal),
+    Text(String),
+    Date(NaiveDate),
+    Time(NaiveTime),
+    Timestamp(NaiveDateTime),
+    TimestampTz(DateTime<Utc>),
+    Interval(Interval),
+    Uuid(Uuid),
+    Inet(Inet),
+    MacAddr(MacAddr)
+}
+
+impl Key {
+
+    // Floats are totally ordered (NaN after every number), so that sorting is consistent.
+    fn total_cmp(&self, other : &Key) -> Ordering {
+        match (self, other) {
+            (Key::Float(a), Key::Float(b)) => a.total_cmp(b),
+            (a, b) => a.partial_cmp(b).unwrap_or(Ordering::Equal)
+        }
+    }
+
+    // Parses a literal into a key of the same variant.
+    fn parse_like(&self, s : &str) -> Option<Key> {
+        let t = s.trim();
+        match self {
+            Key::Bool(_) => match t {
+                "t" | "true" => Some(Key::Bool(true)),
+                "f" | "false" => Some(Key::Bool(false)),
+                _ => None
+            },
+            Key::Int(_) => i64::from_str(t).ok().map(Key::Int),
+            Key::Float(_) => f64::from_str(t).ok().map(Key::Float),
+            Key::Numeric(_) => Decimal::from_str(t).ok().map(Key::Numeric),
+            Key::Text(_) => Some(Key::Text(s.to_string())),
+            Key::Date(_) => temporal::parse_date(t).map(Key::Date),
+            Key::Time(_) => temporal::parse_time(t).map(Key::Time),
+            Key::Timestamp(_) => temporal::parse_timestamp(t).map(Key::Timestamp),
+            Key::TimestampTz(_) => temporal::parse_timestamptz(t).map(Key::TimestampTz),
+            Key::Interval(_) => Interval::from_str(t).ok().map(Key::Interval),
+            Key::Uuid(_) => Uuid::from_str(t).ok().map(Key::Uuid),
+            Key::Inet(_) => Inet::from_str(t).ok().map(Key::Inet),
+            Key::MacAddr(_) => MacAddr::from_str(t).ok().map(Key::MacAddr)
+        }
+    }
+
+}
+
+fn some_keys<T>(vs : &[T], f : impl Fn(&T)->Key) -> Vec<Option<Key>> {
+    vs.iter().map(|v| Some(f(v)) ).collect()
+}
+
+fn opt_keys<T>(vs : &[Option<T>], f : impl Fn(&T)->Key) -> Vec<Option<Key>> {
+    vs.iter().map(|v| v.as_ref().map(|v| f(v) ) ).collect()
+}
+
+fn display_keys(col : &Column) -> Vec<Option<Key>> {
+    col.display_content(None).into_iter().enumerate()
+        .map(|(ix, s)| if col.is_null(ix) { None } else { Some(Key::Text(s)) } )
+        .collect()
+}
+
+fn column_keys(col : &Column) -> Vec<Option<Key>> {
+    match col {
+        Column::Bool(v) => some_keys(v, |v| Key::Bool(*v) ),
+        Column::I8(v) => some_keys(v, |v| Key::Int(*v as i64) ),
+        Column::I16(v) => some_keys(v, |v| Key::Int(*v as i64) ),
+        Column::I32(v) => some_keys(v, |v| Key::Int(*v as i64) ),
+        Column::U32(v) => some_keys(v, |v| Key::Int(*v as i64) ),
+        Column::I64(v) => some_keys(v, |v| Key::Int(*v) ),
+        Column::F32(v) => some_keys(v, |v| Key::Float(*v as f64) ),
+        Column::F64(v) => some_keys(v, |v| Key::Float(*v) ),
+        Column::Numeric(v) => some_keys(v, |v| Key::Numeric(*v) ),
+        Column::Str(v) => some_keys(v, |v| Key::Text(v.clone()) ),
+        Column::Date(v) => some_keys(v, |v| Key::Date(*v) ),
+        Column::Time(v) => some_keys(v, |v| Key::Time(*v) ),
+        Column::Timestamp(v) => some_keys(v, |v| Key::Timestamp(*v) ),
+        Column::TimestampTz(v) => some_keys(v, |v| Key::TimestampTz(*v) ),
+        Column::Interval(v) => some_keys(v, |v| Key::Interval(*v) ),
+        Column::Uuid(v) => some_keys(v, |v| Key::Uuid(*v) ),
+        Column::Inet(v) => some_keys(v, |v| Key::Inet(*v) ),
+        Column::MacAddr(v) => some_keys(v, |v| Key::MacAddr(v.clone()) ),
+        Column::Bytes(_) | Column::Json(_) | Column::Geometry(_) => display_keys(col),
+        Column::Nullable(n) => match n {
+            NullableColumn::Bool(v) => opt_keys(v, |v| Key::Bool(*v) ),
+            NullableColumn::I8(v) => opt_keys(v, |v| Key::Int(*v as i64) ),
+            NullableColumn::I16(v) => opt_keys(v, |v| Key::Int(*v as i64) ),
+            NullableColumn::I32(v) => opt_keys(v, |v| Key::Int(*v as i64) ),
+            NullableColumn::U32(v) => opt_keys(v, |v| Key::Int(*v as i64) ),
+            NullableColumn::I64(v) => opt_keys(v, |v| Key::Int(*v) ),
+            NullableColumn::F32(v) => opt_keys(v, |v| Key::Float(*v as f64) ),
+            NullableColumn::F64(v) => opt_keys(v, |v| Key::Float(*v) ),
+            NullableColumn::Numeric(v) => opt_keys(v, |v| Key::Numeric(*v) ),
+            NullableColumn::Str(v) => opt_keys(v, |v| Key::Text(v.clone()) ),
+            NullableColumn::Date(v) => opt_keys(v, |v| Key::Date(*v) ),
+            NullableColumn::Time(v) => opt_keys(v, |v| Key::Time(*v) ),
+            NullableColumn::Timestamp(v) => opt_keys(v, |v| Key::Timestamp(*v) ),
+            NullableColumn::TimestampTz(v) => opt_keys(v, |v| Key::TimestampTz(*v) ),
+            NullableColumn::Interval(v) => opt_keys(v, |v| Key::Interval(*v) ),
+            NullableColumn::Uuid(v) => opt_keys(v, |v| Key::Uuid(*v) ),
+            NullableColumn::Inet(v) => opt_keys(v, |v| Key::Inet(*v) ),
+            NullableColumn::MacAddr(v) => opt_keys(v, |v| Key::MacAddr(v.clone()) ),
+            NullableColumn::Bytes(_) | NullableColumn::Json(_) | NullableColumn::Geometry(_) => display_keys(col)
+        }
+    }
+}
+
+// Predicate with its literals parsed into the column type (or its regex compiled).
+enum Test {
+    Cmp(Box<dyn Fn(Ordering)->bool>, Option<Key>),
+    Between(Option<Key>, Option<Key>),
+    Contains(String),
+    Matches(Regex),
+    IsNull,
+    IsNotNull
+}
+
+fn prepare(pred : &Predicate, keys : &[Option<Key>]) -> Result<Test, String> {
+    let template = keys.iter().filter_map(|k| k.as_ref() ).next();
+    let parse = |s : &str| -> Result<Option<Key>, String> {
+        match template {
+            Some(t) => t.parse_like(s).map(Some).ok_or(format!("Invalid value for column: {}", s)),
+            None => Ok(None)
+        }
+    };
+    let test = match pred {
+        Predicate::Equal(s) => Test::Cmp(Box::new(|o| o == Ordering::Equal ), parse(s)?),
+        Predicate::NotEqual(s) => Test::Cmp(Box::new(|o| o != Ordering::Equal ), parse(s)?),
+        Predicate::Less(s) => Test::Cmp(Box::new(|o| o == Ordering::Less ), parse(s)?),
+        Predicate::LessOrEqual(s) => Test::Cmp(Box::new(|o| o != Ordering::Greater ), parse(s)?),
+        Predicate::Greater(s) => Test::Cmp(Box::new(|o| o == Ordering::Greater ), parse(s)?),
+        Predicate::GreaterOrEqual(s) => Test::Cmp(Box::new(|o| o != Ordering::Less ), parse(s)?),
+        Predicate::Between(low, high) => Test::Between(parse(low)?, parse(high)?),
+        Predicate::Contains(s) => Test::Contains(s.clone()),
+        Predicate::Matches(s) => Test::Matches(Regex::new(s).map_err(|e| format!("{}", e) )?),
+        Predicate::IsNull => Test::IsNull,
+        Predicate::IsNotNull => Test::IsNotNull
+    };
+    Ok(test)
+}
+
+// Nulls never satisfy comparisons, as in SQL.
+fn satisfies(test : &Test, key : &Option<Key>, col : &Column, row : usize) -> bool {
+    match (test, key) {
+        (Test::IsNull, k) => k.is_none(),
+        (Test::IsNotNull, k) => k.is_some(),
+        (_, None) => false,
+        (Test::Cmp(f, Some(lit)), Some(k)) => k.partial_cmp(lit).map(|o| f(o) ).unwrap_or(false),
+        (Test::Cmp(_, None), _) => false,
+        (Test::Between(Some(low), Some(high)), Some(k)) => k >= low && k <= high,
+        (Test::Between(_, _), _) => false,
+        (Test::Contains(s), Some(_)) => col.display_content_at_index(row, None).contains(&s[..]),
+        (Test::Matches(re), Some(_)) => re.is_match(&col.display_content_at_index(row, None))
+    }
+}
+
+fn check_col(tbl : &Table, col : usize) -> Result<(), String> {
+    if col < tbl.ncols() {
+        Ok(())
+    } else {
+        Err(format!("Invalid column index: {}", col))
+    }
+}
+
+// Evaluates the filter over all rows, caching the keys of each column.
+fn evaluate(filter : &Filter, tbl : &Table, keys : &mut Vec<Option<Vec<Option<Key>>>>) -> Result<Vec<bool>, String> {
+    match filter {
+        Filter::Condition { col, pred } => {
+            check_col(tbl, *col)?;
+            if keys[*col].is_none() {
+                keys[*col] = Some(column_keys(&tbl[*col]));
+            }
+            let col_keys = keys[*col].as_ref().unwrap();
+            let test = prepare(pred, &col_keys[..])?;
+            Ok(col_keys.iter().enumerate().map(|(row, k)| satisfies(&test, k, &tbl[*col], row) ).collect())
+        },
+        Filter::And(fs) => {
+            let mut acc = vec![true; tbl.nrows()];
+            for f in fs {
+                let res = evaluate(f, tbl, keys)?;
+                acc.iter_mut().zip(res).for_each(|(a, r)| *a = *a && r );
+            }
+            Ok(acc)
+        },
+        Filter::Or(fs) => {
+            let mut acc = vec![fs.is_empty(); tbl.nrows()];
+            for f in fs {
+                let res = evaluate(f, tbl, keys)?;
+                acc.iter_mut().zip(res).for_each(|(a, r)| *a = *a || r );
+            }
+            Ok(acc)
+        }
+    }
+}
+
+fn compare(a : &Option<Key>, b : &Option<Key>, key : &SortKey) -> Ordering {
+    match (a, b) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => if key.nulls == NullOrder::First { Ordering::Less } else { Ordering::Greater },
+        (Some(_), None) => if key.nulls == NullOrder::First { Ordering::Greater } else { Ordering::Less },
+        (Some(a), Some(b)) => {
+            let ord = a.total_cmp(b);
+            if key.ascending { ord } else { ord.reverse() }
+        }
+    }
+}
+
+impl TableView {
+
+    pub fn is_empty(&self) -> bool {
+        self.sort.is_empty() && self.filter.is_none()
+    }
+
+    pub fn clear(&mut self) {
+        self.sort.clear();
+        self.filter = None;
+    }
+
+    /// Adds a sort key, used to order rows with equal values for the previous keys. If the
+    /// column was already sorted, its previous key is replaced.
+    pub fn then_by(&mut self, key : SortKey) {
+        self.sort.retain(|k| k.col != key.col );
+        self.sort.push(key);
+    }
+
+    pub fn and(&mut self, filter : Filter) {
+        self.filter = Some(match self.filter.take() {
+            Some(f) => f.and(filter),
+            None => filter
+        });
+    }
+
+    pub fn or(&mut self, filter : Filter) {
+        self.filter = Some(match self.filter.take() {
+            Some(f) => f.or(filter),
+            None => filter
+        });
+    }
+
+    /// Calculates the indices of the rows of the table that satisfy the filter, in the
+    /// order given by the sort keys. Rows with equal keys keep their original order.
+    pub fn indices(&self, tbl : &Table) -> Result<Vec<usize>, String> {
+        let mut keys : Vec<Option<Vec<Option<Key>>>> = vec![None; tbl.ncols()];
+        let mut ixs : Vec<usize> = match &self.filter {
+            Some(filter) => evaluate(filter, tbl, &mut keys)?
+                .iter()
+                .enumerate()
+                .filter(|(_, keep)| **keep )
+                .map(|(ix, _)| ix )
+                .collect(),
+            None => (0..tbl.nrows()).collect()
+        };
+        for key in self.sort.iter() {
+            check_col(tbl, key.col)?;
+            if keys[key.col].is_none() {
+                keys[key.col] = Some(column_keys(&tbl[key.col]));
+            }
+        }
+        ixs.sort_by(|a, b| {
+            for key in self.sort.iter() {
+                let col_keys = keys[key.col].as_ref().unwrap();
+                let ord = compare(&col_keys[*a], &col_keys[*b], key);
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+            }
+            Ordering::Equal
+        });
+        Ok(ixs)
+    }
+
+    /// Builds a new table with the rows of this view.
+    pub fn apply(&self, tbl : &Table) -> Result<Table, String> {
+        let ixs = self.indices(tbl)?;
+        let cols = (0..tbl.ncols()).map(|i| tbl[i].rearranged(&ixs[..]) ).collect();
+        Table::new(None, tbl.names(), cols).map_err(|e| format!("{}", e) )
+    }
+
+}
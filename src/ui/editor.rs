@@ -40,6 +40,7 @@ pub struct QueriesEditor {
     pub save_dialog : SaveDialog,
     pub open_dialog : OpenDialog,
     pub export_dialog : ExportDialog,
+    pub import_dialog : crate::ui::ImportDialog,
     user_state : SharedUserState
 }
 
@@ -51,6 +52,7 @@ impl QueriesEditor {
         let save_dialog = SaveDialog::build();
         let open_dialog = OpenDialog::build();
         let export_dialog = ExportDialog::build();
+        let import_dialog = crate::ui::ImportDialog::build();
         stack.add_named(&script_list.bx, Some("list"));
         let views : [sourceview5::View; MAX_VIEWS]= Default::default();
         for ix in 0..MAX_VIEWS {
@@ -61,7 +63,7 @@ impl QueriesEditor {
         }
         open_dialog.react(&script_list);
         let ignore_file_save_action = gio::SimpleAction::new("ignore_file_save", Some(&i32::static_variant_type()));
-        Self { views, stack, script_list, save_dialog, open_dialog, ignore_file_save_action, export_dialog, user_state : user_state.clone() }
+        Self { views, stack, script_list, save_dialog, open_dialog, ignore_file_save_action, export_dialog, import_dialog, user_state : user_state.clone() }
     }
 
     pub fn configure(&self, settings : &EditorSettings) {
@@ -439,6 +441,17 @@ impl React<MainMenu> for ExportDialog {
 
 }
 
+impl React<MainMenu> for crate::ui::ImportDialog {
+
+    fn react(&self, menu : &MainMenu) {
+        let dialog = self.dialog.clone();
+        menu.action_import.connect_activate(move |_,_| {
+            dialog.show();
+        });
+    }
+
+}
+
 #[derive(Debug, Clone)]
 pub struct FindDialog {
     pub dialog  : Dialog,
@@ -127,6 +127,26 @@ impl Form {
         }
     }
 
+    pub fn update_from_params(&self, names : &[String]) -> bool {
+        self.entries.iter().for_each(|e| e.set_visible(false) );
+        self.dialog.set_title(Some("Parameters"));
+        if names.len() > MAX_ENTRIES {
+            self.err_lbl.set_visible(true);
+            self.btn_ok.set_sensitive(false);
+            false
+        } else {
+            self.err_lbl.set_visible(false);
+            self.btn_ok.set_sensitive(true);
+            for (ix, name) in names.iter().enumerate() {
+                self.entries[ix].set_visible(true);
+                self.entries[ix].set_primary_icon_name(None);
+                self.entries[ix].set_placeholder_text(Some(name));
+            }
+            self.bx.grab_focus();
+            self.btn_ok.set_label("Execute");
+            true
+        }
+    }
 
 }
 
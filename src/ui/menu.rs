@@ -17,6 +17,7 @@ pub struct MainMenu {
     pub action_save : gio::SimpleAction,
     pub action_save_as : gio::SimpleAction,
     pub action_export : gio::SimpleAction,
+    pub action_import : gio::SimpleAction,
     pub action_settings : gio::SimpleAction,
     pub action_find_replace : gio::SimpleAction,
     pub action_about : gio::SimpleAction,
@@ -36,6 +37,7 @@ impl MainMenu {
         menu.append(Some("Query builder"), Some("win.builder"));
         menu.append(Some("Graph editor"), Some("win.graph"));
         menu.append(Some("Export"), Some("win.export"));
+        menu.append(Some("Import"), Some("win.import_table"));
         menu.append(Some("Settings"), Some("win.settings"));
         menu.append(Some("About"), Some("win.about"));
         let popover = PopoverMenu::from_model(Some(&menu));
@@ -47,6 +49,7 @@ impl MainMenu {
         let action_graph = gio::SimpleAction::new("graph", None);
         let action_builder = gio::SimpleAction::new("builder", None);
         let action_export = gio::SimpleAction::new("export", None);
+        let action_import = gio::SimpleAction::new("import_table", None);
         let action_settings = gio::SimpleAction::new("settings", None);
         let action_find_replace = gio::SimpleAction::new("find_replace", None);
         let action_about = gio::SimpleAction::new("about", None);
@@ -57,7 +60,7 @@ impl MainMenu {
         action_export.set_enabled(false);
         action_find_replace.set_enabled(false);
 
-        Self { popover, action_new, action_open, action_save, action_save_as, action_export,
+        Self { popover, action_new, action_open, action_save, action_save_as, action_export, action_import,
         action_settings, action_find_replace, action_about, action_graph, action_builder
         }
     }
@@ -284,6 +284,21 @@ impl React<ActiveConnection> for QueriesContent {
                 }
             }
         });
+        conn.connect_import_progress({
+            let overlay = self.overlay.clone();
+            let curr_toast = self.curr_toast.clone();
+            move |(n, total)| {
+                let mut last_toast = curr_toast.borrow_mut();
+                if let Some(t) = last_toast.take() {
+                    t.dismiss();
+                }
+                let msg = format!("Imported {} of {} row(s)", n, total);
+                let toast = libadwaita::Toast::builder().title(&msg).build();
+                overlay.add_toast(&toast);
+                connect_toast_dismissed(&toast, &curr_toast);
+                *last_toast = Some(toast);
+            }
+        });
         conn.connect_db_conn_failure({
             let overlay = self.overlay.clone();
             let results_page = self.results_page.clone();
@@ -407,10 +422,14 @@ impl QueriesWindow {
         content.editor.save_dialog.0.dialog.set_transient_for(Some(&window));
         content.editor.open_dialog.0.dialog.set_transient_for(Some(&window));
         content.editor.export_dialog.dialog.set_transient_for(Some(&window));
+        content.editor.import_dialog.dialog.set_transient_for(Some(&window));
         sidebar.schema_tree.form.dialog.set_transient_for(Some(&window));
+        titlebar.exec_btn.params_form.dialog.set_transient_for(Some(&window));
         sidebar.schema_tree.report_dialog.dialog.set_transient_for(Some(&window));
         sidebar.schema_tree.report_export_dialog.dialog.set_transient_for(Some(&window));
         sidebar.schema_tree.import_dialog.dialog.set_transient_for(Some(&window));
+        sidebar.schema_tree.import_preview_dialog.dialog.set_transient_for(Some(&window));
+        content.results.workspace.edit_dialog.dialog.set_transient_for(Some(&window));
         sidebar.schema_tree.react(&content.results.overview.conn_bx);
         find_dialog.dialog.set_transient_for(Some(&window));
 
@@ -435,6 +454,7 @@ impl QueriesWindow {
         window.add_action(&titlebar.main_menu.action_graph);
         window.add_action(&titlebar.main_menu.action_builder);
         window.add_action(&titlebar.main_menu.action_export);
+        window.add_action(&titlebar.main_menu.action_import);
         window.add_action(&titlebar.main_menu.action_settings);
         window.add_action(&titlebar.main_menu.action_about);
         window.add_action(&content.editor.ignore_file_save_action);
@@ -446,6 +466,8 @@ impl QueriesWindow {
         window.add_action(&titlebar.exec_btn.clear_action);
         window.add_action(&titlebar.exec_btn.schedule_action);
         window.add_action(&titlebar.exec_btn.single_action);
+        window.add_action(&titlebar.exec_btn.notification_action);
+        window.add_action(&titlebar.exec_btn.cancel_action);
         window.add_action(&titlebar.exec_btn.restore_action);
 
         window.add_action(&sidebar.file_list.close_action);
@@ -459,6 +481,7 @@ impl QueriesWindow {
 
         content.editor.open_dialog.react(&titlebar.main_menu);
         content.editor.export_dialog.react(&titlebar.main_menu);
+        content.editor.import_dialog.react(&titlebar.main_menu);
 
         content.react(&sidebar.file_list);
         titlebar.exec_btn.react(&sidebar.file_list);
@@ -743,7 +766,7 @@ pub fn get_type_icon_name(ty : &DBType, is_dark : bool) -> &'static str {
             DBType::F32 | DBType::F64 | DBType::Numeric => "type-real-white",
             DBType::Text => "type-text-white",
             DBType::Date => "type-date-white",
-            DBType::Time => "type-time-white",
+            DBType::Time | DBType::Timestamp => "type-time-white",
             DBType::Json => "type-json-white",
             DBType::Xml => "type-xml-white",
             DBType::Bytes => "type-binary-white",
@@ -757,7 +780,7 @@ pub fn get_type_icon_name(ty : &DBType, is_dark : bool) -> &'static str {
             DBType::F32 | DBType::F64 | DBType::Numeric => "type-real-symbolic",
             DBType::Text => "type-text-symbolic",
             DBType::Date => "type-date-symbolic",
-            DBType::Time => "type-time-symbolic",
+            DBType::Time | DBType::Timestamp => "type-time-symbolic",
             DBType::Json => "type-json-symbolic",
             DBType::Xml => "type-xml-symbolic",
             DBType::Bytes => "type-binary-symbolic",
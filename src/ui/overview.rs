@@ -8,7 +8,7 @@ use tuples::TupleIter;
 use gtk4::prelude::*;
 use gtk4::*;
 use stateful::React;
-use crate::client::ConnectionInfo;
+use crate::client::{ConnectionInfo, Engine};
 use crate::ui::PackedImageEntry;
 use crate::ui::PackedImagePasswordEntry;
 use crate::ui::PackedImageLabel;
@@ -20,6 +20,7 @@ use crate::ui::settings::QueriesSettings;
 use std::rc::Rc;
 use std::cell::RefCell;
 use crate::ui::SharedSignal;
+use crate::client::{SshJump, SSH_DEFAULT_PORT};
 
 #[derive(Debug, Clone)]
 pub struct QueriesOverview {
@@ -212,7 +213,7 @@ fn update_with_info(
     encryption_img : &Image,
     info : &ConnectionInfo
 ) {
-    if info.is_default() || info.host.is_empty() || info.is_file() {
+    if info.is_default() || info.host.is_empty() || info.is_file() || info.is_socket() {
         encryption_lbl.set_text("");
         certificate_lbl.set_text("");
         encryption_img.set_from_icon_name(Some("padlock2-open-symbolic"));
@@ -518,6 +519,174 @@ fn change_text_at_conn_row(list : &ListBox, label_ix : usize, entry : &Entry) {
     }
 }
 
+/* Popover to paste libpq connection strings into the selected connection, and to import
+the connections defined at the user service and password files. */
+#[derive(Debug, Clone)]
+pub struct ConnStringPopover {
+    pub btn : MenuButton,
+    pub popover : Popover,
+    pub entry : Entry,
+    pub btn_apply : Button,
+    pub btn_import : Button,
+    pub msg_lbl : Label
+}
+
+impl ConnStringPopover {
+
+    pub fn build() -> Self {
+        let entry = Entry::new();
+        entry.set_placeholder_text(Some("postgresql://user@host/db or host=... dbname=..."));
+        entry.set_width_chars(48);
+        let btn_apply = Button::with_label("Use connection string");
+        btn_apply.style_context().add_class("suggested-action");
+        btn_apply.set_sensitive(false);
+        let btn_import = Button::with_label("Import service and password files");
+        btn_import.set_tooltip_text(Some("Add the connections at ~/.pg_service.conf and ~/.pgpass"));
+        let msg_lbl = Label::new(None);
+        msg_lbl.set_visible(false);
+        msg_lbl.set_wrap(true);
+        msg_lbl.set_max_width_chars(48);
+
+        let bx = Box::new(Orientation::Vertical, 12);
+        bx.append(&entry);
+        bx.append(&msg_lbl);
+        bx.append(&btn_apply);
+        bx.append(&btn_import);
+        super::set_margins(&bx, 12, 12);
+        let popover = Popover::new();
+        popover.set_child(Some(&bx));
+        let btn = MenuButton::builder().icon_name("edit-paste-symbolic").build();
+        btn.style_context().add_class("flat");
+        btn.set_tooltip_text(Some("Connection string"));
+        btn.set_halign(Align::End);
+        btn.set_hexpand(true);
+        btn.set_popover(Some(&popover));
+        popover.connect_show({
+            let msg_lbl = msg_lbl.clone();
+            move |_| {
+                msg_lbl.set_visible(false);
+            }
+        });
+        Self { btn, popover, entry, btn_apply, btn_import, msg_lbl }
+    }
+
+    pub fn show_message(&self, msg : &str, is_error : bool) {
+        if is_error {
+            self.msg_lbl.style_context().add_class("error");
+        } else {
+            self.msg_lbl.style_context().remove_class("error");
+        }
+        self.msg_lbl.set_text(msg);
+        self.msg_lbl.set_visible(true);
+    }
+
+}
+
+/* Popover to configure the bastion host through which the selected connection is made. */
+#[derive(Debug, Clone)]
+pub struct SshPopover {
+    pub btn : MenuButton,
+    pub popover : Popover,
+    pub host_entry : Entry,
+    pub port_entry : Entry,
+    pub user_entry : Entry,
+    pub key_entry : Entry,
+    pub btn_apply : Button,
+    pub msg_lbl : Label
+}
+
+impl SshPopover {
+
+    pub fn build() -> Self {
+        let host_entry = Entry::new();
+        host_entry.set_placeholder_text(Some("Bastion host (empty for direct connections)"));
+        let port_entry = Entry::new();
+        port_entry.set_placeholder_text(Some(&SSH_DEFAULT_PORT.to_string()));
+        port_entry.set_input_purpose(InputPurpose::Digits);
+        let user_entry = Entry::new();
+        user_entry.set_placeholder_text(Some("User"));
+        let key_entry = Entry::new();
+        key_entry.set_placeholder_text(Some("Private key path (empty to use the agent)"));
+
+        let grid = Grid::new();
+        grid.set_row_spacing(6);
+        grid.set_column_spacing(12);
+        for (ix, (name, entry)) in [("Host", &host_entry), ("Port", &port_entry), ("User", &user_entry), ("Key", &key_entry)].iter().enumerate() {
+            let lbl = Label::new(Some(*name));
+            lbl.set_halign(Align::Start);
+            entry.set_hexpand(true);
+            entry.set_width_chars(36);
+            grid.attach(&lbl, 0, ix as i32, 1, 1);
+            grid.attach(*entry, 1, ix as i32, 1, 1);
+        }
+
+        let btn_apply = Button::with_label("Apply");
+        btn_apply.style_context().add_class("suggested-action");
+        btn_apply.set_sensitive(false);
+        let msg_lbl = Label::new(None);
+        msg_lbl.style_context().add_class("error");
+        msg_lbl.set_visible(false);
+        msg_lbl.set_wrap(true);
+        msg_lbl.set_max_width_chars(48);
+
+        let bx = Box::new(Orientation::Vertical, 12);
+        bx.append(&grid);
+        bx.append(&msg_lbl);
+        bx.append(&btn_apply);
+        super::set_margins(&bx, 12, 12);
+        let popover = Popover::new();
+        popover.set_child(Some(&bx));
+        let btn = MenuButton::builder().icon_name("network-server-symbolic").build();
+        btn.style_context().add_class("flat");
+        btn.set_tooltip_text(Some("SSH tunnel"));
+        btn.set_popover(Some(&popover));
+        popover.connect_show({
+            let msg_lbl = msg_lbl.clone();
+            move |_| {
+                msg_lbl.set_visible(false);
+            }
+        });
+        Self { btn, popover, host_entry, port_entry, user_entry, key_entry, btn_apply, msg_lbl }
+    }
+
+    pub fn set_jump(&self, jump : Option<&SshJump>) {
+        self.host_entry.set_text(jump.map(|j| &j.host[..] ).unwrap_or(""));
+        self.port_entry.set_text(&jump.map(|j| j.port.to_string() ).unwrap_or_default());
+        self.user_entry.set_text(jump.map(|j| &j.user[..] ).unwrap_or(""));
+        self.key_entry.set_text(jump.and_then(|j| j.key_path.as_ref().map(|k| &k[..] ) ).unwrap_or(""));
+    }
+
+    /// The bastion informed by the user, or None if the host entry is empty.
+    pub fn jump(&self) -> Result<Option<SshJump>, String> {
+        let host = self.host_entry.text().trim().to_string();
+        if host.is_empty() {
+            return Ok(None);
+        }
+        let port_txt = self.port_entry.text();
+        let port = if port_txt.is_empty() {
+            SSH_DEFAULT_PORT
+        } else {
+            port_txt.parse::<u16>().map_err(|_| format!("Invalid SSH port: {}", port_txt) )?
+        };
+        let key = self.key_entry.text().trim().to_string();
+        if !key.is_empty() && !std::path::Path::new(&key).is_file() {
+            return Err(format!("Key file {} not found", key));
+        }
+        Ok(Some(SshJump {
+            host,
+            port,
+            user : self.user_entry.text().trim().to_string(),
+            key_path : Some(key).filter(|k| !k.is_empty() )
+        }))
+    }
+
+    pub fn show_error(&self, msg : &str) {
+        self.msg_lbl.set_text(msg);
+        self.msg_lbl.set_visible(true);
+    }
+
+}
+
 #[derive(Debug, Clone)]
 pub struct ConnectionBox {
     pub host : PackedImageEntry,
@@ -526,11 +695,15 @@ pub struct ConnectionBox {
     pub db : PackedImageEntry,
     pub password : PackedImagePasswordEntry,
     pub switch : Switch,
+    pub conn_str : ConnStringPopover,
+    pub ssh : SshPopover,
+    pub engine : ComboBoxText,
     pub bx : Box,
     pub host_changed : SharedSignal,
     pub port_changed : SharedSignal,
     pub user_changed : SharedSignal,
-    pub db_changed : SharedSignal
+    pub db_changed : SharedSignal,
+    pub engine_changed : SharedSignal
 }
 
 impl ConnectionBox {
@@ -541,8 +714,18 @@ impl ConnectionBox {
         let port = PackedImageEntry::build("arrow-into-box-symbolic", "Port");
         port.entry.set_max_width_chars(8);
         port.entry.set_input_purpose(InputPurpose::Digits);
+
+        // The server engine is chosen explicitly, since a MySQL server might listen
+        // at any port (and a PostgreSQL server at 3306).
+        let engine = ComboBoxText::new();
+        engine.append(Some("postgres"), "PostgreSQL");
+        engine.append(Some("mysql"), "MySQL/MariaDB");
+        engine.set_active_id(Some("postgres"));
+        engine.set_valign(Align::Center);
+        engine.set_margin_start(6);
         host_bx.append(&host.bx);
         host_bx.append(&port.bx);
+        host_bx.append(&engine);
         let db = PackedImageEntry::build("db-symbolic", "Database");
         let cred_bx = Box::new(Orientation::Horizontal, 0);
         let user = PackedImageEntry::build("avatar-default-symbolic", "User");
@@ -554,8 +737,14 @@ impl ConnectionBox {
         cred_bx.append(&password.bx);
         cred_bx.append(&switch);
         let title = super::title_label("Authentication");
+        let conn_str = ConnStringPopover::build();
+        let title_bx = Box::new(Orientation::Horizontal, 0);
+        let ssh = SshPopover::build();
+        title_bx.append(&title);
+        title_bx.append(&conn_str.btn);
+        title_bx.append(&ssh.btn);
         let bx = Box::new(Orientation::Vertical, 0);
-        bx.append(&title);
+        bx.append(&title_bx);
         bx.append(&host_bx);
         bx.append(&db.bx);
         bx.append(&cred_bx);
@@ -571,17 +760,20 @@ impl ConnectionBox {
             let user_entry = user.entry.clone();
             let pwd_entry = password.entry.clone();
             let db_entry = db.entry.clone();
+            let engine = engine.clone();
             move |host_entry| {
                 if host_entry.text().starts_with("file://") {
                     user_entry.set_sensitive(false);
                     pwd_entry.set_sensitive(false);
                     port_entry.set_sensitive(false);
                     db_entry.set_sensitive(false);
+                    engine.set_sensitive(false);
                 } else {
                     user_entry.set_sensitive(true);
                     pwd_entry.set_sensitive(true);
                     port_entry.set_sensitive(true);
                     db_entry.set_sensitive(true);
+                    engine.set_sensitive(true);
                 }
             }
         });
@@ -594,10 +786,14 @@ impl ConnectionBox {
             password,
             bx,
             switch,
+            conn_str,
+            ssh,
+            engine,
             host_changed : Default::default(),
             port_changed : Default::default(),
             user_changed : Default::default(),
-            db_changed : Default::default()
+            db_changed : Default::default(),
+            engine_changed : Default::default()
         };
         conn_bx.set_sensitive(false);
         conn_bx
@@ -611,6 +807,21 @@ impl ConnectionBox {
         &self.password.entry
     }
 
+    // Sets the engine combo without issuing a connection update.
+    fn set_engine(&self, engine : Engine) {
+        let id = match engine {
+            Engine::MySQL => "mysql",
+            _ => "postgres"
+        };
+        if let Some(sig) = &*self.engine_changed.borrow() {
+            self.engine.block_signal(sig);
+            self.engine.set_active_id(Some(id));
+            self.engine.unblock_signal(sig);
+        } else {
+            self.engine.set_active_id(Some(id));
+        }
+    }
+
     pub fn set_non_db_mode(&self) {
         self.entries().iter().for_each(|entry| entry.set_sensitive(true) );
     }
@@ -629,7 +840,10 @@ impl ConnectionBox {
         self.db.entry.set_sensitive(sensitive);
         self.user.entry.set_sensitive(sensitive);
         self.password.entry.set_sensitive(sensitive);
+        self.engine.set_sensitive(sensitive);
         self.switch.set_sensitive(sensitive);
+        self.conn_str.btn_apply.set_sensitive(sensitive);
+        self.ssh.btn_apply.set_sensitive(sensitive);
     }
 
 }
@@ -679,8 +893,13 @@ impl React<ConnectionSet> for ConnectionBox {
                         conn_bx.user.entry.set_text(&sel_info.user);
                     }
                     conn_bx.password.entry.set_text("");
+                    conn_bx.set_engine(sel_info.engine);
+                    conn_bx.engine.set_sensitive(!sel_info.is_file());
+                    conn_bx.ssh.set_jump(sel_info.ssh.as_ref());
                     conn_bx.password.entry.grab_focus();
                 } else {
+                    conn_bx.ssh.set_jump(None);
+                    conn_bx.set_engine(Engine::Postgres);
                     conn_bx.host.entry.set_text("");
                     conn_bx.port.entry.set_text("");
                     conn_bx.db.entry.set_text("");
@@ -702,6 +921,41 @@ impl React<ConnectionSet> for ConnectionBox {
                 conn_bx.set_sensitive(true);
             }
         });
+        connections.connect_pasted({
+            let conn_bx = self.clone();
+            move |(_, conn_str)| {
+
+                // The connection set was already updated, so the entry signals are blocked
+                // as when a connection is selected.
+                let signals = (
+                    &*conn_bx.host_changed.borrow(),
+                    &*conn_bx.port_changed.borrow(),
+                    &*conn_bx.db_changed.borrow(),
+                    &*conn_bx.user_changed.borrow()
+                );
+                if let (Some(host_s), Some(port_s), Some(db_s), Some(user_s)) = signals {
+                    conn_bx.host.entry.block_signal(host_s);
+                    conn_bx.port.entry.block_signal(port_s);
+                    conn_bx.db.entry.block_signal(db_s);
+                    conn_bx.user.entry.block_signal(user_s);
+                }
+                conn_bx.host.entry.set_text(&conn_str.info.host);
+                conn_bx.port.entry.set_text(&conn_str.info.port);
+                conn_bx.db.entry.set_text(&conn_str.info.database);
+                conn_bx.user.entry.set_text(&conn_str.info.user);
+                conn_bx.password.entry.set_text(conn_str.password.as_ref().map(|p| &p[..] ).unwrap_or(""));
+                if let (Some(host_s), Some(port_s), Some(db_s), Some(user_s)) = signals {
+                    conn_bx.host.entry.unblock_signal(host_s);
+                    conn_bx.port.entry.unblock_signal(port_s);
+                    conn_bx.user.entry.unblock_signal(user_s);
+                    conn_bx.db.entry.unblock_signal(db_s);
+                }
+                conn_bx.set_engine(conn_str.info.engine);
+                conn_bx.ssh.set_jump(conn_str.info.ssh.as_ref());
+                conn_bx.conn_str.entry.set_text("");
+                conn_bx.conn_str.popover.popdown();
+            }
+        });
     }
 
 }
@@ -721,23 +975,35 @@ impl React<ActiveConnection> for ConnectionBox {
             let host_entry = self.host.entry.clone();
             let other_entries = (&self.port.entry, &self.user.entry, &self.db.entry).cloned();
             let pwd_entry = self.password.entry.clone();
+            let conn_str_btn = self.conn_str.btn.clone();
+            let ssh_btn = self.ssh.btn.clone();
+            let engine = self.engine.clone();
             move |_| {
+                ssh_btn.set_sensitive(false);
+                engine.set_sensitive(false);
                 switch.set_sensitive(true);
                 host_entry.set_sensitive(false);
                 other_entries.iter().for_each(|e| e.set_sensitive(false) );
                 pwd_entry.set_sensitive(false);
+                conn_str_btn.set_sensitive(false);
             }
         });
         conn.connect_db_disconnected({
             let host_entry = self.host.entry.clone();
             let other_entries = (&self.port.entry, &self.user.entry, &self.db.entry).cloned();
             let pwd_entry = self.password.entry.clone();
+            let conn_str_btn = self.conn_str.btn.clone();
+            let ssh_btn = self.ssh.btn.clone();
+            let engine = self.engine.clone();
             move|_| {
                 let is_file = host_entry.text().to_string().starts_with("file://");
+                conn_str_btn.set_sensitive(true);
+                ssh_btn.set_sensitive(true);
                 host_entry.set_sensitive(true);
                 if !is_file {
                     other_entries.iter().for_each(|e| e.set_sensitive(true) );
                     pwd_entry.set_sensitive(true);
+                    engine.set_sensitive(true);
                 }
             }
         });
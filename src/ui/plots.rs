@@ -266,6 +266,33 @@ fn nested_column_sql(nested : &BTreeMap<ColSource, Vec<String>>) -> BTreeMap<Str
     exprs
 }
 
+/* Maps CTE names to inner queries that aggregate the vertex coordinates of geometry columns
+(as {col}_x and {col}_y), which requires PostGIS. One CTE is created per column, since
+each one is expanded into its vertices. */
+fn geometry_column_sql(geoms : &[MappingCol]) -> BTreeMap<String, String> {
+    let mut exprs = BTreeMap::new();
+    for g in geoms {
+        let agg_name = format!("{}_{}_geom", g.src.tbl, g.col);
+        let pts_expr = format!(
+            "array_agg(ST_X(pt.geom)) as {}_x, array_agg(ST_Y(pt.geom)) as {}_y",
+            g.col,
+            g.col
+        );
+        exprs.insert(
+            agg_name.clone(),
+            format!(
+                "{} as (SELECT {} FROM {} CROSS JOIN LATERAL ST_DumpPoints({}.{}::geometry) as pt)",
+                agg_name,
+                pts_expr,
+                g.src.to_string(),
+                g.src.tbl,
+                g.col
+            )
+        );
+    }
+    exprs
+}
+
 pub fn nested_columns(
     entries : &[DataEntry]
 ) -> BTreeMap<ColSource, Vec<String>> {
@@ -279,30 +306,41 @@ pub fn nested_columns(
     nested
 }
 
+fn mapping_col(txt : &str) -> Option<MappingCol> {
+    let mut txt_iter = txt.split(".");
+    let s1 = txt_iter.next()?;
+    let s2 = txt_iter.next()?;
+    if let Some(s3) = txt_iter.next() {
+        Some(MappingCol { src : ColSource { schema : Some(s1.to_string()), tbl : s2.to_string() }, col : s3.to_string() })
+    } else {
+        Some(MappingCol { src : ColSource { schema : None, tbl : s1.to_string() }, col : s2.to_string() })
+    }
+}
+
 impl DataEntry {
 
     pub fn values(&self) -> Vec<MappingCol> {
         let mut out = Vec::new();
         for e in &self.entries {
-            let txt = e.text();
-            let mut txt_iter = txt.split(".");
-            if let Some(s1) = txt_iter.next() {
-                if let Some(s2) = txt_iter.next() {
-                    if let Some(s3) = txt_iter.next() {
-                        out.push(MappingCol { src : ColSource { schema : Some(s1.to_string()), tbl : s2.to_string() }, col : s3.to_string() });
-                    } else {
-                        out.push(MappingCol { src : ColSource { schema : None, tbl : s1.to_string() }, col : s2.to_string() });
-                    }
-                } else {
-                    return Vec::new();
-                }
-            } else{
+            if let Some(col) = mapping_col(&e.text()) {
+                out.push(col);
+            } else {
                 return Vec::new();
             }
         }
         out
     }
 
+    /// When only the X entry of a line or scatter mapping is filled, it is taken to be
+    /// a geometry column, whose vertices are plotted.
+    pub fn geometry_value(&self) -> Option<MappingCol> {
+        if self.entries.len() == 2 && self.entries[1].text().is_empty() {
+            mapping_col(&self.entries[0].text())
+        } else {
+            None
+        }
+    }
+
     // Receives pairs of (placeholder, primary icon)
     pub fn build(entries : &[(&str, &str)]) -> Self {
         let entries : Vec<_> = entries
@@ -683,9 +721,13 @@ impl MappingRow {
     pub fn sql(&self) -> String {
 
         let data = self.data.values();
-        if data.is_empty() {
-            return String::new();
-        }
+        let geom = self.data.geometry_value();
+        let (x, y) = match (&geom, data.len()) {
+            (Some(g), 0) => (format!("{}_x", g.col), format!("{}_y", g.col)),
+            (None, 0) => return String::new(),
+            (_, 1) => (data[0].col.clone(), String::new()),
+            _ => (data[0].col.clone(), data[1].col.clone())
+        };
 
         // TODO make sure source is same for other entries.
         // let tbl = data[0].src.to_string();
@@ -694,22 +736,17 @@ impl MappingRow {
             MappingBox::Line(line_bx) => {
                 let dash = line_bx.dash_scale.value();
                 let width = line_bx.width_scale.value();
-                let x = &data[0].col;
-                let y = &data[1].col;
                 let color = color_literal(&line_bx.color_btn);
                 let map = format!("json_build_object('x', {x}, 'y', {y})");
                 format!("json_build_object('kind', 'line', 'map', {map}, 'color', {color}, 'width', {width}, 'spacing', {dash})")
 	        },
 	        MappingBox::Scatter(scatter_bx) => {
-	            let x = &data[0].col;
-                let y = &data[1].col;
                 let color = color_literal(&scatter_bx.color_btn);
                 let radius = scatter_bx.radius_scale.value();
                 let map = format!("json_build_object('x', {x}, 'y', {y})");
 	            format!("json_build_object('kind', 'scatter', 'map', {map}, 'color', {color}, 'radius', {radius} )")
             },
             MappingBox::Bar(bar_bx) => {
-                let x = &data[0].col;
                 let color = color_literal(&bar_bx.color_btn);
                 let origin = bar_bx.origin_entry.text();
                 let spacing = bar_bx.spacing_entry.text();
@@ -1109,9 +1146,11 @@ impl GraphWindow {
             }
         }
         let nc = nested_columns(&entries);
-        let ns = nested_column_sql(&nc);
+        let mut ns = nested_column_sql(&nc);
+        let geoms : Vec<MappingCol> = entries.iter().filter_map(|e| e.geometry_value() ).collect();
+        ns.extend(geometry_column_sql(&geoms));
         let cte_arg : String = ns.values().cloned().collect::<Vec<_>>().join(",\n");
-        let src_expr : String = ns.keys().cloned().collect::<Vec<_>>().join("CROSS JOIN ");
+        let src_expr : String = ns.keys().cloned().collect::<Vec<_>>().join(" CROSS JOIN ");
         let layout_expr = self.layout.sql();
         let design_expr = self.design.sql();
         let plots_expr : String = rows.iter().cloned().map(|r| r.sql() )
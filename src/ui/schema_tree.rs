@@ -24,6 +24,7 @@ use crate::ui::NamedBox;
 use std::io::Write;
 use crate::client::ActiveConnectionAction;
 use crate::ui::ConnectionBox;
+use crate::tables::dialect::CsvOptions;
 
 // The TreeView to the bottom left region that shows the database schema and allows 
 // interactions with tables, views and functions.
@@ -48,13 +49,14 @@ pub struct SchemaTree {
     pub report_action : gio::SimpleAction,
     pub form : super::Form,
     pub import_dialog : ImportDialog,
+    pub import_preview_dialog : ImportPreviewDialog,
     pub report_dialog : ReportDialog,
     pub report_export_dialog : filecase::SaveDialog
 }
 
 // TODO views with a homonimous table are not being shown at the schema tree.
 
-const ALL_TYPES : [DBType; 15] = [
+const ALL_TYPES : [DBType; 16] = [
     DBType::Bool,
     DBType::I16,
     DBType::I32,
@@ -65,6 +67,7 @@ const ALL_TYPES : [DBType; 15] = [
     DBType::Text,
     DBType::Date,
     DBType::Time,
+    DBType::Timestamp,
     DBType::Bytes,
     DBType::Json,
     DBType::Xml,
@@ -203,6 +206,7 @@ impl SchemaTree {
             }
         });
         let import_dialog = ImportDialog::build();
+        let import_preview_dialog = ImportPreviewDialog::build();
         import_action.connect_activate({
             let import_dialog = import_dialog.clone();
             move |_, _| {
@@ -265,6 +269,7 @@ impl SchemaTree {
             call_action,
             form,
             import_dialog,
+            import_preview_dialog,
             report_dialog,
             report_export_dialog,
         }
@@ -371,6 +376,12 @@ impl React<ActiveConnection> for SchemaTree {
                 schema_tree.repopulate(vec![DBObject::Schema { name : format!("Catalog unavailable"), children : Vec::new() }]);
             }
         });
+        conn.connect_import_preview({
+            let import_preview_dialog = self.import_preview_dialog.clone();
+            move |(path, dst, sql)| {
+                import_preview_dialog.show_preview(path, dst, &sql);
+            }
+        });
         conn.connect_db_disconnected({
             let schema_tree = self.clone();
             move |_| {
@@ -412,11 +423,17 @@ impl React<ActiveConnection> for SchemaTree {
                             action.set_state(&String::new().to_variant());
                         }
                     },
-                    Some(DBObject::Schema { .. }) => {
-                        for action in [&insert_action, &query_action, &import_action, &call_action, &report_action] {
+                    Some(DBObject::Schema { name, .. }) => {
+                        for action in [&insert_action, &query_action, &call_action, &report_action] {
                             action.set_enabled(false);
                             action.set_state(&String::new().to_variant());
                         }
+
+                        // Files imported into a schema create a new table. The views and functions
+                        // groups are also represented as schemas, but can't hold tables.
+                        let accepts_tables = !name.ends_with(')');
+                        import_action.set_enabled(accepts_tables);
+                        import_action.set_state(&String::new().to_variant());
                     },
                     Some(DBObject::Function { .. }) => {
                         let s = serde_json::to_string(&opt_obj.unwrap()).unwrap().to_variant();
@@ -554,10 +571,107 @@ impl ImportDialog {
         super::configure_dialog(&dialog, true);
         let filter = FileFilter::new();
         filter.add_pattern("*.csv");
+        filter.add_pattern("*.tsv");
+        filter.add_pattern("*.txt");
+        filter.add_pattern("*.parquet");
+        filter.add_pattern("*.arrow");
         dialog.set_filter(&filter);
+
+        // Options for delimited text files. Automatic options are detected from the file content.
+        dialog.add_choice("delimiter", "Delimiter", &[("auto", "Automatic"), (",", "Comma"), (";", "Semicolon"), ("tab", "Tab"), ("|", "Pipe")]);
+        dialog.add_choice("quote", "Quote", &[("auto", "Automatic"), ("\"", "Double quote"), ("'", "Single quote")]);
+        dialog.add_choice("header", "Header", &[("auto", "Automatic"), ("true", "First row"), ("false", "None")]);
+        dialog.add_choice("encoding", "Encoding", &[("auto", "Automatic"), ("utf-8", "UTF-8"), ("latin-1", "Latin-1")]);
+        dialog.add_choice("decimal", "Decimal", &[("auto", "Automatic"), (".", "Point"), (",", "Comma")]);
+        for choice in ["delimiter", "quote", "header", "encoding", "decimal"] {
+            dialog.set_choice(choice, "auto");
+        }
+
+        // Options that are never detected, since they cannot be told apart from regular content.
+        dialog.add_choice("escape", "Escape", &[("none", "Doubled quote"), ("\\", "Backslash")]);
+        dialog.add_choice("null", "Null", &[("none", "None"), ("empty", "Empty field"), ("NULL", "NULL"), ("NA", "NA"), ("\\N", "\\N")]);
+        dialog.add_choice("skip", "Skip rows", &[("0", "None"), ("1", "1"), ("2", "2"), ("3", "3"), ("4", "4"), ("5", "5"), ("10", "10")]);
+        dialog.set_choice("escape", "none");
+        dialog.set_choice("null", "none");
+        dialog.set_choice("skip", "0");
         Self { dialog }
     }
 
+    /// Reads the options for delimited text files selected at the dialog.
+    pub fn csv_options(dialog : &FileChooserDialog) -> CsvOptions {
+        let choice = |id : &str| dialog.choice(id).map(|c| c.to_string() ).filter(|c| &c[..] != "auto" );
+        CsvOptions {
+            delimiter : choice("delimiter").map(|d| if &d[..] == "tab" { b'\t' } else { d.as_bytes()[0] }),
+            quote : choice("quote").map(|q| q.as_bytes()[0] ),
+            header : choice("header").map(|h| &h[..] == "true" ),
+            encoding : choice("encoding").and_then(|e| e.parse().ok() ),
+            decimal_comma : choice("decimal").map(|d| &d[..] == "," ),
+            escape : choice("escape").filter(|e| &e[..] != "none" ).map(|e| e.as_bytes()[0] ),
+            null : choice("null").filter(|n| &n[..] != "none" ).map(|n| if &n[..] == "empty" { String::new() } else { n }),
+            skip_rows : choice("skip").and_then(|s| s.parse().ok() ).unwrap_or(0),
+            ..Default::default()
+        }
+    }
+
+}
+
+/// Shows the statement that creates the destination table before a file
+/// is imported into a schema, so the user can review the inferred column types.
+#[derive(Debug, Clone)]
+pub struct ImportPreviewDialog {
+    pub dialog : Dialog,
+    view : TextView,
+    pub btn_import : Button,
+
+    // File path and destination table waiting for confirmation.
+    pub pending : Rc<RefCell<Option<(String, String)>>>
+}
+
+impl ImportPreviewDialog {
+
+    pub fn build() -> Self {
+        let dialog = Dialog::new();
+        dialog.set_title(Some("Create table"));
+        crate::ui::configure_dialog(&dialog, true);
+        let view = TextView::new();
+        view.set_editable(false);
+        view.set_monospace(true);
+        view.set_cursor_visible(false);
+        let scroll = ScrolledWindow::new();
+        scroll.set_child(Some(&view));
+        scroll.set_min_content_height(240);
+        scroll.set_vexpand(true);
+
+        let btn_import = Button::builder().label("Create and import").build();
+        btn_import.style_context().add_class("pill");
+        btn_import.style_context().add_class("suggested-action");
+        btn_import.set_hexpand(false);
+        btn_import.set_halign(Align::Center);
+        let bx = Box::new(Orientation::Vertical, 0);
+        bx.append(&scroll);
+        bx.append(&btn_import);
+        dialog.set_child(Some(&bx));
+
+        super::set_margins(&btn_import, 64,  16);
+        super::set_margins(&bx, 32,  32);
+
+        let pending = Rc::new(RefCell::new(None));
+        dialog.connect_close_request({
+            let pending = pending.clone();
+            move |_| {
+                pending.replace(None);
+                glib::signal::Inhibit(false)
+            }
+        });
+        Self { dialog, view, btn_import, pending }
+    }
+
+    pub fn show_preview(&self, path : String, dst : String, sql : &str) {
+        self.view.buffer().set_text(sql);
+        self.pending.replace(Some((path, dst)));
+        self.dialog.show();
+    }
+
 }
 
 #[derive(Debug, Clone)]
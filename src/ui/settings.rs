@@ -126,7 +126,9 @@ pub struct ConnBox {
     list : ListBox,
     pub app_name_entry : Entry,
     pub timeout_scale : Scale,
-    pub save_switch : Switch
+    pub save_switch : Switch,
+    pub vault_switch : Switch,
+    pub vault_entry : PasswordEntry
 }
 
 impl ConnBox {
@@ -146,7 +148,21 @@ impl ConnBox {
         save_row.set_selectable(false);
         let save_switch = Switch::new();
         list.append(&NamedBox::new("Remember credentials", Some("Store credentials (except passwords)\nand load them at future sessions"), save_switch.clone()).bx);
-        Self { app_name_entry, timeout_scale, list, save_switch  }
+        let vault_switch = Switch::new();
+        list.append(&NamedBox::new("Password vault", Some("Store passwords at a file encrypted\nwith a master passphrase"), vault_switch.clone()).bx);
+        let vault_entry = PasswordEntry::new();
+        vault_entry.set_show_peek_icon(true);
+        vault_entry.set_placeholder_text(Some("Passphrase"));
+        vault_entry.set_sensitive(false);
+        list.append(&NamedBox::new("Unlock vault", Some("Press enter to unlock the vault (or to create\nit, if this is the first time it is used)"), vault_entry.clone()).bx);
+        vault_switch.connect_state_set({
+            let vault_entry = vault_entry.clone();
+            move |switch, _| {
+                vault_entry.set_sensitive(switch.is_active());
+                Inhibit(false)
+            }
+        });
+        Self { app_name_entry, timeout_scale, list, save_switch, vault_switch, vault_entry }
     }
     
 }
@@ -204,7 +220,10 @@ impl EditorBox {
 pub struct ExecutionBox {
     pub list : ListBox,
     pub row_limit_spin : SpinButton,
+    pub page_size_spin : SpinButton,
     pub schedule_scale : Scale,
+    pub channel_entry : Entry,
+    pub filter_entry : Entry,
     pub timeout_scale : Scale,
     pub dml_switch : Switch,
     pub ddl_switch : Switch,
@@ -219,6 +238,9 @@ impl ExecutionBox {
         let row_limit_spin = SpinButton::with_range(0.0, 10_000.0, 1.0);
         row_limit_spin.set_digits(0);
         row_limit_spin.set_value(500.);
+        let page_size_spin = SpinButton::with_range(0.0, 100_000.0, 1.0);
+        page_size_spin.set_digits(0);
+        page_size_spin.set_value(500.);
 
         // let col_limit_spin = SpinButton::with_range(0.0, 100.0, 1.0);
         // col_limit_spin.set_digits(0);
@@ -240,9 +262,17 @@ impl ExecutionBox {
         // overflow_combo.append_text("Random sample (ordered)");
 
         list.append(&NamedBox::new("Row limit", None, row_limit_spin.clone()).bx);
+        list.append(&NamedBox::new("Page size", Some("Rows fetched at a time for query\nresults (zero fetches all rows)"), page_size_spin.clone()).bx);
         // list.append(&NamedBox::new("Column limit", None, col_limit_spin.clone()).bx);
         // list.append(&NamedBox::new("Row overflow", Some("Which rows to display when results\n extrapolate the row limit"), schedule_scale.clone()).bx);
         list.append(&NamedBox::new("Schedule interval", Some("Interval (in seconds)\nbetween scheduled executions"), schedule_scale.clone()).bx);
+
+        let channel_entry = Entry::new();
+        channel_entry.set_placeholder_text(Some("channel"));
+        let filter_entry = Entry::new();
+        filter_entry.set_placeholder_text(Some("{ \"key\" : \"value\" }"));
+        list.append(&NamedBox::new("Notification channel", Some("Channel that triggers executions\nin notification mode"), channel_entry.clone()).bx);
+        list.append(&NamedBox::new("Notification filter", Some("JSON object the notification payload\nmust match (optional)"), filter_entry.clone()).bx);
         list.append(&NamedBox::new("Statement timeout", Some("Maximum time (in seconds)\nto wait for database response"), timeout_scale.clone()).bx);
         
         let dml_switch = Switch::new();
@@ -255,7 +285,7 @@ impl ExecutionBox {
 
         set_all_not_selectable(&list);
         
-        Self { list, row_limit_spin, /*col_limit_spin*/ schedule_scale, timeout_scale, dml_switch, ddl_switch, async_switch }
+        Self { list, row_limit_spin, page_size_spin, /*col_limit_spin*/ schedule_scale, channel_entry, filter_entry, timeout_scale, dml_switch, ddl_switch, async_switch }
     }
 
 }
@@ -281,22 +311,45 @@ the settings window is opened. */
 #[derive(Debug, Clone)]
 pub struct SecurityRow {
     pub exp_row : ExpanderRow,
+    pub ssl_combo : ComboBoxText,
     pub tls_combo : ComboBoxText,
     pub hostname_switch : Switch,
-    pub cert_entry : Entry
+    pub cert_entry : Entry,
+    pub client_cert_entry : Entry,
+    pub client_key_entry : Entry,
+    pub passphrase_entry : PasswordEntry
 }
 
+const SSL_MODE_MSG : &str =
+r#"Whether connections are encrypted, and whether the
+server certificate and hostname are verified."#;
+
 const TLS_MSG : &str =
-r#"Connecting with TLS disabled is only supported
-for hosts accessible locally or through a private network."#;
+r#"Minimum TLS version of encrypted connections. Disabling
+TLS is only advised for hosts accessible locally or through
+a private network."#;
 
 const HOSTNAME_MSG : &str =
 r#"Disabling verification is discouraged, unless
 you are connecting through a trusted network."#;
 
 const CERT_MSG : &str =
-r#"Path to the root certificate
-(.crt or .pem file)."#;
+r#"Path to the root certificate (.crt or .pem file).
+The system certificates are used when empty."#;
+
+const CLIENT_CERT_MSG : &str =
+r#"Certificate presented to servers that authenticate
+clients by certificate (.pem, .crt, .p12 or .pfx file)."#;
+
+const CLIENT_KEY_MSG : &str =
+r#"Private key of the client certificate
+(unencrypted PKCS#8 .pem or .key file)."#;
+
+const PASSPHRASE_MSG : &str =
+r#"Passphrase of the PKCS#12 archive
+(.p12 or .pfx file). It is not saved with
+the settings, but kept at the password
+vault when it is enabled."#;
 
 /* The security settings work a bit differently, since its menu is generated
 dynamically every time the settings window is opened. Any changes in the new
@@ -307,7 +360,9 @@ the activated signal in this enum. */
 pub enum SecurityChange {
     Hostname { host : String, verify : Option<bool> },
     TLSVersion { host : String, version : Option<TlsVersion> },
-    Certificate { host : String, path : Option<String> }
+    Certificate { host : String, path : Option<String> },
+    ClientCertificate { host : String, cert : Option<ClientCert> },
+    SslMode { host : String, mode : SslMode }
 }
 
 impl SecurityChange {
@@ -316,7 +371,9 @@ impl SecurityChange {
         match self {
             SecurityChange::Hostname { ref host, .. } => &host[..],
             SecurityChange::TLSVersion { ref host, .. } => &host[..],
-            SecurityChange::Certificate { ref host, .. } => &host[..]
+            SecurityChange::Certificate { ref host, .. } => &host[..],
+            SecurityChange::ClientCertificate { ref host, .. } => &host[..],
+            SecurityChange::SslMode { ref host, .. } => &host[..]
         }
     }
 
@@ -337,6 +394,8 @@ pub fn try_modify_security_for_conn(
                 if version.is_none() {
                     conn.security.cert_path = None;
                     conn.security.verify_hostname = None;
+                    conn.security.client_cert = None;
+                    conn.security.ssl_mode = None;
                 }
                 true
             } else {
@@ -349,6 +408,13 @@ pub fn try_modify_security_for_conn(
                     verify.is_none()
                 {
                     conn.security.verify_hostname = verify.clone();
+
+                    // Hostname verification distinguishes the verify-full and verify-ca modes.
+                    match (conn.security.ssl_mode, verify) {
+                        (Some(SslMode::VerifyCa), Some(true)) => conn.security.ssl_mode = Some(SslMode::VerifyFull),
+                        (Some(SslMode::VerifyFull), Some(false)) => conn.security.ssl_mode = Some(SslMode::VerifyCa),
+                        _ => { }
+                    }
                     true
                 } else {
                     false
@@ -371,11 +437,65 @@ pub fn try_modify_security_for_conn(
             } else {
                 false
             }
+        },
+
+        SecurityChange::SslMode { ref host, mode } => {
+            if &host[..] == &conn.host[..] {
+                if *mode == SslMode::Disable {
+                    conn.security = Security::new_insecure();
+                } else {
+                    if conn.security.tls_version.is_none() {
+                        conn.security.tls_version = Some(TlsVersion { major : 1, minor : 2 });
+                    }
+                    conn.security.verify_hostname = Some(mode.verifies_hostname());
+                    conn.security.ssl_mode = Some(*mode);
+                }
+                true
+            } else {
+                false
+            }
+        },
+
+        SecurityChange::ClientCertificate { ref host, cert } => {
+            if &host[..] == &conn.host[..] {
+                if (conn.security.tls_version.is_some() && cert.is_some()) ||
+                    cert.is_none()
+                {
+                    conn.security.client_cert = cert.clone();
+                    true
+                } else {
+                    false
+                }
+            } else {
+                false
+            }
         }
 
     }
 }
 
+/* PKCS#12 archives hold both the certificate and the key, so the key entry is only
+used for PEM certificates, and the passphrase entry only for PKCS#12 archives. */
+fn client_cert_from_entries(cert_entry : &Entry, key_entry : &Entry, passphrase_entry : &PasswordEntry) -> Option<ClientCert> {
+    let path = cert_entry.text().to_string();
+    if path.is_empty() {
+        return None;
+    }
+    if ClientCert::is_pkcs12_path(&path) {
+        let passphrase = passphrase_entry.text().to_string();
+        Some(ClientCert::Pkcs12 { path, passphrase : if passphrase.is_empty() { None } else { Some(passphrase) } })
+    } else {
+        Some(ClientCert::Pem { cert_path : path, key_path : key_entry.text().to_string() })
+    }
+}
+
+fn update_client_cert_sensitivity(cert_entry : &Entry, key_entry : &Entry, passphrase_entry : &PasswordEntry) {
+    let enabled = cert_entry.is_sensitive();
+    let is_pkcs12 = ClientCert::is_pkcs12_path(&cert_entry.text());
+    key_entry.set_sensitive(enabled && !is_pkcs12);
+    passphrase_entry.set_sensitive(enabled && is_pkcs12);
+}
+
 fn update_security_row_info(exp_row : &ExpanderRow, info : &ConnectionInfo) {
     exp_row.set_title(&info.host);
     exp_row.set_subtitle(&info.description());
@@ -392,22 +512,60 @@ impl SecurityRow {
         let exp_row = libadwaita::ExpanderRow::new();
         exp_row.set_selectable(false);
         update_security_row_info(&exp_row, &info);
+        let ssl_combo = ComboBoxText::new();
+        for mode in SslMode::ALL {
+            ssl_combo.append(Some(mode.name()), mode.name());
+        }
+        let mode = info.security.effective_ssl_mode();
+        ssl_combo.set_active_id(Some(mode.name()));
         let tls_combo = ComboBoxText::new();
         let hostname_switch = Switch::new();
         let cert_entry = Entry::new();
         cert_entry.set_sensitive(info.security.tls_version.is_some());
-        hostname_switch.set_sensitive(info.security.tls_version.is_some());
+        hostname_switch.set_sensitive(info.security.tls_version.is_some() && mode.verifies_certificate());
         cert_entry.set_primary_icon_name(Some("application-certificate-symbolic"));
         cert_entry.set_placeholder_text(Some("~/certificate.pem"));
         cert_entry.set_max_width_chars(40);
 
+        let client_cert_entry = Entry::new();
+        let client_key_entry = Entry::new();
+        let passphrase_entry = PasswordEntry::new();
+        client_cert_entry.set_primary_icon_name(Some("application-certificate-symbolic"));
+        client_cert_entry.set_placeholder_text(Some("~/client.pem"));
+        client_cert_entry.set_max_width_chars(40);
+        client_key_entry.set_primary_icon_name(Some("dialog-password-symbolic"));
+        client_key_entry.set_placeholder_text(Some("~/client.key"));
+        client_key_entry.set_max_width_chars(40);
+        passphrase_entry.set_show_peek_icon(true);
+        match info.security.client_cert.as_ref() {
+            Some(ClientCert::Pem { cert_path, key_path }) => {
+                client_cert_entry.set_text(cert_path);
+                client_key_entry.set_text(key_path);
+            },
+            Some(ClientCert::Pkcs12 { path, passphrase }) => {
+                client_cert_entry.set_text(path);
+                passphrase_entry.set_text(passphrase.as_ref().map(|p| &p[..] ).unwrap_or(""));
+            },
+            None => { }
+        }
+        client_cert_entry.set_sensitive(info.security.tls_version.is_some());
+        update_client_cert_sensitivity(&client_cert_entry, &client_key_entry, &passphrase_entry);
+
+        let ssl_bx = NamedBox::new("SSL mode", Some(SSL_MODE_MSG), ssl_combo.clone());
         let enc_bx = NamedBox::new("Encryption", Some(TLS_MSG), tls_combo.clone());
         let cert_bx = NamedBox::new("Certificate", Some(CERT_MSG), cert_entry.clone());
         let hostname_bx = NamedBox::new("Verify hostname", Some(HOSTNAME_MSG), hostname_switch.clone());
-        let rows = [ListBoxRow::new(), ListBoxRow::new(), ListBoxRow::new()];
-        rows[0].set_child(Some(&enc_bx.bx));
-        rows[1].set_child(Some(&cert_bx.bx));
-        rows[2].set_child(Some(&hostname_bx.bx));
+        let client_cert_bx = NamedBox::new("Client certificate", Some(CLIENT_CERT_MSG), client_cert_entry.clone());
+        let client_key_bx = NamedBox::new("Client key", Some(CLIENT_KEY_MSG), client_key_entry.clone());
+        let passphrase_bx = NamedBox::new("Passphrase", Some(PASSPHRASE_MSG), passphrase_entry.clone());
+        let rows = [ListBoxRow::new(), ListBoxRow::new(), ListBoxRow::new(), ListBoxRow::new(), ListBoxRow::new(), ListBoxRow::new(), ListBoxRow::new()];
+        rows[0].set_child(Some(&ssl_bx.bx));
+        rows[1].set_child(Some(&enc_bx.bx));
+        rows[2].set_child(Some(&cert_bx.bx));
+        rows[3].set_child(Some(&hostname_bx.bx));
+        rows[4].set_child(Some(&client_cert_bx.bx));
+        rows[5].set_child(Some(&client_key_bx.bx));
+        rows[6].set_child(Some(&passphrase_bx.bx));
         for r in &rows {
             r.set_selectable(false);
             r.set_activatable(false);
@@ -449,6 +607,10 @@ impl SecurityRow {
             let host = info.host.to_string();
             let cert_entry = cert_entry.clone();
             let hostname_switch = hostname_switch.clone();
+            let ssl_combo = ssl_combo.clone();
+            let client_cert_entry = client_cert_entry.clone();
+            let client_key_entry = client_key_entry.clone();
+            let passphrase_entry = passphrase_entry.clone();
             move |tls_combo| {
                 let active_txt = tls_combo.active_text();
                 if let Some(txt) = active_txt {
@@ -482,8 +644,18 @@ impl SecurityRow {
                         if !cert_entry.text().is_empty() {
                             cert_entry.set_text("");
                         }
+                        for entry in [&client_cert_entry, &client_key_entry] {
+                            if !entry.text().is_empty() {
+                                entry.set_text("");
+                            }
+                        }
+                        if !passphrase_entry.text().is_empty() {
+                            passphrase_entry.set_text("");
+                        }
                         hostname_switch.set_sensitive(false);
                         cert_entry.set_sensitive(false);
+                        client_cert_entry.set_sensitive(false);
+                        update_client_cert_sensitivity(&client_cert_entry, &client_key_entry, &passphrase_entry);
                     }
 
                     // Do the actual encryption change.
@@ -502,6 +674,46 @@ impl SecurityRow {
                         cert_entry.set_sensitive(true);
                         cert_entry.set_text("");
                     }
+                    if version.is_some() && !client_cert_entry.is_sensitive() {
+                        client_cert_entry.set_sensitive(true);
+                        update_client_cert_sensitivity(&client_cert_entry, &client_key_entry, &passphrase_entry);
+                    }
+
+                    // Keep the sslmode consistent with the encryption state.
+                    let ssl_disabled = ssl_combo.active_id().map(|id| &id[..] == SslMode::Disable.name() ).unwrap_or(true);
+                    if version.is_none() && !ssl_disabled {
+                        ssl_combo.set_active_id(Some(SslMode::Disable.name()));
+                    }
+                    if version.is_some() && ssl_disabled {
+                        ssl_combo.set_active_id(Some(SslMode::VerifyFull.name()));
+                    }
+                }
+            }
+        });
+        ssl_combo.connect_changed({
+            let action = action.clone();
+            let host = info.host.to_string();
+            let tls_combo = tls_combo.clone();
+            let hostname_switch = hostname_switch.clone();
+            move |ssl_combo| {
+                let mode = match ssl_combo.active_id().and_then(|id| id.parse::<SslMode>().ok() ) {
+                    Some(mode) => mode,
+                    None => return
+                };
+                let tls_disabled = tls_combo.active_id().map(|id| &id[..] == "0" ).unwrap_or(true);
+                if mode == SslMode::Disable && !tls_disabled {
+                    tls_combo.set_active_id(Some("0"));
+                }
+                if mode != SslMode::Disable && tls_disabled {
+                    tls_combo.set_active_id(Some("3"));
+                }
+                let change = SecurityChange::SslMode { host : host.clone(), mode };
+                action.activate(Some(&serde_json::to_string(&change).unwrap().to_variant()));
+
+                // Hostnames are only verified together with the server certificate.
+                hostname_switch.set_sensitive(mode.verifies_certificate());
+                if hostname_switch.is_active() != mode.verifies_hostname() {
+                    hostname_switch.set_active(mode.verifies_hostname());
                 }
             }
         });
@@ -519,13 +731,42 @@ impl SecurityRow {
                 action.activate(Some(&serde_json::to_string(&change).unwrap().to_variant()));
             }
         });
+        let send_client_cert = {
+            let action = action.clone();
+            let host = info.host.to_string();
+            let client_cert_entry = client_cert_entry.clone();
+            let client_key_entry = client_key_entry.clone();
+            let passphrase_entry = passphrase_entry.clone();
+            move || {
+                update_client_cert_sensitivity(&client_cert_entry, &client_key_entry, &passphrase_entry);
+                let cert = client_cert_from_entries(&client_cert_entry, &client_key_entry, &passphrase_entry);
+                let change = SecurityChange::ClientCertificate { host : host.clone(), cert };
+                action.activate(Some(&serde_json::to_string(&change).unwrap().to_variant()));
+            }
+        };
+        for entry in [&client_cert_entry, &client_key_entry] {
+            entry.connect_changed({
+                let send_client_cert = send_client_cert.clone();
+                move |_| send_client_cert()
+            });
+        }
+        passphrase_entry.connect_changed(move |_| send_client_cert() );
         hostname_switch.connect_state_set({
             let action = action.clone();
             let host = info.host.to_string();
             let _exp_row = exp_row.clone();
+            let ssl_combo = ssl_combo.clone();
             move |switch, _| {
                 let change = SecurityChange::Hostname { host : host.clone(), verify : Some(switch.is_active()) };
                 action.activate(Some(&serde_json::to_string(&change).unwrap().to_variant()));
+                let verifies_certificate = ssl_combo.active_id()
+                    .and_then(|id| id.parse::<SslMode>().ok() )
+                    .map(|mode| mode.verifies_certificate() )
+                    .unwrap_or(false);
+                if verifies_certificate {
+                    let mode = if switch.is_active() { SslMode::VerifyFull } else { SslMode::VerifyCa };
+                    ssl_combo.set_active_id(Some(mode.name()));
+                }
                 Inhibit(false)
             }
         });
@@ -558,7 +799,7 @@ impl SecurityRow {
             }
         });*/
 
-        Self { exp_row, tls_combo, hostname_switch, cert_entry }
+        Self { exp_row, ssl_combo, tls_combo, hostname_switch, cert_entry, client_cert_entry, client_key_entry, passphrase_entry }
     }
 
 }
@@ -580,7 +821,8 @@ impl SecurityBox {
         for conn in conns.iter().sorted_by(|a, b| a.host.cmp(&b.host) ).unique_by(|c| &c.host[..] ) {
             let must_add = !conn.host.is_empty() &&
                 &conn.host[..] != crate::client::DEFAULT_HOST
-                && !conn.is_file();
+                && !conn.is_file()
+                && !conn.is_socket();
             if must_add {
                 let sec_row = SecurityRow::new(conn.clone(), &self.update_action);
                 self.list.append(&sec_row.exp_row);
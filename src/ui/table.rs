@@ -6,9 +6,15 @@ For a copy, see http://www.gnu.org/licenses.*/
 use gtk4::*;
 use gtk4::prelude::*;
 use crate::tables::table::Table;
+use crate::tables::nullable::NullableColumn;
+use crate::sql::edit::TableEdit;
+use crate::tables::view::{TableView, SortKey, NullOrder, Filter, Predicate};
+use crate::tables::aggregate::Aggregate;
+use crate::sql::object::DBObject;
+use crate::client::Engine;
 use gtk4::gdk::Cursor;
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{RefCell, Ref};
 
 #[derive(Debug, Clone)]
 pub struct TableAction {
@@ -17,7 +23,12 @@ pub struct TableAction {
     pub num_scale : Scale,
     pub filter_entry : Entry,
     pub btn_ascending : ToggleButton,
-    pub btn_descending : ToggleButton
+    pub btn_descending : ToggleButton,
+
+    // Only shown for query results that might be written back to the database.
+    pub btn_edit : ToggleButton,
+
+    pub btn_summary : MenuButton
 }
 
 fn configure_scale(scale : &Scale) {
@@ -88,7 +99,155 @@ impl TableAction {
         bx.set_margin_end(128);
         action.set_center_widget(Some(&bx));
 
-        Self { action, btn_ascending, btn_descending, filter_entry, fst_scale, num_scale }
+        let btn_edit = ToggleButton::builder().icon_name("document-edit-symbolic").build();
+        btn_edit.style_context().add_class("flat");
+        btn_edit.set_tooltip_text(Some("Edit rows"));
+        btn_edit.set_visible(false);
+        action.pack_end(&btn_edit);
+
+        let btn_summary = MenuButton::builder().icon_name("view-grid-symbolic").build();
+        btn_summary.set_tooltip_text(Some("Group or pivot rows"));
+        btn_summary.set_has_frame(false);
+        action.pack_start(&btn_summary);
+
+        Self { action, btn_ascending, btn_descending, filter_entry, fst_scale, num_scale, btn_edit, btn_summary }
+    }
+
+}
+
+#[derive(Debug, Clone)]
+pub struct TableEditBar {
+    pub action : ActionBar,
+    pub btn_add : Button,
+    pub btn_discard : Button,
+    pub btn_review : Button
+}
+
+impl TableEditBar {
+
+    pub fn new() -> Self {
+        let btn_add = Button::builder().icon_name("list-add-symbolic").build();
+        btn_add.style_context().add_class("flat");
+        btn_add.set_tooltip_text(Some("Insert row"));
+        let btn_discard = Button::builder().label("Discard").build();
+        let btn_review = Button::builder().label("Review changes").build();
+        btn_review.style_context().add_class("suggested-action");
+
+        let action = ActionBar::new();
+        action.set_revealed(false);
+        action.set_valign(Align::End);
+        action.set_vexpand(false);
+        action.pack_start(&btn_add);
+        action.pack_end(&btn_review);
+        action.pack_end(&btn_discard);
+        Self { action, btn_add, btn_discard, btn_review }
+    }
+
+}
+
+/// Aggregates the rows of a table by the values of a column, optionally pivoting
+/// the values of a second column into new columns.
+#[derive(Debug, Clone)]
+pub struct SummaryPopover {
+    pub popover : Popover,
+    group_combo : ComboBoxText,
+    pivot_combo : ComboBoxText,
+    value_combo : ComboBoxText,
+    agg_combo : ComboBoxText,
+    pub btn_apply : Button,
+    err_lbl : Label
+}
+
+impl SummaryPopover {
+
+    pub fn build() -> Self {
+        let group_combo = ComboBoxText::new();
+        let pivot_combo = ComboBoxText::new();
+        let value_combo = ComboBoxText::new();
+        let agg_combo = ComboBoxText::new();
+        for agg in Aggregate::ALL {
+            agg_combo.append(Some(agg.name()), agg.name());
+        }
+        agg_combo.set_active_id(Some(Aggregate::Count.name()));
+
+        let grid = Grid::new();
+        grid.set_row_spacing(6);
+        grid.set_column_spacing(12);
+        let rows = [("Group by", &group_combo), ("Pivot", &pivot_combo), ("Value", &value_combo), ("Aggregate", &agg_combo)];
+        for (ix, (name, combo)) in rows.iter().enumerate() {
+            let lbl = Label::new(Some(*name));
+            lbl.set_halign(Align::Start);
+            combo.set_hexpand(true);
+            grid.attach(&lbl, 0, ix as i32, 1, 1);
+            grid.attach(*combo, 1, ix as i32, 1, 1);
+        }
+
+        let btn_apply = Button::with_label("Summarize");
+        btn_apply.style_context().add_class("suggested-action");
+        let err_lbl = Label::new(None);
+        err_lbl.style_context().add_class("error");
+        err_lbl.set_visible(false);
+        err_lbl.set_wrap(true);
+        err_lbl.set_max_width_chars(40);
+
+        let bx = Box::new(Orientation::Vertical, 12);
+        bx.append(&grid);
+        bx.append(&err_lbl);
+        bx.append(&btn_apply);
+        super::set_margins(&bx, 12, 12);
+        let popover = Popover::new();
+        popover.set_child(Some(&bx));
+        Self { popover, group_combo, pivot_combo, value_combo, agg_combo, btn_apply, err_lbl }
+    }
+
+    pub fn set_columns(&self, names : &[String]) {
+        for combo in [&self.group_combo, &self.pivot_combo, &self.value_combo] {
+            let past = combo.active();
+            combo.remove_all();
+            if combo == &self.pivot_combo {
+                combo.append(Some(""), "None");
+            }
+            for (ix, name) in names.iter().enumerate() {
+                combo.append(Some(&ix.to_string()), name);
+            }
+            if past.is_some() {
+                combo.set_active(past);
+            } else {
+                combo.set_active(Some(0));
+            }
+        }
+        self.err_lbl.set_visible(false);
+    }
+
+    pub fn show_error(&self, msg : &str) {
+        self.err_lbl.set_text(msg);
+        self.err_lbl.set_visible(true);
+    }
+
+    /// Aggregates the table with the columns chosen by the user. The summary is named after
+    /// the aggregate and the grouping columns.
+    pub fn summarize(&self, tbl : &Table) -> Result<Table, String> {
+        let col_ix = |combo : &ComboBoxText| -> Option<usize> {
+            combo.active_id().and_then(|id| id.parse::<usize>().ok() )
+        };
+        let group = col_ix(&self.group_combo).ok_or(format!("No column to group by"))?;
+        let value = col_ix(&self.value_combo).ok_or(format!("No column to aggregate"))?;
+        let agg : Aggregate = self.agg_combo.active_id()
+            .ok_or(format!("No aggregate"))?
+            .parse()?;
+        let names = tbl.names();
+        let (mut summary, title) = match col_ix(&self.pivot_combo) {
+            Some(pivot) => (
+                tbl.pivoted(group, pivot, value, agg)?,
+                format!("{} of {} by {} and {}", agg, names[value], names[group], names[pivot])
+            ),
+            None => (
+                tbl.grouped_by(&[group], &[(value, agg)])?,
+                format!("{} of {} by {}", agg, names[value], names[group])
+            )
+        };
+        summary.set_name(Some(title));
+        Ok(summary)
     }
 
 }
@@ -103,9 +262,50 @@ pub struct TableWidget {
 
     action : TableAction,
 
-    tbl : Rc<Table>,
+    pub summary : SummaryPopover,
 
-    max_nrows : usize
+    tbl : Rc<RefCell<Table>>,
+
+    // Keeps state of user navigation (sort, filter, offset and length)
+    displayed : Rc<RefCell<Option<DisplayedTable>>>,
+
+    // Set when further rows of the table can be fetched from the server.
+    paging : Rc<RefCell<Option<TablePaging>>>,
+
+    max_nrows : usize,
+
+    // Sort keys and filters combined at the column header popovers. Cleared when
+    // the rows are sorted or filtered from the action bar.
+    view : Rc<RefCell<TableView>>,
+
+    // Set while the user edits the rows of a table with a primary key. The displayed rows are
+    // then shown as entries at edit_grid, which replaces grid at the scrolled window.
+    edit : Rc<RefCell<Option<TableEdit>>>,
+
+    edit_grid : Grid,
+
+    edit_bar : TableEditBar
+
+}
+
+#[derive(Clone, Debug)]
+struct TablePaging {
+
+    query : String,
+
+    // Index of the result that returned the table, if its next pages can be fetched.
+    ix : Option<usize>,
+
+    // Rows at the first page. Only the last page has fewer rows.
+    page_size : usize,
+
+    action : gio::SimpleAction,
+
+    // Whether a page was requested, and is still to be appended.
+    waiting : bool,
+
+    // Whether the last page was already appended.
+    complete : bool
 
 }
 
@@ -257,12 +457,295 @@ fn set_table_selection_style(grid : &Grid, col : usize, ncols : usize, was_selec
 impl TableWidget {
 
     pub fn new_from_table(tbl : &Table, max_nrows : usize, _max_ncols : usize) -> Self {
-        let mut tbl_wid = Self::new(tbl.nrows(), max_nrows);
-        tbl_wid.tbl = Rc::new(tbl.clone());
+        let tbl_wid = Self::new(tbl.nrows(), max_nrows);
+        tbl_wid.tbl.replace(tbl.clone());
         tbl_wid.update_data(&tbl, Some(1), Some(max_nrows), true);
         tbl_wid
     }
 
+    /// Builds a table widget holding the rows of a query result. When page holds the index
+    /// of the result, its next pages are requested by activating page_action with the index
+    /// as parameter when the user navigates past the last row, and should be appended with append_page.
+    pub fn new_paged(
+        tbl : &Table,
+        max_nrows : usize,
+        max_ncols : usize,
+        query : &str,
+        page : Option<usize>,
+        page_action : &gio::SimpleAction
+    ) -> Self {
+        let tbl_wid = Self::new_from_table(tbl, max_nrows, max_ncols);
+        tbl_wid.paging.replace(Some(TablePaging {
+            query : query.to_string(),
+            ix : page,
+            page_size : tbl.nrows(),
+            action : page_action.clone(),
+            waiting : false,
+            complete : page.is_none()
+        }));
+        tbl_wid
+    }
+
+    pub fn table(&self) -> Ref<Table> {
+        self.tbl.borrow()
+    }
+
+    /// Aggregates all rows of the table (not only the displayed rows) as chosen at the summary popover.
+    pub fn summarize(&self) -> Result<Table, String> {
+        self.summary.summarize(&self.tbl.borrow())
+    }
+
+    /// Appends the next page of rows of a paged table, keeping the current
+    /// sort and filter of the displayed rows.
+    pub fn append_page(&self, page : &Table) {
+        if let Some(paging) = self.paging.borrow_mut().as_mut() {
+            paging.waiting = false;
+            paging.complete = page.nrows() < paging.page_size;
+        } else {
+            eprintln!("Table is not paged");
+            return;
+        }
+
+        if let Err(e) = self.tbl.borrow_mut().append_rows(page) {
+            eprintln!("{}", e);
+            return;
+        }
+        self.redisplay();
+    }
+
+    /// Replaces the rows of the table by a new result of its query (after the changes made by
+    /// the user were written back), leaving the editing mode.
+    pub fn refresh(&self, tbl : &Table) {
+        self.stop_editing();
+        if let Some(paging) = self.paging.borrow_mut().as_mut() {
+            paging.complete = true;
+        }
+        self.tbl.replace(tbl.clone());
+        if self.displayed.borrow().is_none() {
+            update_cols(&tbl, &self.grid, 1, self.max_nrows);
+        }
+        self.redisplay();
+    }
+
+    // Sorts and filters the table again after its rows changed, keeping the current
+    // sort and filter of the displayed rows.
+    fn redisplay(&self) {
+        let nrows = if let Ok(mut displayed) = self.displayed.try_borrow_mut() {
+            if let Some(displ) = displayed.as_mut() {
+                let tbl = self.tbl.borrow();
+                let view = self.view.borrow();
+                let opt_new_tbl = if !view.is_empty() {
+                    view.apply(&tbl).ok()
+                } else {
+                    match &displ.filtered_by {
+                        Some(key) => tbl.filtered_by(displ.sorted_by, key)
+                            .and_then(|tbl| tbl.sorted_by(displ.sorted_by, displ.ascending) ),
+                        None => tbl.sorted_by(displ.sorted_by, displ.ascending)
+                    }
+                };
+                if let Some(new_tbl) = opt_new_tbl {
+                    displ.tbl = new_tbl;
+                } else {
+                    eprintln!("Failed to get new table");
+                }
+                displ.tbl.nrows()
+            } else {
+                self.tbl.borrow().nrows()
+            }
+        } else {
+            eprintln!("Could not acquire mutable borrow over display table");
+            return;
+        };
+
+        // Re-emitting value-changed updates the displayed rows at the current offset
+        // and the range of the row number scale.
+        let adj = self.action.fst_scale.adjustment();
+        adj.set_upper(nrows.max(1) as f64);
+        adj.emit_by_name::<()>("value-changed", &[]);
+    }
+
+    pub fn max_nrows(&self) -> usize {
+        self.max_nrows
+    }
+
+    /// The query of a paged table.
+    pub fn query(&self) -> Option<String> {
+        self.paging.borrow().as_ref().map(|paging| paging.query.clone() )
+    }
+
+    /// Allows the user to edit the rows of a paged table. The query is sent through edit_action
+    /// when the user starts editing, and through review_action when the user reviews the changes.
+    pub fn set_editable(&self, edit_action : &gio::SimpleAction, review_action : &gio::SimpleAction) {
+        let query = match self.query() {
+            Some(query) => query,
+            None => return
+        };
+        self.action.btn_edit.set_visible(true);
+        self.action.btn_edit.connect_toggled({
+            let edit_action = edit_action.clone();
+            let tbl_wid = self.clone();
+            let query = query.clone();
+            move |btn| {
+                if btn.is_active() {
+                    edit_action.activate(Some(&query.to_variant()));
+                } else {
+                    tbl_wid.stop_editing();
+                }
+            }
+        });
+        self.edit_bar.btn_review.connect_clicked({
+            let review_action = review_action.clone();
+            move |_| {
+                review_action.activate(Some(&query.to_variant()));
+            }
+        });
+        self.edit_bar.btn_discard.connect_clicked({
+            let btn_edit = self.action.btn_edit.clone();
+            move |_| {
+                btn_edit.set_active(false);
+            }
+        });
+        self.edit_bar.btn_add.connect_clicked({
+            let tbl_wid = self.clone();
+            move |_| {
+                tbl_wid.append_inserted_row();
+            }
+        });
+    }
+
+    /// Shows the displayed rows as entries, recording the changes made by the user
+    /// to the given table.
+    pub fn start_editing(&self, obj : &DBObject, engine : Engine) -> Result<(), String> {
+        if self.edit.borrow().is_some() {
+            return Ok(());
+        }
+        let edit = match TableEdit::new(&self.tbl.borrow().names()[..], obj, engine) {
+            Ok(edit) => edit,
+            Err(e) => {
+                self.action.btn_edit.set_active(false);
+                return Err(e);
+            }
+        };
+        let tbl = match self.displayed.borrow().as_ref() {
+            Some(displ) => displ.tbl.clone(),
+            None => self.tbl.borrow().clone()
+        };
+        while self.edit_grid.child_at(0, 0).is_some() {
+            self.edit_grid.remove_row(0);
+        }
+
+        let ncols = tbl.ncols();
+        for (j, name) in tbl.names().iter().enumerate() {
+            let lbl = Label::new(Some(&name[..]));
+            lbl.set_hexpand(true);
+            if edit.is_pk(j) {
+                lbl.set_tooltip_text(Some("Primary key"));
+            }
+            add_header_css(&lbl);
+            self.edit_grid.attach(&lbl, j as i32, 0, 1, 1);
+        }
+
+        let fst_row = (self.action.fst_scale.value() as usize).saturating_sub(1);
+        let nrows = tbl.nrows().saturating_sub(fst_row).min(self.max_nrows);
+        for i in 0..nrows {
+            let row = fst_row + i;
+            let key = edit.row_key(&tbl, row).ok_or(format!("Unable to read primary key of row {}", row + 1))?;
+            for j in 0..ncols {
+                let entry = Entry::new();
+                entry.set_placeholder_text(Some(NullableColumn::NULL));
+                let is_null = tbl.get_column(j).map(|c| c.is_null(row) ).unwrap_or(true);
+                if !is_null {
+                    if let Some(content) = tbl.display_content_at(row, j, None) {
+                        entry.set_text(&content);
+                    }
+                }
+                entry.connect_changed({
+                    let edit = self.edit.clone();
+                    let key = key.clone();
+                    move |entry| {
+                        if let Some(edit) = edit.borrow_mut().as_mut() {
+                            edit.update(key.clone(), j, entry_value(entry));
+                        }
+                    }
+                });
+                self.edit_grid.attach(&entry, j as i32, (i + 1) as i32, 1, 1);
+            }
+            let btn_delete = delete_row_button();
+            btn_delete.connect_clicked({
+                let edit = self.edit.clone();
+                let grid = self.edit_grid.clone();
+                move |_| {
+                    if let Some(edit) = edit.borrow_mut().as_mut() {
+                        edit.delete(key.clone());
+                    }
+                    set_row_sensitive(&grid, i + 1, ncols, false);
+                }
+            });
+            self.edit_grid.attach(&btn_delete, ncols as i32, (i + 1) as i32, 1, 1);
+        }
+
+        self.edit.replace(Some(edit));
+        self.scroll_window.set_child(Some(&self.edit_grid));
+        self.edit_bar.action.set_revealed(true);
+        Ok(())
+    }
+
+    // Appends a row of entries for a new row. Columns left empty take their default values.
+    fn append_inserted_row(&self) {
+        let ix = match self.edit.borrow_mut().as_mut() {
+            Some(edit) => edit.insert(),
+            None => return
+        };
+        let ncols = self.tbl.borrow().ncols();
+        let mut row = 1;
+        while self.edit_grid.child_at(0, row).is_some() {
+            row += 1;
+        }
+        for j in 0..ncols {
+            let entry = Entry::new();
+            entry.set_placeholder_text(Some("DEFAULT"));
+            entry.connect_changed({
+                let edit = self.edit.clone();
+                move |entry| {
+                    if let Some(edit) = edit.borrow_mut().as_mut() {
+                        edit.update_inserted(ix, j, entry_value(entry));
+                    }
+                }
+            });
+            self.edit_grid.attach(&entry, j as i32, row, 1, 1);
+        }
+        let btn_delete = delete_row_button();
+        btn_delete.connect_clicked({
+            let edit = self.edit.clone();
+            let grid = self.edit_grid.clone();
+            move |_| {
+                if let Some(edit) = edit.borrow_mut().as_mut() {
+                    for j in 0..ncols {
+                        edit.update_inserted(ix, j, None);
+                    }
+                }
+                set_row_sensitive(&grid, row as usize, ncols, false);
+            }
+        });
+        self.edit_grid.attach(&btn_delete, ncols as i32, row, 1, 1);
+    }
+
+    /// Changes made by the user since the editing started.
+    pub fn pending_edit(&self) -> Option<TableEdit> {
+        self.edit.borrow().clone()
+    }
+
+    /// Discards any changes and shows the table rows again.
+    pub fn stop_editing(&self) {
+        if self.edit.replace(None).is_some() {
+            self.scroll_window.set_child(Some(&self.grid));
+        }
+        self.edit_bar.action.set_revealed(false);
+        if self.action.btn_edit.is_active() {
+            self.action.btn_edit.set_active(false);
+        }
+    }
+
     pub fn new(nrows : usize, max_nrows : usize) -> TableWidget {
         let grid = Grid::new();
         
@@ -275,6 +758,18 @@ impl TableWidget {
         bx.append(&scroll_window);
         let action = TableAction::new(nrows, max_nrows);
         bx.append(&action.action);
+        let edit_bar = TableEditBar::new();
+        bx.append(&edit_bar.action);
+        let summary = SummaryPopover::build();
+        action.btn_summary.set_popover(Some(&summary.popover));
+        let tbl = Rc::new(RefCell::new(Table::empty(Vec::new())));
+        summary.popover.connect_show({
+            let summary = summary.clone();
+            let tbl = tbl.clone();
+            move |_| {
+                summary.set_columns(&tbl.borrow().names()[..]);
+            }
+        });
 
         TableWidget {
             grid,
@@ -282,7 +777,14 @@ impl TableWidget {
             max_nrows,
             scroll_window,
             action,
-            tbl : Rc::new(Table::empty(Vec::new()))
+            summary,
+            tbl,
+            displayed : Rc::new(RefCell::new(None)),
+            paging : Rc::new(RefCell::new(None)),
+            view : Rc::new(RefCell::new(TableView::default())),
+            edit : Rc::new(RefCell::new(None)),
+            edit_grid : Grid::new(),
+            edit_bar
         }
     }
 
@@ -290,6 +792,142 @@ impl TableWidget {
         self.scroll_window.clone()
     }
 
+    // Shows the rows of the view, starting from the first row.
+    fn set_view(&self, view : TableView) -> Result<(), String> {
+        let new_tbl = view.apply(&self.tbl.borrow())?;
+        let rem_rows = new_tbl.nrows().min(self.max_nrows).max(1) as f64;
+        let fst_max = fst_range(&new_tbl, self.paging.borrow().is_some(), self.max_nrows);
+        update_cols(&new_tbl, &self.grid, 1, self.max_nrows);
+        let (sorted_by, ascending) = view.sort.first().map(|k| (k.col, k.ascending) ).unwrap_or((0, true));
+        self.displayed.replace(Some(DisplayedTable { sorted_by, ascending, filtered_by : None, tbl : new_tbl }));
+        self.view.replace(view);
+
+        // It is important to set the new maxima before setting the new values.
+        self.action.num_scale.set_range(1.0, rem_rows);
+        self.action.fst_scale.set_range(1.0, fst_max);
+        self.action.num_scale.set_value(rem_rows);
+        self.action.fst_scale.set_value(1.0);
+        Ok(())
+    }
+
+    fn header_popover(&self, col : usize) -> Popover {
+        let btn_ascending = Button::builder().icon_name("view-sort-ascending-symbolic").build();
+        let btn_descending = Button::builder().icon_name("view-sort-descending-symbolic").build();
+        btn_ascending.set_tooltip_text(Some("Sort ascending (after previous sort keys)"));
+        btn_descending.set_tooltip_text(Some("Sort descending (after previous sort keys)"));
+        let nulls_switch = Switch::new();
+        nulls_switch.set_valign(Align::Center);
+        let sort_bx = Box::new(Orientation::Horizontal, 6);
+        let sort_btns = Box::new(Orientation::Horizontal, 0);
+        sort_btns.style_context().add_class("linked");
+        sort_btns.append(&btn_ascending);
+        sort_btns.append(&btn_descending);
+        sort_bx.append(&sort_btns);
+        sort_bx.append(&Label::new(Some("Nulls first")));
+        sort_bx.append(&nulls_switch);
+
+        let op_combo = ComboBoxText::new();
+        for op in FILTER_OPERATORS {
+            op_combo.append(Some(op), op);
+        }
+        op_combo.set_active_id(Some("="));
+        let fst_entry = Entry::new();
+        fst_entry.set_placeholder_text(Some("Value"));
+        let snd_entry = Entry::new();
+        snd_entry.set_placeholder_text(Some("Upper value"));
+        snd_entry.set_visible(false);
+        op_combo.connect_changed({
+            let fst_entry = fst_entry.clone();
+            let snd_entry = snd_entry.clone();
+            move |combo| {
+                let op = combo.active_id().map(|id| id.to_string() ).unwrap_or_default();
+                fst_entry.set_visible(op != "is null" && op != "is not null");
+                snd_entry.set_visible(op == "between");
+            }
+        });
+        let filter_bx = Box::new(Orientation::Horizontal, 6);
+        filter_bx.append(&op_combo);
+        filter_bx.append(&fst_entry);
+        filter_bx.append(&snd_entry);
+
+        let btn_and = Button::with_label("And");
+        let btn_or = Button::with_label("Or");
+        let btn_clear = Button::with_label("Clear");
+        let combine_bx = Box::new(Orientation::Horizontal, 6);
+        combine_bx.append(&btn_and);
+        combine_bx.append(&btn_or);
+        combine_bx.append(&btn_clear);
+
+        let err_lbl = Label::new(None);
+        err_lbl.style_context().add_class("error");
+        err_lbl.set_visible(false);
+
+        let bx = Box::new(Orientation::Vertical, 12);
+        bx.append(&sort_bx);
+        bx.append(&filter_bx);
+        bx.append(&combine_bx);
+        bx.append(&err_lbl);
+        super::set_margins(&bx, 12, 12);
+        let popover = Popover::new();
+        popover.set_child(Some(&bx));
+
+        // Applies a changed copy of the current view, keeping the current view if it is invalid.
+        let update_view = {
+            let tbl_wid = self.clone();
+            let err_lbl = err_lbl.clone();
+            move |f : &dyn Fn(&mut TableView)| {
+                let mut view = tbl_wid.view.borrow().clone();
+                f(&mut view);
+                match tbl_wid.set_view(view) {
+                    Ok(_) => {
+                        err_lbl.set_visible(false);
+                    },
+                    Err(e) => {
+                        err_lbl.set_text(&e);
+                        err_lbl.set_visible(true);
+                    }
+                }
+            }
+        };
+        let update_view = Rc::new(update_view);
+        for (btn, ascending) in [(&btn_ascending, true), (&btn_descending, false)] {
+            btn.connect_clicked({
+                let update_view = update_view.clone();
+                let nulls_switch = nulls_switch.clone();
+                move |_| {
+                    let nulls = if nulls_switch.is_active() { NullOrder::First } else { NullOrder::Last };
+                    update_view(&|view| view.then_by(SortKey { col, ascending, nulls }) );
+                }
+            });
+        }
+        for (btn, conjunction) in [(&btn_and, true), (&btn_or, false)] {
+            btn.connect_clicked({
+                let update_view = update_view.clone();
+                let op_combo = op_combo.clone();
+                let fst_entry = fst_entry.clone();
+                let snd_entry = snd_entry.clone();
+                move |_| {
+                    let op = op_combo.active_id().map(|id| id.to_string() ).unwrap_or_default();
+                    if let Some(pred) = filter_predicate(&op, &fst_entry.text(), &snd_entry.text()) {
+                        let filter = Filter::Condition { col, pred };
+                        if conjunction {
+                            update_view(&|view| view.and(filter.clone()) );
+                        } else {
+                            update_view(&|view| view.or(filter.clone()) );
+                        }
+                    }
+                }
+            });
+        }
+        btn_clear.connect_clicked({
+            let update_view = update_view.clone();
+            move |_| {
+                update_view(&|view| view.clear() );
+            }
+        });
+        popover
+    }
+
     fn create_header_cell(
         &self,
         data : &str,
@@ -307,6 +945,22 @@ impl TableWidget {
         let cursor = Cursor::builder().name("pointer").build();
         label.set_cursor(Some(&cursor));
 
+        // Sort keys and filters are combined at a popover shown at secondary click.
+        let popover = self.header_popover(col);
+        popover.set_parent(&label);
+        let secondary = GestureClick::new();
+        secondary.set_button(gdk::BUTTON_SECONDARY);
+        label.add_controller(&secondary);
+        secondary.connect_pressed({
+            let popover = popover.clone();
+            move |_gesture, _n_press, _x, _y| {
+                popover.popup();
+            }
+        });
+        label.connect_destroy(move |_| {
+            popover.unparent();
+        });
+
         // TODO disable selection at Esc key press
         let click = GestureClick::new();
         click.set_button(gdk::BUTTON_PRIMARY);
@@ -318,9 +972,11 @@ impl TableWidget {
             let tbl = self.tbl.clone();
             let max_nrows = self.max_nrows.clone();
             let displayed_tbl = displayed_tbl.clone();
+            let view = self.view.clone();
             move |_gesture, _n_press, _x, _y| {
                 let ctx = label.style_context();
                 let was_selected = ctx.has_class("selected");
+                reset_view(&view, &displayed_tbl);
                 if !was_selected {
                     let _lbl_alloc = label.allocation();
 
@@ -363,9 +1019,11 @@ impl TableWidget {
             let max_nrows = self.max_nrows.clone();
             let tbl = self.tbl.clone();
             let grid = self.grid.clone();
+            let view = self.view.clone();
             move |btn| {
                 if btn.is_active() {
-                    if let Some(sel_col) = selected_col(&grid, tbl.ncols()) {
+                    reset_view(&view, &displayed_tbl);
+                    if let Some(sel_col) = selected_col(&grid, tbl.borrow().ncols()) {
                         update_display_table(
                             &tbl,
                             &displayed_tbl,
@@ -388,9 +1046,11 @@ impl TableWidget {
             let max_nrows = self.max_nrows.clone();
             let tbl = self.tbl.clone();
             let grid = self.grid.clone();
+            let view = self.view.clone();
             move |btn| {
                 if btn.is_active() {
-                    if let Some(sel_col) = selected_col(&grid, tbl.ncols()) {
+                    reset_view(&view, &displayed_tbl);
+                    if let Some(sel_col) = selected_col(&grid, tbl.borrow().ncols()) {
                         update_display_table(
                             &tbl,
                             &displayed_tbl,
@@ -412,13 +1072,29 @@ impl TableWidget {
             let num_scale = self.action.num_scale.clone();
             let displayed_tbl = displayed_tbl.clone();
             let max_rows = self.max_nrows.clone();
+            let paging = self.paging.clone();
             move|adj| {
                 let fst_row = adj.value() as usize;
                 let _num_rows = num_scale.adjustment().value() as usize;
                 if let Ok(displ_tbl) = displayed_tbl.try_borrow() {
                     if let Some(tbl) = &*displ_tbl {
-                        let row_limit = tbl.tbl.nrows().min(max_rows);
-                        let rem_rows = row_limit.saturating_sub(fst_row.saturating_sub(1)).max(1);
+
+                        // Paged tables can be navigated up to their last fetched row.
+                        let row_limit = if paging.borrow().is_some() {
+                            tbl.tbl.nrows()
+                        } else {
+                            tbl.tbl.nrows().min(max_rows)
+                        };
+                        let rem_rows = row_limit.saturating_sub(fst_row.saturating_sub(1)).min(max_rows).max(1);
+
+                        if let Some(paging) = paging.borrow_mut().as_mut() {
+                            if !paging.waiting && !paging.complete && fst_row + max_rows > tbl.tbl.nrows() {
+                                if let Some(ix) = paging.ix {
+                                    paging.waiting = true;
+                                    paging.action.activate(Some(&(ix as u64).to_variant()));
+                                }
+                            }
+                        }
 
                         update_cols(&tbl.tbl, &grid, fst_row, row_limit);
                         num_scale.set_range(1.0, rem_rows as f64);
@@ -464,8 +1140,12 @@ impl TableWidget {
             let max_nrows = self.max_nrows.clone();
             let orig_tbl = self.tbl.clone();
             let btn_ascending = self.action.btn_ascending.clone();
+            let paging = self.paging.clone();
+            let view = self.view.clone();
             move |entry| {
+                view.borrow_mut().clear();
                 let txt = entry.buffer().text();
+                let is_paged = paging.borrow().is_some();
                 let mut num_scale_new_val = None;
                 let mut num_scale_new_max = None;
                 let mut fst_scale_new_val = None;
@@ -473,7 +1153,8 @@ impl TableWidget {
                 if let Ok(mut displ_tbl) = displayed_tbl.try_borrow_mut() {
                     if let Some(mut displ_tbl) = displ_tbl.as_mut() {
                         let ascending = btn_ascending.is_active();
-                        if let Some(sel_col) = selected_col(&grid, orig_tbl.ncols()) {
+                        if let Some(sel_col) = selected_col(&grid, orig_tbl.borrow().ncols()) {
+                            let orig_tbl = orig_tbl.borrow();
                             if txt.is_empty() {
                                 if let Some(orig_sorted) = orig_tbl.sorted_by(sel_col, ascending) {
                                     update_cols(&orig_sorted, &grid, 1, max_nrows);
@@ -481,7 +1162,7 @@ impl TableWidget {
                                     fst_scale_new_val = Some(1.0);
                                     num_scale_new_val = Some(rem_rows);
                                     num_scale_new_max = Some(rem_rows);
-                                    fst_scale_new_max = Some(rem_rows);
+                                    fst_scale_new_max = Some(fst_range(&orig_sorted, is_paged, max_nrows));
                                     displ_tbl.tbl = orig_sorted;
                                     displ_tbl.sorted_by = sel_col;
                                     displ_tbl.ascending = ascending;
@@ -499,7 +1180,7 @@ impl TableWidget {
                                         fst_scale_new_val = Some(1.0);
                                         num_scale_new_val = Some(rem_rows);
                                         num_scale_new_max = Some(rem_rows);
-                                        fst_scale_new_max = Some(rem_rows);
+                                        fst_scale_new_max = Some(fst_range(&filtered_sorted_tbl, is_paged, max_nrows));
                                         displ_tbl.tbl = filtered_sorted_tbl;
                                         displ_tbl.sorted_by = sel_col;
                                         displ_tbl.ascending = ascending;
@@ -555,8 +1236,7 @@ impl TableWidget {
             self.update_table_dimensions2(ncols as i32);
         }
 
-        // Keeps state of user navigation (sort, filter, offset and length)
-        let displayed_tbl : Rc<RefCell<Option<DisplayedTable>>> = Rc::new(RefCell::new(None));
+        let displayed_tbl = self.displayed.clone();
 
         // Add header
         for (j, col) in tbl.names().drain(..).enumerate() {
@@ -601,7 +1281,7 @@ impl TableWidget {
 
 // This shows the full table from row 1 up to max_rows.
 fn update_display_table(
-    tbl : &Rc<Table>,
+    tbl : &Rc<RefCell<Table>>,
     displayed_tbl : &Rc<RefCell<Option<DisplayedTable>>>,
     filter_key : &str,
     col : usize,
@@ -629,6 +1309,7 @@ fn update_display_table(
             true
         };
         if requires_update {
+            let tbl = tbl.borrow();
             let opt_new_tbl = if filter_key.is_empty() {
                 tbl.sorted_by(col, ascending)
             } else {
@@ -652,6 +1333,15 @@ fn update_display_table(
     }
 }
 
+// Upper limit of the first row scale. Paged tables can be navigated up to their last fetched row.
+fn fst_range(tbl : &Table, is_paged : bool, max_nrows : usize) -> f64 {
+    if is_paged {
+        tbl.nrows().max(1) as f64
+    } else {
+        tbl.nrows().min(max_nrows).max(1) as f64
+    }
+}
+
 fn selected_col(grid : &Grid, ncols : usize) -> Option<usize> {
     let mut sel_col = None;
     for c in 0..ncols {
@@ -685,4 +1375,54 @@ fn set_selected_style(grid : Grid, col : usize, selected : bool) {
     }
 }
 
+// Empty entries are written as nulls.
+fn entry_value(entry : &Entry) -> Option<String> {
+    let txt = entry.text();
+    if txt.is_empty() {
+        None
+    } else {
+        Some(txt.to_string())
+    }
+}
+
+fn delete_row_button() -> Button {
+    let btn = Button::builder().icon_name("user-trash-symbolic").build();
+    btn.style_context().add_class("flat");
+    btn.set_tooltip_text(Some("Delete row"));
+    btn
+}
+
+fn set_row_sensitive(grid : &Grid, row : usize, ncols : usize, sensitive : bool) {
+    for c in 0..=ncols {
+        if let Some(wid) = grid.child_at(c as i32, row as i32) {
+            wid.set_sensitive(sensitive);
+        }
+    }
+}
+
+const FILTER_OPERATORS : [&str; 11] = ["=", "<>", "<", "<=", ">", ">=", "between", "contains", "matches", "is null", "is not null"];
+
+fn filter_predicate(op : &str, fst : &str, snd : &str) -> Option<Predicate> {
+    match op {
+        "=" => Some(Predicate::Equal(fst.to_string())),
+        "<>" => Some(Predicate::NotEqual(fst.to_string())),
+        "<" => Some(Predicate::Less(fst.to_string())),
+        "<=" => Some(Predicate::LessOrEqual(fst.to_string())),
+        ">" => Some(Predicate::Greater(fst.to_string())),
+        ">=" => Some(Predicate::GreaterOrEqual(fst.to_string())),
+        "between" => Some(Predicate::Between(fst.to_string(), snd.to_string())),
+        "contains" => Some(Predicate::Contains(fst.to_string())),
+        "matches" => Some(Predicate::Matches(fst.to_string())),
+        "is null" => Some(Predicate::IsNull),
+        "is not null" => Some(Predicate::IsNotNull),
+        _ => None
+    }
+}
 
+// Sorting or filtering from the action bar replaces the view set at the header popovers.
+fn reset_view(view : &Rc<RefCell<TableView>>, displayed_tbl : &Rc<RefCell<Option<DisplayedTable>>>) {
+    if !view.borrow().is_empty() {
+        view.borrow_mut().clear();
+        displayed_tbl.replace(None);
+    }
+}
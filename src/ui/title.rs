@@ -8,6 +8,7 @@ use gtk4::*;
 use super::menu::MainMenu;
 use libadwaita::SplitButton;
 use super::FileList;
+use super::Form;
 use stateful::React;
 use crate::client::OpenedScripts;
 use super::QueriesContent;
@@ -122,6 +123,19 @@ pub struct ExecButton {
     pub schedule_action : gio::SimpleAction,
     pub single_action : gio::SimpleAction,
 
+    // Sets the query button to "notification" mode, for which the sequence of SQL
+    // statements is executed every time a notification arrives at the channel
+    // configured at the settings.
+    pub notification_action : gio::SimpleAction,
+
+    // Interrupts the statement currently being executed. The interrupted
+    // statement (and any statements after it) are reported as cancelled.
+    pub cancel_action : gio::SimpleAction,
+
+    // Asks the user for the values of placeholders that weren't
+    // declared with a value at the SQL script.
+    pub params_form : Form,
+
 }
 
 impl ExecButton {
@@ -133,6 +147,8 @@ impl ExecButton {
         self.restore_action.set_enabled(active);
         self.schedule_action.set_enabled(active);
         self.single_action.set_enabled(active);
+        self.notification_action.set_enabled(active);
+        self.cancel_action.set_enabled(active);
     }
     
     fn build() -> Self {
@@ -141,8 +157,13 @@ impl ExecButton {
         let exec_section = gio::Menu::new();
         exec_section.append(Some("Immediate"), Some("win.single"));
         exec_section.append(Some("Scheduled"), Some("win.schedule"));
+        exec_section.append(Some("On notification"), Some("win.notification"));
         exec_menu.append_section(Some("Execution mode"), &exec_section);
 
+        let statement_section = gio::Menu::new();
+        statement_section.append(Some("Cancel"), Some("win.cancel"));
+        exec_menu.append_section(Some("Statement"), &statement_section);
+
         let workspace_section = gio::Menu::new();
         workspace_section.append(Some("Restore"), Some("win.restore"));
         workspace_section.append(Some("Clear"), Some("win.clear"));
@@ -153,9 +174,11 @@ impl ExecButton {
         let queue_exec_action = gio::SimpleAction::new("queue_execution", None);
         let clear_action = gio::SimpleAction::new("clear", None);
         let restore_action = gio::SimpleAction::new("restore", None);
+        let cancel_action = gio::SimpleAction::new("cancel", None);
         exec_action.set_enabled(false);
         clear_action.set_enabled(false);
         restore_action.set_enabled(false);
+        cancel_action.set_enabled(false);
 
         btn.set_sensitive(false);
         btn.connect_clicked({
@@ -168,27 +191,50 @@ impl ExecButton {
         
         let schedule_action = gio::SimpleAction::new_stateful("schedule", None, &(false).to_variant());
         let single_action = gio::SimpleAction::new_stateful("single", None, &(true).to_variant());
+        let notification_action = gio::SimpleAction::new_stateful("notification", None, &(false).to_variant());
 
         single_action.connect_activate({
             let schedule_action = schedule_action.clone();
+            let notification_action = notification_action.clone();
             move |action, _| {
                 action.set_state(&true.to_variant());
                 schedule_action.set_state(&false.to_variant());
+                notification_action.set_state(&false.to_variant());
             }
         });
 
         schedule_action.connect_activate({
             let single_action = single_action.clone();
+            let notification_action = notification_action.clone();
             move |action, _| {
                 action.set_state(&true.to_variant());
                 single_action.set_state(&false.to_variant());
+                notification_action.set_state(&false.to_variant());
+            }
+        });
+
+        notification_action.connect_activate({
+            let single_action = single_action.clone();
+            let schedule_action = schedule_action.clone();
+            move |action, _| {
+                action.set_state(&true.to_variant());
+                single_action.set_state(&false.to_variant());
+                schedule_action.set_state(&false.to_variant());
+            }
+        });
+
+        let params_form = Form::new();
+        params_form.btn_cancel.connect_clicked({
+            let dialog = params_form.dialog.clone();
+            move |_| {
+                dialog.close();
             }
         });
 
         // single_action.set_enabled(true);
         // schedule_action.
         // btn.activate_action(&exec_action, None);
-        Self { btn, queue_exec_action, exec_action, clear_action, restore_action, schedule_action, single_action }
+        Self { btn, queue_exec_action, exec_action, clear_action, restore_action, schedule_action, single_action, notification_action, cancel_action, params_form }
     }
 
 }
@@ -276,11 +322,13 @@ impl React<ActiveConnection> for ExecButton {
             let clear_action = self.clear_action.clone();
             let restore_action = self.restore_action.clone();
             let queue_exec_action = self.queue_exec_action.clone();
+            let cancel_action = self.cancel_action.clone();
             move |_| {
                 exec_action.set_enabled(true);
                 clear_action.set_enabled(true);
                 restore_action.set_enabled(true);
                 queue_exec_action.set_enabled(true);
+                cancel_action.set_enabled(true);
             }
         });
         conn.connect_db_disconnected({
@@ -289,12 +337,14 @@ impl React<ActiveConnection> for ExecButton {
             let clear_action = self.clear_action.clone();
             let restore_action = self.restore_action.clone();
             let queue_exec_action = self.queue_exec_action.clone();
+            let cancel_action = self.cancel_action.clone();
             move |_| {
                 exec_action.set_enabled(false);
                 exec_btn.set_sensitive(false);
                 clear_action.set_enabled(false);
                 restore_action.set_enabled(false);
                 queue_exec_action.set_enabled(false);
+                cancel_action.set_enabled(false);
             }
         });
        
@@ -5,7 +5,7 @@ For a copy, see http://www.gnu.org/licenses.*/
 
 use gtk4::prelude::*;
 use gtk4::*;
-use crate::client::Environment;
+use crate::client::{Environment, EnvironmentAction};
 use stateful::React;
 use libadwaita;
 use super::table::*;
@@ -13,12 +13,31 @@ use crate::tables::table::Table;
 use crate::ui::PlotView;
 use papyri::render::Panel;
 use crate::client::UserState;
+use crate::client::ActiveConnection;
+use crate::client::ActiveConnectionAction;
+use crate::sql::edit::TableEdit;
+use std::rc::Rc;
+use std::cell::RefCell;
 
 #[derive(Debug, Clone)]
 pub struct QueriesWorkspace {
     pub tab_view : libadwaita::TabView,
     pub tab_bar : libadwaita::TabBar,
-    pub bx : Box
+    pub bx : Box,
+
+    // Activated with a query as parameter when the user requests more rows of its table.
+    pub page_action : gio::SimpleAction,
+
+    // Activated with a query as parameter when the user starts editing the rows of its table.
+    pub edit_action : gio::SimpleAction,
+
+    // Activated with a query as parameter when the user reviews the changes to its table.
+    pub review_action : gio::SimpleAction,
+
+    pub edit_dialog : EditPreviewDialog,
+
+    // Table widgets of the current pages (None for plots).
+    widgets : Rc<RefCell<Vec<Option<TableWidget>>>>
 }
 
 fn configure_tab(tab_view : &libadwaita::TabView, tab_bar : &libadwaita::TabBar) {
@@ -47,7 +66,96 @@ impl QueriesWorkspace {
         bx.set_margin_bottom(0);
         bx.append(&tab_view);
         bx.append(&tab_bar);
-        Self { tab_view, tab_bar, bx }
+        let page_action = gio::SimpleAction::new("fetch_page", Some(&u64::static_variant_type()));
+        let edit_action = gio::SimpleAction::new("edit_table", Some(&String::static_variant_type()));
+        let review_action = gio::SimpleAction::new("review_edits", Some(&String::static_variant_type()));
+        let widgets : Rc<RefCell<Vec<Option<TableWidget>>>> = Rc::new(RefCell::new(Vec::new()));
+        let edit_dialog = EditPreviewDialog::build();
+        review_action.connect_activate({
+            let widgets = widgets.clone();
+            let edit_dialog = edit_dialog.clone();
+            move |_action, param| {
+                if let Some(query) = param.and_then(|p| p.get::<String>() ) {
+                    let opt_edit = widget_for_query(&widgets.borrow()[..], &query)
+                        .and_then(|tbl_wid| tbl_wid.pending_edit() );
+                    if let Some(edit) = opt_edit {
+                        edit_dialog.show_preview(query, edit);
+                    }
+                }
+            }
+        });
+        Self { tab_view, tab_bar, bx, page_action, edit_action, review_action, edit_dialog, widgets }
+    }
+
+}
+
+fn widget_for_query(widgets : &[Option<TableWidget>], query : &str) -> Option<TableWidget> {
+    widgets.iter()
+        .filter_map(|w| w.as_ref() )
+        .find(|w| w.query().map(|q| &q[..] == query ).unwrap_or(false) )
+        .cloned()
+}
+
+/// Shows the statements that write back the changes made to the rows of a table,
+/// so the user can review them before they are applied.
+#[derive(Debug, Clone)]
+pub struct EditPreviewDialog {
+    pub dialog : Dialog,
+    view : TextView,
+    pub btn_apply : Button,
+
+    // Query of the edited result and the changes waiting for confirmation.
+    pub pending : Rc<RefCell<Option<(String, TableEdit)>>>
+}
+
+impl EditPreviewDialog {
+
+    pub fn build() -> Self {
+        let dialog = Dialog::new();
+        dialog.set_title(Some("Apply changes"));
+        crate::ui::configure_dialog(&dialog, true);
+        let view = TextView::new();
+        view.set_editable(false);
+        view.set_monospace(true);
+        view.set_cursor_visible(false);
+        let scroll = ScrolledWindow::new();
+        scroll.set_child(Some(&view));
+        scroll.set_min_content_height(240);
+        scroll.set_vexpand(true);
+
+        let btn_apply = Button::builder().label("Apply").build();
+        btn_apply.style_context().add_class("pill");
+        btn_apply.style_context().add_class("suggested-action");
+        btn_apply.set_hexpand(false);
+        btn_apply.set_halign(Align::Center);
+        let bx = Box::new(Orientation::Vertical, 0);
+        bx.append(&scroll);
+        bx.append(&btn_apply);
+        dialog.set_child(Some(&bx));
+
+        super::set_margins(&btn_apply, 64,  16);
+        super::set_margins(&bx, 32,  32);
+
+        let pending = Rc::new(RefCell::new(None));
+        dialog.connect_close_request({
+            let pending = pending.clone();
+            move |_| {
+                pending.replace(None);
+                glib::signal::Inhibit(false)
+            }
+        });
+        Self { dialog, view, btn_apply, pending }
+    }
+
+    pub fn show_preview(&self, query : String, edit : TableEdit) {
+        if edit.is_empty() {
+            self.view.buffer().set_text("-- No changes to apply");
+        } else {
+            self.view.buffer().set_text(&edit.statements().join("\n"));
+        }
+        self.btn_apply.set_sensitive(!edit.is_empty());
+        self.pending.replace(Some((query, edit)));
+        self.dialog.show();
     }
 
 }
@@ -64,11 +172,18 @@ const COLUMN_LIMIT : usize = 50;
 pub fn populate_with_tables(
     tab_view : &libadwaita::TabView,
     tables : &[Table],
+    queries : &[String],
+    pages : &[Option<usize>],
     state : &UserState,
-) -> Vec<libadwaita::TabPage> {
+    page_action : &gio::SimpleAction,
+    edit_action : &gio::SimpleAction,
+    review_action : &gio::SimpleAction,
+    env_send : &glib::Sender<EnvironmentAction>
+) -> (Vec<libadwaita::TabPage>, Vec<Option<TableWidget>>) {
     close_all_pages(&tab_view);
     let mut new_pages = Vec::new();
-    for tbl in tables.iter() {
+    let mut widgets = Vec::new();
+    for ((tbl, query), page) in tables.iter().zip(queries.iter()).zip(pages.iter()) {
         if let Some(val) = tbl.single_json_field() {
             match Panel::new_from_json(&val.to_string()) {
                 Ok(panel) => {
@@ -76,17 +191,58 @@ pub fn populate_with_tables(
                     let tab_page = tab_view.append(&view.parent);
                     configure_plot_page(&tab_page, &panel);
                     new_pages.push(tab_page);
+                    widgets.push(None);
                     continue;
                 },
                 _ => { }
             }
         }
-        let tbl_wid = TableWidget::new_from_table(&tbl, state.execution.row_limit as usize, COLUMN_LIMIT);
+        let tbl_wid = TableWidget::new_paged(&tbl, state.execution.row_limit as usize, COLUMN_LIMIT, query, *page, page_action);
+
+        // Only results of queries over a single table might be written back.
+        let source = tbl.source();
+        if source.name.is_some() && source.relation.is_none() {
+            tbl_wid.set_editable(edit_action, review_action);
+        }
+        connect_summary(tab_view, &tbl_wid, state.execution.row_limit as usize, env_send);
         let tab_page = tab_view.append(&tbl_wid.bx);
         new_pages.push(tab_page.clone());
         configure_table_page(&tab_page, &tbl, state.execution.row_limit as usize);
+        widgets.push(Some(tbl_wid));
     }
-    new_pages
+    (new_pages, widgets)
+}
+
+// Summaries are shown at new pages, which are closed with the other pages when
+// the next results arrive. The environment keeps the summaries, so they can be exported
+// like the other tables.
+fn connect_summary(
+    tab_view : &libadwaita::TabView,
+    tbl_wid : &TableWidget,
+    row_limit : usize,
+    env_send : &glib::Sender<EnvironmentAction>
+) {
+    tbl_wid.summary.btn_apply.connect_clicked({
+        let tab_view = tab_view.clone();
+        let tbl_wid = tbl_wid.clone();
+        let env_send = env_send.clone();
+        move |_| {
+            match tbl_wid.summarize() {
+                Ok(summary) => {
+                    tbl_wid.summary.popover.popdown();
+                    let summary_wid = TableWidget::new_from_table(&summary, row_limit, COLUMN_LIMIT);
+                    connect_summary(&tab_view, &summary_wid, row_limit, &env_send);
+                    env_send.send(EnvironmentAction::AddSummary(summary.clone())).unwrap();
+                    let tab_page = tab_view.append(&summary_wid.bx);
+                    configure_table_page(&tab_page, &summary, row_limit);
+                    tab_view.set_selected_page(&tab_page);
+                },
+                Err(e) => {
+                    tbl_wid.summary.show_error(&e);
+                }
+            }
+        }
+    });
 }
 
 impl<'a> React<Environment> for QueriesWorkspace {
@@ -94,14 +250,74 @@ impl<'a> React<Environment> for QueriesWorkspace {
     fn react(&self, env : &Environment) {
         let tab_view = self.tab_view.clone();
         let user_state = env.user_state.clone();
-        env.connect_table_update(move |tables| {
-            let user_state = user_state.borrow();
-            let past_sel_page = tab_view.selected_page().map(|page| tab_view.page_position(&page) as usize );
-            let past_n_pages = tab_view.n_pages() as usize;
-            let new_pages = populate_with_tables(&tab_view, &tables[..], &*user_state);
-            if let Some(page_ix) = past_sel_page {
-                if new_pages.len() == past_n_pages {
-                    tab_view.set_selected_page(&new_pages[page_ix]);
+        env.connect_table_update({
+            let tab_view = tab_view.clone();
+            let user_state = user_state.clone();
+            let page_action = self.page_action.clone();
+            let edit_action = self.edit_action.clone();
+            let review_action = self.review_action.clone();
+            let widgets = self.widgets.clone();
+            let env_send = env.sender().clone();
+            move |(tables, queries, pages)| {
+                let user_state = user_state.borrow();
+                let past_sel_page = tab_view.selected_page().map(|page| tab_view.page_position(&page) as usize );
+                let past_n_pages = tab_view.n_pages() as usize;
+                let (new_pages, new_widgets) = populate_with_tables(
+                    &tab_view,
+                    &tables[..],
+                    &queries[..],
+                    &pages[..],
+                    &*user_state,
+                    &page_action,
+                    &edit_action,
+                    &review_action,
+                    &env_send
+                );
+                widgets.replace(new_widgets);
+                if let Some(page_ix) = past_sel_page {
+                    if new_pages.len() == past_n_pages {
+                        tab_view.set_selected_page(&new_pages[page_ix]);
+                    }
+                }
+            }
+        });
+        env.connect_page_update({
+            let widgets = self.widgets.clone();
+            move |(ix, page)| {
+                if let Some(Some(tbl_wid)) = widgets.borrow().get(ix) {
+                    tbl_wid.append_page(&page);
+                    let tab_page = tab_view.page(&tbl_wid.bx);
+                    let tbl = tbl_wid.table();
+                    configure_table_page(&tab_page, &tbl, user_state.borrow().execution.row_limit as usize);
+                }
+            }
+        });
+    }
+
+}
+
+impl React<ActiveConnection> for QueriesWorkspace {
+
+    fn react(&self, conn : &ActiveConnection) {
+        conn.connect_edit_ready({
+            let widgets = self.widgets.clone();
+            let send = conn.sender().clone();
+            move |(query, obj, engine)| {
+                if let Some(tbl_wid) = widget_for_query(&widgets.borrow()[..], &query) {
+                    if let Err(e) = tbl_wid.start_editing(&obj, engine) {
+                        send.send(ActiveConnectionAction::Error(e)).unwrap();
+                    }
+                }
+            }
+        });
+        conn.connect_edit_result({
+            let widgets = self.widgets.clone();
+            let tab_view = self.tab_view.clone();
+            move |(query, tbl)| {
+                if let Some(tbl_wid) = widget_for_query(&widgets.borrow()[..], &query) {
+                    tbl_wid.refresh(&tbl);
+                    let tab_page = tab_view.page(&tbl_wid.bx);
+                    configure_table_page(&tab_page, &tbl_wid.table(), tbl_wid.max_nrows());
                 }
             }
         });
@@ -0,0 +1,97 @@
+use queries::tables::table::Table;
+use queries::tables::column::Column;
+use queries::tables::nullable::NullableColumn;
+use queries::tables::aggregate::*;
+use rust_decimal::Decimal;
+use chrono::NaiveDate;
+use std::str::FromStr;
+
+fn sales() -> Table {
+    Table::new(
+        None,
+        vec![format!("region"), format!("year"), format!("amount"), format!("seller")],
+        vec![
+            Column::from(vec![format!("south"), format!("north"), format!("south"), format!("north"), format!("south")]),
+            Column::from(vec![2021i32, 2021, 2022, 2022, 2022]),
+            Column::Nullable(NullableColumn::from(vec![Some(10.0f64), Some(4.0), Some(20.0), None, Some(30.0)])),
+            Column::from(vec![format!("Ann"), format!("Bob"), format!("Ann"), format!("Carl"), format!("Dan")])
+        ]
+    ).unwrap()
+}
+
+// cargo test -- aggregate --nocapture
+#[test]
+fn aggregate() {
+    assert_eq!(Aggregate::Median.calculate(&[3., 1., 2., 10.]), Some(2.5));
+    assert_eq!(Aggregate::Median.calculate(&[3., f64::NAN, 1., 2.]), Some(2.5));
+    assert_eq!(Aggregate::StdDev.calculate(&[2., 4.]), Some(2.0f64.sqrt()));
+    assert_eq!(Aggregate::StdDev.calculate(&[2.]), None);
+    assert_eq!(Aggregate::Sum.calculate(&[]), None);
+    assert_eq!("mean".parse::<Aggregate>().unwrap(), Aggregate::Mean);
+
+    let tbl = sales();
+
+    // Groups are sorted by their keys, and nulls are ignored by aggregates.
+    let grouped = tbl.grouped_by(&[0], &[(2, Aggregate::Count), (2, Aggregate::Sum), (2, Aggregate::Max)]).unwrap();
+    assert_eq!(grouped.names(), vec![format!("region"), format!("count(amount)"), format!("sum(amount)"), format!("max(amount)")]);
+    assert_eq!(grouped.nrows(), 2);
+    assert_eq!(grouped.display_content_at(0, 0, None).unwrap(), "north");
+    assert_eq!(grouped.display_content_at(0, 1, None).unwrap(), "1");
+    assert_eq!(grouped.display_content_at(1, 1, None).unwrap(), "3");
+    assert_eq!(grouped.get_columns(&[2]).try_numeric(0).unwrap(), vec![4., 60.]);
+    assert_eq!(grouped.get_columns(&[3]).try_numeric(0).unwrap(), vec![4., 30.]);
+
+    let grouped = tbl.grouped_by(&[0, 1], &[(2, Aggregate::Mean)]).unwrap();
+    assert_eq!(grouped.nrows(), 4);
+    assert!(grouped[2].is_null(1));
+    assert_eq!(grouped.display_content_at(3, 1, None).unwrap(), "2022");
+
+    // Overall aggregates without keys.
+    let total = tbl.grouped_by(&[], &[(2, Aggregate::Median)]).unwrap();
+    assert_eq!(total.get_columns(&[0]).try_numeric(0).unwrap(), vec![15.]);
+
+    let pivot = tbl.pivoted(0, 1, 2, Aggregate::Sum).unwrap();
+    assert_eq!(pivot.names(), vec![format!("region"), format!("2021"), format!("2022")]);
+    assert_eq!(pivot.display_content_at(1, 0, None).unwrap(), "south");
+    assert!(pivot[2].is_null(0));
+    assert_eq!(pivot.get_columns(&[1]).try_numeric(0).unwrap(), vec![4., 10.]);
+
+    let counts = tbl.pivoted(1, 0, 3, Aggregate::Count).unwrap();
+    assert_eq!(counts.names(), vec![format!("year"), format!("north"), format!("south")]);
+    assert_eq!(counts.display_content_at(1, 2, None).unwrap(), "2");
+
+    // Only counts are calculated over text columns.
+    assert!(tbl.grouped_by(&[0], &[(3, Aggregate::Sum)]).is_err());
+    assert!(tbl.grouped_by(&[7], &[(2, Aggregate::Sum)]).is_err());
+}
+
+#[test]
+fn aggregate_types() {
+    let date = |d : u32| NaiveDate::from_ymd_opt(2022, 1, d).unwrap();
+    let dec = |s : &str| Decimal::from_str(s).unwrap();
+    let tbl = Table::new(
+        None,
+        vec![format!("region"), format!("day"), format!("amount")],
+        vec![
+            Column::from(vec![format!("south"), format!("north"), format!("south"), format!("north")]),
+            Column::Nullable(NullableColumn::from(vec![Some(date(3)), None, Some(date(1)), None])),
+            Column::from(vec![dec("0.1"), dec("12345678901234567.89"), dec("0.2"), dec("0.01")])
+        ]
+    ).unwrap();
+
+    // Minimum and maximum values keep the column type.
+    let grouped = tbl.grouped_by(&[0], &[(1, Aggregate::Min), (1, Aggregate::Max)]).unwrap();
+    assert_eq!(grouped[1], Column::Nullable(NullableColumn::from(vec![None, Some(date(1))])));
+    assert_eq!(grouped[2], Column::Nullable(NullableColumn::from(vec![None, Some(date(3))])));
+
+    // Temporal columns are not summed or averaged.
+    for agg in [Aggregate::Sum, Aggregate::Mean, Aggregate::StdDev] {
+        assert!(tbl.grouped_by(&[0], &[(1, agg)]).is_err());
+    }
+
+    // Decimal columns are aggregated without loss of precision.
+    let grouped = tbl.grouped_by(&[0], &[(2, Aggregate::Sum), (2, Aggregate::Mean), (2, Aggregate::Max)]).unwrap();
+    assert_eq!(grouped[1], Column::from(vec![dec("12345678901234567.90"), dec("0.3")]));
+    assert_eq!(grouped[2], Column::from(vec![dec("6172839450617283.95"), dec("0.15")]));
+    assert_eq!(grouped[3], Column::from(vec![dec("12345678901234567.89"), dec("0.2")]));
+}
@@ -0,0 +1,78 @@
+use queries::tables::pgtype::*;
+use tokio_postgres::types::{Type, Kind, Field};
+use serde_json::json;
+
+fn be(vals : &[i32]) -> Vec<u8> {
+    vals.iter().flat_map(|v| v.to_be_bytes().to_vec() ).collect()
+}
+
+fn be16(vals : &[i16]) -> Vec<u8> {
+    vals.iter().flat_map(|v| v.to_be_bytes().to_vec() ).collect()
+}
+
+// Binary numeric: digit count, weight, sign and scale, followed by base-10000 digits.
+fn numeric_value(header : [i16; 4], digits : &[i16]) -> Vec<u8> {
+    let mut num = be16(&header);
+    num.extend(be16(digits));
+    let mut out = be(&[num.len() as i32]);
+    out.extend(num);
+    out
+}
+
+fn text_value(s : &str) -> Vec<u8> {
+    let mut out = be(&[s.len() as i32]);
+    out.extend(s.as_bytes());
+    out
+}
+
+// cargo test -- arrays --nocapture
+#[test]
+fn arrays() {
+
+    // int4[][] holding {{1,2},{NULL,4}}
+    let raw = be(&[2, 1, 23, 2, 1, 2, 1, 4, 1, 4, 2, -1, 4, 4]);
+    assert_eq!(decode_value(&Type::INT4_ARRAY, &raw[..]).unwrap(), json!([[1, 2], [null, 4]]));
+
+    // Empty arrays have no dimensions.
+    assert_eq!(decode_value(&Type::TEXT_ARRAY, &be(&[0, 0, 25])[..]).unwrap(), json!([]));
+
+    let mood = Type::new(
+        String::from("mood"),
+        100_000,
+        Kind::Enum(vec![String::from("sad"), String::from("happy")]),
+        String::from("public")
+    );
+    assert!(is_nested(&mood));
+    assert_eq!(decode_value(&mood, b"happy").unwrap(), json!("happy"));
+
+    let person = Type::new(
+        String::from("person"),
+        100_001,
+        Kind::Composite(vec![
+            Field::new(String::from("name"), Type::TEXT),
+            Field::new(String::from("mood"), mood.clone())
+        ]),
+        String::from("public")
+    );
+    let mut raw = be(&[2, 25]);
+    raw.extend(text_value("ann"));
+    raw.extend(be(&[100_000]));
+    raw.extend(text_value("sad"));
+    assert_eq!(decode_value(&person, &raw[..]).unwrap(), json!({ "name" : "ann", "mood" : "sad" }));
+
+    let positive = Type::new(String::from("positive"), 100_002, Kind::Domain(Type::INT4), String::from("public"));
+    assert_eq!(decode_value(&positive, &be(&[7])[..]).unwrap(), json!(7));
+
+    // numeric[] holding {10.25,3} keeps its elements as numbers.
+    let mut raw = be(&[1, 0, 1700, 2, 1]);
+    raw.extend(numeric_value([2, 0, 0, 2], &[10, 2500]));
+    raw.extend(numeric_value([1, 0, 0, 0], &[3]));
+    let decoded = decode_value(&Type::NUMERIC_ARRAY, &raw[..]).unwrap();
+    assert_eq!(decoded, json!([10.25, 3]));
+    assert!(decoded.as_array().unwrap().iter().all(|v| v.is_number() ));
+
+    let price = Type::new(String::from("price"), 100_003, Kind::Domain(Type::NUMERIC), String::from("public"));
+    let decoded = decode_value(&price, &numeric_value([2, 0, 0, 2], &[10, 2500])[4..]).unwrap();
+    assert!(decoded.is_number());
+    assert_eq!(decoded.as_f64(), Some(10.25));
+}
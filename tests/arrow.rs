@@ -0,0 +1,35 @@
+#![cfg(feature="arrowext")]
+
+use queries::client::*;
+use queries::server::*;
+use queries::sql::*;
+use std::collections::HashMap;
+use std::convert::TryInto;
+mod common;
+
+const CSV_CONTENT : &'static str = "id,name,score\n1,alpha,1.5\n2,beta,\n3,gamma,3.5\n";
+
+// cargo test --features arrowext -- arrow --nocapture
+#[test]
+fn arrow() {
+    let dt = common::run("date +%y_%m_%d_%H_%M_%S").unwrap().trim().to_string();
+    let path = format!("/tmp/queries_arrow_test_{}.csv", dt);
+    std::fs::write(&path, CSV_CONTENT).unwrap();
+    let uri = conn_uri_from_str(&format!("file://{}", path), None).unwrap();
+    assert_eq!(uri.info.engine, Engine::DataFusion);
+
+    let mut conn = ArrowConnection::try_new(uri).unwrap();
+    let lock = SafetyLock { accept_ddl : true, accept_dml : true, enable_async : false };
+    let sql = format!("select id, name, score from queries_arrow_test_{} order by id", dt);
+    let tbl = match conn.try_run(sql, &HashMap::new(), lock, false).unwrap().remove(0) {
+        StatementOutput::Valid(_, tbl) => tbl,
+        out => panic!("Unexpected output: {:?}", out)
+    };
+    assert_eq!(tbl.nrows(), 3);
+    assert_eq!(tbl.names(), vec!["id".to_string(), "name".to_string(), "score".to_string()]);
+    let names : Vec<String> = tbl.get_column(1).unwrap().clone().try_into().unwrap();
+    assert_eq!(names, vec!["alpha".to_string(), "beta".to_string(), "gamma".to_string()]);
+
+    let info = conn.db_info().unwrap();
+    assert!(info.schema.iter().any(|obj| obj.obj_name() == "public" ));
+}
@@ -0,0 +1,33 @@
+use queries::client::*;
+use queries::server::*;
+use queries::sql::*;
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::time::Duration;
+mod common;
+
+const LONG_QUERY : &'static str = r#"
+with recursive counter(n) as (select 1 union all select n + 1 from counter)
+select count(*) from counter;
+select 1;
+"#;
+
+// cargo test -- cancel --nocapture
+#[test]
+fn cancel() {
+    let dt = common::run("date +%y_%m_%d_%H_%M_%S").unwrap().trim().to_string();
+    let uri = conn_uri_from_str(&format!("file:///tmp/queries_cancel_test_{}.db", dt), None).unwrap();
+    let (tx, rx) = mpsc::channel();
+    let mut listener = SqlListener::launch(move |res, _, _| { tx.send(res).unwrap(); });
+    listener.update_engine(Box::new(SqliteConnection::try_new(uri).unwrap())).unwrap();
+    assert!(listener.cancel().is_err());
+
+    listener.send_commands(LONG_QUERY.to_string(), HashMap::new(), SafetyLock::default(), false).unwrap();
+    std::thread::sleep(Duration::from_millis(500));
+    listener.cancel().unwrap();
+    let res = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+
+    // The second query is not executed after the first is cancelled.
+    assert_eq!(res.len(), 1);
+    assert!(matches!(res[0], StatementOutput::Cancelled(_)));
+}
@@ -0,0 +1,44 @@
+use queries::tables::table::*;
+use queries::tables::column::*;
+use queries::tables::nullable::*;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+use std::path::Path;
+mod common;
+
+fn sample_table() -> Table {
+    let names = ["id", "score", "price", "label", "payload", "attrs"].iter().map(|s| s.to_string() ).collect();
+    let cols = vec![
+        Column::from(vec![1i32, 2, 3]),
+        Column::from(vec![1.5f64, 2.5, 3.5]),
+        Column::from(NullableColumn::from(vec![Some(Decimal::from_str("10.25").unwrap()), None, Some(Decimal::from_str("3.1").unwrap())])),
+        Column::from(NullableColumn::from(vec![Some(String::from("a")), Some(String::from("b")), None])),
+        Column::from(vec![vec![0u8, 1], vec![2], vec![]]),
+        Column::from(vec![serde_json::json!({ "a" : 1 }), serde_json::json!([1, 2]), serde_json::json!(null)])
+    ];
+    Table::new(None, names, cols).unwrap()
+}
+
+fn assert_same_columns(a : &Table, b : &Table) {
+    assert_eq!(a.names(), b.names());
+    for ix in 0..a.ncols() {
+        assert_eq!(a.get_column(ix), b.get_column(ix));
+    }
+}
+
+// cargo test -- columnar --nocapture
+#[test]
+fn columnar() {
+    let dt = common::run("date +%y_%m_%d_%H_%M_%S").unwrap().trim().to_string();
+    let tbl = sample_table();
+    for ext in ["parquet", "arrow"] {
+        let path = format!("/tmp/queries_columnar_test_{}.{}", dt, ext);
+        match ext {
+            "parquet" => queries::tables::arrow::write_parquet(&tbl, Path::new(&path)).unwrap(),
+            _ => queries::tables::arrow::write_ipc(&tbl, Path::new(&path)).unwrap()
+        }
+        let read = Table::open(Path::new(&path)).unwrap();
+        assert_eq!(read.nrows(), 3);
+        assert_same_columns(&tbl, &read);
+    }
+}
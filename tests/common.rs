@@ -158,8 +158,12 @@ impl ExistingDB {
             security : Security {
                 tls_version : Some(TlsVersion { major : 1, minor : 0 }),
                 cert_path : Some(env::var("PGSSLROOTCERT").or(Err("Missing hostname"))?),
-                verify_hostname : Some(true)
-            }
+                verify_hostname : Some(true),
+                client_cert : None,
+                ssl_mode : None
+            },
+            params : Vec::new(),
+            ssh : None
         };
         let mut uri = ConnURI::new(info.clone(), &env::var("PGPASSWORD").or(Err("Missing password"))?)?;
         uri.uri = Url::parse(&format!("{}?application_name=Queries&sslmode=require", uri.uri)).unwrap();
@@ -0,0 +1,112 @@
+use queries::client::*;
+use std::str::FromStr;
+
+// cargo test -- keyword_conn_string --nocapture
+#[test]
+fn keyword_conn_string() {
+    let s = "host=db.example.com port = 6432 dbname=sales user=analyst password='se cr\\'et' \
+        options='-c search_path=app' application_name=reports sslmode=verify-ca sslrootcert=/tmp/root.crt";
+    assert!(is_keyword_conn_string(s));
+    let conn = ConnString::parse(s).unwrap();
+    assert_eq!(conn.info.host, "db.example.com");
+    assert_eq!(conn.info.port, "6432");
+    assert_eq!(conn.info.database, "sales");
+    assert_eq!(conn.info.user, "analyst");
+    assert_eq!(conn.password.as_ref().map(|p| &p[..] ), Some("se cr'et"));
+    assert_eq!(conn.info.security.effective_ssl_mode(), SslMode::VerifyCa);
+    assert_eq!(conn.info.security.cert_path.as_ref().map(|p| &p[..] ), Some("/tmp/root.crt"));
+    assert_eq!(&conn.info.params[..], &[
+        (String::from("options"), String::from("-c search_path=app")),
+        (String::from("application_name"), String::from("reports"))
+    ]);
+
+    // Parameters are percent-encoded at the URL, and take precedence over the user settings.
+    let params = merge_conn_params(vec![format!("application_name=Queries"), format!("connect_timeout=10")], &conn.info);
+    assert_eq!(params, vec![
+        format!("connect_timeout=10"),
+        format!("options=-c%20search_path%3Dapp"),
+        format!("application_name=reports")
+    ]);
+
+    assert!(!is_keyword_conn_string("localhost"));
+    assert!(ConnString::parse("host=localhost dbname='unterminated").is_err());
+    assert!(ConnString::parse("host=localhost fallback=1").is_err());
+    assert!(ConnString::parse("host=localhost sslmode=verify_full").is_err());
+}
+
+#[test]
+fn uri_conn_string() {
+    let s = "postgresql://us%3Aer:p%40ss@%2Fvar%2Frun%2Fpostgresql/sales?target_session_attrs=read-write&sslmode=disable";
+    assert!(is_uri_conn_string(s));
+    let conn = ConnString::parse(s).unwrap();
+    assert_eq!(conn.info.host, "/var/run/postgresql");
+    assert_eq!(conn.info.port, "5432");
+    assert_eq!(conn.info.user, "us:er");
+    assert_eq!(conn.password.as_ref().map(|p| &p[..] ), Some("p@ss"));
+    assert_eq!(conn.info.database, "sales");
+    assert_eq!(conn.info.security, Security::new_insecure());
+    assert_eq!(conn.info.params, vec![(String::from("target_session_attrs"), String::from("read-write"))]);
+
+    // User names with ':' are percent-encoded at the connection URL.
+    let uri = ConnURI::new(conn.info.clone(), conn.password.as_ref().unwrap()).unwrap();
+    assert_eq!(uri.uri.as_str(), "postgresql://us%3Aer:p%40ss@%2Fvar%2Frun%2Fpostgresql:5432/sales");
+    assert!(uri.verify_integrity().is_ok());
+
+    let conn = ConnString::parse("postgres://analyst@[::1]:6432/sales").unwrap();
+    assert_eq!(conn.info.host, "::1");
+    assert_eq!(conn.info.port, "6432");
+    assert!(conn.password.is_none());
+    assert!(ConnString::parse("postgresql://a,b/sales").is_err());
+}
+
+#[test]
+fn service_file() {
+    let content = "# Shared connections\n\
+        [sales]\n\
+        host=db.example.com\n\
+        port=6432\n\
+        dbname=sales\n\
+        user=analyst\n\
+        options=-c search_path=app\n\
+        \n\
+        [local]\n\
+        host=localhost\n\
+        dbname=dev\n\
+        user=dev\n";
+    let services = ServiceFile::from_str(content).unwrap();
+    assert_eq!(services.services.len(), 2);
+    assert_eq!(services.service("local").unwrap().len(), 3);
+    let conns = services.connections().unwrap();
+    assert_eq!(conns[0].host, "db.example.com");
+    assert_eq!(conns[0].params, vec![(String::from("options"), String::from("-c search_path=app"))]);
+    assert_eq!(conns[0].security, Security::new_secure());
+    assert_eq!(conns[1].port, "5432");
+    assert_eq!(conns[1].security, Security::new_insecure());
+    assert!(ServiceFile::from_str("host=localhost").is_err());
+}
+
+#[test]
+fn pass_file() {
+    let content = "# host:port:database:user:password\n\
+        db.example.com:6432:sales:analyst:s\\:ecret\n\
+        localhost:*:*:dev:dev\n\
+        *:*:*:*:fallback\n";
+    let pass = PassFile::from_str(content).unwrap();
+    assert_eq!(pass.entries.len(), 3);
+    assert_eq!(pass.entries[0].password, "s:ecret");
+
+    let conns = pass.connections();
+    assert_eq!(conns.len(), 1);
+    assert_eq!(conns[0].user, "analyst");
+    assert_eq!(pass.password(&conns[0]), Some("s:ecret"));
+
+    let mut info = ConnectionInfo::default();
+    info.host = format!("/var/run/postgresql");
+    info.port = format!("5432");
+    info.database = format!("dev");
+    info.user = format!("dev");
+    assert_eq!(pass.password(&info), Some("dev"));
+    info.user = format!("other");
+    assert_eq!(pass.password(&info), Some("fallback"));
+    assert!(PassFile::from_str("localhost:5432:db").is_err());
+}
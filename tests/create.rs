@@ -0,0 +1,57 @@
+use queries::client::*;
+use queries::server::*;
+use queries::sql::*;
+use queries::tables::table::*;
+use queries::tables::column::*;
+use std::collections::HashMap;
+mod common;
+
+// cargo test -- create --nocapture
+#[test]
+fn create() {
+    assert_eq!(infer_text_type(["1", "-20", ""].iter().cloned()), TextType::Integer);
+    assert_eq!(infer_text_type(["1", "2.5"].iter().cloned()), TextType::Float);
+    assert_eq!(infer_text_type(["true", "F"].iter().cloned()), TextType::Bool);
+    assert_eq!(infer_text_type(["2022-01-01", "2022-12-31"].iter().cloned()), TextType::Date);
+    assert_eq!(infer_text_type(["2022-01-01 10:00:00", "2022-12-31T23:59"].iter().cloned()), TextType::Timestamp);
+    assert_eq!(infer_text_type(["1", "a"].iter().cloned()), TextType::Text);
+
+    let names = ["id", "Score", "label"].iter().map(|s| s.to_string() ).collect();
+    let cols = vec![
+        Column::from(vec![1i64, 2, 3]),
+        Column::from(vec![1.5f64, 2.5, 3.5]),
+        Column::from(vec![String::from("a"), String::from("b"), String::from("c")])
+    ];
+    let mut tbl = Table::new(None, names, cols).unwrap();
+    let sql = tbl.sql_table_creation("scores", &[], Engine::Postgres).unwrap();
+    assert_eq!(sql, "create table \"scores\" (\n    \"id\" bigint,\n    \"Score\" double precision,\n    \"label\" text\n);\n");
+
+    // Importing into a missing table creates it before inserting the rows.
+    let dt = common::run("date +%y_%m_%d_%H_%M_%S").unwrap().trim().to_string();
+    let uri = conn_uri_from_str(&format!("file:///tmp/queries_create_test_{}.db", dt), None).unwrap();
+    let mut conn = SqliteConnection::try_new(uri).unwrap();
+    assert_eq!(conn.import(&mut tbl, "scores", &[]).unwrap(), 3);
+    let lock = SafetyLock { accept_ddl : true, accept_dml : true, enable_async : false };
+    match conn.try_run("select count(*) from scores".to_string(), &HashMap::new(), lock, false).unwrap().remove(0) {
+        StatementOutput::Valid(_, tbl) => assert_eq!(tbl.display_content_at(0, 0, None).unwrap(), "3"),
+        out => panic!("Unexpected output: {:?}", out)
+    }
+
+    // Names that require quoting are quoted both at the creation and at the insertion, and
+    // qualified or quoted names of existing tables are not created again.
+    assert_eq!(conn.import(&mut tbl, "Order Items", &[]).unwrap(), 3);
+    assert_eq!(conn.import(&mut tbl, "main.\"Order Items\"", &[]).unwrap(), 3);
+    assert_eq!(conn.import(&mut tbl, "main.scores", &[String::from("Score")]).unwrap(), 3);
+    match conn.try_run("select count(*) from \"Order Items\"".to_string(), &HashMap::new(), lock, false).unwrap().remove(0) {
+        StatementOutput::Valid(_, tbl) => assert_eq!(tbl.display_content_at(0, 0, None).unwrap(), "6"),
+        out => panic!("Unexpected output: {:?}", out)
+    }
+
+    // A failed insertion does not leave the created table behind.
+    let mut empty = Table::new(None, vec![String::from("id")], vec![Column::from(Vec::<i64>::new())]).unwrap();
+    assert!(conn.import(&mut empty, "missing", &[]).is_err());
+    match conn.try_run("select count(*) from sqlite_master where name = 'missing'".to_string(), &HashMap::new(), lock, false).unwrap().remove(0) {
+        StatementOutput::Valid(_, tbl) => assert_eq!(tbl.display_content_at(0, 0, None).unwrap(), "0"),
+        out => panic!("Unexpected output: {:?}", out)
+    }
+}
@@ -0,0 +1,68 @@
+use queries::tables::table::*;
+use queries::tables::column::*;
+use queries::tables::nullable::*;
+use queries::tables::dialect::*;
+use queries::sql::copy::*;
+use std::str::FromStr;
+
+// cargo test -- dialect --nocapture
+#[test]
+fn dialect() {
+
+    // Semicolon-delimited, Latin-1 encoded content with decimal commas.
+    let mut latin1 = b"nome;pre\xe7o\nca\xe7a;1,5\np\xe3o;2,25\n".to_vec();
+    let opts = CsvOptions::default().sniff(&latin1[..]);
+    assert_eq!(opts.delimiter, Some(b';'));
+    assert_eq!(opts.encoding, Some(CsvEncoding::Latin1));
+    assert_eq!(opts.decimal_comma, Some(true));
+    assert_eq!(opts.header, Some(true));
+    let tbl = Table::new_from_delimited(&latin1[..], &CsvOptions::default()).unwrap();
+    assert_eq!(tbl.names(), vec![String::from("nome"), String::from("preço")]);
+    assert_eq!(tbl.get_column(0).unwrap(), &Column::Str(vec![String::from("caça"), String::from("pão")]));
+    assert_eq!(tbl.get_column(1).unwrap(), &Column::F64(vec![1.5, 2.25]));
+
+    // Skipped preamble lines.
+    latin1.splice(0..0, b"Exported by partner\n".iter().cloned());
+    let skip = CsvOptions { skip_rows : 1, ..Default::default() };
+    assert_eq!(Table::new_from_delimited(&latin1[..], &skip).unwrap().names()[0], "nome");
+
+    // Headerless content quoted with single quotes.
+    let quoted = b"1|'a|b'\n2|'c'\n";
+    let opts = CsvOptions::default().sniff(&quoted[..]);
+    assert_eq!(opts.quote, Some(b'\''));
+    assert_eq!(opts.delimiter, Some(b'|'));
+    assert_eq!(opts.header, Some(false));
+    let tbl = Table::new_from_delimited(&quoted[..], &CsvOptions::default()).unwrap();
+    assert_eq!(tbl.names(), vec![String::from("column1"), String::from("column2")]);
+    assert_eq!(tbl.get_column(1).unwrap(), &Column::Str(vec![String::from("a|b"), String::from("c")]));
+
+    // Null markers give nullable columns.
+    let nulls = CsvOptions { null : Some(String::from("NA")), ..Default::default() };
+    let tbl = Table::new_from_delimited(b"a,b\n1,x\nNA,NA\n", &nulls).unwrap();
+    assert_eq!(tbl.get_column(0).unwrap(), &Column::Nullable(NullableColumn::from(vec![Some(1i64), None])));
+
+    // Plain comma-separated content keeps being read as before.
+    let tbl = Table::new_from_text(String::from("a,b\n1,2.5\n")).unwrap();
+    assert_eq!(tbl.get_column(0).unwrap(), &Column::I64(vec![1]));
+    assert_eq!(tbl.get_column(1).unwrap(), &Column::F64(vec![2.5]));
+
+    // Quotes escaped by a backslash.
+    let escaped = CsvOptions { escape : Some(b'\\'), ..Default::default() };
+    let tbl = Table::new_from_delimited(b"a;b\n1;\"x\\\"y\"\n", &escaped).unwrap();
+    assert_eq!(tbl.get_column(1).unwrap(), &Column::Str(vec![String::from("x\"y")]));
+
+    // Content is only inspected when some option is left to be detected.
+    assert!(CsvOptions::default().is_partial());
+    let fixed = CsvOptions::with_delimiter(b',', false);
+    assert!(!fixed.is_partial());
+    let tbl = Table::new_from_delimited(b"a;b\n1;2\n", &fixed).unwrap();
+    assert_eq!(tbl.names(), vec![String::from("column1")]);
+
+    // Copy options are interpreted as a dialect.
+    let copy_opts = CopyOptions::from_str("format csv, header true, delimiter ';', encoding 'latin1', null 'NA'").unwrap();
+    let csv_opts = copy_opts.csv_options();
+    assert_eq!(csv_opts.delimiter, Some(b';'));
+    assert_eq!(csv_opts.encoding, Some(CsvEncoding::Latin1));
+    let tbl = copy_opts.read_table(b"a;b\n1;NA\n", &[]).unwrap();
+    assert_eq!(tbl.get_column(1).unwrap(), &Column::Nullable(NullableColumn::from(vec![None as Option<String>])));
+}
@@ -0,0 +1,135 @@
+use queries::client::Engine;
+use queries::sql::SafetyLock;
+use queries::sql::edit::*;
+use queries::sql::parsing::AnyStatement;
+use queries::sql::object::{DBObject, DBColumn, DBType};
+use queries::tables::table::Table;
+use queries::tables::column::Column;
+use queries::tables::field::Field;
+
+fn people() -> DBObject {
+    let cols = vec![
+        DBColumn { name : format!("id"), ty : DBType::I32, is_pk : true },
+        DBColumn { name : format!("name"), ty : DBType::Text, is_pk : false },
+        DBColumn { name : format!("age"), ty : DBType::I32, is_pk : false }
+    ];
+    DBObject::Table { schema : format!("public"), name : format!("people"), cols, rels : Vec::new() }
+}
+
+// cargo test -- edit --nocapture
+#[test]
+fn edit() {
+    let names = vec![format!("id"), format!("name")];
+    let tbl = Table::new(
+        Some(format!("people")),
+        names.clone(),
+        vec![Column::from(vec![1i32, 2, 3]), Column::from(vec![format!("Ann"), format!("Bob"), format!("Carl")])]
+    ).unwrap();
+
+    let schema = vec![DBObject::Schema { name : format!("public"), children : vec![people()] }];
+    let obj = find_table(&schema[..], "public.people").unwrap();
+    let mut edit = TableEdit::new(&names[..], obj, Engine::Postgres).unwrap();
+    assert!(edit.is_empty());
+
+    let key = edit.row_key(&tbl, 1).unwrap();
+    assert_eq!(key, vec![format!("2")]);
+    edit.update(key.clone(), 1, Some(format!("O'Brien")));
+    edit.update(edit.row_key(&tbl, 0).unwrap(), 1, None);
+    edit.delete(edit.row_key(&tbl, 2).unwrap());
+    let row = edit.insert();
+    edit.update_inserted(row, 1, Some(format!("Dan")));
+    let empty_row = edit.insert();
+    edit.update_inserted(empty_row, 0, None);
+
+    assert_eq!(edit.statements(), vec![
+        format!("UPDATE \"public\".\"people\" SET \"name\" = NULL WHERE \"id\" = 1;"),
+        format!("UPDATE \"public\".\"people\" SET \"name\" = 'O''Brien' WHERE \"id\" = 2;"),
+        format!("DELETE FROM \"public\".\"people\" WHERE \"id\" = 3;"),
+        format!("INSERT INTO \"public\".\"people\" (\"name\") VALUES ('Dan');")
+    ]);
+
+    let lock = SafetyLock { accept_ddl : false, accept_dml : true, enable_async : false };
+    match edit.transaction(&lock).unwrap() {
+        AnyStatement::ParsedTransaction { middle, .. } => assert_eq!(middle.len(), 4),
+        other => panic!("Unexpected statement: {:?}", other)
+    }
+
+    // Updates and deletes are rejected when DML is disabled.
+    let lock = SafetyLock { accept_ddl : false, accept_dml : false, enable_async : false };
+    assert!(edit.transaction(&lock).is_err());
+
+    // Columns of the result must belong to the table and hold its primary key.
+    assert!(TableEdit::new(&[format!("name")], obj, Engine::Postgres).is_err());
+    assert!(TableEdit::new(&[format!("id"), format!("count")], obj, Engine::Postgres).is_err());
+
+    let mut edit = TableEdit::new(&names[..], obj, Engine::MySQL).unwrap();
+    edit.update(vec![format!("1")], 1, Some(format!("Ann")));
+    assert_eq!(edit.statements(), vec![format!("UPDATE `public`.`people` SET `name` = 'Ann' WHERE `id` = 1;")]);
+    edit.clear();
+    assert!(edit.transaction(&lock).is_err());
+}
+
+// cargo test -- edit_keys --nocapture
+#[test]
+fn edit_keys() {
+
+    // Keys are built from the values of the result rather than from their displayed text.
+    let names = vec![format!("name"), format!("score")];
+    let tbl = Table::new(
+        Some(format!("scores")),
+        names.clone(),
+        vec![Column::from(vec![format!("O'Brien")]), Column::from(vec![0.1f64 + 0.2])]
+    ).unwrap();
+    let cols = vec![
+        DBColumn { name : format!("name"), ty : DBType::Text, is_pk : true },
+        DBColumn { name : format!("score"), ty : DBType::F64, is_pk : true }
+    ];
+    let obj = DBObject::Table { schema : format!("public"), name : format!("scores"), cols, rels : Vec::new() };
+    let mut edit = TableEdit::new(&names[..], &obj, Engine::Postgres).unwrap();
+    let key = edit.row_key(&tbl, 0).unwrap();
+    assert_eq!(key, vec![format!("'O''Brien'"), format!("0.30000000000000004")]);
+    edit.delete(key);
+    assert_eq!(edit.statements(), vec![
+        format!("DELETE FROM \"public\".\"scores\" WHERE \"name\" = 'O''Brien' AND \"score\" = 0.30000000000000004;")
+    ]);
+
+    assert_eq!(key_literal(&Field::Bytes(vec![0xde, 0xad]), Engine::Postgres), Some(format!("'\\xdead'")));
+    assert_eq!(key_literal(&Field::Bytes(vec![0xde, 0xad]), Engine::MySQL), Some(format!("X'dead'")));
+    assert_eq!(key_literal(&Field::F64(f64::NAN), Engine::Postgres), None);
+}
+
+// cargo test -- edit_ambiguous --nocapture
+#[test]
+fn edit_ambiguous() {
+
+    // Unqualified names matching tables at more than one schema are not edited.
+    let orders = |schema : &str| DBObject::Table {
+        schema : schema.to_string(),
+        name : format!("orders"),
+        cols : vec![
+            DBColumn { name : format!("id"), ty : DBType::I32, is_pk : true },
+            DBColumn { name : format!("total"), ty : DBType::F64, is_pk : false }
+        ],
+        rels : Vec::new()
+    };
+    let schema = vec![
+        DBObject::Schema { name : format!("public"), children : vec![orders("public")] },
+        DBObject::Schema { name : format!("archive"), children : vec![orders("archive")] }
+    ];
+    assert!(find_table(&schema[..], "orders").is_err());
+    assert!(find_table(&schema[..], "missing").is_err());
+    let obj = find_table(&schema[..], "archive.orders").unwrap();
+    assert!(matches!(obj, DBObject::Table { schema, .. } if &schema[..] == "archive"));
+
+    // Non-finite values are quoted rather than written as numbers.
+    let names = vec![format!("id"), format!("total")];
+    let mut edit = TableEdit::new(&names[..], obj, Engine::Postgres).unwrap();
+    edit.update(vec![format!("1")], 1, Some(format!("NaN")));
+    edit.update(vec![format!("2")], 1, Some(format!("-1.5e3")));
+    edit.update(vec![format!("3")], 1, Some(format!("infinity")));
+    assert_eq!(edit.statements(), vec![
+        format!("UPDATE \"archive\".\"orders\" SET \"total\" = 'NaN' WHERE \"id\" = 1;"),
+        format!("UPDATE \"archive\".\"orders\" SET \"total\" = -1.5e3 WHERE \"id\" = 2;"),
+        format!("UPDATE \"archive\".\"orders\" SET \"total\" = 'infinity' WHERE \"id\" = 3;")
+    ]);
+}
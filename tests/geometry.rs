@@ -0,0 +1,30 @@
+use queries::tables::column::*;
+use queries::tables::geometry::*;
+use bytes::BytesMut;
+
+// cargo test -- geometry --nocapture
+#[test]
+fn geometry() {
+    let poly = Geometry::Polygon(vec![vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 0.0)]]);
+    let mut buf = BytesMut::new();
+    to_wkb(&poly, &mut buf);
+    assert_eq!(from_wkb(&buf[..]).unwrap(), poly);
+    assert_eq!(poly.to_string(), "POLYGON((0 0,1 0,1 1,0 0))");
+
+    // EWKB point with SRID 4326, as sent by PostGIS.
+    let mut ewkb = vec![1u8];
+    ewkb.extend(0x20000001u32.to_le_bytes());
+    ewkb.extend(4326u32.to_le_bytes());
+    ewkb.extend(1.5f64.to_le_bytes());
+    ewkb.extend((-2.0f64).to_le_bytes());
+    let pt = from_wkb(&ewkb[..]).unwrap();
+    assert_eq!(pt, Geometry::Point((1.5, -2.0)));
+    assert_eq!(pt.to_geojson(), serde_json::json!({ "type" : "Point", "coordinates" : [1.5, -2.0] }));
+
+    let col = Column::from(vec![pt.clone(), poly.clone()]);
+    let (ixs, sorted) = col.sorted(true);
+    assert_eq!(ixs, vec![1, 0]);
+    assert_eq!(sorted, Column::from(vec![poly, pt]));
+    let (ixs, _) = col.filtered("POINT").unwrap();
+    assert_eq!(ixs, vec![0]);
+}
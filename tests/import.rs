@@ -0,0 +1,106 @@
+use queries::client::Engine;
+use queries::server::*;
+use queries::sql::*;
+use queries::tables::table::*;
+use queries::tables::column::*;
+use queries::tables::nullable::*;
+use std::cell::RefCell;
+mod common;
+
+const TABLE_CREATION : &'static str = "create table imported(id integer, label text, score double precision);";
+
+// cargo test -- import --nocapture
+#[test]
+fn import() {
+    common::run_with_temp_db(|mut temp| {
+        let n = 25_000;
+        temp.conn.exec(&AnyStatement::from_sql(TABLE_CREATION).unwrap());
+
+        // Integers are copied in the binary format, since they match the destination type.
+        let ids : Vec<i32> = (0..n).collect();
+        let labels : Vec<Option<String>> = (0..n).map(|i| if i % 2 == 0 { Some(format!("row {}", i)) } else { None } ).collect();
+        let mut tbl = Table::new(
+            None,
+            vec![String::from("id"), String::from("label")],
+            vec![Column::from(ids), Column::from(NullableColumn::from(labels))]
+        ).unwrap();
+        let reports = RefCell::new(Vec::new());
+        let imported = temp.conn.import_with_progress(&mut tbl, "imported", &[], &|n| reports.borrow_mut().push(n) ).unwrap();
+        assert_eq!(imported, n as usize);
+        assert_eq!(reports.borrow().last(), Some(&(n as usize)));
+        assert!(reports.borrow().len() > 1);
+
+        // 64-bit integers are not accepted by the integer column, so the rows are copied as CSV. Only
+        // the id and label columns are copied.
+        let mut tbl = Table::new(
+            None,
+            vec![String::from("score"), String::from("id"), String::from("label")],
+            vec![
+                Column::from(vec![0.5f64, 1.5]),
+                Column::from(vec![-1i64, -2]),
+                Column::from(NullableColumn::from(vec![Some(String::from("\"quoted\", text")), None]))
+            ]
+        ).unwrap();
+        let imported = temp.conn.import(&mut tbl, "imported", &[String::from("id"), String::from("label")]).unwrap();
+        assert_eq!(imported, 2);
+
+        match temp.conn.query("select count(*) from imported where label is null and id >= 0") {
+            StatementOutput::Valid(_, tbl) => assert_eq!(tbl.display_content_at(0, 0, None).unwrap(), (n / 2).to_string()),
+            out => panic!("Unexpected output: {:?}", out)
+        }
+        match temp.conn.query("select label from imported where id = -1 and score is null") {
+            StatementOutput::Valid(_, tbl) => assert_eq!(tbl.display_content_at(0, 0, None).unwrap(), "\"quoted\", text"),
+            out => panic!("Unexpected output: {:?}", out)
+        }
+        match temp.conn.query("select count(*) from imported where id = -2 and label is null") {
+            StatementOutput::Valid(_, tbl) => assert_eq!(tbl.display_content_at(0, 0, None).unwrap(), "1"),
+            out => panic!("Unexpected output: {:?}", out)
+        }
+    });
+}
+
+#[test]
+fn import_names() {
+    let tbl = Table::new(
+        None,
+        vec![String::from("Score"), String::from("first name"), String::from("order"), String::from("a\"b")],
+        vec![Column::from(vec![1.5f64]), Column::from(vec![String::from("a")]), Column::from(vec![1i64]), Column::from(vec![1i64])]
+    ).unwrap();
+    let (dst, cols) = queries::server::import_names(&tbl, "public.Scores");
+    assert_eq!(dst, "\"public\".\"Scores\"");
+    assert_eq!(cols, "\"Score\", \"first name\", \"order\", \"a\"\"b\"");
+    assert_eq!(quote_qualified("\"my.schema\".scores", Engine::Postgres), "\"my.schema\".\"scores\"");
+    let create = tbl.sql_table_creation(&dst, &[], Engine::Postgres).unwrap();
+    assert!(create.starts_with("create table \"public\".\"Scores\" (\n    \"Score\" double precision,"));
+}
+
+// cargo test -- import_create --nocapture
+#[test]
+fn import_create() {
+    common::run_with_temp_db(|mut temp| {
+
+        // Mixed-case headers, spaces and reserved words are quoted at the created table.
+        let mut tbl = Table::new(
+            None,
+            vec![String::from("Score"), String::from("first name"), String::from("order")],
+            vec![Column::from(vec![0.5f64, 1.5]), Column::from(vec![String::from("a"), String::from("b")]), Column::from(vec![1i64, 2])]
+        ).unwrap();
+        assert_eq!(temp.conn.import(&mut tbl, "Created", &[]).unwrap(), 2);
+        match temp.conn.query("select sum(\"Score\") from \"Created\" where \"first name\" = 'b' and \"order\" = 2") {
+            StatementOutput::Valid(_, tbl) => assert_eq!(tbl.display_content_at(0, 0, None).unwrap(), "1.5"),
+            out => panic!("Unexpected output: {:?}", out)
+        }
+
+        // A failed copy does not leave the created table behind.
+        let mut tbl = Table::new(
+            None,
+            vec![String::from("label")],
+            vec![Column::from(vec![String::from("nul \0 byte")])]
+        ).unwrap();
+        assert!(temp.conn.import(&mut tbl, "not_created", &[]).is_err());
+        match temp.conn.query("select count(*) from pg_class where relname = 'not_created'") {
+            StatementOutput::Valid(_, tbl) => assert_eq!(tbl.display_content_at(0, 0, None).unwrap(), "0"),
+            out => panic!("Unexpected output: {:?}", out)
+        }
+    });
+}
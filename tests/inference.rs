@@ -0,0 +1,79 @@
+use queries::tables::table::*;
+use queries::tables::column::*;
+use queries::tables::nullable::*;
+use queries::tables::dialect::*;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+use std::collections::HashMap;
+
+const CONTENT : &'static str = r#"id,ratio,amount,active,born,at,meta,code
+1,0.5,12345678901234567.89,true,2001-01-01,08:30,{"a":1},007
+2,,1.5,f,2002-02-02,17:45:10,[1],010
+3,2,,T,,,,
+"#;
+
+// cargo test -- inference --nocapture
+#[test]
+fn inference() {
+    assert_eq!(infer_text_type(["08:30", "17:45:10"].iter().cloned()), TextType::Time);
+    assert_eq!(infer_text_type(["1.5", "12345678901234567.89"].iter().cloned()), TextType::Numeric);
+    assert_eq!(infer_text_type(["{\"a\" : 1}", "2"].iter().cloned()), TextType::Json);
+    assert_eq!(infer_text_type(["", " "].iter().cloned()), TextType::Text);
+
+    let tbl = Table::new_from_text(CONTENT.to_string()).unwrap();
+    assert_eq!(tbl.get_column(0).unwrap(), &Column::I64(vec![1, 2, 3]));
+    assert_eq!(tbl.get_column(1).unwrap(), &Column::Nullable(NullableColumn::from(vec![Some(0.5), None, Some(2.0)])));
+    assert_eq!(tbl.get_column(2).unwrap(), &Column::Nullable(NullableColumn::from(vec![
+        Some(Decimal::from_str("12345678901234567.89").unwrap()),
+        Some(Decimal::from_str("1.5").unwrap()),
+        None
+    ])));
+    assert_eq!(tbl.get_column(3).unwrap(), &Column::Bool(vec![true, false, true]));
+    assert_eq!(tbl.get_column(6).unwrap(), &Column::Nullable(NullableColumn::from(vec![
+        Some(serde_json::json!({ "a" : 1 })),
+        Some(serde_json::json!([1])),
+        None
+    ])));
+
+    // Column types informed by the user take precedence over inference.
+    let mut types = HashMap::new();
+    types.insert(String::from("code"), TextType::Text);
+    let opts = CsvOptions { column_types : types, ..Default::default() };
+    let tbl = Table::new_from_delimited(CONTENT.as_bytes(), &opts).unwrap();
+    assert_eq!(tbl.get_column(7).unwrap(), &Column::Str(vec![String::from("007"), String::from("010"), String::new()]));
+
+    // Codes with leading zeros are kept as text.
+    let tbl = Table::new_from_text(CONTENT.to_string()).unwrap();
+    assert_eq!(tbl.get_column(7).unwrap(), &Column::Str(vec![String::from("007"), String::from("010"), String::new()]));
+    assert_eq!(infer_text_type(["0", "0.5", "-0.25"].iter().cloned()), TextType::Float);
+    assert_eq!(infer_text_type(["1", "-01"].iter().cloned()), TextType::Text);
+}
+
+// cargo test -- inference_late_values --nocapture
+#[test]
+fn inference_late_values() {
+    // Values past the first rows are considered when deciding the column type.
+    let mut content = String::from("id,amount\n");
+    for i in 0..1500 {
+        if i == 1200 {
+            content += &format!("{},n/a\n", i);
+        } else {
+            content += &format!("{},{}.5\n", i, i);
+        }
+    }
+    let tbl = Table::new_from_text(content).unwrap();
+    assert_eq!(tbl.get_column(0).unwrap().len(), 1500);
+    match tbl.get_column(1).unwrap() {
+        Column::Str(v) => {
+            assert_eq!(v[1200], "n/a");
+            assert_eq!(v[1499], "1499.5");
+        },
+        other => panic!("Expected text column, found {:?}", other)
+    }
+
+    // A type informed by the user falls back to text when a value cannot be parsed.
+    let values = vec![Some(String::from("1.5")), None, Some(String::from("1.5e3x"))];
+    assert_eq!(column_from_text(values.clone(), TextType::Float), Column::Nullable(NullableColumn::from(values)));
+    let values = vec![Some(String::from("1.5")), Some(String::new())];
+    assert_eq!(column_from_text(values, TextType::Float), Column::Nullable(NullableColumn::from(vec![Some(1.5), None])));
+}
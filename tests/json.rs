@@ -0,0 +1,52 @@
+use queries::tables::table::*;
+use queries::tables::column::*;
+use queries::tables::nullable::*;
+use rust_decimal::Decimal;
+use serde_json::json;
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+// cargo test -- json --nocapture
+#[test]
+fn json() {
+    let names = ["id", "price", "label", "attrs"].iter().map(|s| s.to_string() ).collect();
+    let cols = vec![
+        Column::from(vec![1i64, 2]),
+        Column::from(vec![Decimal::from_str("10.25").unwrap(), Decimal::from_str("3").unwrap()]),
+        Column::from(NullableColumn::from(vec![Some(String::from("a")), None])),
+        Column::from(vec![json!({ "nested" : [1, 2] }), json!(null)])
+    ];
+    let tbl = Table::new(None, names, cols).unwrap();
+
+    let rows = tbl.to_json(JsonLayout::Rows);
+    assert_eq!(rows, json!([
+        { "id" : 1, "price" : 10.25, "label" : "a", "attrs" : { "nested" : [1, 2] } },
+        { "id" : 2, "price" : 3, "label" : null, "attrs" : null }
+    ]));
+
+    // Decimals are written as numbers with all their digits.
+    let precise = Table::new(
+        None,
+        vec![String::from("price")],
+        vec![Column::from(vec![Decimal::from_str("1234567890123456.789").unwrap()])]
+    ).unwrap();
+    assert_eq!(precise.to_ndjson().trim(), "{\"price\":1234567890123456.789}");
+
+    let ndjson = tbl.to_ndjson();
+    let lines : Vec<serde_json::Value> = ndjson.lines().map(|l| serde_json::from_str(l).unwrap() ).collect();
+    assert_eq!(serde_json::Value::Array(lines), rows);
+
+    let cols = tbl.to_json(JsonLayout::Columns);
+    assert_eq!(cols["id"], json!([1, 2]));
+    assert_eq!(cols["label"], json!(["a", null]));
+
+    let simple = Table::new(
+        None,
+        vec![String::from("id"), String::from("score")],
+        vec![Column::from(vec![1i64, 2]), Column::from(vec![0.5f64, 1.5])]
+    ).unwrap();
+    let parsed = Table::try_from(simple.to_json(JsonLayout::Columns)).unwrap();
+    assert_eq!(parsed.names(), simple.names());
+    assert_eq!(parsed.get_column(0), simple.get_column(0));
+    assert_eq!(parsed.get_column(1), simple.get_column(1));
+}
@@ -0,0 +1,39 @@
+use queries::tables::column::*;
+use queries::tables::network::*;
+use std::str::FromStr;
+use std::convert::TryInto;
+
+// cargo test -- network --nocapture
+#[test]
+fn network() {
+    let net = Inet::from_str("192.168.0.0/16").unwrap();
+    assert_eq!(net.to_string(), "192.168.0.0/16");
+    assert_eq!(Inet::from_str("10.0.0.1").unwrap().to_string(), "10.0.0.1");
+    assert!(net.contains(&Inet::from_str("192.168.10.4").unwrap()));
+    assert!(!net.contains(&Inet::from_str("192.169.0.1").unwrap()));
+    assert!(!net.contains(&Inet::from_str("::1").unwrap()));
+    assert!(Inet::from_str("10.0.0.1/33").is_err());
+
+    let mac = MacAddr::from_str("08-00-2B-01-02-03").unwrap();
+    assert_eq!(mac.to_string(), "08:00:2b:01:02:03");
+    assert!(MacAddr::from_str("08:00:2b").is_err());
+
+    let col = Column::from(vec![
+        Inet::from_str("10.0.0.1").unwrap(),
+        Inet::from_str("192.168.1.1").unwrap(),
+        Inet::from_str("10.1.2.3").unwrap()
+    ]);
+    let (ixs, filtered) = col.filtered("10.0.0.0/8").unwrap();
+    assert_eq!(ixs, vec![0, 2]);
+    assert_eq!(filtered.display_content(None), vec!["10.0.0.1", "10.1.2.3"]);
+
+    let ids = Column::from(vec![
+        uuid::Uuid::parse_str("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11").unwrap(),
+        uuid::Uuid::parse_str("00000000-0000-0000-0000-000000000001").unwrap()
+    ]);
+    let (ixs, sorted) = ids.sorted(true);
+    assert_eq!(ixs, vec![1, 0]);
+    let sorted : Vec<uuid::Uuid> = sorted.try_into().unwrap();
+    assert_eq!(sorted[1].to_string(), "a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11");
+    assert_eq!(ids.json_values()[1], serde_json::json!("00000000-0000-0000-0000-000000000001"));
+}
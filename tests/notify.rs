@@ -0,0 +1,24 @@
+use queries::server::*;
+use queries::sql::notify::*;
+mod common;
+
+const NOTIFY_QUERY : &'static str = r#"select pg_notify('queries_channel', '{ "table" : "a", "op" : "insert" }');"#;
+
+// cargo test -- notify --nocapture
+#[test]
+fn notify() {
+    assert!(payload_matches("", "any payload"));
+    assert!(payload_matches(r#"{ "table" : "a" }"#, r#"{ "table" : "a", "op" : "insert" }"#));
+    assert!(!payload_matches(r#"{ "table" : "a" }"#, r#"{ "table" : "b" }"#));
+    assert!(!payload_matches(r#"{ "table" : "a" }"#, "a"));
+    assert!(validate_channel("queries; drop table a").is_err());
+
+    common::run_with_temp_db(|mut temp| {
+        temp.conn.listen_at_channel(String::from("queries_channel")).unwrap();
+        temp.conn.query(NOTIFY_QUERY);
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        let notifs = temp.conn.take_notifications();
+        assert!(notifs.iter().any(|n| n.matches("queries_channel", r#"{ "table" : "a" }"#) ));
+        temp.conn.unlisten_at_channel(String::from("queries_channel")).unwrap();
+    });
+}
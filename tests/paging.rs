@@ -0,0 +1,93 @@
+use queries::client::*;
+use queries::server::*;
+use queries::sql::*;
+use queries::tables::column::*;
+use std::collections::HashMap;
+mod common;
+
+const TABLE_CREATION : &'static str = "create table numbers(n integer);";
+
+const TABLE_SELECTION : &'static str = "select n from numbers order by n";
+
+// cargo test -- paging --nocapture
+#[test]
+fn paging() {
+    let dt = common::run("date +%y_%m_%d_%H_%M_%S").unwrap().trim().to_string();
+    let uri = conn_uri_from_str(&format!("file:///tmp/queries_paging_test_{}.db", dt), None).unwrap();
+    let mut conn = SqliteConnection::try_new(uri).unwrap();
+    conn.configure(ConnConfig { timeout : 0, page_size : 10 });
+    let lock = SafetyLock { accept_ddl : true, accept_dml : true, enable_async : false };
+    let values : Vec<String> = (1..=95).map(|n| format!("({})", n) ).collect();
+    let creation = format!("{}\ninsert into numbers values {};", TABLE_CREATION, values.join(","));
+    conn.try_run(creation, &HashMap::new(), lock, false).unwrap();
+
+    let mut tbl = match conn.try_run(TABLE_SELECTION.to_string(), &HashMap::new(), lock, false).unwrap().remove(0) {
+        StatementOutput::Valid(_, tbl) => tbl,
+        out => panic!("Unexpected output: {:?}", out)
+    };
+    assert_eq!(tbl.nrows(), 10);
+    assert_eq!(conn.paged_results(), vec![0]);
+
+    // Pages are read by stepping the query, without copying the result to temporary tables.
+    match conn.query("select count(*) from sqlite_temp_master") {
+        StatementOutput::Valid(_, tmp) => assert_eq!(tmp.display_content_at(0, 0, None).unwrap(), "0"),
+        out => panic!("Unexpected output: {:?}", out)
+    }
+
+    let mut n_pages = 1;
+    loop {
+        let page = conn.fetch_page(0).unwrap();
+        if page.nrows() == 0 {
+            break;
+        }
+        tbl.append_rows(&page).unwrap();
+        n_pages += 1;
+        if page.nrows() < 10 {
+            break;
+        }
+    }
+    assert_eq!(n_pages, 10);
+    assert_eq!(tbl.nrows(), 95);
+
+    // The result is released after the last page is fetched.
+    assert!(conn.fetch_page(0).is_err());
+    assert!(conn.paged_results().is_empty());
+}
+
+// cargo test -- paging_repeated --nocapture
+#[test]
+fn paging_repeated() {
+    let dt = common::run("date +%y_%m_%d_%H_%M_%S").unwrap().trim().to_string();
+    let uri = conn_uri_from_str(&format!("file:///tmp/queries_paging_repeated_test_{}.db", dt), None).unwrap();
+    let mut conn = SqliteConnection::try_new(uri).unwrap();
+    conn.configure(ConnConfig { timeout : 0, page_size : 10 });
+    let lock = SafetyLock { accept_ddl : true, accept_dml : true, enable_async : false };
+    let values : Vec<String> = (1..=25).map(|n| format!("({}, '2021-01-{:02}')", n, n) ).collect();
+    let creation = format!("create table days(n integer, day date);\ninsert into days values {};", values.join(","));
+    conn.try_run(creation, &HashMap::new(), lock, false).unwrap();
+
+    // Identical statements keep their pages apart.
+    let selection = "select n, day from days order by n;";
+    let out = conn.try_run(format!("{}\n{}", selection, selection), &HashMap::new(), lock, false).unwrap();
+    assert_eq!(out.len(), 2);
+    assert_eq!(conn.paged_results(), vec![0, 1]);
+    let page = conn.fetch_page(1).unwrap();
+    assert_eq!(page.display_content_at(0, 0, None).unwrap(), "11");
+    let page = conn.fetch_page(1).unwrap();
+    assert_eq!(page.display_content_at(0, 0, None).unwrap(), "21");
+    assert_eq!(page.nrows(), 5);
+    assert_eq!(conn.paged_results(), vec![0]);
+
+    // Pages keep the declared types of the queried columns.
+    let mut tbl = out[0].table().unwrap().clone();
+    let page = conn.fetch_page(0).unwrap();
+    assert_eq!(page.display_content_at(0, 0, None).unwrap(), "11");
+    assert!(matches!(page.get_column(1).unwrap(), Column::Date(_)));
+    tbl.append_rows(&page).unwrap();
+    assert_eq!(tbl.nrows(), 20);
+
+    // Results that fit in a single page are not kept.
+    conn.try_run(String::from("select n from days where n > 20;"), &HashMap::new(), lock, false).unwrap();
+    assert!(conn.paged_results().is_empty());
+    assert!(conn.fetch_page(0).is_err());
+}
@@ -0,0 +1,65 @@
+use queries::client::*;
+use queries::ui::{SecurityChange, try_modify_security_for_conn};
+
+// cargo test -- sslmode --nocapture
+#[test]
+fn sslmode() {
+    for mode in SslMode::ALL {
+        assert_eq!(mode.name().parse::<SslMode>().unwrap(), mode);
+    }
+    assert!("verify_full".parse::<SslMode>().is_err());
+    assert_eq!(SslMode::Allow.url_param(), "disable");
+    assert_eq!(SslMode::VerifyCa.url_param(), "require");
+
+    // Modes of settings saved before the sslmode could be chosen.
+    let mut sec = Security::new_secure();
+    assert_eq!(sec.effective_ssl_mode(), SslMode::VerifyFull);
+    sec.verify_hostname = Some(false);
+    assert_eq!(sec.effective_ssl_mode(), SslMode::VerifyCa);
+    assert_eq!(Security::new_insecure().effective_ssl_mode(), SslMode::Disable);
+
+    let mut conn = ConnectionInfo::default();
+    conn.host = format!("db.example.com");
+    conn.security = conn.default_security();
+    let host = conn.host.clone();
+    assert!(try_modify_security_for_conn(&mut conn, &SecurityChange::SslMode { host : host.clone(), mode : SslMode::Require }));
+    assert_eq!(conn.security.effective_ssl_mode(), SslMode::Require);
+    assert!(try_modify_security_for_conn(&mut conn, &SecurityChange::SslMode { host : host.clone(), mode : SslMode::VerifyCa }));
+    assert!(try_modify_security_for_conn(&mut conn, &SecurityChange::Hostname { host : host.clone(), verify : Some(true) }));
+    assert_eq!(conn.security.effective_ssl_mode(), SslMode::VerifyFull);
+    assert!(try_modify_security_for_conn(&mut conn, &SecurityChange::SslMode { host : host.clone(), mode : SslMode::Disable }));
+    assert_eq!(conn.security, Security::new_insecure());
+
+    // Prefer mode without a minimum TLS version gets the default version.
+    assert!(try_modify_security_for_conn(&mut conn, &SecurityChange::SslMode { host : host.clone(), mode : SslMode::Prefer }));
+    assert!(conn.security.tls_version.is_some());
+    let json = serde_json::to_string(&conn.security).unwrap();
+    assert_eq!(serde_json::from_str::<Security>(&json).unwrap().effective_ssl_mode(), SslMode::Prefer);
+}
+
+#[test]
+fn unix_socket() {
+    let mut info = ConnectionInfo::default();
+    info.host = format!("/var/run/postgresql");
+    info.port = format!("5432");
+    info.user = format!("postgres");
+    info.database = format!("postgres");
+    assert!(info.is_socket());
+    assert_eq!(info.default_security(), Security::new_insecure());
+    info.security = info.default_security();
+
+    // Peer authentication does not require a password.
+    let uri = ConnURI::new(info.clone(), "").unwrap();
+    assert_eq!(uri.uri.as_str(), "postgresql://postgres@%2Fvar%2Frun%2Fpostgresql:5432/postgres");
+    assert!(uri.verify_integrity().is_ok());
+    let uri = ConnURI::new(info.clone(), "secret").unwrap();
+    assert!(uri.verify_integrity().is_ok());
+
+    info.engine = Engine::MySQL;
+    assert!(ConnURI::new(info.clone(), "").is_err());
+
+    // Remote hosts still require a password.
+    info.engine = Engine::Postgres;
+    info.host = format!("db.example.com");
+    assert!(ConnURI::new(info, "").is_err());
+}
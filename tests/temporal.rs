@@ -0,0 +1,77 @@
+use queries::client::*;
+use queries::server::*;
+use queries::sql::*;
+use queries::tables::table::*;
+use queries::tables::column::*;
+use queries::tables::nullable::*;
+use queries::tables::temporal::*;
+use chrono::{NaiveDate, NaiveTime};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::convert::TryInto;
+mod common;
+
+const CONTENT : &'static str = r#"id,day,at,seen
+1,2021-03-01,08:30,2021-03-01 08:30:00
+2,2020-12-31,17:45:10,
+3,2021-01-15,12:00,2021-01-15T12:00:00
+"#;
+
+// cargo test -- temporal --nocapture
+#[test]
+fn temporal() {
+    let intv = Interval::from_str("1 year 2 mons 3 days 04:05:06.5").unwrap();
+    assert_eq!(intv, Interval::new(14, 3, 14_706_500_000));
+    assert_eq!(intv.to_string(), "1 year 2 mons 3 days 04:05:06.5");
+    assert_eq!(Interval::from_str("3 hours 20 minutes").unwrap().to_string(), "03:20:00");
+    assert!(Interval::from_str("3 fortnights").is_err());
+
+    // Fractional units are carried to the smaller units.
+    assert_eq!(Interval::from_str("1.5 years").unwrap(), Interval::new(18, 0, 0));
+    assert_eq!(Interval::from_str("1.5 mons").unwrap(), Interval::new(1, 15, 0));
+    assert_eq!(Interval::from_str("1.5 days").unwrap().to_string(), "1 day 12:00:00");
+    assert_eq!(Interval::from_str("0.25 hours").unwrap().to_string(), "00:15:00");
+
+    // Intervals are compared by their normalized span.
+    assert!(Interval::new(1, 0, 0) > Interval::new(0, 29, 0));
+    assert!(Interval::new(0, 1, 0) > Interval::new(0, 0, 3_600_000_000));
+    assert!(Interval::new(0, 31, 0) > Interval::new(1, 0, 0));
+
+    // Timestamps with time zone are written at the local offset and read back to the same instant.
+    let tz = parse_timestamptz("2021-03-01 08:30:00-03").unwrap();
+    assert_eq!(parse_timestamptz(&timestamptz_to_string(&tz)), Some(tz));
+    assert_eq!(parse_timestamptz("2021-03-01 08:30:00-03").unwrap().to_string(), "2021-03-01 11:30:00 UTC");
+
+    let tbl = Table::new_from_text(CONTENT.to_string()).unwrap();
+    let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+    assert_eq!(tbl.get_column(1).unwrap(), &Column::Date(vec![date(2021, 3, 1), date(2020, 12, 31), date(2021, 1, 15)]));
+    assert_eq!(tbl.get_column(2).unwrap(), &Column::Time(vec![
+        NaiveTime::from_hms_opt(8, 30, 0).unwrap(),
+        NaiveTime::from_hms_opt(17, 45, 10).unwrap(),
+        NaiveTime::from_hms_opt(12, 0, 0).unwrap()
+    ]));
+    assert!(matches!(tbl.get_column(3).unwrap(), Column::Nullable(NullableColumn::Timestamp(_))));
+
+    let sorted = tbl.sorted_by(1, true).unwrap();
+    let ids : Vec<i64> = sorted.get_column(0).unwrap().clone().try_into().unwrap();
+    assert_eq!(ids, vec![2, 3, 1]);
+    let filtered = tbl.filtered_by(1, "2021-01-15").unwrap();
+    assert_eq!(filtered.nrows(), 1);
+
+    // Dates are exported as ISO 8601 strings, and are positioned along plot axes as seconds.
+    assert_eq!(tbl.get_column(3).unwrap().json_values()[0], serde_json::json!("2021-03-01T08:30:00"));
+    assert_eq!(tbl.get_columns(&[1]).try_numeric(0).unwrap()[1], 1609372800.0);
+
+    let dt = common::run("date +%y_%m_%d_%H_%M_%S").unwrap().trim().to_string();
+    let uri = conn_uri_from_str(&format!("file:///tmp/queries_temporal_test_{}.db", dt), None).unwrap();
+    let mut conn = SqliteConnection::try_new(uri).unwrap();
+    let lock = SafetyLock { accept_ddl : true, accept_dml : true, enable_async : false };
+    let sql = String::from("create table events(day date, note text);\n\
+        insert into events values ('2021-03-01', 'a'), ('2020-12-31', 'b');\n\
+        select day, note from events order by day;");
+    let tbl = match conn.try_run(sql, &HashMap::new(), lock, false).unwrap().pop().unwrap() {
+        StatementOutput::Valid(_, tbl) => tbl,
+        out => panic!("Unexpected output: {:?}", out)
+    };
+    assert_eq!(tbl.get_column(0).unwrap(), &Column::Date(vec![date(2020, 12, 31), date(2021, 3, 1)]));
+}
@@ -0,0 +1,78 @@
+use queries::client::*;
+use std::path::PathBuf;
+
+fn vault_test_path(name : &str) -> PathBuf {
+    let dt = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos();
+    std::env::temp_dir().join(format!("queries_{}_test_{}.json", name, dt))
+}
+
+// cargo test -- file_vault --nocapture
+#[test]
+fn file_vault() {
+    let path = vault_test_path("vault");
+    let mut info = ConnectionInfo::default();
+    info.host = format!("db.example.com");
+    info.port = format!("5432");
+    info.user = format!("analyst");
+    info.database = format!("sales");
+
+    let mut vault = FileVault::open(&path, "master passphrase").unwrap();
+    assert!(path.is_file());
+    assert!(vault.password(&info).is_none());
+    vault.set_password(&info, "s3cret").unwrap();
+    assert_eq!(vault.password(&info).as_ref().map(|p| &p[..] ), Some("s3cret"));
+
+    // Passwords are not written in plain text.
+    let content = std::fs::read_to_string(&path).unwrap();
+    assert!(!content.contains("s3cret"));
+    assert!(!content.contains("db.example.com"));
+
+    // The vault is replaced by a fully written file, readable only by the user.
+    let tmp = path.with_file_name(format!(".{}.tmp", path.file_name().unwrap().to_str().unwrap()));
+    assert!(!tmp.exists());
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+
+    let mut reopened = FileVault::open(&path, "master passphrase").unwrap();
+    assert_eq!(reopened.len(), 1);
+    assert_eq!(reopened.password(&info).as_ref().map(|p| &p[..] ), Some("s3cret"));
+
+    // Passwords are keyed by all the credentials.
+    let mut other = info.clone();
+    other.database = format!("other");
+    assert!(reopened.password(&other).is_none());
+
+    assert!(FileVault::open(&path, "wrong passphrase").is_err());
+    assert!(FileVault::open(&path, "").is_err());
+
+    reopened.remove_password(&info).unwrap();
+    let reopened = FileVault::open(&path, "master passphrase").unwrap();
+    assert_eq!(reopened.len(), 0);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn credential_store_backend() {
+    let path = vault_test_path("store");
+    let mut info = ConnectionInfo::default();
+    info.host = format!("localhost");
+    info.user = format!("dev");
+
+    // The connection set only sees the store through the trait.
+    let mut store : Box<dyn CredentialStore> = Box::new(FileVault::open(&path, "passphrase").unwrap());
+    store.set_password(&info, "dev").unwrap();
+    assert_eq!(store.password(&info).as_ref().map(|p| &p[..] ), Some("dev"));
+
+    // Client certificate passphrases are recovered into the security settings, which do not save them.
+    store.set_cert_passphrase("/tmp/client.p12", "p12 secret").unwrap();
+    let mut sec = Security::new_secure();
+    sec.client_cert = Some(ClientCert::Pkcs12 { path : format!("/tmp/client.p12"), passphrase : None });
+    sec.recover_passphrase(store.as_ref());
+    assert_eq!(sec.client_cert.as_ref().and_then(|c| c.pkcs12_passphrase() ), Some(("/tmp/client.p12", "p12 secret")));
+    assert!(!std::fs::read_to_string(&path).unwrap().contains("p12 secret"));
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,78 @@
+use queries::tables::table::Table;
+use queries::tables::column::Column;
+use queries::tables::nullable::NullableColumn;
+use queries::tables::view::*;
+
+fn scores() -> Table {
+    Table::new(
+        None,
+        vec![format!("team"), format!("score"), format!("player")],
+        vec![
+            Column::from(vec![format!("b"), format!("a"), format!("b"), format!("a"), format!("c")]),
+            Column::Nullable(NullableColumn::from(vec![Some(10i32), None, Some(10), Some(3), Some(7)])),
+            Column::from(vec![format!("Ann"), format!("Bob"), format!("Carl"), format!("Dan"), format!("Eve")])
+        ]
+    ).unwrap()
+}
+
+fn cond(col : usize, pred : Predicate) -> Filter {
+    Filter::Condition { col, pred }
+}
+
+// cargo test -- view --nocapture
+#[test]
+fn view() {
+    let tbl = scores();
+
+    // Rows with equal keys keep their original order.
+    let mut view = TableView::default();
+    view.then_by(SortKey { col : 0, ascending : true, nulls : NullOrder::Last });
+    assert_eq!(view.indices(&tbl).unwrap(), vec![1, 3, 0, 2, 4]);
+    view.then_by(SortKey { col : 1, ascending : false, nulls : NullOrder::Last });
+    assert_eq!(view.indices(&tbl).unwrap(), vec![3, 1, 0, 2, 4]);
+
+    // Null ordering does not depend on the direction of the sort.
+    view.then_by(SortKey { col : 1, ascending : false, nulls : NullOrder::First });
+    assert_eq!(view.indices(&tbl).unwrap(), vec![1, 3, 0, 2, 4]);
+    let mut view = TableView::default();
+    view.then_by(SortKey { col : 1, ascending : true, nulls : NullOrder::Last });
+    assert_eq!(view.indices(&tbl).unwrap(), vec![3, 4, 0, 2, 1]);
+
+    // NaN is sorted after every number.
+    let floats = Table::new(None, vec![format!("x")], vec![Column::from(vec![2.0f64, f64::NAN, 1.0, 0.5])]).unwrap();
+    let mut view = TableView::default();
+    view.then_by(SortKey { col : 0, ascending : true, nulls : NullOrder::Last });
+    assert_eq!(view.indices(&floats).unwrap(), vec![3, 2, 0, 1]);
+
+    let mut view = TableView::default();
+    view.and(cond(1, Predicate::GreaterOrEqual(format!("7"))));
+    assert_eq!(view.indices(&tbl).unwrap(), vec![0, 2, 4]);
+    view.and(cond(2, Predicate::Contains(format!("a"))));
+    assert_eq!(view.indices(&tbl).unwrap(), vec![2]);
+    view.or(cond(1, Predicate::IsNull));
+    assert_eq!(view.indices(&tbl).unwrap(), vec![1, 2]);
+
+    let mut view = TableView::default();
+    view.and(cond(1, Predicate::Between(format!("3"), format!("7"))));
+    view.or(cond(2, Predicate::Matches(format!("^C"))));
+    assert_eq!(view.indices(&tbl).unwrap(), vec![2, 3, 4]);
+    let filtered = view.apply(&tbl).unwrap();
+    assert_eq!(filtered.nrows(), 3);
+    assert_eq!(filtered.display_content_at(0, 2, None).unwrap(), "Carl");
+
+    let mut view = TableView::default();
+    view.and(cond(1, Predicate::NotEqual(format!("10"))));
+    assert_eq!(view.indices(&tbl).unwrap(), vec![3, 4]);
+
+    // Literals must parse into the column type, and patterns must be valid expressions.
+    let mut view = TableView::default();
+    view.and(cond(1, Predicate::Less(format!("ten"))));
+    assert!(view.indices(&tbl).is_err());
+    let mut view = TableView::default();
+    view.and(cond(2, Predicate::Matches(format!("("))));
+    assert!(view.apply(&tbl).is_err());
+
+    view.clear();
+    assert!(view.is_empty());
+    assert_eq!(view.indices(&tbl).unwrap(), vec![0, 1, 2, 3, 4]);
+}